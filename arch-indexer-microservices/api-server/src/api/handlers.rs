@@ -19,6 +19,7 @@ use super::types::{ApiError, NetworkStats, SyncStatus, ProgramStats};
 use super::program_ids as pid;
 use crate::{db::models::{Block, Transaction, BlockWithTransactions}, indexer::BlockProcessor};
 use crate::arch_rpc::ArchRpcClient;
+use decoder::{decode_transaction, AccountRole};
 use std::collections::HashSet;
 use axum::http::StatusCode as AxStatusCode;
 
@@ -822,6 +823,9 @@ pub struct TokenLeaderboardRow {
     pub decimals: i32,
     pub supply: Option<String>,
     pub mint_authority: Option<String>,
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub uri: Option<String>,
 }
 
 pub async fn get_token_leaderboard(
@@ -875,9 +879,13 @@ pub async fn get_token_leaderboard(
                 a.total_balance,
                 a.decimals,
                 tm.supply,
-                tm.mint_authority
+                tm.mint_authority,
+                md.name,
+                md.symbol,
+                md.uri
             FROM agg a
             LEFT JOIN token_mints tm ON tm.mint_address = a.mint_address
+            LEFT JOIN token_metadata md ON md.mint_address = a.mint_address
             WHERE COALESCE(tm.program_id, a.program_id) IN (
                 normalize_program_id('TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA'),
                 normalize_program_id('AplToken111111111111111111111111')
@@ -905,6 +913,9 @@ pub async fn get_token_leaderboard(
             decimals: r.get::<i32, _>("decimals"),
             supply: r.try_get::<Option<String>, _>("supply").ok().flatten(),
             mint_authority: r.try_get::<Option<String>, _>("mint_authority").ok().flatten(),
+            name: r.try_get::<Option<String>, _>("name").ok().flatten(),
+            symbol: r.try_get::<Option<String>, _>("symbol").ok().flatten(),
+            uri: r.try_get::<Option<String>, _>("uri").ok().flatten(),
         }).collect();
 
         let total: i64 = if let Some(a) = &authority {
@@ -955,6 +966,9 @@ pub async fn get_token_leaderboard(
             decimals: r.get::<i32, _>("decimals"),
             supply: None,
             mint_authority: None,
+            name: None,
+            symbol: None,
+            uri: None,
         }).collect();
         let total: i64 = sqlx::query_scalar("SELECT COUNT(DISTINCT mint_address) FROM token_balances")
             .fetch_one(&*pool)
@@ -1145,9 +1159,13 @@ pub async fn get_account_token_balances(
             tb.program_id,
             tm.supply::text AS supply,
             tm.is_frozen,
+            md.name,
+            md.symbol,
+            md.uri,
             tb.last_updated
         FROM token_balances tb
         LEFT JOIN token_mints tm ON tb.mint_address = tm.mint_address
+        LEFT JOIN token_metadata md ON tb.mint_address = md.mint_address
         WHERE tb.account_address ILIKE $1
         ORDER BY tb.last_updated DESC
         LIMIT $2 OFFSET $3
@@ -1182,6 +1200,9 @@ pub async fn get_account_token_balances(
                 "program_name": program_name,
                 "supply": r.try_get::<Option<String>, _>("supply").ok().flatten(),
                 "is_frozen": r.try_get::<Option<bool>, _>("is_frozen").ok().flatten(),
+                "name": r.try_get::<Option<String>, _>("name").ok().flatten(),
+                "symbol": r.try_get::<Option<String>, _>("symbol").ok().flatten(),
+                "uri": r.try_get::<Option<String>, _>("uri").ok().flatten(),
                 "last_updated": r.get::<DateTime<Utc>, _>("last_updated")
             })
         }).collect();
@@ -1389,8 +1410,21 @@ pub async fn get_account_token_balances(
             }
         }
 
+        let mints: Vec<String> = by_mint.keys().cloned().collect();
+        let metadata: HashMap<String, (String, String, String)> = sqlx::query(
+            "SELECT mint_address, name, symbol, uri FROM token_metadata WHERE mint_address = ANY($1)"
+        )
+        .bind(&mints)
+        .fetch_all(&*pool)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|r| (r.get("mint_address"), (r.get("name"), r.get("symbol"), r.get("uri"))))
+        .collect();
+
         let list: Vec<serde_json::Value> = by_mint.into_iter().map(|(mint_hex, (amount, decimals))| {
             let mint_b58 = try_hex_to_base58(&mint_hex);
+            let md = metadata.get(&mint_hex);
             json!({
                 "mint_address": if mint_b58.is_empty() { mint_hex.clone() } else { mint_b58 },
                 "mint_address_hex": mint_hex,
@@ -1402,6 +1436,9 @@ pub async fn get_account_token_balances(
                 "program_name": null,
                 "supply": null,
                 "is_frozen": null,
+                "name": md.map(|m| &m.0),
+                "symbol": md.map(|m| &m.1),
+                "uri": md.map(|m| &m.2),
                 "last_updated": chrono::Utc::now(),
             })
         }).collect();
@@ -1417,56 +1454,1043 @@ pub async fn get_account_token_balances(
     })))
 }
 
-fn shortvec_len(len: usize) -> usize {
-    // Solana short_vec length prefix (LEB128-like, 7 bits per byte)
-    let mut n = 0usize;
-    let mut v = len;
-    loop {
-        n += 1;
-        if v < 0x80 { break; }
-        v >>= 7;
+#[derive(sqlx::FromRow)]
+struct TokenTransferRow {
+    txid: String,
+    instruction_index: i32,
+    block_height: i64,
+    block_time: Option<DateTime<Utc>>,
+    kind: String,
+    mint_address: String,
+    source_address: Option<String>,
+    destination_address: Option<String>,
+    source_owner: Option<String>,
+    destination_owner: Option<String>,
+    authority_address: Option<String>,
+    amount: String,
+    decimals: Option<i32>,
+    program_id: String,
+}
+
+/// Accepts unix seconds or RFC 3339 for the `start_time` / `end_time` filters.
+fn parse_time_param(params: &HashMap<String, String>, name: &str) -> Result<Option<DateTime<Utc>>, ApiError> {
+    let Some(raw) = params.get(name).filter(|v| !v.is_empty()) else { return Ok(None) };
+    if let Ok(secs) = raw.parse::<i64>() {
+        return DateTime::from_timestamp(secs, 0)
+            .map(Some)
+            .ok_or_else(|| ApiError::BadRequest(format!("Invalid {}", name)));
     }
-    n
-}
-
-fn estimate_tx_size(tx: &serde_json::Value) -> i64 {
-    // signatures
-    let sigs_len = tx.get("signatures")
-        .and_then(|a| a.as_array())
-        .map(|a| a.len()).unwrap_or(0);
-    let sigs_size = shortvec_len(sigs_len) + sigs_len * 64;
-
-    // message
-    let message = tx.get("message").unwrap_or(&serde_json::Value::Null);
-
-    // header is 3 bytes
-    let header_size = 3usize;
-
-    // account keys
-    let keys_len = message.get("account_keys").and_then(|a| a.as_array()).map(|a| a.len()).unwrap_or(0);
-    let keys_size = shortvec_len(keys_len) + keys_len * 32;
-
-    // recent_blockhash (32 bytes)
-    let rb_size = 32usize;
-
-    // instructions
-    let mut instr_size = 0usize;
-    if let Some(instrs) = message.get("instructions").and_then(|a| a.as_array()) {
-        instr_size += shortvec_len(instrs.len());
-        for ins in instrs {
-            let accounts_len = ins.get("accounts").and_then(|a| a.as_array()).map(|a| a.len()).unwrap_or(0);
-            let data_len = ins.get("data").and_then(|a| a.as_array()).map(|a| a.len()).unwrap_or(0);
-            instr_size += 1; // program_id_index
-            instr_size += shortvec_len(accounts_len) + accounts_len; // account indices (u8 each)
-            instr_size += shortvec_len(data_len) + data_len; // instruction data bytes
+    DateTime::parse_from_rfc3339(raw)
+        .map(|t| Some(t.with_timezone(&Utc)))
+        .map_err(|_| ApiError::BadRequest(format!("Invalid {}", name)))
+}
+
+/// Keyset cursor `<block_height>:<txid>:<instruction_index>` of the last row returned.
+fn parse_transfer_cursor(cursor: &str) -> Option<(i64, String, i32)> {
+    let mut parts = cursor.splitn(3, ':');
+    let height = parts.next()?.parse().ok()?;
+    let txid = parts.next()?.to_string();
+    let index = parts.next()?.parse().ok()?;
+    Some((height, txid, index))
+}
+
+fn b58_or_hex(hex_str: &str) -> String {
+    let b58 = try_hex_to_base58(hex_str);
+    if b58.is_empty() { hex_str.to_string() } else { b58 }
+}
+
+/// Lists `token_transfers` rows matching `predicate` (which binds the address as `$1`),
+/// newest first, with `start_time` / `end_time` filters and keyset pagination via `cursor`.
+async fn query_token_transfers(
+    pool: &PgPool,
+    predicate: &str,
+    address_hex: &str,
+    params: &HashMap<String, String>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let limit = params.get("limit").and_then(|v| v.parse::<i64>().ok()).map(|v| v.clamp(1, 200)).unwrap_or(50);
+    let start_time = parse_time_param(params, "start_time")?;
+    let end_time = parse_time_param(params, "end_time")?;
+    let cursor = match params.get("cursor").filter(|v| !v.is_empty()) {
+        Some(c) => Some(parse_transfer_cursor(c).ok_or(ApiError::BadRequest("Invalid cursor".into()))?),
+        None => None,
+    };
+    let (cursor_height, cursor_txid, cursor_index) = match cursor {
+        Some((h, t, i)) => (Some(h), Some(t), Some(i)),
+        None => (None, None, None),
+    };
+
+    let sql = format!(
+        r#"
+        SELECT txid, instruction_index, block_height, block_time, kind, mint_address,
+               source_address, destination_address, source_owner, destination_owner,
+               authority_address, amount::text AS amount, decimals, program_id
+        FROM token_transfers
+        WHERE {}
+          AND ($2::timestamptz IS NULL OR block_time >= $2)
+          AND ($3::timestamptz IS NULL OR block_time <= $3)
+          AND ($4::bigint IS NULL OR (block_height, txid, instruction_index) < ($4, $5::text, $6::int))
+        ORDER BY block_height DESC, txid DESC, instruction_index DESC
+        LIMIT $7
+        "#,
+        predicate
+    );
+    let mut rows = sqlx::query_as::<_, TokenTransferRow>(&sql)
+        .bind(address_hex)
+        .bind(start_time)
+        .bind(end_time)
+        .bind(cursor_height)
+        .bind(cursor_txid)
+        .bind(cursor_index)
+        .bind(limit + 1)
+        .fetch_all(pool)
+        .await?;
+
+    let next_cursor = if rows.len() as i64 > limit {
+        rows.truncate(limit as usize);
+        rows.last().map(|r| format!("{}:{}:{}", r.block_height, r.txid, r.instruction_index))
+    } else {
+        None
+    };
+    let transfers: Vec<serde_json::Value> = rows
+        .into_iter()
+        .map(|r| {
+            json!({
+                "txid": r.txid,
+                "instruction_index": r.instruction_index,
+                "block_height": r.block_height,
+                "block_time": r.block_time,
+                "kind": r.kind,
+                "mint_address": b58_or_hex(&r.mint_address),
+                "mint_address_hex": r.mint_address,
+                "source": r.source_address.as_deref().map(b58_or_hex),
+                "destination": r.destination_address.as_deref().map(b58_or_hex),
+                "source_owner": r.source_owner.as_deref().map(b58_or_hex),
+                "destination_owner": r.destination_owner.as_deref().map(b58_or_hex),
+                "authority": r.authority_address.as_deref().map(b58_or_hex),
+                "amount": r.amount,
+                "decimals": r.decimals,
+                "program_id": b58_or_hex(&r.program_id),
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "limit": limit,
+        "transfers": transfers,
+        "next_cursor": next_cursor
+    })))
+}
+
+/// Transfers, mints and burns of one token mint.
+pub async fn get_token_transfers(
+    State(pool): State<Arc<PgPool>>,
+    AxPath(mint): AxPath<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let mint_hex = normalize_program_param(&mint).ok_or(ApiError::BadRequest("Invalid mint".into()))?;
+    query_token_transfers(&pool, "mint_address = $1", &mint_hex, &params).await
+}
+
+/// Token movements of an address, matched either as the token account itself or as the
+/// owner of the source or destination token account.
+pub async fn get_account_token_transfers(
+    State(pool): State<Arc<PgPool>>,
+    AxPath(address): AxPath<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let address_hex = normalize_program_param(&address).ok_or(ApiError::BadRequest("Invalid address".into()))?;
+    query_token_transfers(
+        &pool,
+        "(source_address = $1 OR destination_address = $1 OR source_owner = $1 OR destination_owner = $1)",
+        &address_hex,
+        &params,
+    )
+    .await
+}
+
+#[derive(sqlx::FromRow)]
+struct NativeTransferRow {
+    txid: String,
+    instruction_index: i32,
+    block_height: i64,
+    block_time: Option<DateTime<Utc>>,
+    kind: String,
+    source_address: String,
+    destination_address: String,
+    lamports: String,
+}
+
+/// System Program transfers and account-creation fundings sent or received by an address,
+/// newest first, with the same `start_time` / `end_time` / `cursor` parameters as the token
+/// transfer lists.
+pub async fn get_account_native_transfers(
+    State(pool): State<Arc<PgPool>>,
+    AxPath(address): AxPath<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let address_hex = normalize_program_param(&address).ok_or(ApiError::BadRequest("Invalid address".into()))?;
+    let limit = params.get("limit").and_then(|v| v.parse::<i64>().ok()).map(|v| v.clamp(1, 200)).unwrap_or(50);
+    let start_time = parse_time_param(&params, "start_time")?;
+    let end_time = parse_time_param(&params, "end_time")?;
+    let cursor = match params.get("cursor").filter(|v| !v.is_empty()) {
+        Some(c) => Some(parse_transfer_cursor(c).ok_or(ApiError::BadRequest("Invalid cursor".into()))?),
+        None => None,
+    };
+    let (cursor_height, cursor_txid, cursor_index) = match cursor {
+        Some((h, t, i)) => (Some(h), Some(t), Some(i)),
+        None => (None, None, None),
+    };
+
+    let mut rows = sqlx::query_as::<_, NativeTransferRow>(
+        r#"
+        SELECT txid, instruction_index, block_height, block_time, kind,
+               source_address, destination_address, lamports::text AS lamports
+        FROM native_transfers
+        WHERE (source_address = $1 OR destination_address = $1)
+          AND ($2::timestamptz IS NULL OR block_time >= $2)
+          AND ($3::timestamptz IS NULL OR block_time <= $3)
+          AND ($4::bigint IS NULL OR (block_height, txid, instruction_index) < ($4, $5::text, $6::int))
+        ORDER BY block_height DESC, txid DESC, instruction_index DESC
+        LIMIT $7
+        "#
+    )
+    .bind(&address_hex)
+    .bind(start_time)
+    .bind(end_time)
+    .bind(cursor_height)
+    .bind(cursor_txid)
+    .bind(cursor_index)
+    .bind(limit + 1)
+    .fetch_all(&*pool)
+    .await?;
+
+    let next_cursor = if rows.len() as i64 > limit {
+        rows.truncate(limit as usize);
+        rows.last().map(|r| format!("{}:{}:{}", r.block_height, r.txid, r.instruction_index))
+    } else {
+        None
+    };
+    let transfers: Vec<serde_json::Value> = rows
+        .into_iter()
+        .map(|r| {
+            let direction = match (r.source_address == address_hex, r.destination_address == address_hex) {
+                (true, true) => "self",
+                (true, false) => "out",
+                _ => "in",
+            };
+            json!({
+                "txid": r.txid,
+                "instruction_index": r.instruction_index,
+                "block_height": r.block_height,
+                "block_time": r.block_time,
+                "kind": r.kind,
+                "direction": direction,
+                "source": b58_or_hex(&r.source_address),
+                "source_hex": r.source_address,
+                "destination": b58_or_hex(&r.destination_address),
+                "destination_hex": r.destination_address,
+                "lamports": r.lamports,
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "limit": limit,
+        "transfers": transfers,
+        "next_cursor": next_cursor
+    })))
+}
+
+/// Native balance of an address as OHLC points bucketed by `interval` (`hour` or `day`,
+/// default `day`) between `from` and `to` (unix seconds or RFC 3339). Only buckets in which
+/// the balance changed are returned; `open` is the balance carried in from the previous
+/// change, so `high` and `low` include it.
+pub async fn get_account_balance_history(
+    State(pool): State<Arc<PgPool>>,
+    AxPath(address): AxPath<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let address_hex = normalize_program_param(&address).ok_or(ApiError::BadRequest("Invalid address".into()))?;
+    let interval = match params.get("interval").map(|s| s.as_str()).unwrap_or("day") {
+        "hour" => "hour",
+        "day" => "day",
+        other => return Err(ApiError::BadRequest(format!("Invalid interval '{}': expected hour or day", other))),
+    };
+    let limit = params.get("limit").and_then(|v| v.parse::<i64>().ok()).map(|v| v.clamp(1, 2000)).unwrap_or(500);
+    let from = parse_time_param(&params, "from")?;
+    let to = parse_time_param(&params, "to")?;
+
+    let rows = sqlx::query(
+        r#"
+        WITH buckets AS (
+            SELECT date_trunc($2, block_time) AS bucket,
+                   (array_agg(balance ORDER BY block_height DESC))[1] AS close,
+                   MAX(balance) AS high,
+                   MIN(balance) AS low,
+                   COUNT(*) AS changes,
+                   MIN(block_height) AS first_block_height,
+                   MAX(block_height) AS last_block_height
+            FROM native_balance_history
+            WHERE address_hex = $1
+              AND block_time IS NOT NULL
+              AND ($3::timestamptz IS NULL OR block_time >= $3)
+              AND ($4::timestamptz IS NULL OR block_time <= $4)
+            GROUP BY bucket
+        ),
+        opened AS (
+            SELECT b.*,
+                   COALESCE(
+                       LAG(b.close) OVER (ORDER BY b.bucket),
+                       (SELECT h.balance FROM native_balance_history h
+                        WHERE h.address_hex = $1 AND h.block_height < b.first_block_height
+                        ORDER BY h.block_height DESC LIMIT 1),
+                       0
+                   ) AS open
+            FROM buckets b
+        )
+        SELECT bucket, open::text AS open, GREATEST(high, open)::text AS high, LEAST(low, open)::text AS low,
+               close::text AS close, changes, first_block_height, last_block_height
+        FROM opened
+        ORDER BY bucket
+        LIMIT $5
+        "#
+    )
+    .bind(&address_hex)
+    .bind(interval)
+    .bind(from)
+    .bind(to)
+    .bind(limit)
+    .fetch_all(&*pool)
+    .await?;
+
+    let points: Vec<serde_json::Value> = rows
+        .into_iter()
+        .map(|r| {
+            json!({
+                "bucket": r.get::<DateTime<Utc>, _>("bucket"),
+                "open": r.get::<String, _>("open"),
+                "high": r.get::<String, _>("high"),
+                "low": r.get::<String, _>("low"),
+                "close": r.get::<String, _>("close"),
+                "changes": r.get::<i64, _>("changes"),
+                "first_block_height": r.get::<i64, _>("first_block_height"),
+                "last_block_height": r.get::<i64, _>("last_block_height"),
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "address": b58_or_hex(&address_hex),
+        "address_hex": address_hex,
+        "interval": interval,
+        "points": points
+    })))
+}
+
+/// Block height a point-in-time query is answered at: `at_height`, or the last block at or
+/// before `at_time` (unix seconds or RFC 3339). Returns the height and that block's time.
+async fn resolve_snapshot_height(pool: &PgPool, params: &HashMap<String, String>) -> Result<(i64, Option<DateTime<Utc>>), ApiError> {
+    if let Some(h) = params.get("at_height").filter(|v| !v.is_empty()) {
+        let height = h.parse::<i64>().map_err(|_| ApiError::BadRequest(format!("Invalid at_height '{}'", h)))?;
+        let time: Option<DateTime<Utc>> = sqlx::query_scalar("SELECT timestamp FROM blocks WHERE height = $1")
+            .bind(height)
+            .fetch_optional(pool)
+            .await?;
+        return Ok((height, time));
+    }
+    let Some(at_time) = parse_time_param(params, "at_time")? else {
+        return Err(ApiError::BadRequest("Missing at_height or at_time".into()));
+    };
+    let row = sqlx::query("SELECT height, timestamp FROM blocks WHERE timestamp <= $1 ORDER BY timestamp DESC, height DESC LIMIT 1")
+        .bind(at_time)
+        .fetch_optional(pool)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+    Ok((row.get("height"), Some(row.get("timestamp"))))
+}
+
+/// Native and token balances of an address as of `at_height` or `at_time`. The native
+/// balance is the last `native_balance_history` entry at or below the height; token
+/// balances replay the `token_transfers` ledger of every token account that is the address
+/// or was owned by it at that height, so they only cover activity since indexing started.
+/// The owner at the height comes from the account's last `set_owner` event at or below it,
+/// else the previous owner of its first later one, else the owner its ledger rows record.
+pub async fn get_account_balances_at(
+    State(pool): State<Arc<PgPool>>,
+    AxPath(address): AxPath<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let address_hex = normalize_program_param(&address).ok_or(ApiError::BadRequest("Invalid address".into()))?;
+    let (height, block_time) = resolve_snapshot_height(&pool, &params).await?;
+
+    let native = sqlx::query(
+        r#"
+        SELECT balance::text AS balance, block_height
+        FROM native_balance_history
+        WHERE address_hex = $1 AND block_height <= $2
+        ORDER BY block_height DESC
+        LIMIT 1
+        "#
+    )
+    .bind(&address_hex)
+    .bind(height)
+    .fetch_optional(&*pool)
+    .await?;
+
+    let rows = sqlx::query(
+        r#"
+        WITH accounts AS (
+            SELECT destination_address AS account FROM token_transfers
+            WHERE (destination_address = $1 OR destination_owner = $1) AND block_height <= $2
+            UNION
+            SELECT source_address FROM token_transfers
+            WHERE (source_address = $1 OR source_owner = $1) AND block_height <= $2
+            UNION
+            SELECT token_account FROM token_account_authority_events
+            WHERE kind = 'set_owner' AND new_authority = $1 AND block_height <= $2
+        ),
+        owners AS (
+            SELECT a.account,
+                   CASE WHEN before.txid IS NOT NULL THEN before.new_authority
+                        WHEN after.txid IS NOT NULL THEN after.previous_authority
+                   END AS owner
+            FROM accounts a
+            LEFT JOIN LATERAL (
+                SELECT e.txid, e.new_authority FROM token_account_authority_events e
+                WHERE e.token_account = a.account AND e.kind = 'set_owner' AND e.block_height <= $2
+                ORDER BY e.block_height DESC, e.txid DESC, e.instruction_index DESC
+                LIMIT 1
+            ) before ON TRUE
+            LEFT JOIN LATERAL (
+                SELECT e.txid, e.previous_authority FROM token_account_authority_events e
+                WHERE e.token_account = a.account AND e.kind = 'set_owner' AND e.block_height > $2
+                ORDER BY e.block_height, e.txid, e.instruction_index
+                LIMIT 1
+            ) after ON TRUE
+        ),
+        moves AS (
+            SELECT tt.destination_address AS account, tt.destination_owner AS owner, tt.mint_address, tt.amount AS delta,
+                   tt.block_height, tt.txid, tt.instruction_index
+            FROM token_transfers tt JOIN accounts a ON a.account = tt.destination_address
+            WHERE tt.block_height <= $2
+            UNION ALL
+            SELECT tt.source_address, tt.source_owner, tt.mint_address, -tt.amount,
+                   tt.block_height, tt.txid, tt.instruction_index
+            FROM token_transfers tt JOIN accounts a ON a.account = tt.source_address
+            WHERE tt.block_height <= $2
+        ),
+        balances AS (
+            SELECT m.account, m.mint_address,
+                   COALESCE(o.owner, (array_agg(m.owner ORDER BY m.block_height DESC, m.txid DESC, m.instruction_index DESC)
+                       FILTER (WHERE m.owner IS NOT NULL))[1]) AS owner,
+                   SUM(m.delta) AS balance
+            FROM moves m
+            JOIN owners o ON o.account = m.account
+            GROUP BY m.account, m.mint_address, o.owner
+        )
+        SELECT b.account, b.mint_address, b.owner, b.balance::text AS balance,
+               tm.decimals, md.symbol, md.name
+        FROM balances b
+        LEFT JOIN token_mints tm ON tm.mint_address = b.mint_address
+        LEFT JOIN token_metadata md ON md.mint_address = b.mint_address
+        WHERE b.balance > 0 AND (b.account = $1 OR b.owner = $1)
+        ORDER BY b.mint_address, b.account
+        "#
+    )
+    .bind(&address_hex)
+    .bind(height)
+    .fetch_all(&*pool)
+    .await?;
+
+    let tokens: Vec<serde_json::Value> = rows
+        .into_iter()
+        .map(|r| {
+            let account: String = r.get("account");
+            let mint: String = r.get("mint_address");
+            let owner: Option<String> = r.get("owner");
+            json!({
+                "account": b58_or_hex(&account),
+                "account_hex": account,
+                "mint_address": b58_or_hex(&mint),
+                "mint_address_hex": mint,
+                "owner": owner.as_deref().map(b58_or_hex),
+                "balance": r.get::<String, _>("balance"),
+                "decimals": r.get::<Option<i32>, _>("decimals"),
+                "symbol": r.get::<Option<String>, _>("symbol"),
+                "name": r.get::<Option<String>, _>("name"),
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "address": b58_or_hex(&address_hex),
+        "address_hex": address_hex,
+        "block_height": height,
+        "block_time": block_time,
+        "native": {
+            "balance": native.as_ref().map(|r| r.get::<String, _>("balance")).unwrap_or_else(|| "0".to_string()),
+            "as_of_height": native.as_ref().map(|r| r.get::<i64, _>("block_height")),
+        },
+        "tokens": tokens
+    })))
+}
+
+/// Every token account of a mint with a positive balance as of `at_height` or `at_time`,
+/// replayed from the `token_transfers` ledger, largest first with the holders' `cursor`
+/// pagination. `owner` is the owner recorded by the account's latest movement.
+pub async fn get_token_holders_snapshot(
+    State(pool): State<Arc<PgPool>>,
+    AxPath(mint): AxPath<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let mint_hex = normalize_program_param(&mint).ok_or(ApiError::BadRequest("Invalid mint".into()))?;
+    let (height, block_time) = resolve_snapshot_height(&pool, &params).await?;
+    let limit = params.get("limit").and_then(|v| v.parse::<i64>().ok()).map(|v| v.clamp(1, 1000)).unwrap_or(200);
+    let (cursor_balance, cursor_account) = match params.get("cursor").filter(|v| !v.is_empty()) {
+        Some(c) => {
+            let (balance, account) = parse_holder_cursor(c).ok_or(ApiError::BadRequest("Invalid cursor".into()))?;
+            (Some(balance), Some(account))
         }
+        None => (None, None),
+    };
+
+    let supply: Option<String> = sqlx::query_scalar(
+        r#"
+        SELECT supply_after::text FROM token_mint_events
+        WHERE mint_address = $1 AND block_height <= $2
+        ORDER BY block_height DESC, txid DESC, instruction_index DESC
+        LIMIT 1
+        "#
+    )
+    .bind(&mint_hex)
+    .bind(height)
+    .fetch_optional(&*pool)
+    .await?;
+
+    let mut rows = sqlx::query(
+        r#"
+        WITH moves AS (
+            SELECT destination_address AS account, destination_owner AS owner, amount AS delta,
+                   block_height, txid, instruction_index
+            FROM token_transfers
+            WHERE mint_address = $1 AND block_height <= $2 AND destination_address IS NOT NULL
+            UNION ALL
+            SELECT source_address, source_owner, -amount, block_height, txid, instruction_index
+            FROM token_transfers
+            WHERE mint_address = $1 AND block_height <= $2 AND source_address IS NOT NULL
+        ),
+        balances AS (
+            SELECT account,
+                   (array_agg(owner ORDER BY block_height DESC, txid DESC, instruction_index DESC)
+                       FILTER (WHERE owner IS NOT NULL))[1] AS owner,
+                   SUM(delta) AS balance
+            FROM moves
+            GROUP BY account
+        )
+        SELECT account, owner, balance::text AS balance
+        FROM balances
+        WHERE balance > 0
+          AND ($3::numeric IS NULL OR (balance, account) < ($3::numeric, $4::text))
+        ORDER BY balance DESC, account DESC
+        LIMIT $5
+        "#
+    )
+    .bind(&mint_hex)
+    .bind(height)
+    .bind(cursor_balance)
+    .bind(cursor_account)
+    .bind(limit + 1)
+    .fetch_all(&*pool)
+    .await?;
+
+    let next_cursor = if rows.len() as i64 > limit {
+        rows.truncate(limit as usize);
+        rows.last().map(|r| format!("{}:{}", r.get::<String, _>("balance"), r.get::<String, _>("account")))
+    } else {
+        None
+    };
+    let holders: Vec<serde_json::Value> = rows
+        .into_iter()
+        .map(|r| {
+            let account: String = r.get("account");
+            let owner: Option<String> = r.get("owner");
+            json!({
+                "account": b58_or_hex(&account),
+                "account_hex": account,
+                "owner": owner.as_deref().map(b58_or_hex),
+                "owner_hex": owner,
+                "balance": r.get::<String, _>("balance"),
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "mint_address": b58_or_hex(&mint_hex),
+        "mint_address_hex": mint_hex,
+        "block_height": height,
+        "block_time": block_time,
+        "supply": supply,
+        "limit": limit,
+        "holders": holders,
+        "next_cursor": next_cursor
+    })))
+}
+
+/// State changes of an account recorded by the Atlas account processor, newest first.
+/// Deletions appear as tombstones (`is_deleted`, no lamports/owner/data). `cursor` is the
+/// height of the last entry returned.
+pub async fn get_account_history(
+    State(pool): State<Arc<PgPool>>,
+    AxPath(address): AxPath<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let address_hex = normalize_program_param(&address).ok_or(ApiError::BadRequest("Invalid address".into()))?;
+    let limit = params.get("limit").and_then(|v| v.parse::<i64>().ok()).map(|v| v.clamp(1, 200)).unwrap_or(50);
+    let cursor = match params.get("cursor").filter(|v| !v.is_empty()) {
+        Some(c) => Some(c.parse::<i64>().map_err(|_| ApiError::BadRequest("Invalid cursor".into()))?),
+        None => None,
+    };
+
+    let mut rows = sqlx::query(
+        r#"
+        SELECT height, lamports, owner, data_hash, octet_length(data) AS data_len, is_deleted, recorded_at
+        FROM account_history
+        WHERE pubkey = $1 AND ($2::bigint IS NULL OR height < $2)
+        ORDER BY height DESC
+        LIMIT $3
+        "#
+    )
+    .bind(&address_hex)
+    .bind(cursor)
+    .bind(limit + 1)
+    .fetch_all(&*pool)
+    .await?;
+
+    let next_cursor = if rows.len() as i64 > limit {
+        rows.truncate(limit as usize);
+        rows.last().map(|r| r.get::<i64, _>("height").to_string())
     } else {
-        instr_size += shortvec_len(0);
+        None
+    };
+    let changes: Vec<serde_json::Value> = rows
+        .into_iter()
+        .map(|r| {
+            json!({
+                "height": r.get::<i64, _>("height"),
+                "lamports": r.get::<Option<i64>, _>("lamports"),
+                "owner": r.get::<Option<String>, _>("owner"),
+                "data_hash": r.get::<Option<String>, _>("data_hash"),
+                "data_len": r.get::<Option<i32>, _>("data_len"),
+                "is_deleted": r.get::<bool, _>("is_deleted"),
+                "recorded_at": r.get::<DateTime<Utc>, _>("recorded_at"),
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "address": b58_or_hex(&address_hex),
+        "address_hex": address_hex,
+        "limit": limit,
+        "changes": changes,
+        "next_cursor": next_cursor
+    })))
+}
+
+/// The account's last recorded state at or below `at_height` (or the block at `at_time`),
+/// with its data as hex. A tombstone means the account was deleted by then.
+pub async fn get_account_state_at(
+    State(pool): State<Arc<PgPool>>,
+    AxPath(address): AxPath<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let address_hex = normalize_program_param(&address).ok_or(ApiError::BadRequest("Invalid address".into()))?;
+    let (height, block_time) = resolve_snapshot_height(&pool, &params).await?;
+
+    let row = sqlx::query(
+        r#"
+        SELECT height, lamports, owner, data, data_hash, is_deleted, recorded_at
+        FROM account_history
+        WHERE pubkey = $1 AND height <= $2
+        ORDER BY height DESC
+        LIMIT 1
+        "#
+    )
+    .bind(&address_hex)
+    .bind(height)
+    .fetch_optional(&*pool)
+    .await?
+    .ok_or(ApiError::NotFound)?;
+
+    let data: Option<Vec<u8>> = row.get("data");
+    Ok(Json(json!({
+        "address": b58_or_hex(&address_hex),
+        "address_hex": address_hex,
+        "at_height": height,
+        "at_block_time": block_time,
+        "state_height": row.get::<i64, _>("height"),
+        "lamports": row.get::<Option<i64>, _>("lamports"),
+        "owner": row.get::<Option<String>, _>("owner"),
+        "data": data.as_ref().map(hex::encode),
+        "data_len": data.as_ref().map(|d| d.len()),
+        "data_hash": row.get::<Option<String>, _>("data_hash"),
+        "is_deleted": row.get::<bool, _>("is_deleted"),
+        "recorded_at": row.get::<DateTime<Utc>, _>("recorded_at"),
+    })))
+}
+
+/// Mint, authority and metadata details of a token with holder, transfer and activity
+/// statistics. Holders are distinct owners (the token account when the owner is unknown)
+/// of open token accounts with a positive balance.
+pub async fn get_token_details(
+    State(pool): State<Arc<PgPool>>,
+    AxPath(mint): AxPath<String>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let mint_hex = normalize_program_param(&mint).ok_or(ApiError::BadRequest("Invalid mint".into()))?;
+
+    let row = sqlx::query(
+        r#"
+        SELECT tm.mint_address, tm.program_id, tm.decimals, tm.supply::text AS supply, tm.is_frozen,
+               tm.mint_authority, tm.freeze_authority, tm.first_seen_at, tm.last_seen_at,
+               md.metadata_address, md.name, md.symbol, md.uri, md.update_authority, md.additional_metadata
+        FROM token_mints tm
+        LEFT JOIN token_metadata md ON md.mint_address = tm.mint_address
+        WHERE tm.mint_address = $1
+        "#
+    )
+    .bind(&mint_hex)
+    .fetch_optional(&*pool)
+    .await?
+    .ok_or(ApiError::NotFound)?;
+
+    let holders = sqlx::query(
+        r#"
+        SELECT COUNT(DISTINCT COALESCE(ta.owner_address_hex, tb.owner_address, tb.account_address)) AS holder_count,
+               COUNT(*) AS holder_accounts
+        FROM token_balances tb
+        LEFT JOIN token_accounts ta ON ta.token_account_hex = tb.account_address
+        WHERE tb.mint_address = $1 AND tb.balance > 0 AND NOT COALESCE(ta.is_closed, FALSE)
+        "#
+    )
+    .bind(&mint_hex)
+    .fetch_one(&*pool)
+    .await?;
+
+    let activity = sqlx::query(
+        r#"
+        SELECT COUNT(*) FILTER (WHERE kind = 'transfer') AS transfer_count,
+               COUNT(*) FILTER (WHERE kind = 'mint') AS mint_count,
+               COUNT(*) FILTER (WHERE kind = 'burn') AS burn_count,
+               MIN(block_height) AS first_height, MAX(block_height) AS last_height,
+               MIN(block_time) AS first_time, MAX(block_time) AS last_time
+        FROM token_transfers
+        WHERE mint_address = $1
+        "#
+    )
+    .bind(&mint_hex)
+    .fetch_one(&*pool)
+    .await?;
+
+    let program_id: String = row.get("program_id");
+    let metadata = row.get::<Option<String>, _>("metadata_address").map(|metadata_address| {
+        json!({
+            "metadata_address": b58_or_hex(&metadata_address),
+            "name": row.get::<Option<String>, _>("name"),
+            "symbol": row.get::<Option<String>, _>("symbol"),
+            "uri": row.get::<Option<String>, _>("uri"),
+            "update_authority": row.get::<Option<String>, _>("update_authority").as_deref().map(b58_or_hex),
+            "additional_metadata": row.get::<Option<serde_json::Value>, _>("additional_metadata"),
+        })
+    });
+
+    Ok(Json(json!({
+        "mint_address": b58_or_hex(&mint_hex),
+        "mint_address_hex": mint_hex,
+        "program_id": b58_or_hex(&program_id),
+        "program_name": fallback_program_name_from_hex(&program_id),
+        "decimals": row.get::<i32, _>("decimals"),
+        "supply": row.get::<String, _>("supply"),
+        "is_frozen": row.get::<Option<bool>, _>("is_frozen"),
+        "mint_authority": row.get::<Option<String>, _>("mint_authority").as_deref().map(b58_or_hex),
+        "freeze_authority": row.get::<Option<String>, _>("freeze_authority").as_deref().map(b58_or_hex),
+        "metadata": metadata,
+        "holder_count": holders.get::<i64, _>("holder_count"),
+        "holder_accounts": holders.get::<i64, _>("holder_accounts"),
+        "transfer_count": activity.get::<i64, _>("transfer_count"),
+        "mint_count": activity.get::<i64, _>("mint_count"),
+        "burn_count": activity.get::<i64, _>("burn_count"),
+        "first_activity": {
+            "block_height": activity.get::<Option<i64>, _>("first_height"),
+            "block_time": activity.get::<Option<DateTime<Utc>>, _>("first_time"),
+        },
+        "last_activity": {
+            "block_height": activity.get::<Option<i64>, _>("last_height"),
+            "block_time": activity.get::<Option<DateTime<Utc>>, _>("last_time"),
+        },
+        "first_seen_at": row.get::<DateTime<Utc>, _>("first_seen_at"),
+        "last_seen_at": row.get::<DateTime<Utc>, _>("last_seen_at"),
+    })))
+}
+
+#[derive(sqlx::FromRow)]
+struct TokenHolderRow {
+    account_address: String,
+    owner_address: Option<String>,
+    balance: String,
+    decimals: i32,
+    percent_of_supply: Option<f64>,
+    is_frozen: Option<bool>,
+    last_updated: DateTime<Utc>,
+}
+
+/// Keyset cursor `<balance>:<token account hex>` of the last holder returned.
+fn parse_holder_cursor(cursor: &str) -> Option<(String, String)> {
+    let (balance, account) = cursor.split_once(':')?;
+    if balance.is_empty() || !balance.chars().all(|c| c.is_ascii_digit()) || account.is_empty() {
+        return None;
     }
+    Some((balance.to_string(), account.to_string()))
+}
 
-    // approximate message size
-    let message_size = header_size + keys_size + rb_size + instr_size;
-    (sigs_size + message_size) as i64
+/// Open token accounts of a mint with a positive balance, largest first, with their
+/// resolved owner and share of the current supply. Paginated with `cursor`.
+pub async fn get_token_holders(
+    State(pool): State<Arc<PgPool>>,
+    AxPath(mint): AxPath<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let mint_hex = normalize_program_param(&mint).ok_or(ApiError::BadRequest("Invalid mint".into()))?;
+    let limit = params.get("limit").and_then(|v| v.parse::<i64>().ok()).map(|v| v.clamp(1, 200)).unwrap_or(50);
+    let (cursor_balance, cursor_account) = match params.get("cursor").filter(|v| !v.is_empty()) {
+        Some(c) => {
+            let (balance, account) = parse_holder_cursor(c).ok_or(ApiError::BadRequest("Invalid cursor".into()))?;
+            (Some(balance), Some(account))
+        }
+        None => (None, None),
+    };
+
+    let mut rows = sqlx::query_as::<_, TokenHolderRow>(
+        r#"
+        SELECT tb.account_address,
+               COALESCE(ta.owner_address_hex, tb.owner_address) AS owner_address,
+               tb.balance::text AS balance,
+               tb.decimals,
+               CASE WHEN tm.supply > 0 THEN (tb.balance * 100 / tm.supply)::float8 END AS percent_of_supply,
+               ta.is_frozen,
+               tb.last_updated
+        FROM token_balances tb
+        LEFT JOIN token_accounts ta ON ta.token_account_hex = tb.account_address
+        LEFT JOIN token_mints tm ON tm.mint_address = tb.mint_address
+        WHERE tb.mint_address = $1
+          AND tb.balance > 0
+          AND NOT COALESCE(ta.is_closed, FALSE)
+          AND ($2::numeric IS NULL OR (tb.balance, tb.account_address) < ($2::numeric, $3::text))
+        ORDER BY tb.balance DESC, tb.account_address DESC
+        LIMIT $4
+        "#
+    )
+    .bind(&mint_hex)
+    .bind(cursor_balance)
+    .bind(cursor_account)
+    .bind(limit + 1)
+    .fetch_all(&*pool)
+    .await?;
+
+    let next_cursor = if rows.len() as i64 > limit {
+        rows.truncate(limit as usize);
+        rows.last().map(|r| format!("{}:{}", r.balance, r.account_address))
+    } else {
+        None
+    };
+    let holders: Vec<serde_json::Value> = rows
+        .into_iter()
+        .map(|r| {
+            json!({
+                "account": b58_or_hex(&r.account_address),
+                "account_hex": r.account_address,
+                "owner": r.owner_address.as_deref().map(b58_or_hex),
+                "owner_hex": r.owner_address,
+                "balance": r.balance,
+                "decimals": r.decimals,
+                "percent_of_supply": r.percent_of_supply,
+                "is_frozen": r.is_frozen,
+                "last_updated": r.last_updated,
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "mint_address": b58_or_hex(&mint_hex),
+        "mint_address_hex": mint_hex,
+        "limit": limit,
+        "holders": holders,
+        "next_cursor": next_cursor
+    })))
+}
+
+/// Supply of a token bucketed by `interval` (`hour` or `day`, default `day`) from its mints
+/// and burns. Only buckets with activity are returned; `supply` is the supply after the
+/// last mint or burn of the bucket and `opening_supply` the supply before the first one.
+pub async fn get_token_supply_history(
+    State(pool): State<Arc<PgPool>>,
+    AxPath(mint): AxPath<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let mint_hex = normalize_program_param(&mint).ok_or(ApiError::BadRequest("Invalid mint".into()))?;
+    let interval = match params.get("interval").map(|s| s.as_str()).unwrap_or("day") {
+        "hour" => "hour",
+        "day" => "day",
+        other => return Err(ApiError::BadRequest(format!("Invalid interval '{}': expected hour or day", other))),
+    };
+    let limit = params.get("limit").and_then(|v| v.parse::<i64>().ok()).map(|v| v.clamp(1, 2000)).unwrap_or(500);
+    let start_time = parse_time_param(&params, "start_time")?;
+    let end_time = parse_time_param(&params, "end_time")?;
+
+    let rows = sqlx::query(
+        r#"
+        SELECT date_trunc($2, block_time) AS bucket,
+               COALESCE(SUM(amount) FILTER (WHERE kind = 'mint'), 0)::text AS minted,
+               COALESCE(SUM(amount) FILTER (WHERE kind = 'burn'), 0)::text AS burned,
+               COUNT(*) FILTER (WHERE kind = 'mint') AS mint_count,
+               COUNT(*) FILTER (WHERE kind = 'burn') AS burn_count,
+               ((array_agg(supply_after ORDER BY block_height, txid, instruction_index))[1]
+                   - COALESCE((array_agg(CASE WHEN kind = 'mint' THEN amount ELSE -amount END ORDER BY block_height, txid, instruction_index))[1], 0))::text AS opening_supply,
+               ((array_agg(supply_after ORDER BY block_height DESC, txid DESC, instruction_index DESC))[1])::text AS supply,
+               MIN(block_height) AS first_block_height,
+               MAX(block_height) AS last_block_height
+        FROM token_mint_events
+        WHERE mint_address = $1
+          AND kind IN ('mint', 'burn')
+          AND block_time IS NOT NULL
+          AND ($3::timestamptz IS NULL OR block_time >= $3)
+          AND ($4::timestamptz IS NULL OR block_time <= $4)
+        GROUP BY bucket
+        ORDER BY bucket
+        LIMIT $5
+        "#
+    )
+    .bind(&mint_hex)
+    .bind(interval)
+    .bind(start_time)
+    .bind(end_time)
+    .bind(limit)
+    .fetch_all(&*pool)
+    .await?;
+
+    let buckets: Vec<serde_json::Value> = rows
+        .into_iter()
+        .map(|r| {
+            json!({
+                "bucket": r.get::<DateTime<Utc>, _>("bucket"),
+                "minted": r.get::<String, _>("minted"),
+                "burned": r.get::<String, _>("burned"),
+                "mint_count": r.get::<i64, _>("mint_count"),
+                "burn_count": r.get::<i64, _>("burn_count"),
+                "opening_supply": r.get::<String, _>("opening_supply"),
+                "supply": r.get::<String, _>("supply"),
+                "first_block_height": r.get::<i64, _>("first_block_height"),
+                "last_block_height": r.get::<i64, _>("last_block_height"),
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "mint_address": b58_or_hex(&mint_hex),
+        "mint_address_hex": mint_hex,
+        "interval": interval,
+        "buckets": buckets
+    })))
+}
+
+#[derive(sqlx::FromRow)]
+struct TokenMintEventRow {
+    txid: String,
+    instruction_index: i32,
+    block_height: i64,
+    block_time: Option<DateTime<Utc>>,
+    kind: String,
+    amount: Option<String>,
+    supply_after: String,
+    previous_authority: Option<String>,
+    new_authority: Option<String>,
+}
+
+/// Timeline of a token's initialization, mints, burns and mint/freeze authority changes,
+/// newest first, optionally filtered by `kind`, with the transfer lists' `start_time` /
+/// `end_time` / `cursor` parameters.
+pub async fn get_token_supply_events(
+    State(pool): State<Arc<PgPool>>,
+    AxPath(mint): AxPath<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let mint_hex = normalize_program_param(&mint).ok_or(ApiError::BadRequest("Invalid mint".into()))?;
+    let limit = params.get("limit").and_then(|v| v.parse::<i64>().ok()).map(|v| v.clamp(1, 200)).unwrap_or(50);
+    let kind = params.get("kind").filter(|v| !v.is_empty()).cloned();
+    let start_time = parse_time_param(&params, "start_time")?;
+    let end_time = parse_time_param(&params, "end_time")?;
+    let cursor = match params.get("cursor").filter(|v| !v.is_empty()) {
+        Some(c) => Some(parse_transfer_cursor(c).ok_or(ApiError::BadRequest("Invalid cursor".into()))?),
+        None => None,
+    };
+    let (cursor_height, cursor_txid, cursor_index) = match cursor {
+        Some((h, t, i)) => (Some(h), Some(t), Some(i)),
+        None => (None, None, None),
+    };
+
+    let mut rows = sqlx::query_as::<_, TokenMintEventRow>(
+        r#"
+        SELECT txid, instruction_index, block_height, block_time, kind, amount::text AS amount,
+               supply_after::text AS supply_after, previous_authority, new_authority
+        FROM token_mint_events
+        WHERE mint_address = $1
+          AND ($2::text IS NULL OR kind = $2)
+          AND ($3::timestamptz IS NULL OR block_time >= $3)
+          AND ($4::timestamptz IS NULL OR block_time <= $4)
+          AND ($5::bigint IS NULL OR (block_height, txid, instruction_index) < ($5, $6::text, $7::int))
+        ORDER BY block_height DESC, txid DESC, instruction_index DESC
+        LIMIT $8
+        "#
+    )
+    .bind(&mint_hex)
+    .bind(kind)
+    .bind(start_time)
+    .bind(end_time)
+    .bind(cursor_height)
+    .bind(cursor_txid)
+    .bind(cursor_index)
+    .bind(limit + 1)
+    .fetch_all(&*pool)
+    .await?;
+
+    let next_cursor = if rows.len() as i64 > limit {
+        rows.truncate(limit as usize);
+        rows.last().map(|r| format!("{}:{}:{}", r.block_height, r.txid, r.instruction_index))
+    } else {
+        None
+    };
+    let events: Vec<serde_json::Value> = rows
+        .into_iter()
+        .map(|r| {
+            json!({
+                "txid": r.txid,
+                "instruction_index": r.instruction_index,
+                "block_height": r.block_height,
+                "block_time": r.block_time,
+                "kind": r.kind,
+                "amount": r.amount,
+                "supply_after": r.supply_after,
+                "previous_authority": r.previous_authority.as_deref().map(b58_or_hex),
+                "new_authority": r.new_authority.as_deref().map(b58_or_hex),
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "mint_address": b58_or_hex(&mint_hex),
+        "mint_address_hex": mint_hex,
+        "limit": limit,
+        "events": events,
+        "next_cursor": next_cursor
+    })))
 }
 
 pub async fn get_blocks(
@@ -1492,11 +2516,10 @@ pub async fn get_blocks(
             b.hash,
             b.timestamp::timestamptz as timestamp,
             b.bitcoin_block_height,
-            COALESCE(COUNT(t.txid), 0) as transaction_count,
-            NULL::bigint as block_size_bytes
+            COALESCE(b.transaction_count, (SELECT COUNT(*) FROM transactions t WHERE t.block_height = b.height)) as transaction_count,
+            b.previous_block_hash,
+            b.block_size_bytes
         FROM blocks b 
-        LEFT JOIN transactions t ON b.height = t.block_height
-        GROUP BY b.height, b.hash, b.timestamp, b.bitcoin_block_height
         ORDER BY b.height DESC 
         LIMIT $1 OFFSET $2
         "#
@@ -1600,7 +2623,8 @@ pub async fn get_block_gaps(State(pool): State<Arc<PgPool>>) -> Result<Json<serd
     })))
 }
 
-/// Backfill missing blocks by fetching from RPC and inserting into DB.
+/// Backfill missing blocks by fetching them from RPC and storing them through the
+/// indexer's write path, so their transactions and derived rows are written with them.
 /// WARNING: Long-running; use small limits in production.
 pub async fn backfill_missing_blocks(
     State(pool): State<Arc<PgPool>>,
@@ -1651,77 +2675,55 @@ pub async fn backfill_missing_blocks(
         return Ok(Json(json!({ "processed": 0, "message": "no gaps detected" })));
     }
 
-    let rpc_url = std::env::var("ARCH_NODE_URL").unwrap_or_else(|_| "http://localhost:8081".to_string());
-    let arch_client = ArchRpcClient::new(rpc_url);
+    let rpc = crate::arch_rpc::indexer_client();
 
     let mut processed: i64 = 0;
     for &height in &to_fill {
-        // Fetch block
-        let hash = match arch_client.get_block_hash(height).await {
-            Ok(h) => h,
-            Err(e) => { error!("backfill: get_block_hash {} failed: {:?}", height, e); continue; }
-        };
-        let block = match arch_client.get_block(&hash, height).await {
-            Ok(b) => b,
-            Err(e) => { error!("backfill: get_block {} failed: {:?}", height, e); continue; }
-        };
-
-        // Insert block
-        let timestamp = crate::utils::convert_arch_timestamp(block.timestamp);
-        if let Err(e) = sqlx::query(
-            r#"INSERT INTO blocks (height, hash, timestamp, bitcoin_block_height)
-               VALUES ($1, $2, $3, $4)
-               ON CONFLICT (height) DO UPDATE SET hash = EXCLUDED.hash, timestamp = EXCLUDED.timestamp, bitcoin_block_height = EXCLUDED.bitcoin_block_height"#
-        )
-        .bind(block.height)
-        .bind(&block.hash)
-        .bind(timestamp)
-        .bind(block.bitcoin_block_height)
-        .execute(&*pool)
-        .await {
-            error!("backfill: insert block {} failed: {:?}", height, e);
+        if let Err(e) = arch_indexer::indexer::index_block(&pool, &rpc, height).await {
+            error!("backfill: indexing block {} failed: {:?}", height, e);
             continue;
         }
+        processed += 1;
+    }
 
-        // Insert transactions (if any) – fetch full details first
-        for txid in &block.transactions {
-            let ptx = match arch_client.get_processed_transaction(txid).await {
-                Ok(t) => t,
-                Err(e) => { error!("backfill: get_processed_transaction {} failed: {:?}", txid, e); continue; }
-            };
+    Ok(Json(json!({ "requested": max_to_process, "processed": processed, "remaining_estimate": (to_fill.len() as i64 - processed).max(0) })))
+}
+
+/// Backfill an explicit height range [start, end] through the indexer's write path,
+/// indexing only heights not present
+pub async fn backfill_block_range(
+    State(pool): State<Arc<PgPool>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let start: i64 = params.get("start").and_then(|v| v.parse::<i64>().ok()).ok_or_else(|| ApiError::BadRequest("start is required".to_string()))?;
+    let end: i64 = params.get("end").and_then(|v| v.parse::<i64>().ok()).ok_or_else(|| ApiError::BadRequest("end is required".to_string()))?;
+    if end < start { return Ok(Json(json!({"processed": 0, "message": "empty range"}))); }
 
-            let compute_units: Option<i32> = if let Some(logs) = ptx.runtime_transaction.get("logs") {
-                if let Some(arr) = logs.as_array() {
-                    arr.iter().filter_map(|log| log.as_str()).find_map(|log| {
-                        if log.contains("Consumed") { log.split_whitespace().filter_map(|w| w.parse::<i32>().ok()).next() } else { None }
-                    })
-                } else { None }
-            } else { None };
+    let rpc = crate::arch_rpc::indexer_client();
 
-            if let Err(e) = sqlx::query(
-                r#"INSERT INTO transactions (txid, block_height, data, status, bitcoin_txids, created_at, logs, rollback_status, accounts_tags, compute_units_consumed)
-                    VALUES ($1, $2, $3, $4, $5, CURRENT_TIMESTAMP, $6, $7, $8, $9)
-                    ON CONFLICT (txid) DO UPDATE SET block_height = EXCLUDED.block_height, data = EXCLUDED.data, status = EXCLUDED.status, bitcoin_txids = EXCLUDED.bitcoin_txids, logs = EXCLUDED.logs, rollback_status = EXCLUDED.rollback_status, accounts_tags = EXCLUDED.accounts_tags, compute_units_consumed = EXCLUDED.compute_units_consumed"#
-            )
-            .bind(txid)
-            .bind(block.height)
-            .bind(serde_json::to_value(&ptx.runtime_transaction).unwrap_or(serde_json::Value::Null))
-            .bind(serde_json::to_value(&ptx.status).unwrap_or(serde_json::Value::Null))
-            .bind(ptx.bitcoin_txids.as_ref().map(|v| v.as_slice()))
-            .bind(serde_json::to_value(ptx.runtime_transaction.get("logs").unwrap_or(&serde_json::Value::Array(vec![]))).unwrap_or(serde_json::Value::Null))
-            .bind(serde_json::to_value("NotRolledback").unwrap_or(serde_json::Value::Null))
-            .bind(serde_json::to_value(&ptx.accounts_tags).unwrap_or(serde_json::Value::Array(vec![])))
-            .bind(compute_units)
-            .execute(&*pool)
-            .await {
-                error!("backfill: upsert tx {}@{} failed: {:?}", txid, height, e);
-            }
+    let mut processed = 0i64;
+    let mut inserted = 0i64;
+    let mut skipped = 0i64;
+    for h in start..=end {
+        // Skip if exists
+        if sqlx::query_scalar::<_, i32>("SELECT 1 FROM blocks WHERE height = $1")
+            .bind(h)
+            .fetch_optional(&*pool)
+            .await?
+            .is_some()
+        {
+            skipped += 1; processed += 1; continue;
         }
 
+        if let Err(e) = arch_indexer::indexer::index_block(&pool, &rpc, h).await {
+            return Ok(Json(json!({"processed": processed, "inserted": inserted, "skipped": skipped, "error": format!("indexing block {} failed: {}", h, e)})));
+        }
+        inserted += 1;
         processed += 1;
+        if processed % 500 == 0 { tokio::task::yield_now().await; }
     }
 
-    Ok(Json(json!({ "requested": max_to_process, "processed": processed, "remaining_estimate": (to_fill.len() as i64 - processed).max(0) })))
+    Ok(Json(json!({"processed": processed, "inserted": inserted, "skipped": skipped})))
 }
 
 /// Return explicit list of missing block heights, with optional bounds and limit
@@ -1782,64 +2784,6 @@ pub async fn get_missing_block_heights(
     })))
 }
 
-/// Backfill an explicit height range [start, end], inserting only heights not present
-pub async fn backfill_block_range(
-    State(pool): State<Arc<PgPool>>,
-    Query(params): Query<HashMap<String, String>>,
-) -> Result<Json<serde_json::Value>, ApiError> {
-    let start: i64 = params.get("start").and_then(|v| v.parse::<i64>().ok()).ok_or_else(|| ApiError::BadRequest("start is required".to_string()))?;
-    let end: i64 = params.get("end").and_then(|v| v.parse::<i64>().ok()).ok_or_else(|| ApiError::BadRequest("end is required".to_string()))?;
-    if end < start { return Ok(Json(json!({"processed": 0, "message": "empty range"}))); }
-
-    let rpc = ArchRpcClient::new(std::env::var("ARCH_NODE_URL").unwrap_or_else(|_| "http://localhost:8081".to_string()));
-
-    let mut processed = 0i64;
-    let mut inserted = 0i64;
-    let mut skipped = 0i64;
-    for h in start..=end {
-        // Skip if exists
-        if let Some(_) = sqlx::query_scalar::<_, i64>("SELECT 1 FROM blocks WHERE height = $1")
-            .bind(h)
-            .fetch_optional(&*pool)
-            .await? {
-            skipped += 1; processed += 1; continue;
-        }
-
-        // Fetch block by height -> hash -> block data
-        match rpc.get_block_hash(h).await {
-            Ok(hash) => {
-                match rpc.get_block(&hash, h).await {
-                    Ok(block) => {
-                        let ts_dt = crate::utils::convert_arch_timestamp(block.timestamp);
-                        let _ = sqlx::query(
-                            r#"INSERT INTO blocks (height, hash, timestamp, bitcoin_block_height)
-                               VALUES ($1, $2, $3, $4)
-                               ON CONFLICT (height) DO UPDATE SET hash = EXCLUDED.hash, timestamp = EXCLUDED.timestamp, bitcoin_block_height = EXCLUDED.bitcoin_block_height"#
-                        )
-                        .bind(h)
-                        .bind(&block.hash)
-                        .bind(ts_dt)
-                        .bind(block.bitcoin_block_height)
-                        .execute(&*pool)
-                        .await?;
-                        inserted += 1;
-                    },
-                    Err(e) => {
-                        return Ok(Json(json!({"processed": processed, "inserted": inserted, "skipped": skipped, "error": format!("get_block failed at {}: {}", h, e)})));
-                    }
-                }
-            },
-            Err(e) => {
-                return Ok(Json(json!({"processed": processed, "inserted": inserted, "skipped": skipped, "error": format!("get_block_hash failed at {}: {}", h, e)})));
-            }
-        }
-        processed += 1;
-        if processed % 500 == 0 { tokio::task::yield_now().await; }
-    }
-
-    Ok(Json(json!({"processed": processed, "inserted": inserted, "skipped": skipped})))
-}
-
 fn format_time(seconds: f64) -> String {
     let hours = (seconds / 3600.0).floor();
     let minutes = ((seconds % 3600.0) / 60.0).floor();
@@ -1920,12 +2864,11 @@ pub async fn get_block_by_hash(
             b.hash,
             b.timestamp,
             b.bitcoin_block_height,
-            COUNT(t.txid) as transaction_count,
-            NULL::bigint as block_size_bytes
+            COALESCE(b.transaction_count, (SELECT COUNT(*) FROM transactions t WHERE t.block_height = b.height)) as transaction_count,
+            b.previous_block_hash,
+            b.block_size_bytes
         FROM blocks b
-        LEFT JOIN transactions t ON b.height = t.block_height
         WHERE b.hash = $1
-        GROUP BY b.height, b.hash, b.timestamp, b.bitcoin_block_height
         "#
     )
     .bind(&blockhash)
@@ -1941,7 +2884,7 @@ pub async fn get_block_by_hash(
         bitcoin_block_height: row.try_get::<Option<i64>, _>("bitcoin_block_height").ok().flatten(),
         transaction_count: row.get::<i64, _>("transaction_count"),
         block_size_bytes: row.try_get::<Option<i64>, _>("block_size_bytes").ok().flatten(),
-        previous_block_hash: None,
+        previous_block_hash: row.try_get::<Option<String>, _>("previous_block_hash").ok().flatten(),
     };
 
     // Then get the transactions for this block
@@ -1964,8 +2907,8 @@ pub async fn get_block_by_hash(
     .fetch_all(&*pool)
     .await?;
 
-    // Compute approximate block size from transactions
-    let approx_bytes: i64 = transactions.iter().map(|t| estimate_tx_size(&t.data)).sum();
+    // Blocks indexed before sizes were stored: estimate it from the transactions
+    let approx_bytes: i64 = transactions.iter().map(|t| decoder::transaction_size(&t.data)).sum();
     if block.block_size_bytes.is_none() { block.block_size_bytes = Some(approx_bytes); }
 
     // Ensure previous_block_hash is set: DB fallback then RPC
@@ -1982,8 +2925,7 @@ pub async fn get_block_by_hash(
             }
         }
         if block.previous_block_hash.is_none() {
-            let rpc_url = std::env::var("ARCH_NODE_URL").unwrap_or_else(|_| "http://localhost:8081".to_string());
-            let arch_client = ArchRpcClient::new(rpc_url);
+            let arch_client = ArchRpcClient::shared();
             if let Ok(rb) = arch_client.get_block(&block.hash, block.height).await {
                 if rb.previous_block_hash.is_some() { block.previous_block_hash = rb.previous_block_hash; }
             }
@@ -2014,12 +2956,11 @@ pub async fn get_block_by_height(
             b.hash,
             b.timestamp,
             b.bitcoin_block_height,
-            COUNT(t.txid) as transaction_count,
-            NULL::bigint as block_size_bytes
+            COALESCE(b.transaction_count, (SELECT COUNT(*) FROM transactions t WHERE t.block_height = b.height)) as transaction_count,
+            b.previous_block_hash,
+            b.block_size_bytes
         FROM blocks b
-        LEFT JOIN transactions t ON b.height = t.block_height
         WHERE b.height = $1
-        GROUP BY b.height, b.hash, b.timestamp, b.bitcoin_block_height
         "#
     )
     .bind(height as i64)
@@ -2035,10 +2976,10 @@ pub async fn get_block_by_height(
         bitcoin_block_height: row.try_get::<Option<i64>, _>("bitcoin_block_height").ok().flatten(),
         transaction_count: row.get::<i64, _>("transaction_count"),
         block_size_bytes: row.try_get::<Option<i64>, _>("block_size_bytes").ok().flatten(),
-        previous_block_hash: None,
+        previous_block_hash: row.try_get::<Option<String>, _>("previous_block_hash").ok().flatten(),
     };
 
-    // Compute approximate size by summing tx sizes for this block
+    // Blocks indexed before sizes were stored: estimate it from the transactions
     if block.block_size_bytes.is_none() {
         if let Ok(tx_rows) = sqlx::query(
             r#"
@@ -2051,7 +2992,7 @@ pub async fn get_block_by_height(
         {
             let approx: i64 = tx_rows
                 .into_iter()
-                .map(|r| estimate_tx_size(&r.get::<sqlx::types::JsonValue, _>("data")))
+                .map(|r| decoder::transaction_size(&r.get::<sqlx::types::JsonValue, _>("data")))
                 .sum();
             if approx > 0 {
                 block.block_size_bytes = Some(approx);
@@ -2074,8 +3015,7 @@ pub async fn get_block_by_height(
             }
         }
         if block.previous_block_hash.is_none() {
-            let rpc_url = std::env::var("ARCH_NODE_URL").unwrap_or_else(|_| "http://localhost:8081".to_string());
-            let arch_client = ArchRpcClient::new(rpc_url);
+            let arch_client = ArchRpcClient::shared();
             if let Ok(rb) = arch_client.get_block(&block.hash, block.height).await {
                 if rb.previous_block_hash.is_some() {
                     block.previous_block_hash = rb.previous_block_hash;
@@ -2180,31 +3120,14 @@ pub async fn get_transaction(
     .await {
         Ok(Some(transaction)) => Ok(Json(transaction)),
         Ok(None) => {
-            // Fallback: try RPC so we can serve transactions not yet persisted.
-            // Additionally, opportunistically persist the transaction into Postgres so
-            // account participation and program links populate immediately via triggers.
-            let rpc_url = std::env::var("ARCH_NODE_URL").unwrap_or_else(|_| "http://localhost:8081".to_string());
-            let arch_client = ArchRpcClient::new(rpc_url);
+            // Fallback: serve transactions the indexer has not stored yet straight from RPC.
+            // Nothing is written here: the node does not say which block holds the
+            // transaction, so it is stored when the indexer reaches that block.
+            let arch_client = ArchRpcClient::shared();
             match arch_client.get_processed_transaction(&txid).await {
                 Ok(rpc_tx) => {
                     let now = chrono::Utc::now();
 
-                    // Best-effort persist into DB (fires DB triggers on INSERT/UPDATE)
-                    let _ = sqlx::query(
-                        r#"
-                        INSERT INTO transactions (txid, block_height, data, status, bitcoin_txids, created_at)
-                        VALUES ($1, $2, $3, $4, $5, CURRENT_TIMESTAMP)
-                        ON CONFLICT (txid) DO UPDATE SET data = $3, status = $4, bitcoin_txids = $5
-                        "#
-                    )
-                    .bind(&txid)
-                    .bind(0i64)
-                    .bind(&rpc_tx.runtime_transaction)
-                    .bind(serde_json::to_value(&rpc_tx.status).unwrap_or(serde_json::json!({})))
-                    .bind(rpc_tx.bitcoin_txids.as_deref())
-                    .execute(&*pool)
-                    .await;
-
                     // Synthesize a Transaction-like response so the UI can render immediately
                     let synthesized = Transaction {
                         txid,
@@ -2254,8 +3177,7 @@ fn parse_compute_units_from_logs(logs: &[String]) -> Option<u64> {
 pub async fn get_transaction_execution(
     AxPath(txid): AxPath<String>,
 ) -> Result<Json<ExecutionResponse>, ApiError> {
-    let rpc_url = std::env::var("ARCH_NODE_URL").unwrap_or_else(|_| "http://localhost:8081".to_string());
-    let arch_client = ArchRpcClient::new(rpc_url);
+    let arch_client = ArchRpcClient::shared();
     let rpc = match arch_client.get_processed_transaction(&txid).await {
         Ok(v) => v,
         Err(_) => return Err(ApiError::NotFound),
@@ -2327,8 +3249,7 @@ pub async fn get_transaction_participants(
     AxPath(txid): AxPath<String>,
 ) -> Result<Json<Vec<ParticipantRow>>, ApiError> {
     // Prefer RPC so we always match latest shape
-    let rpc_url = std::env::var("ARCH_NODE_URL").unwrap_or_else(|_| "http://localhost:8081".to_string());
-    let arch_client = ArchRpcClient::new(rpc_url);
+    let arch_client = ArchRpcClient::shared();
     let data_opt_rpc = arch_client.get_processed_transaction(&txid).await.ok().map(|r| r.runtime_transaction);
 
     let row_opt = sqlx::query(
@@ -2373,6 +3294,7 @@ pub struct InstructionRow {
     pub program_id_base58: String,
     pub program_name: Option<String>,
     pub accounts: Vec<String>,
+    pub account_roles: Vec<AccountRole>,
     pub data_len: usize,
     pub action: Option<String>,
     pub decoded: Option<serde_json::Value>,
@@ -2383,588 +3305,171 @@ pub async fn get_transaction_instructions(
     State(pool): State<Arc<PgPool>>,
     AxPath(txid): AxPath<String>,
 ) -> Result<Json<Vec<InstructionRow>>, ApiError> {
-    let rpc_url = std::env::var("ARCH_NODE_URL").unwrap_or_else(|_| "http://localhost:8081".to_string());
-    let arch_client = ArchRpcClient::new(rpc_url);
-    let data_opt_rpc = arch_client.get_processed_transaction(&txid).await.ok().map(|r| r.runtime_transaction);
-
-    let row_opt = sqlx::query(
+    // Instructions are decoded and stored by the indexer
+    let rows = sqlx::query(
         r#"
-        SELECT data FROM transactions WHERE txid = $1
+        SELECT instruction_index, program_id, instruction_type, args, accounts, data
+        FROM instructions
+        WHERE txid = $1
+        ORDER BY instruction_index
         "#
     )
     .bind(&txid)
-    .fetch_optional(&*pool)
+    .fetch_all(&*pool)
     .await?;
 
-    let data: serde_json::Value = if let Some(v) = data_opt_rpc { v } else if let Some(row) = row_opt { row.get("data") } else { return Err(ApiError::NotFound) };
-    let message = data.get("message").ok_or(ApiError::NotFound)?;
-    let keys = message.get("account_keys").and_then(|a| a.as_array()).ok_or(ApiError::NotFound)?;
-    let instructions = message.get("instructions").and_then(|a| a.as_array()).ok_or(ApiError::NotFound)?;
-
-    fn u32_le(bytes: &[u8]) -> Option<u32> { if bytes.len() >= 4 { Some(u32::from_le_bytes([bytes[0],bytes[1],bytes[2],bytes[3]])) } else { None } }
-    fn u64_le(bytes: &[u8]) -> Option<u64> { if bytes.len() >= 8 { Some(u64::from_le_bytes([bytes[0],bytes[1],bytes[2],bytes[3],bytes[4],bytes[5],bytes[6],bytes[7]])) } else { None } }
-
-    fn decode_instruction(program_b58: &str, program_hex: &str, data: &[u8], accounts: &[String]) -> (Option<String>, Option<serde_json::Value>) {
-        println!("program_b58: {}", program_b58);
-        fn is_system_b58(s: &str) -> bool {
-            if s.is_empty() { return false; }
-            if s.chars().all(|c| c == '1') { return true; }
-            let ones = "111111111111111111111111111111"; // 30 ones prefix
-            s.starts_with(ones) && s.ends_with('2')
-        }
-        // Compute Budget program (Solana convention)
-        if program_b58 == "ComputeBudget111111111111111111111111111111" {
-            if let Some((&tag, rest)) = data.split_first() {
-                // 1: RequestHeapFrame(u32 size)
-                if tag == 1 { if let Some(size) = u32_le(rest) {
-                    let decoded = json!({
-                        "discriminator": {"type": "u8", "data": tag},
-                        "bytes": {"type": "u32", "data": size}
-                    });
-                    return (Some("Compute Budget: RequestHeapFrame".to_string()), Some(decoded)); }}
-                // 2: SetComputeUnitLimit(u32 units)
-                if tag == 2 && rest.len() >= 4 {
-                    let units = u32_le(rest).unwrap_or(0);
-                    let decoded = json!({
-                        "discriminator": {"type": "u8", "data": tag},
-                        "units": {"type": "u32", "data": units}
-                    });
-                    return (Some("Compute Budget: SetComputeUnitLimit".to_string()), Some(decoded));
-                }
-                // 3: SetComputeUnitPrice(u64 micro_lamports)
-                if tag == 3 && rest.len() >= 8 {
-                    let price = u64_le(rest).unwrap_or(0);
-                    let decoded = json!({
-                        "discriminator": {"type": "u8", "data": tag},
-                        "price_micro_lamports": {"type": "u64", "data": price}
-                    });
-                    return (Some("Compute Budget: SetComputeUnitPrice".to_string()), Some(decoded));
-                }
-            }
-        }
-        // System Program (Arch discriminators)
-        if is_system_b58(program_b58)
-            || program_hex.eq_ignore_ascii_case("0000000000000000000000000000000000000000000000000000000000000000")
-            || program_hex.eq_ignore_ascii_case("0000000000000000000000000000000000000000000000000000000000000001") {
-            if data.len() >= 4 {
-                let tag = u32_le(&data[0..4]).unwrap_or(9999);
-                // Arch: 3 => AssignOwnership { owner: Pubkey }
-                if tag == 3 && data.len() >= 4 + 32 {
-                    let owner_b58 = bs58::encode(&data[4..36]).into_string();
-                    let account = accounts.get(0).cloned();
-                    let decoded = json!({
-                        "discriminator": {"type":"u32", "data": tag},
-                        "owner": owner_b58,
-                        "account": account,
-                    });
-                    return (Some("System Program: AssignOwnership".to_string()), Some(decoded));
-                }
-                // Arch: 0 => CreateAccount { lamports: u64, space: u64, owner: Pubkey(32) }
-                if tag == 0 && data.len() >= 4 + 8 + 8 + 32 {
-                    let lamports = u64_le(&data[4..12]).unwrap_or(0);
-                    let space = u64_le(&data[12..20]).unwrap_or(0);
-                    let owner = bs58::encode(&data[20..52]).into_string();
-                    let from = accounts.get(0).cloned(); // funder
-                    let new_account = accounts.get(1).cloned();
-                    let decoded = json!({
-                        "discriminator": {"type":"u32", "data": tag},
-                        "lamports": {"type":"u64", "data": lamports},
-                        "space": {"type":"u64", "data": space},
-                        "owner": owner,
-                        "funder": from,
-                        "new_account": new_account,
-                    });
-                    return (Some("System Program: CreateAccount".to_string()), Some(decoded));
-                }
-                // Arch: 4 => Transfer { lamports: u64 }
-                if tag == 4 && data.len() == 12 {
-                    let lamports = u64_le(&data[4..12]).unwrap_or(0);
-                    let src = accounts.get(0).cloned();
-                    let dst = accounts.get(1).cloned();
-                    let decoded = json!({
-                        "discriminator": {"type":"u32", "data": tag},
-                        "lamports": {"type":"u64", "data": lamports},
-                        "source": src,
-                        "destination": dst,
-                    });
-                    return (Some("System Program: Transfer".to_string()), Some(decoded));
-                }
-                // Arch: 2 => MakeExecutable (no payload)
-                if tag == 2 {
-                    let account = accounts.get(0).cloned();
-                    let decoded = json!({
-                        "discriminator": {"type":"u32", "data": tag},
-                        "account": account,
-                    });
-                    return (Some("System Program: MakeExecutable".to_string()), Some(decoded));
-                }
-                // Arch: 1 => WriteBytes { offset: u64, len: u64, bytes: [..] }
-                if tag == 1 && data.len() >= 4 + 8 + 8 {
-                    let offset = u64_le(&data[4..12]).unwrap_or(0);
-                    let len = u64_le(&data[12..20]).unwrap_or(0) as usize;
-                    let bytes_hex = if data.len() >= 20 + len { Some(hex::encode(&data[20..20+len])) } else { None };
-                    let account = accounts.get(0).cloned();
-                    let decoded = json!({
-                        "discriminator": {"type":"u32", "data": tag},
-                        "offset": {"type":"u64", "data": offset},
-                        "len": {"type":"u64", "data": len as u64},
-                        "bytes_hex": bytes_hex,
-                        "account": account,
-                    });
-                    return (Some("System Program: WriteBytes".to_string()), Some(decoded));
-                }
-                // 8: Allocate { space: u64 }
-                if tag == 8 && data.len() >= 4 + 8 {
-                    let space = u64_le(&data[4..12]).unwrap_or(0);
-                    let account = accounts.get(0).cloned();
-                    let decoded = json!({
-                        "discriminator": {"type":"u32", "data": tag},
-                        "space": {"type":"u64", "data": space},
-                        "account": account,
-                    });
-                    return (Some("System Program: Allocate".to_string()), Some(decoded));
-                }
-            }
-        }
-        // BPF Loader (program deployment/management)
-        if program_b58 == pid::BPF_LOADER_BASE58 {
-            if data.len() >= 4 {
-                let tag = u32_le(&data[0..4]).unwrap_or(9999);
-                let rest = &data[4..];
-                // 0: Write { offset: u64, bytes: Vec<u8> (len: u64, then bytes) }
-                if tag == 0 {
-                    let mut offset_val: Option<u64> = None;
-                    let mut bytes_hex: Option<String> = None;
-                    if rest.len() >= 8 {
-                        let offset = u64_le(&rest[0..8]).unwrap_or(0);
-                        offset_val = Some(offset);
-                        if rest.len() >= 16 {
-                            let len = u64_le(&rest[8..16]).unwrap_or(0) as usize;
-                            if rest.len() >= 16 + len {
-                                let slice = &rest[16..16+len];
-                                bytes_hex = Some(hex::encode(slice));
-                            }
-                        }
-                    }
-                    let decoded = json!({
-                        "discriminator": {"type":"u32", "data": tag},
-                        "offset": offset_val,
-                        "bytes_hex": bytes_hex,
-                    });
-                    return (Some("BPF Loader: Write".to_string()), Some(decoded));
-                }
-                // 1: Truncate { new_size: u64 }
-                if tag == 1 {
-                    let new_size = if rest.len() >= 8 { Some(u64_le(&rest[0..8]).unwrap_or(0)) } else { None };
-                    let decoded = json!({
-                        "discriminator": {"type":"u32", "data": tag},
-                        "new_size": new_size
-                    });
-                    return (Some("BPF Loader: Truncate".to_string()), Some(decoded));
-                }
-                // 2: Deploy
-                if tag == 2 {
-                    let decoded = json!({
-                        "discriminator": {"type":"u32", "data": tag}
-                    });
-                    return (Some("BPF Loader: Deploy".to_string()), Some(decoded));
-                }
-                // 3: Retract
-                if tag == 3 {
-                    let decoded = json!({
-                        "discriminator": {"type":"u32", "data": tag}
-                    });
-                    return (Some("BPF Loader: Retract".to_string()), Some(decoded));
-                }
-                // 4: TransferAuthority
-                if tag == 4 {
-                    let decoded = json!({
-                        "discriminator": {"type":"u32", "data": tag}
-                    });
-                    return (Some("BPF Loader: TransferAuthority".to_string()), Some(decoded));
-                }
-                // 5: Finalize
-                if tag == 5 {
-                    let decoded = json!({
-                        "discriminator": {"type":"u32", "data": tag}
-                    });
-                    return (Some("BPF Loader: Finalize".to_string()), Some(decoded));
-                }
-            }
-        }
-        // Token Program (Arch APL Token)
-        if program_b58 == pid::APL_TOKEN_PROGRAM_BASE58 {
-            if !data.is_empty() {
-                let tag = data[0];
-                println!("tag: {}", tag);
-                // 0: InitializeMint { decimals, mint_authority, freeze_authority: COption<Pubkey> }
-                if tag == 0 && data.len() >= 1 + 1 + 32 + 1 {
-                    let decimals = data[1];
-                    let mint_authority = bs58::encode(&data[2..34]).into_string();
-                    let has_freeze = data[34] != 0;
-                    let mut obj = serde_json::Map::new();
-                    obj.insert("discriminator".to_string(), json!({"type":"u8", "data": tag}));
-                    obj.insert("decimals".to_string(), json!({"type":"u8", "data": decimals}));
-                    obj.insert("mint_authority".to_string(), json!(mint_authority));
-                    if has_freeze && data.len() >= 1 + 1 + 32 + 1 + 32 {
-                        obj.insert("freeze_authority".to_string(), json!(bs58::encode(&data[35..67]).into_string()));
-                    } else {
-                        obj.insert("freeze_authority".to_string(), json!(null));
-                    }
-                    return (Some("Token: InitializeMint".to_string()), Some(serde_json::Value::Object(obj)));
-                }
-                // 1: InitializeAccount (no data)
-                if tag == 1 {
-                    let decoded = json!({
-                        "discriminator": {"type":"u8", "data": tag},
-                        "account": accounts.get(0),
-                        "mint": accounts.get(1),
-                        "owner": accounts.get(2),
-                    });
-                    return (Some("Token: InitializeAccount".to_string()), Some(decoded));
-                }
-                // 2: InitializeMultisig { m: u8 } ; accounts: [multisig, signer1..signerN]
-                if tag == 2 && data.len() >= 2 {
-                    let m = data[1];
-                    let multisig = accounts.get(0).cloned();
-                    let signers: Vec<String> = if accounts.len() > 1 { accounts[1..].to_vec() } else { Vec::new() };
-                    let decoded = json!({
-                        "discriminator": {"type":"u8", "data": tag},
-                        "m": {"type":"u8", "data": m},
-                        "multisig": multisig,
-                        "signers": signers,
-                    });
-                    return (Some("Token: InitializeMultisig".to_string()), Some(decoded));
-                }
-                // 3: Transfer { amount: u64 }, accounts: [source, destination, authority]
-                if tag == 3 && data.len() >= 1 + 8 {
-                    let amount = u64_le(&data[1..9]).unwrap_or(0);
-                    let source = accounts.get(0).cloned();
-                    let destination = accounts.get(1).cloned();
-                    let authority = accounts.get(2).cloned();
-                    let decoded = json!({
-                        "type": "transfer",
-                        "amount": amount,
-                        "from": source,
-                        "to": destination,
-                        "authority": authority,
-                    });
-                    return (Some("Token: Transfer".to_string()), Some(decoded));
-                }
-                // 4: Approve { amount: u64 }
-                if tag == 4 && data.len() >= 1 + 8 {
-                    let amount = u64_le(&data[1..9]).unwrap_or(0);
-                    let source = accounts.get(0).cloned();
-                    let delegate = accounts.get(2).cloned();
-                    let decoded = json!({
-                        "discriminator": {"type":"u8", "data": tag},
-                        "amount": {"type":"u64", "data": amount},
-                        "source": source,
-                        "delegate": delegate,
-                    });
-                    return (Some("Token: Approve".to_string()), Some(decoded));
-                }
-                // 5: Revoke
-                if tag == 5 {
-                    let source = accounts.get(0).cloned();
-                    let decoded = json!({
-                        "discriminator": {"type":"u8", "data": tag},
-                        "source": source,
-                    });
-                    return (Some("Token: Revoke".to_string()), Some(decoded));
-                }
-                // 6: SetAuthority { authority_type: u8, new_authority: COption<Pubkey> }
-                if tag == 6 && data.len() >= 2 {
-                    let authority_type = data[1];
-                    let mut idx = 2usize;
-                    let mut new_authority_str: Option<String> = None;
-                    if data.len() > idx {
-                        let flag = data[idx];
-                        idx += 1;
-                        if flag == 1 && data.len() >= idx + 32 {
-                            new_authority_str = Some(bs58::encode(&data[idx..idx+32]).into_string());
-                        }
-                    }
-                    let owned = accounts.get(0).cloned();
-                    let owner = accounts.get(1).cloned();
-                    let decoded = json!({
-                        "discriminator": {"type":"u8", "data": tag},
-                        "authority_type": {"type":"u8", "data": authority_type},
-                        "new_authority": new_authority_str,
-                        "owned_account": owned,
-                        "current_authority": owner,
-                    });
-                    return (Some("Token: SetAuthority".to_string()), Some(decoded));
-                }
-                // 12: TransferChecked { amount: u64, decimals: u8 }
-                if tag == 12 && data.len() >= 1 + 8 + 1 {
-                    let amount = u64_le(&data[1..9]).unwrap_or(0);
-                    let decimals = data[9];
-                    let token = accounts.get(0).cloned();
-                    let source = accounts.get(1).cloned();
-                    let mint = accounts.get(2).cloned();
-                    let destination = accounts.get(3).cloned();
-                    let authority = accounts.get(4).cloned();
-                    let decoded = json!({
-                        "discriminator": {"type":"u8", "data": tag},
-                        "amount": {"type":"u64", "data": amount},
-                        "decimals": {"type":"u8", "data": decimals},
-                        "token": token,
-                        "source": source,
-                        "mint": mint,
-                        "destination": destination,
-                        "authority": authority,
-                    });
-                    return (Some("Token: TransferChecked".to_string()), Some(decoded));
-                }
-                // 13: ApproveChecked { amount: u64, decimals: u8 }
-                if tag == 13 && data.len() >= 1 + 8 + 1 {
-                    let amount = u64_le(&data[1..9]).unwrap_or(0);
-                    let decimals = data[9];
-                    let source = accounts.get(0).cloned();
-                    let mint = accounts.get(1).cloned();
-                    let delegate = accounts.get(2).cloned();
-                    let owner = accounts.get(3).cloned();
-                    let decoded = json!({
-                        "discriminator": {"type":"u8", "data": tag},
-                        "amount": {"type":"u64", "data": amount},
-                        "decimals": {"type":"u8", "data": decimals},
-                        "source": source,
-                        "mint": mint,
-                        "delegate": delegate,
-                        "owner": owner,
-                    });
-                    return (Some("Token: ApproveChecked".to_string()), Some(decoded));
-                }
-                // 14: MintToChecked { amount: u64, decimals: u8 }
-                if tag == 14 && data.len() >= 1 + 8 + 1 {
-                    let amount = u64_le(&data[1..9]).unwrap_or(0);
-                    let decimals = data[9];
-                    let mint = accounts.get(0).cloned();
-                    let destination = accounts.get(1).cloned();
-                    let authority = accounts.get(2).cloned();
-                    let decoded = json!({
-                        "discriminator": {"type":"u8", "data": tag},
-                        "amount": {"type":"u64", "data": amount},
-                        "decimals": {"type":"u8", "data": decimals},
-                        "mint": mint,
-                        "destination": destination,
-                        "authority": authority,
-                    });
-                    return (Some("Token: MintToChecked".to_string()), Some(decoded));
-                }
-                // 15: BurnChecked { amount: u64, decimals: u8 }
-                if tag == 15 && data.len() >= 1 + 8 + 1 {
-                    let amount = u64_le(&data[1..9]).unwrap_or(0);
-                    let decimals = data[9];
-                    let account = accounts.get(0).cloned();
-                    let mint = accounts.get(1).cloned();
-                    let owner = accounts.get(2).cloned();
-                    let decoded = json!({
-                        "discriminator": {"type":"u8", "data": tag},
-                        "amount": {"type":"u64", "data": amount},
-                        "decimals": {"type":"u8", "data": decimals},
-                        "account": account,
-                        "mint": mint,
-                        "owner": owner,
-                    });
-                    return (Some("Token: BurnChecked".to_string()), Some(decoded));
-                }
-                // 7: MintTo { amount: u64 }
-                if tag == 7 && data.len() >= 1 + 8 {
-                    let amount = u64_le(&data[1..9]).unwrap_or(0);
-                    let mint = accounts.get(0).cloned();
-                    let dest = accounts.get(1).cloned();
-                    let decoded = json!({
-                        "discriminator": {"type":"u8", "data": tag},
-                        "amount": {"type":"u64", "data": amount},
-                        "mint": mint,
-                        "destination": dest,
-                    });
-                    return (Some("Token: MintTo".to_string()), Some(decoded));
-                }
-                // 8: Burn { amount: u64 }
-                if tag == 8 && data.len() >= 1 + 8 {
-                    let amount = u64_le(&data[1..9]).unwrap_or(0);
-                    let account = accounts.get(0).cloned();
-                    let mint = accounts.get(1).cloned();
-                    let decoded = json!({
-                        "discriminator": {"type":"u8", "data": tag},
-                        "amount": {"type":"u64", "data": amount},
-                        "account": account,
-                        "mint": mint,
-                    });
-                    return (Some("Token: Burn".to_string()), Some(decoded));
-                }
-                // 9: CloseAccount
-                if tag == 9 {
-                    let account = accounts.get(0).cloned();
-                    let destination = accounts.get(1).cloned();
-                    let decoded = json!({
-                        "discriminator": {"type":"u8", "data": tag},
-                        "account": account,
-                        "destination": destination,
-                    });
-                    return (Some("Token: CloseAccount".to_string()), Some(decoded));
-                }
-                // 10: FreezeAccount
-                if tag == 10 {
-                    let account = accounts.get(0).cloned();
-                    let mint = accounts.get(1).cloned();
-                    let decoded = json!({
-                        "discriminator": {"type":"u8", "data": tag},
-                        "account": account,
-                        "mint": mint,
-                    });
-                    return (Some("Token: FreezeAccount".to_string()), Some(decoded));
-                }
-                // 11: ThawAccount
-                if tag == 11 {
-                    let account = accounts.get(0).cloned();
-                    let mint = accounts.get(1).cloned();
-                    let decoded = json!({
-                        "discriminator": {"type":"u8", "data": tag},
-                        "account": account,
-                        "mint": mint,
-                    });
-                    return (Some("Token: ThawAccount".to_string()), Some(decoded));
-                }
-                // 16: InitializeAccount2 { owner: Pubkey }
-                if tag == 16 && data.len() >= 1 + 32 {
-                    let owner = bs58::encode(&data[1..33]).into_string();
-                    let account = accounts.get(0).cloned();
-                    let mint = accounts.get(1).cloned();
-                    let decoded = json!({
-                        "discriminator": {"type":"u8", "data": tag},
-                        "owner": owner,
-                        "account": account,
-                        "mint": mint,
-                    });
-                    return (Some("Token: InitializeAccount2".to_string()), Some(decoded));
-                }
-                // 17: InitializeAccount3 { owner: Pubkey }
-                if tag == 17 && data.len() >= 1 + 32 {
-                    let owner = bs58::encode(&data[1..33]).into_string();
-                    let account = accounts.get(0).cloned();
-                    let mint = accounts.get(1).cloned();
-                    let decoded = json!({
-                        "discriminator": {"type":"u8", "data": tag},
-                        "owner": owner,
-                        "account": account,
-                        "mint": mint,
-                    });
-                    return (Some("Token: InitializeAccount3".to_string()), Some(decoded));
-                }
-                // 18: InitializeMint2 { decimals, mint_authority, freeze_authority: COption<Pubkey> }
-                if tag == 18 && data.len() >= 1 + 1 + 32 + 1 {
-                    let decimals = data[1];
-                    let mint_authority = bs58::encode(&data[2..34]).into_string();
-                    let has_freeze = data[34] != 0;
-                    let mut obj = serde_json::Map::new();
-                    obj.insert("discriminator".to_string(), json!({"type":"u8", "data": tag}));
-                    obj.insert("decimals".to_string(), json!({"type":"u8", "data": decimals}));
-                    obj.insert("mint_authority".to_string(), json!(mint_authority));
-                    if has_freeze && data.len() >= 1 + 1 + 32 + 1 + 32 {
-                        obj.insert("freeze_authority".to_string(), json!(bs58::encode(&data[35..67]).into_string()));
-                    } else {
-                        obj.insert("freeze_authority".to_string(), json!(null));
-                    }
-                    return (Some("Token: InitializeMint2".to_string()), Some(serde_json::Value::Object(obj)));
-                }
-                // 19: GetAccountDataSize (no data)
-                if tag == 19 {
-                    let mint = accounts.get(0).cloned();
-                    let decoded = json!({
-                        "discriminator": {"type":"u8", "data": tag},
-                        "mint": mint,
-                    });
-                    return (Some("Token: GetAccountDataSize".to_string()), Some(decoded));
-                }
-                // 20: InitializeImmutableOwner (no data)
-                if tag == 20 {
-                    let account = accounts.get(0).cloned();
-                    let decoded = json!({
-                        "discriminator": {"type":"u8", "data": tag},
-                        "account": account,
-                    });
-                    return (Some("Token: InitializeImmutableOwner".to_string()), Some(decoded));
-                }
-                // 21: AmountToUiAmount { amount: u64 }
-                if tag == 21 && data.len() >= 1 + 8 {
-                    let amount = u64_le(&data[1..9]).unwrap_or(0);
-                    let mint = accounts.get(0).cloned();
-                    let decoded = json!({
-                        "discriminator": {"type":"u8", "data": tag},
-                        "amount": {"type":"u64", "data": amount},
-                        "mint": mint,
-                    });
-                    return (Some("Token: AmountToUiAmount".to_string()), Some(decoded));
-                }
-                // 22: UiAmountToAmount { ui_amount: string }
-                if tag == 22 && data.len() >= 1 {
-                    let ui_amount = match std::str::from_utf8(&data[1..]) { Ok(s) => s.to_string(), Err(_) => String::new() };
-                    let mint = accounts.get(0).cloned();
-                    let decoded = json!({
-                        "discriminator": {"type":"u8", "data": tag},
-                        "ui_amount": ui_amount,
-                        "mint": mint,
-                    });
-                    return (Some("Token: UiAmountToAmount".to_string()), Some(decoded));
+    if !rows.is_empty() {
+        let out = rows
+            .into_iter()
+            .map(|row| {
+                let program_id_hex: String = row.get("program_id");
+                let program_id_base58 = hex::decode(&program_id_hex).map(|b| bs58::encode(b).into_string()).unwrap_or_default();
+                let account_roles: Vec<AccountRole> = serde_json::from_value(row.get("accounts")).unwrap_or_default();
+                let accounts = account_roles
+                    .iter()
+                    .map(|r| hex::decode(&r.address_hex).map(|b| bs58::encode(b).into_string()).unwrap_or_default())
+                    .collect();
+                let data: Vec<u8> = row.get("data");
+                InstructionRow {
+                    index: row.get::<i32, _>("instruction_index") as usize,
+                    program_name: pid::get_program_name(&program_id_base58).map(|s| s.to_string()),
+                    program_id_hex,
+                    program_id_base58,
+                    accounts,
+                    account_roles,
+                    data_len: data.len(),
+                    action: row.get("instruction_type"),
+                    decoded: row.get("args"),
+                    data_hex: hex::encode(&data),
                 }
-            }
-        }
-        // Associated Token Account Program (Arch). Instruction has no data
-        if program_b58 == pid::APL_ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_BASE58 && data.is_empty() {
-            let funder = accounts.get(0).cloned();
-            let associated_account = accounts.get(1).cloned();
-            let wallet = accounts.get(2).cloned();
-            let mint = accounts.get(3).cloned();
-            let system_program = accounts.get(4).cloned();
-            let token_program = accounts.get(5).cloned();
-            let decoded = json!({
-                "type": "create_associated_token_account",
-                "funder": funder,
-                "associated_account": associated_account,
-                "wallet": wallet,
-                "mint": mint,
-                "system_program": system_program,
-                "token_program": token_program,
-            });
-            return (Some("Associated Token Account: Create".to_string()), Some(decoded));
-        }
-        // Memo program: utf-8
-        if program_b58 == "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr" {
-            if let Ok(text) = std::str::from_utf8(data) {
-                return (Some("Memo: Write".to_string()), Some(json!({"memo": text})));
-            }
-        }
-        (None, None)
+            })
+            .collect();
+        return Ok(Json(out));
     }
 
-    let mut out = Vec::with_capacity(instructions.len());
-    for (idx, ins) in instructions.iter().enumerate() {
-        // Resolve program id: prefer explicit program_id, else program_id_index into account_keys
-        let program_val = if let Some(v) = ins.get("program_id") {
-            Some(v.clone())
-        } else if let Some(ix) = ins.get("program_id_index").and_then(|v| v.as_i64()) {
-            keys.get(ix as usize).cloned()
-        } else { None };
-        let program_hex = program_val.as_ref().map(|v| key_to_hex(v)).unwrap_or_default();
-        let program_b58 = program_val.as_ref().map(|v| key_to_base58(v)).unwrap_or_default();
-        let program_name = super::program_ids::get_program_name(&program_b58).map(|s| s.to_string());
-        let acc_idx: Vec<usize> = ins.get("accounts").and_then(|a| a.as_array()).map(|a| a.iter().filter_map(|v| v.as_i64().map(|n| n as usize)).collect()).unwrap_or_default();
-        let accounts: Vec<String> = acc_idx.into_iter().filter_map(|i| keys.get(i).map(|k| key_to_base58(k))).collect();
-        let data_vec: Vec<u8> = ins.get("data").and_then(|d| d.as_array()).map(|a| a.iter().filter_map(|v| v.as_i64().map(|n| n as u8)).collect()).unwrap_or_default();
-        let data_len = data_vec.len();
-        let data_hex = hex::encode(&data_vec);
-        let (action, decoded) = decode_instruction(&program_b58, &program_hex, &data_vec, &accounts);
-        println!("action: {:?}", action);
-        out.push(InstructionRow { index: idx, program_id_hex: program_hex, program_id_base58: program_b58, program_name, accounts, data_len, action, decoded, data_hex });
+    // Not decoded at index time (e.g. indexed before the instructions table existed)
+    let row_opt = sqlx::query("SELECT data FROM transactions WHERE txid = $1")
+        .bind(&txid)
+        .fetch_optional(&*pool)
+        .await?;
+    let data: serde_json::Value = match row_opt {
+        Some(row) => row.get("data"),
+        None => ArchRpcClient::shared()
+            .get_processed_transaction(&txid)
+            .await
+            .map(|r| r.runtime_transaction)
+            .map_err(|_| ApiError::NotFound)?,
+    };
+    if data.get("message").and_then(|m| m.get("instructions")).is_none() {
+        return Err(ApiError::NotFound);
     }
 
+    let out = decode_transaction(&data)
+        .into_iter()
+        .map(|ix| InstructionRow {
+            index: ix.index,
+            program_name: pid::get_program_name(&ix.program_id_base58).map(|s| s.to_string()),
+            program_id_hex: ix.program_id_hex,
+            program_id_base58: ix.program_id_base58,
+            accounts: ix.accounts,
+            account_roles: ix.account_roles,
+            data_len: ix.data.len(),
+            action: ix.instruction_type,
+            decoded: ix.args,
+            data_hex: hex::encode(&ix.data),
+        })
+        .collect();
     Ok(Json(out))
 }
 
+/// Times the node rolled a transaction back or reapplied it, oldest first, with whether it
+/// is rolled back now. Recorded by the indexer as it reverses and re-applies the effects.
+pub async fn get_transaction_rollback_history(
+    State(pool): State<Arc<PgPool>>,
+    AxPath(txid): AxPath<String>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let exists: Option<String> = sqlx::query_scalar("SELECT txid FROM transactions WHERE txid = $1")
+        .bind(&txid)
+        .fetch_optional(&*pool)
+        .await?;
+    if exists.is_none() {
+        return Err(ApiError::NotFound);
+    }
+
+    let rows = sqlx::query(
+        r#"
+        SELECT action, block_height, recorded_at
+        FROM transaction_rollbacks
+        WHERE txid = $1
+        ORDER BY id
+        "#
+    )
+    .bind(&txid)
+    .fetch_all(&*pool)
+    .await?;
+
+    let count = |action: &str| rows.iter().filter(|r| r.get::<String, _>("action") == action).count();
+    let rolled_back = rows.last().map(|r| r.get::<String, _>("action") == "rolled_back").unwrap_or(false);
+    let events: Vec<serde_json::Value> = rows
+        .iter()
+        .map(|r| {
+            json!({
+                "action": r.get::<String, _>("action"),
+                "block_height": r.get::<Option<i64>, _>("block_height"),
+                "recorded_at": r.get::<DateTime<Utc>, _>("recorded_at"),
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "txid": txid,
+        "rolled_back": rolled_back,
+        "rollback_count": count("rolled_back"),
+        "reapply_count": count("reapplied"),
+        "events": events
+    })))
+}
+
+#[derive(serde::Serialize, sqlx::FromRow)]
+pub struct InstructionTypeStats {
+    pub program_id: String,
+    pub instruction_type: Option<String>,
+    pub count: i64,
+    pub last_block_height: i64,
+}
+
+/// Instruction counts grouped by program and decoded type, optionally filtered by
+/// `program_id` (hex or base58) and `since_height`.
+pub async fn get_instruction_type_stats(
+    State(pool): State<Arc<PgPool>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<Vec<InstructionTypeStats>>, ApiError> {
+    let program_hex = match params.get("program_id") {
+        Some(p) => Some(normalize_program_param(p).ok_or(ApiError::BadRequest("Invalid program_id".into()))?),
+        None => None,
+    };
+    let since_height = params.get("since_height").and_then(|v| v.parse::<i64>().ok()).unwrap_or(0);
+    let limit = params.get("limit").and_then(|v| v.parse::<i64>().ok()).map(|v| v.min(500)).unwrap_or(100);
+
+    let stats = sqlx::query_as::<_, InstructionTypeStats>(
+        r#"
+        SELECT program_id, instruction_type, COUNT(*) AS count, MAX(block_height) AS last_block_height
+        FROM instructions
+        WHERE ($1::text IS NULL OR program_id = $1) AND block_height >= $2
+        GROUP BY program_id, instruction_type
+        ORDER BY count DESC
+        LIMIT $3
+        "#
+    )
+    .bind(program_hex)
+    .bind(since_height)
+    .bind(limit)
+    .fetch_all(&*pool)
+    .await?;
+
+    Ok(Json(stats))
+}
+
 fn extract_program_ids(data: &serde_json::Value) -> Vec<String> {
     let mut program_ids = Vec::new();
     
@@ -3086,8 +3591,7 @@ pub async fn get_network_stats(
             debug!("Cache hit for {} => {}", head_key, h);
             h
         } else {
-            let rpc_url = std::env::var("ARCH_NODE_URL").unwrap_or_else(|_| "http://localhost:8081".to_string());
-            let arch_client = ArchRpcClient::new(rpc_url);
+            let arch_client = ArchRpcClient::shared();
             match arch_client.get_block_count().await {
                 Ok(h) => {
                     if let Ok(client) = redis::Client::open(redis_url.as_str()) {
@@ -3190,13 +3694,11 @@ pub async fn search_handler(
                 b.hash,
                 b.timestamp,
                 b.bitcoin_block_height,
-                COUNT(t.txid) as transaction_count,
+                COALESCE(b.transaction_count, (SELECT COUNT(*) FROM transactions t WHERE t.block_height = b.height)) as transaction_count,
                 b.previous_block_hash
-                , NULL::bigint as block_size_bytes
+                , b.block_size_bytes
             FROM blocks b
-            LEFT JOIN transactions t ON b.height = t.block_height
             WHERE b.hash = $1
-            GROUP BY b.height, b.hash, b.timestamp, b.bitcoin_block_height, b.previous_block_hash
             "#
         )
         .bind(term)
@@ -3216,8 +3718,7 @@ pub async fn search_handler(
 
             // Fallback: if previous_block_hash is missing, fetch from RPC
             if block.previous_block_hash.is_none() {
-                let rpc_url = std::env::var("ARCH_NODE_URL").unwrap_or_else(|_| "http://localhost:8081".to_string());
-                let arch_client = crate::arch_rpc::ArchRpcClient::new(rpc_url);
+                let arch_client = crate::arch_rpc::ArchRpcClient::shared();
                 if let Ok(rb) = arch_client.get_block(&block.hash, block.height).await {
                     block.previous_block_hash = rb.previous_block_hash;
                 }
@@ -3256,9 +3757,9 @@ pub async fn search_handler(
                 })
                 .collect();
 
-            // Compute approximate bytes from serialized tx structure
+            // Blocks indexed before sizes were stored: estimate it from the transactions
             let approx_bytes: i64 = txs.iter()
-                .map(|t| estimate_tx_size(&t.data))
+                .map(|t| decoder::transaction_size(&t.data))
                 .sum();
             if block.block_size_bytes.is_none() {
                 block.block_size_bytes = Some(approx_bytes);
@@ -3285,13 +3786,11 @@ pub async fn search_handler(
                     b.hash,
                     b.timestamp,
                     b.bitcoin_block_height,
-                    COUNT(t.txid) as transaction_count,
+                    COALESCE(b.transaction_count, (SELECT COUNT(*) FROM transactions t WHERE t.block_height = b.height)) as transaction_count,
                     b.previous_block_hash,
-                    NULL::bigint as block_size_bytes
+                    b.block_size_bytes
                 FROM blocks b
-                LEFT JOIN transactions t ON b.height = t.block_height
                 WHERE b.height = $1
-                GROUP BY b.height, b.hash, b.timestamp, b.bitcoin_block_height, b.previous_block_hash
                 "#
             )
             .bind(height)
@@ -3343,10 +3842,10 @@ pub async fn search_handler(
                     })
                     .collect();
 
-                // Compute approximate bytes from serialized tx structure
+                // Blocks indexed before sizes were stored: estimate it from the transactions
                 if block.block_size_bytes.is_none() {
                     let approx_bytes: i64 = txs.iter()
-                        .map(|t| estimate_tx_size(&t.data))
+                        .map(|t| decoder::transaction_size(&t.data))
                         .sum();
                     block.block_size_bytes = Some(approx_bytes);
                 }
@@ -3365,8 +3864,7 @@ pub async fn search_handler(
                         }
                     }
                     if block.previous_block_hash.is_none() {
-                        let rpc_url = std::env::var("ARCH_NODE_URL").unwrap_or_else(|_| "http://localhost:8081".to_string());
-                        let arch_client = crate::arch_rpc::ArchRpcClient::new(rpc_url);
+                        let arch_client = crate::arch_rpc::ArchRpcClient::shared();
                         if let Ok(rb) = arch_client.get_block(&block.hash, block.height).await {
                             if rb.previous_block_hash.is_some() { block.previous_block_hash = rb.previous_block_hash; }
                         }
@@ -3386,8 +3884,55 @@ pub async fn search_handler(
             }
         }
 
+        // Check if the term is a token mint address, name or symbol
+        let mint_hex = normalize_program_param(term).unwrap_or_default();
+        if let Ok(rows) = sqlx::query(
+            r#"
+            SELECT
+                md.mint_address,
+                md.metadata_address,
+                md.name,
+                md.symbol,
+                md.uri,
+                md.update_authority,
+                md.additional_metadata,
+                tm.decimals,
+                tm.supply::text AS supply
+            FROM token_metadata md
+            LEFT JOIN token_mints tm ON tm.mint_address = md.mint_address
+            WHERE md.mint_address = $1 OR LOWER(md.symbol) = LOWER($2) OR LOWER(md.name) = LOWER($2)
+            ORDER BY (md.mint_address = $1) DESC, md.symbol, md.mint_address
+            LIMIT 20
+            "#
+        )
+        .bind(&mint_hex)
+        .bind(term.trim())
+        .fetch_all(&*pool)
+        .await
+        {
+            if !rows.is_empty() {
+                let tokens: Vec<serde_json::Value> = rows.into_iter().map(|r| {
+                    let mint_address: String = r.get("mint_address");
+                    let update_authority: Option<String> = r.get("update_authority");
+                    json!({
+                        "mint_address": b58_or_hex(&mint_address),
+                        "mint_address_hex": mint_address,
+                        "metadata_address": b58_or_hex(&r.get::<String, _>("metadata_address")),
+                        "name": r.get::<String, _>("name"),
+                        "symbol": r.get::<String, _>("symbol"),
+                        "uri": r.get::<String, _>("uri"),
+                        "update_authority": update_authority.as_deref().map(b58_or_hex),
+                        "additional_metadata": r.get::<serde_json::Value, _>("additional_metadata"),
+                        "decimals": r.try_get::<Option<i32>, _>("decimals").ok().flatten(),
+                        "supply": r.try_get::<Option<String>, _>("supply").ok().flatten(),
+                    })
+                }).collect();
+                return Json(json!({ "type": "tokens", "data": tokens }));
+            }
+        }
+
         // If no match is found
-        return Json(json!({ "error": "No matching transaction or block found" }));
+        return Json(json!({ "error": "No matching transaction, block or token found" }));
     } else {
         // Return an error response if the term is missing
         return Json(json!({ "error": "Missing search term" }));
@@ -4005,66 +4550,12 @@ fn compute_native_balance_delta_for_address_in_tx(data: &serde_json::Value, addr
 }
 
 fn build_instruction_summaries_from_tx(data: &serde_json::Value) -> Vec<serde_json::Value> {
-    let mut out = Vec::new();
-    let message = match data.get("message") { Some(m) => m, None => return out };
-    let keys = match message.get("account_keys").and_then(|a| a.as_array()) { Some(k) => k, None => return out };
-    let instructions = match message.get("instructions").and_then(|a| a.as_array()) { Some(i) => i, None => return out };
-    let key_b58 = |k: &serde_json::Value| -> String { key_to_base58(k) };
-    for ins in instructions {
-        let program_val = if let Some(v) = ins.get("program_id") { Some(v.clone()) } else if let Some(ix) = ins.get("program_id_index").and_then(|v| v.as_i64()) { keys.get(ix as usize).cloned() } else { None };
-        let program_b58 = program_val.as_ref().map(|v| key_to_base58(v)).unwrap_or_default();
-        let acc_idx: Vec<usize> = ins.get("accounts").and_then(|a| a.as_array()).map(|a| a.iter().filter_map(|v| v.as_i64().map(|n| n as usize)).collect()).unwrap_or_default();
-        let accounts: Vec<String> = acc_idx.into_iter().filter_map(|i| keys.get(i).map(|k| key_b58(k))).collect();
-        let data_vec: Vec<u8> = ins.get("data").and_then(|d| d.as_array()).map(|a| a.iter().filter_map(|v| v.as_i64().map(|n| n as u8)).collect()).unwrap_or_default();
-
-        // Compute Budget unit price
-        if program_b58 == "ComputeBudget111111111111111111111111111111" {
-            if let Some((&tag, rest)) = data_vec.split_first() { if tag == 3 && rest.len() >= 8 {
-                let price = v2_u64_le(rest).unwrap_or(0);
-                out.push(json!({"action": "Compute Budget: SetComputeUnitPrice", "decoded": {"price_micro_lamports": {"type": "u64", "data": price}} }));
-                continue;
-            }}
-        }
-        // Token transfers
-        if program_b58 == pid::APL_TOKEN_PROGRAM_BASE58 {
-            if !data_vec.is_empty() {
-                let tag = data_vec[0];
-                if tag == 3 && data_vec.len() >= 9 {
-                    let amount = v2_u64_le(&data_vec[1..9]).unwrap_or(0);
-                    let source = accounts.get(0).cloned();
-                    let destination = accounts.get(1).cloned();
-                    let authority = accounts.get(2).cloned();
-                    out.push(json!({"action":"Token: Transfer","decoded": {"type":"transfer","amount": amount, "from": source, "to": destination, "authority": authority}}));
-                    continue;
-                }
-                if tag == 12 && data_vec.len() >= 10 {
-                    let amount = v2_u64_le(&data_vec[1..9]).unwrap_or(0);
-                    let decimals = data_vec[9] as u64;
-                    out.push(json!({"action":"Token: TransferChecked","decoded": {"amount": amount, "decimals": decimals}}));
-                    continue;
-                }
-            }
-        }
-        // System summary (Arch discriminators)
-        if v2_is_system_b58(&program_b58) && data_vec.len() >= 4 {
-            let tag = v2_u32_le(&data_vec[0..4]).unwrap_or(9999);
-            if tag == 4 && data_vec.len() == 12 && accounts.len() >= 2 {
-                let lamports = v2_u64_le(&data_vec[4..12]).unwrap_or(0);
-                let src = accounts.get(0).cloned();
-                let dst = accounts.get(1).cloned();
-                out.push(json!({"action":"System: Transfer","decoded": {"lamports": {"type":"u64","data": lamports}, "source": src, "destination": dst}}));
-                continue;
-            }
-            if tag == 3 && data_vec.len() >= 36 {
-                let owner_b58 = bs58::encode(&data_vec[4..36]).into_string();
-                let account = accounts.get(0).cloned();
-                out.push(json!({"action":"System: AssignOwnership","decoded": {"owner": owner_b58, "account": account, "discriminator": {"type":"u32","data": tag}}}));
-                continue;
-            }
-        }
-        // Fallback chip: program name
-        let label = pid::get_program_name(&program_b58).unwrap_or("Program").to_string();
-        out.push(json!({"action": label }));
-    }
-    out
+    decode_transaction(data)
+        .into_iter()
+        .map(|ix| match (ix.instruction_type, ix.args) {
+            (Some(action), decoded) => json!({ "action": action, "decoded": decoded }),
+            // Fallback chip: program name
+            (None, _) => json!({ "action": pid::get_program_name(&ix.program_id_base58).unwrap_or("Program") }),
+        })
+        .collect()
 }
@@ -23,6 +23,8 @@ pub fn create_router(pool: Arc<PgPool>) -> Router {
         .route("/api/transactions/:txid/execution", get(handlers::get_transaction_execution))
         .route("/api/transactions/:txid/participants", get(handlers::get_transaction_participants))
         .route("/api/transactions/:txid/instructions", get(handlers::get_transaction_instructions))
+        .route("/api/transactions/:txid/rollback-history", get(handlers::get_transaction_rollback_history))
+        .route("/api/instructions/types", get(handlers::get_instruction_type_stats))
         .route("/api/search", get(handlers::search_handler))
         .route("/api/network/stats", get(handlers::get_network_stats))
         .route("/api/programs", get(handlers::list_programs))
@@ -31,12 +33,24 @@ pub fn create_router(pool: Arc<PgPool>) -> Router {
         .route("/api/programs/:program_id/transactions", get(handlers::get_transactions_by_program))
         .route("/api/programs/backfill", get(handlers::backfill_programs))
         .route("/api/tokens/leaderboard", get(handlers::get_token_leaderboard))
+        .route("/api/tokens/:mint", get(handlers::get_token_details))
+        .route("/api/tokens/:mint/holders", get(handlers::get_token_holders))
+        .route("/api/tokens/:mint/holders/snapshot", get(handlers::get_token_holders_snapshot))
+        .route("/api/tokens/:mint/transfers", get(handlers::get_token_transfers))
+        .route("/api/tokens/:mint/supply-history", get(handlers::get_token_supply_history))
+        .route("/api/tokens/:mint/supply-events", get(handlers::get_token_supply_events))
         // Accounts
         .route("/api/accounts/:address", get(handlers::get_account_summary))
         .route("/api/accounts/:address/transactions", get(handlers::get_account_transactions))
         .route("/api/accounts/:address/transactions/v2", get(handlers::get_account_transactions_v2))
         .route("/api/accounts/:address/programs", get(handlers::get_account_programs))
         .route("/api/accounts/:address/token-balances", get(handlers::get_account_token_balances))
+        .route("/api/accounts/:address/token-transfers", get(handlers::get_account_token_transfers))
+        .route("/api/accounts/:address/native-transfers", get(handlers::get_account_native_transfers))
+        .route("/api/accounts/:address/balances", get(handlers::get_account_balances_at))
+        .route("/api/accounts/:address/balance-history", get(handlers::get_account_balance_history))
+        .route("/api/accounts/:address/history", get(handlers::get_account_history))
+        .route("/api/accounts/:address/state", get(handlers::get_account_state_at))
         .route("/api/realtime/status", get(handlers::get_realtime_status))
         .route("/api/realtime/events", get(handlers::get_recent_events))
         .route("/api/websocket/stats", get(handlers::get_websocket_stats))
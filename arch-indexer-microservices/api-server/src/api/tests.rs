@@ -125,4 +125,662 @@ where
         .unwrap();
     println!("Response body: {:?}", String::from_utf8_lossy(&bytes));
     serde_json::from_slice(&bytes).unwrap()
-}
\ No newline at end of file
+}
+/// Seeded rows live at and above this height, far above any indexed chain.
+const SEED_HEIGHT: i64 = 900_000_000;
+
+/// Seeded tests share heights and keys, so they run one at a time.
+static SEEDED: std::sync::OnceLock<tokio::sync::Mutex<()>> = std::sync::OnceLock::new();
+
+fn key(byte: u8) -> String {
+    hex::encode([byte; 32])
+}
+
+/// Time of the seeded block `n` blocks above `SEED_HEIGHT`: an hour apart from 2100-01-01,
+/// after any indexed block, so `at_time` lookups resolve to seeded blocks.
+fn seed_time(n: i64) -> chrono::DateTime<chrono::Utc> {
+    chrono::DateTime::from_timestamp(4_102_444_800 + n * 3600, 0).unwrap()
+}
+
+fn time(v: &Value) -> chrono::DateTime<chrono::Utc> {
+    chrono::DateTime::parse_from_rfc3339(v.as_str().unwrap()).unwrap().with_timezone(&chrono::Utc)
+}
+
+struct Seeded {
+    app: Router,
+    pool: PgPool,
+    _guard: tokio::sync::MutexGuard<'static, ()>,
+}
+
+/// Router over a database initialized from db-init, holding four seeded blocks at
+/// `SEED_HEIGHT..SEED_HEIGHT + 4` with one transaction `seed-<n>` each.
+async fn seeded() -> Seeded {
+    let guard = SEEDED.get_or_init(|| tokio::sync::Mutex::new(())).lock().await;
+    dotenv::from_filename(".env.test").ok();
+    let pool = PgPool::connect(&std::env::var("DATABASE_URL").unwrap()).await.unwrap();
+    clear_seed(&pool).await;
+    for n in 0..4 {
+        sqlx::query("INSERT INTO blocks (height, hash, timestamp) VALUES ($1, $2, $3)")
+            .bind(SEED_HEIGHT + n)
+            .bind(format!("seed{}", n))
+            .bind(seed_time(n))
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO transactions (txid, block_height, data, status) VALUES ($1, $2, '{}'::jsonb, '{\"type\": \"processed\"}'::jsonb)")
+            .bind(format!("seed-{}", n))
+            .bind(SEED_HEIGHT + n)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+    Seeded { app: create_router(Arc::new(pool.clone())), pool, _guard: guard }
+}
+
+/// Removes every seeded row; rows keyed by transaction go with it.
+async fn clear_seed(pool: &PgPool) {
+    let mints = vec![key(0xe1)];
+    for (sql, by_mint) in [
+        ("DELETE FROM token_balances WHERE mint_address = ANY($1)", true),
+        ("DELETE FROM token_accounts WHERE mint_address_hex = ANY($1)", true),
+        ("DELETE FROM token_metadata WHERE mint_address = ANY($1)", true),
+        ("DELETE FROM token_mints WHERE mint_address = ANY($1)", true),
+        ("DELETE FROM native_balance_history WHERE block_height >= $1", false),
+        ("DELETE FROM account_history WHERE height >= $1", false),
+        ("DELETE FROM transactions WHERE block_height >= $1", false),
+        ("DELETE FROM blocks WHERE height >= $1", false),
+    ] {
+        let query = sqlx::query(sql);
+        let query = if by_mint { query.bind(&mints) } else { query.bind(SEED_HEIGHT) };
+        query.execute(pool).await.unwrap();
+    }
+}
+
+impl Seeded {
+    async fn get(&self, uri: &str) -> (StatusCode, Value) {
+        let response = self.app.clone().oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap()).await.unwrap();
+        (response.status(), read_body(response).await)
+    }
+
+    async fn finish(self) {
+        clear_seed(&self.pool).await;
+    }
+
+    /// Native balance of `key(0xe3)`: 1000 at the first seeded block, 800 at the second and
+    /// 900 at the fourth.
+    async fn seed_native_history(&self) {
+        for (n, balance) in [(0, 1000), (1, 800), (3, 900)] {
+            sqlx::query(
+                "INSERT INTO native_balance_history (address_hex, block_height, block_time, balance, txid) VALUES ($1, $2, $3, $4, $5)",
+            )
+            .bind(key(0xe3))
+            .bind(SEED_HEIGHT + n)
+            .bind(seed_time(n))
+            .bind(balance)
+            .bind(format!("seed-{}", n))
+            .execute(&self.pool)
+            .await
+            .unwrap();
+        }
+    }
+
+    /// Mint `key(0xe1)` (token program `key(0xe7)`, 6 decimals): 100 minted to account
+    /// `key(0xe5)` of owner `key(0xe3)`, 30 and 10 moved to account `key(0xe6)` of owner
+    /// `key(0xe9)`, account `key(0xe5)` handed to owner `key(0xe4)`, and 5 burned from
+    /// `key(0xe6)`, one block apart.
+    async fn seed_token_ledger(&self) {
+        let (mint, program, alice, bob, carol, a, b) = (key(0xe1), key(0xe7), key(0xe3), key(0xe4), key(0xe9), key(0xe5), key(0xe6));
+        sqlx::query("INSERT INTO token_mints (mint_address, program_id, decimals, supply, mint_authority) VALUES ($1, $2, 6, 95, $3)")
+            .bind(&mint)
+            .bind(&program)
+            .bind(&alice)
+            .execute(&self.pool)
+            .await
+            .unwrap();
+        for (account, owner, balance) in [(&a, &bob, 60), (&b, &carol, 35)] {
+            sqlx::query("INSERT INTO token_accounts (token_account_hex, mint_address_hex, owner_address_hex, program_id_hex) VALUES ($1, $2, $3, $4)")
+                .bind(account)
+                .bind(&mint)
+                .bind(owner)
+                .bind(&program)
+                .execute(&self.pool)
+                .await
+                .unwrap();
+            sqlx::query("INSERT INTO token_balances (account_address, mint_address, balance, decimals, owner_address, program_id) VALUES ($1, $2, $3, 6, $4, $5)")
+                .bind(account)
+                .bind(&mint)
+                .bind(balance)
+                .bind(owner)
+                .bind(&program)
+                .execute(&self.pool)
+                .await
+                .unwrap();
+        }
+        let transfers: [(i64, i32, &str, Option<&String>, Option<&String>, Option<&String>, Option<&String>, i64); 4] = [
+            (0, 0, "mint", None, Some(&a), None, Some(&alice), 100),
+            (1, 0, "transfer", Some(&a), Some(&b), Some(&alice), Some(&carol), 30),
+            (1, 1, "transfer", Some(&a), Some(&b), Some(&alice), Some(&carol), 10),
+            (3, 0, "burn", Some(&b), None, Some(&carol), None, 5),
+        ];
+        for (n, index, kind, source, destination, source_owner, destination_owner, amount) in transfers {
+            sqlx::query(
+                r#"INSERT INTO token_transfers (txid, instruction_index, block_height, block_time, kind, mint_address,
+                       source_address, destination_address, source_owner, destination_owner, amount, decimals, program_id)
+                   VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, 6, $12)"#,
+            )
+            .bind(format!("seed-{}", n))
+            .bind(index)
+            .bind(SEED_HEIGHT + n)
+            .bind(seed_time(n))
+            .bind(kind)
+            .bind(&mint)
+            .bind(source)
+            .bind(destination)
+            .bind(source_owner)
+            .bind(destination_owner)
+            .bind(amount)
+            .bind(&program)
+            .execute(&self.pool)
+            .await
+            .unwrap();
+        }
+        sqlx::query(
+            r#"INSERT INTO token_account_authority_events (txid, instruction_index, block_height, token_account, kind, previous_authority, new_authority)
+               VALUES ('seed-2', 0, $1, $2, 'set_owner', $3, $4)"#,
+        )
+        .bind(SEED_HEIGHT + 2)
+        .bind(&a)
+        .bind(&alice)
+        .bind(&bob)
+        .execute(&self.pool)
+        .await
+        .unwrap();
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs DATABASE_URL pointing at a database initialized from db-init"]
+async fn token_transfers_page_newest_first() {
+    let seeded = seeded().await;
+    seeded.seed_token_ledger().await;
+    let mint = key(0xe1);
+
+    let (status, page) = seeded.get(&format!("/api/tokens/{}/transfers?limit=2", mint)).await;
+    assert_eq!(status, StatusCode::OK);
+    let rows = |page: &Value| -> Vec<(String, i64)> {
+        page["transfers"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| (t["txid"].as_str().unwrap().to_string(), t["instruction_index"].as_i64().unwrap()))
+            .collect()
+    };
+    assert_eq!(rows(&page), vec![("seed-3".to_string(), 0), ("seed-1".to_string(), 1)]);
+    assert_eq!(page["transfers"][0]["kind"], "burn");
+    assert_eq!(page["transfers"][0]["amount"], "5");
+    let cursor = page["next_cursor"].as_str().unwrap().to_string();
+    assert_eq!(cursor, format!("{}:seed-1:1", SEED_HEIGHT + 1));
+
+    // The cursor continues after the last row returned, and the last page has none
+    let (_, page) = seeded.get(&format!("/api/tokens/{}/transfers?limit=2&cursor={}", mint, cursor)).await;
+    assert_eq!(rows(&page), vec![("seed-1".to_string(), 0), ("seed-0".to_string(), 0)]);
+    assert!(page["next_cursor"].is_null());
+
+    let (_, page) = seeded.get(&format!("/api/tokens/{}/transfers?start_time={}", mint, seed_time(3).timestamp())).await;
+    assert_eq!(rows(&page), vec![("seed-3".to_string(), 0)]);
+
+    // An account matches as the token account or as the owner of either side
+    let (_, page) = seeded.get(&format!("/api/accounts/{}/token-transfers", key(0xe3))).await;
+    assert_eq!(rows(&page), vec![("seed-1".to_string(), 1), ("seed-1".to_string(), 0), ("seed-0".to_string(), 0)]);
+    let (_, page) = seeded.get(&format!("/api/accounts/{}/token-transfers", key(0xe6))).await;
+    assert_eq!(rows(&page).len(), 3);
+
+    let (status, page) = seeded.get(&format!("/api/tokens/{}/transfers", key(0xe2))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(page["transfers"], serde_json::json!([]));
+    assert!(page["next_cursor"].is_null());
+
+    let (status, _) = seeded.get(&format!("/api/tokens/{}/transfers?cursor=not-a-cursor", mint)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    seeded.finish().await;
+}
+
+#[tokio::test]
+#[ignore = "needs DATABASE_URL pointing at a database initialized from db-init"]
+async fn token_details_and_holders() {
+    let seeded = seeded().await;
+    seeded.seed_token_ledger().await;
+    let mint = key(0xe1);
+    sqlx::query("INSERT INTO token_metadata (mint_address, metadata_address, name, symbol) VALUES ($1, $2, 'Seed', 'SEED')")
+        .bind(&mint)
+        .bind(key(0xe8))
+        .execute(&seeded.pool)
+        .await
+        .unwrap();
+
+    let (status, token) = seeded.get(&format!("/api/tokens/{}", mint)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(token["supply"], "95");
+    assert_eq!(token["decimals"], 6);
+    assert_eq!(token["metadata"]["symbol"], "SEED");
+    assert_eq!((token["holder_count"].as_i64(), token["holder_accounts"].as_i64()), (Some(2), Some(2)));
+    assert_eq!(
+        (token["transfer_count"].as_i64(), token["mint_count"].as_i64(), token["burn_count"].as_i64()),
+        (Some(2), Some(1), Some(1))
+    );
+    assert_eq!(token["first_activity"]["block_height"], SEED_HEIGHT);
+    assert_eq!(token["last_activity"]["block_height"], SEED_HEIGHT + 3);
+
+    let (status, _) = seeded.get(&format!("/api/tokens/{}", key(0xe2))).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Largest balance first; the cursor continues below the last holder returned
+    let (status, page) = seeded.get(&format!("/api/tokens/{}/holders?limit=1", mint)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(page["holders"].as_array().unwrap().len(), 1);
+    assert_eq!(page["holders"][0]["account_hex"], key(0xe5));
+    assert_eq!(page["holders"][0]["owner_hex"], key(0xe4));
+    assert_eq!(page["holders"][0]["balance"], "60");
+    assert!((page["holders"][0]["percent_of_supply"].as_f64().unwrap() - 6000.0 / 95.0).abs() < 1e-9);
+    let cursor = page["next_cursor"].as_str().unwrap().to_string();
+    assert_eq!(cursor, format!("60:{}", key(0xe5)));
+
+    let (_, page) = seeded.get(&format!("/api/tokens/{}/holders?limit=1&cursor={}", mint, cursor)).await;
+    assert_eq!(page["holders"][0]["account_hex"], key(0xe6));
+    assert_eq!(page["holders"][0]["balance"], "35");
+    assert!(page["next_cursor"].is_null());
+
+    // A closed account is no longer a holder
+    sqlx::query("UPDATE token_accounts SET is_closed = TRUE WHERE token_account_hex = $1")
+        .bind(key(0xe6))
+        .execute(&seeded.pool)
+        .await
+        .unwrap();
+    let (_, page) = seeded.get(&format!("/api/tokens/{}/holders", mint)).await;
+    assert_eq!(page["holders"].as_array().unwrap().len(), 1);
+
+    let (status, page) = seeded.get(&format!("/api/tokens/{}/holders", key(0xe2))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(page["holders"], serde_json::json!([]));
+    assert!(page["next_cursor"].is_null());
+
+    seeded.finish().await;
+}
+
+#[tokio::test]
+#[ignore = "needs DATABASE_URL pointing at a database initialized from db-init"]
+async fn token_supply_history_and_events() {
+    let seeded = seeded().await;
+    seeded.seed_token_ledger().await;
+    let mint = key(0xe1);
+    for (n, kind, amount, supply_after) in [(0, "mint", 100, 100), (3, "burn", 5, 95)] {
+        sqlx::query(
+            r#"INSERT INTO token_mint_events (txid, instruction_index, block_height, block_time, mint_address, kind, amount, supply_after)
+               VALUES ($1, 0, $2, $3, $4, $5, $6, $7)"#,
+        )
+        .bind(format!("seed-{}", n))
+        .bind(SEED_HEIGHT + n)
+        .bind(seed_time(n))
+        .bind(&mint)
+        .bind(kind)
+        .bind(amount)
+        .bind(supply_after)
+        .execute(&seeded.pool)
+        .await
+        .unwrap();
+    }
+
+    let (status, history) = seeded.get(&format!("/api/tokens/{}/supply-history?interval=hour", mint)).await;
+    assert_eq!(status, StatusCode::OK);
+    let buckets = history["buckets"].as_array().unwrap();
+    assert_eq!(buckets.len(), 2);
+    assert_eq!(time(&buckets[0]["bucket"]), seed_time(0));
+    assert_eq!(buckets[0]["minted"], "100");
+    assert_eq!(buckets[0]["opening_supply"], "0");
+    assert_eq!(buckets[0]["supply"], "100");
+    assert_eq!(time(&buckets[1]["bucket"]), seed_time(3));
+    assert_eq!(buckets[1]["burned"], "5");
+    assert_eq!(buckets[1]["opening_supply"], "100");
+    assert_eq!(buckets[1]["supply"], "95");
+
+    let (status, _) = seeded.get(&format!("/api/tokens/{}/supply-history?interval=week", mint)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Newest first, one page at a time
+    let (status, page) = seeded.get(&format!("/api/tokens/{}/supply-events?limit=1", mint)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(page["events"][0]["kind"], "burn");
+    assert_eq!(page["events"][0]["supply_after"], "95");
+    let cursor = page["next_cursor"].as_str().unwrap().to_string();
+    assert_eq!(cursor, format!("{}:seed-3:0", SEED_HEIGHT + 3));
+    let (_, page) = seeded.get(&format!("/api/tokens/{}/supply-events?limit=1&cursor={}", mint, cursor)).await;
+    assert_eq!(page["events"][0]["kind"], "mint");
+    assert!(page["next_cursor"].is_null());
+
+    let (_, page) = seeded.get(&format!("/api/tokens/{}/supply-events?kind=mint", mint)).await;
+    assert_eq!(page["events"].as_array().unwrap().len(), 1);
+
+    let (status, page) = seeded.get(&format!("/api/tokens/{}/supply-events", key(0xe2))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(page["events"], serde_json::json!([]));
+    let (_, history) = seeded.get(&format!("/api/tokens/{}/supply-history", key(0xe2))).await;
+    assert_eq!(history["buckets"], serde_json::json!([]));
+
+    seeded.finish().await;
+}
+
+#[tokio::test]
+#[ignore = "needs DATABASE_URL pointing at a database initialized from db-init"]
+async fn native_balance_history_buckets() {
+    let seeded = seeded().await;
+    seeded.seed_native_history().await;
+    let address = key(0xe3);
+    let ohlc = |p: &Value| -> Vec<String> {
+        ["open", "high", "low", "close"].iter().map(|k| p[*k].as_str().unwrap().to_string()).collect()
+    };
+
+    let (status, history) = seeded.get(&format!("/api/accounts/{}/balance-history?interval=hour", address)).await;
+    assert_eq!(status, StatusCode::OK);
+    let points = history["points"].as_array().unwrap();
+    assert_eq!(points.iter().map(|p| time(&p["bucket"])).collect::<Vec<_>>(), vec![seed_time(0), seed_time(1), seed_time(3)]);
+    assert_eq!(ohlc(&points[0]), vec!["0", "1000", "0", "1000"]);
+    assert_eq!(ohlc(&points[1]), vec!["1000", "1000", "800", "800"]);
+    assert_eq!(ohlc(&points[2]), vec!["800", "900", "800", "900"]);
+
+    // A window opens with the balance carried in from before it
+    let (_, history) = seeded
+        .get(&format!("/api/accounts/{}/balance-history?interval=hour&from={}", address, seed_time(1).timestamp()))
+        .await;
+    assert_eq!(ohlc(&history["points"][0]), vec!["1000", "1000", "800", "800"]);
+
+    // Daily buckets fold every change of the day into one point
+    let (_, history) = seeded.get(&format!("/api/accounts/{}/balance-history", address)).await;
+    assert_eq!(history["points"].as_array().unwrap().len(), 1);
+    assert_eq!(ohlc(&history["points"][0]), vec!["0", "1000", "0", "900"]);
+    assert_eq!(history["points"][0]["changes"], 3);
+
+    let (status, history) = seeded.get(&format!("/api/accounts/{}/balance-history", key(0xea))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(history["points"], serde_json::json!([]));
+
+    let (status, _) = seeded.get(&format!("/api/accounts/{}/balance-history?interval=minute", address)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    seeded.finish().await;
+}
+
+#[tokio::test]
+#[ignore = "needs DATABASE_URL pointing at a database initialized from db-init"]
+async fn account_balances_at_a_height() {
+    let seeded = seeded().await;
+    seeded.seed_token_ledger().await;
+    seeded.seed_native_history().await;
+    let (alice, bob) = (key(0xe3), key(0xe4));
+    let tokens = |balances: &Value| -> Vec<(String, String, String)> {
+        balances["tokens"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| {
+                (
+                    t["account_hex"].as_str().unwrap().to_string(),
+                    t["owner"].as_str().unwrap().to_string(),
+                    t["balance"].as_str().unwrap().to_string(),
+                )
+            })
+            .collect()
+    };
+    let b58 = |byte: u8| bs58::encode([byte; 32]).into_string();
+
+    let (status, balances) = seeded.get(&format!("/api/accounts/{}/balances?at_height={}", alice, SEED_HEIGHT)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(balances["native"]["balance"], "1000");
+    assert_eq!(tokens(&balances), vec![(key(0xe5), b58(0xe3), "100".to_string())]);
+
+    // `at_time` resolves to the last block at or before it
+    let at_time = (seed_time(1) + chrono::Duration::minutes(30)).timestamp();
+    let (_, balances) = seeded.get(&format!("/api/accounts/{}/balances?at_time={}", alice, at_time)).await;
+    assert_eq!(balances["block_height"], SEED_HEIGHT + 1);
+    assert_eq!(time(&balances["block_time"]), seed_time(1));
+    assert_eq!(balances["native"]["balance"], "800");
+    assert_eq!(tokens(&balances), vec![(key(0xe5), b58(0xe3), "60".to_string())]);
+
+    // The account moves to the new owner at the height of the SetAuthority, not before
+    let (_, balances) = seeded.get(&format!("/api/accounts/{}/balances?at_height={}", alice, SEED_HEIGHT + 2)).await;
+    assert_eq!(balances["native"]["as_of_height"], SEED_HEIGHT + 1);
+    assert!(tokens(&balances).is_empty());
+    let (_, balances) = seeded.get(&format!("/api/accounts/{}/balances?at_height={}", bob, SEED_HEIGHT + 1)).await;
+    assert!(tokens(&balances).is_empty());
+    let (_, balances) = seeded.get(&format!("/api/accounts/{}/balances?at_height={}", bob, SEED_HEIGHT + 2)).await;
+    assert_eq!(tokens(&balances), vec![(key(0xe5), b58(0xe4), "60".to_string())]);
+
+    // An address with no history has nothing to report
+    let (status, balances) = seeded.get(&format!("/api/accounts/{}/balances?at_height={}", key(0xea), SEED_HEIGHT)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(balances["native"]["balance"], "0");
+    assert!(balances["native"]["as_of_height"].is_null());
+    assert!(tokens(&balances).is_empty());
+
+    let (status, _) = seeded.get(&format!("/api/accounts/{}/balances", alice)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = seeded.get(&format!("/api/accounts/{}/balances?at_height=tip", alice)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    seeded.finish().await;
+}
+
+#[tokio::test]
+#[ignore = "needs DATABASE_URL pointing at a database initialized from db-init"]
+async fn token_holders_snapshot_at_a_height() {
+    let seeded = seeded().await;
+    seeded.seed_token_ledger().await;
+    let mint = key(0xe1);
+    sqlx::query(
+        r#"INSERT INTO token_mint_events (txid, instruction_index, block_height, block_time, mint_address, kind, amount, supply_after)
+           VALUES ('seed-0', 0, $1, $2, $3, 'mint', 100, 100)"#,
+    )
+    .bind(SEED_HEIGHT)
+    .bind(seed_time(0))
+    .bind(&mint)
+    .execute(&seeded.pool)
+    .await
+    .unwrap();
+    let holders = |page: &Value| -> Vec<(String, String)> {
+        page["holders"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|h| (h["account_hex"].as_str().unwrap().to_string(), h["balance"].as_str().unwrap().to_string()))
+            .collect()
+    };
+
+    let (status, page) = seeded.get(&format!("/api/tokens/{}/holders/snapshot?at_height={}&limit=1", mint, SEED_HEIGHT + 1)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(page["supply"], "100");
+    assert_eq!(holders(&page), vec![(key(0xe5), "60".to_string())]);
+    assert_eq!(page["holders"][0]["owner_hex"], key(0xe3));
+    let cursor = page["next_cursor"].as_str().unwrap().to_string();
+    assert_eq!(cursor, format!("60:{}", key(0xe5)));
+    let (_, page) = seeded
+        .get(&format!("/api/tokens/{}/holders/snapshot?at_height={}&limit=1&cursor={}", mint, SEED_HEIGHT + 1, cursor))
+        .await;
+    assert_eq!(holders(&page), vec![(key(0xe6), "40".to_string())]);
+    assert!(page["next_cursor"].is_null());
+
+    let at_time = (seed_time(3) + chrono::Duration::minutes(1)).timestamp();
+    let (_, page) = seeded.get(&format!("/api/tokens/{}/holders/snapshot?at_time={}", mint, at_time)).await;
+    assert_eq!(page["block_height"], SEED_HEIGHT + 3);
+    assert_eq!(holders(&page), vec![(key(0xe5), "60".to_string()), (key(0xe6), "35".to_string())]);
+
+    let (status, page) = seeded.get(&format!("/api/tokens/{}/holders/snapshot?at_height={}", key(0xe2), SEED_HEIGHT + 3)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(page["supply"].is_null());
+    assert!(holders(&page).is_empty());
+
+    let (status, _) = seeded.get(&format!("/api/tokens/{}/holders/snapshot", mint)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    seeded.finish().await;
+}
+
+#[tokio::test]
+#[ignore = "needs DATABASE_URL pointing at a database initialized from db-init"]
+async fn account_history_and_state_at_a_height() {
+    let seeded = seeded().await;
+    let address = key(0xe3);
+    let changes = [(0, Some(1000i64), Some(vec![1u8]), false), (2, Some(500), Some(vec![1, 2]), false), (3, None, None, true)];
+    for (n, lamports, data, is_deleted) in changes {
+        sqlx::query("INSERT INTO account_history (pubkey, height, lamports, owner, data, is_deleted) VALUES ($1, $2, $3, $4, $5, $6)")
+            .bind(&address)
+            .bind(SEED_HEIGHT + n)
+            .bind(lamports)
+            .bind(lamports.map(|_| key(0)))
+            .bind(data)
+            .bind(is_deleted)
+            .execute(&seeded.pool)
+            .await
+            .unwrap();
+    }
+    let heights = |history: &Value| -> Vec<i64> {
+        history["changes"].as_array().unwrap().iter().map(|c| c["height"].as_i64().unwrap()).collect()
+    };
+
+    let (status, history) = seeded.get(&format!("/api/accounts/{}/history?limit=2", address)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(heights(&history), vec![SEED_HEIGHT + 3, SEED_HEIGHT + 2]);
+    assert_eq!(history["changes"][0]["is_deleted"], true);
+    assert!(history["changes"][0]["lamports"].is_null());
+    assert_eq!(history["changes"][1]["data_len"], 2);
+    let cursor = history["next_cursor"].as_str().unwrap().to_string();
+    assert_eq!(cursor, (SEED_HEIGHT + 2).to_string());
+    let (_, history) = seeded.get(&format!("/api/accounts/{}/history?limit=2&cursor={}", address, cursor)).await;
+    assert_eq!(heights(&history), vec![SEED_HEIGHT]);
+    assert!(history["next_cursor"].is_null());
+
+    // The state at a height is the last change at or below it
+    let (status, state) = seeded.get(&format!("/api/accounts/{}/state?at_height={}", address, SEED_HEIGHT + 1)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(state["state_height"], SEED_HEIGHT);
+    assert_eq!(state["lamports"], 1000);
+    assert_eq!(state["data"], "01");
+    let (_, state) = seeded.get(&format!("/api/accounts/{}/state?at_time={}", address, seed_time(2).timestamp())).await;
+    assert_eq!(state["at_height"], SEED_HEIGHT + 2);
+    assert_eq!(state["data"], "0102");
+    let (_, state) = seeded.get(&format!("/api/accounts/{}/state?at_height={}", address, SEED_HEIGHT + 3)).await;
+    assert_eq!(state["is_deleted"], true);
+    assert!(state["data"].is_null());
+
+    // Nothing recorded yet at the height, or ever
+    let (status, _) = seeded.get(&format!("/api/accounts/{}/state?at_height={}", address, SEED_HEIGHT - 1)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, history) = seeded.get(&format!("/api/accounts/{}/history", key(0xea))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(heights(&history).is_empty());
+    assert!(history["next_cursor"].is_null());
+
+    let (status, _) = seeded.get(&format!("/api/accounts/{}/history?cursor=latest", address)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    seeded.finish().await;
+}
+
+#[tokio::test]
+#[ignore = "needs DATABASE_URL pointing at a database initialized from db-init"]
+async fn transaction_rollback_history() {
+    let seeded = seeded().await;
+    for action in ["rolled_back", "reapplied"] {
+        sqlx::query("INSERT INTO transaction_rollbacks (txid, action, block_height) VALUES ('seed-1', $1, $2)")
+            .bind(action)
+            .bind(SEED_HEIGHT + 1)
+            .execute(&seeded.pool)
+            .await
+            .unwrap();
+    }
+
+    // Oldest first; a reapply after the rollback leaves the transaction applied
+    let (status, history) = seeded.get("/api/transactions/seed-1/rollback-history").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(history["rolled_back"], false);
+    assert_eq!(history["rollback_count"], 1);
+    assert_eq!(history["reapply_count"], 1);
+    assert_eq!(history["events"][0]["action"], "rolled_back");
+    assert_eq!(history["events"][1]["action"], "reapplied");
+    assert_eq!(history["events"][1]["block_height"], SEED_HEIGHT + 1);
+
+    sqlx::query("INSERT INTO transaction_rollbacks (txid, action, block_height) VALUES ('seed-1', 'rolled_back', NULL)")
+        .execute(&seeded.pool)
+        .await
+        .unwrap();
+    let (_, history) = seeded.get("/api/transactions/seed-1/rollback-history").await;
+    assert_eq!(history["rolled_back"], true);
+    assert_eq!(history["rollback_count"], 2);
+    assert!(history["events"][2]["block_height"].is_null());
+
+    let (status, history) = seeded.get("/api/transactions/seed-0/rollback-history").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(history["rolled_back"], false);
+    assert_eq!(history["events"], serde_json::json!([]));
+
+    let (status, _) = seeded.get("/api/transactions/seed-unknown/rollback-history").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    seeded.finish().await;
+}
+
+#[tokio::test]
+#[ignore = "needs DATABASE_URL pointing at a database initialized from db-init"]
+async fn instruction_type_stats() {
+    let seeded = seeded().await;
+    let program = key(0xe7);
+    for (n, index, instruction_type) in [(0, 0, "MintTo"), (1, 0, "Transfer"), (1, 1, "Transfer")] {
+        sqlx::query("INSERT INTO instructions (txid, instruction_index, block_height, program_id, instruction_type) VALUES ($1, $2, $3, $4, $5)")
+            .bind(format!("seed-{}", n))
+            .bind(index)
+            .bind(SEED_HEIGHT + n)
+            .bind(&program)
+            .bind(instruction_type)
+            .execute(&seeded.pool)
+            .await
+            .unwrap();
+    }
+    let stats = |body: &Value| -> Vec<(String, i64, i64)> {
+        body.as_array()
+            .unwrap()
+            .iter()
+            .map(|s| {
+                (
+                    s["instruction_type"].as_str().unwrap().to_string(),
+                    s["count"].as_i64().unwrap(),
+                    s["last_block_height"].as_i64().unwrap(),
+                )
+            })
+            .collect()
+    };
+
+    // Most frequent type first; base58 and hex program ids select the same rows
+    let (status, body) = seeded.get(&format!("/api/instructions/types?program_id={}", program)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        stats(&body),
+        vec![("Transfer".to_string(), 2, SEED_HEIGHT + 1), ("MintTo".to_string(), 1, SEED_HEIGHT)]
+    );
+    assert_eq!(body[0]["program_id"], program);
+    let base58 = bs58::encode([0xe7u8; 32]).into_string();
+    let (_, by_base58) = seeded.get(&format!("/api/instructions/types?program_id={}", base58)).await;
+    assert_eq!(by_base58, body);
+
+    let (_, body) = seeded
+        .get(&format!("/api/instructions/types?program_id={}&since_height={}", program, SEED_HEIGHT + 1))
+        .await;
+    assert_eq!(stats(&body), vec![("Transfer".to_string(), 2, SEED_HEIGHT + 1)]);
+
+    let (status, body) = seeded.get(&format!("/api/instructions/types?program_id={}", key(0xe2))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, serde_json::json!([]));
+
+    let (status, _) = seeded.get("/api/instructions/types?program_id=not-a-program").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    seeded.finish().await;
+}
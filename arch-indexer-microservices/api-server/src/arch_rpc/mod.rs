@@ -1,18 +1,76 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::sync::{Arc, OnceLock};
 use std::time::Duration;
 use tracing::{error, info, warn};
-use tokio::time::sleep;
 
 pub mod websocket;
+pub use node_rpc::{limiter, pool, recorder, retry, tls};
+pub use limiter::AdaptiveLimiter;
+pub use pool::EndpointPool;
+pub use retry::{CircuitBreaker, RetryPolicy, RpcError};
 pub use websocket::{WebSocketClient, WebSocketEvent};
 
+use crate::config::settings::ArchNodeSettings;
+use limiter::{LimiterConfig, Outcome};
+
+/// Maximum number of calls sent in a single JSON-RPC batch request.
+const MAX_BATCH_SIZE: usize = 100;
+
 #[derive(Debug, Clone)]
 pub struct ArchRpcClient {
     client: Client,
-    url: String,
+    pool: Arc<EndpointPool>,
+    limiter: Arc<AdaptiveLimiter>,
+    retry: RetryPolicy,
+    breaker: Arc<CircuitBreaker>,
+}
+
+static SHARED_CLIENT: OnceLock<ArchRpcClient> = OnceLock::new();
+static INDEXER_CLIENT: OnceLock<Arc<arch_indexer::arch_rpc::ArchRpcClient>> = OnceLock::new();
+
+/// Indexer-library client over the same `arch_node` settings as [`ArchRpcClient::shared`],
+/// for handlers that store blocks through the indexer's write path.
+pub fn indexer_client() -> Arc<arch_indexer::arch_rpc::ArchRpcClient> {
+    INDEXER_CLIENT
+        .get_or_init(|| {
+            let built = crate::config::Settings::new()
+                .map_err(anyhow::Error::from)
+                .and_then(|settings| arch_indexer::arch_rpc::ArchRpcClient::from_settings(&settings.arch_node.to_indexer()));
+            let client = built.unwrap_or_else(|e| {
+                warn!("Indexer RPC client falls back to the default node settings: {}", e);
+                arch_indexer::arch_rpc::ArchRpcClient::from_settings(&ArchNodeSettings::default().to_indexer())
+                    .expect("indexer RPC client with default settings")
+            });
+            Arc::new(client)
+        })
+        .clone()
+}
+
+/// A JSON-RPC reply whose body has already been read, so the limiter can see RPC errors.
+#[derive(Debug)]
+struct RpcReply {
+    status: reqwest::StatusCode,
+    body: serde_json::Result<serde_json::Value>,
+}
+
+impl RpcReply {
+    fn status(&self) -> reqwest::StatusCode {
+        self.status
+    }
+
+    fn json(self) -> serde_json::Result<serde_json::Value> {
+        self.body
+    }
+
+    fn is_rpc_error(&self) -> bool {
+        match &self.body {
+            Ok(body) => body.get("error").map(|e| !e.is_null()).unwrap_or(false),
+            Err(_) => false,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -46,404 +104,403 @@ pub struct ProcessedTransaction {
     pub logs: Vec<String>,
 }
 
+fn block_from_response(hash: &str, block_response: BlockResponse) -> Block {
+    // Convert raw bytes to hex string for previous_block_hash
+    let previous_hash = hex::encode(&block_response.previous_block_hash);
+
+    // Convert transactions to string format
+    let transaction_strings: Vec<String> = block_response.transactions
+        .iter()
+        .map(|tx| tx.to_string())
+        .collect();
+
+    Block {
+        height: block_response.block_height,
+        hash: hash.to_string(),
+        timestamp: block_response.timestamp,
+        bitcoin_block_height: block_response.bitcoin_block_height,
+        transactions: transaction_strings,
+        transaction_count: block_response.transactions.len() as i64,
+        previous_block_hash: Some(previous_hash),
+    }
+}
+
+/// Matches a JSON-RPC batch response to the `len` requests it answers (ids `0..len`).
+/// Responses may arrive in any order; requests without a response yield an error.
+fn match_batch_response(len: usize, response: serde_json::Value) -> Result<Vec<Result<serde_json::Value>>> {
+    let entries = match response {
+        serde_json::Value::Array(entries) => entries,
+        other => {
+            // Nodes without batch support answer with a single error object
+            if let Some(error) = other.get("error") {
+                return Err(anyhow::anyhow!("RPC batch error: {:?}", error));
+            }
+            return Err(anyhow::anyhow!("Unexpected batch response: {}", other));
+        }
+    };
+
+    let mut slots: Vec<Option<Result<serde_json::Value>>> = (0..len).map(|_| None).collect();
+    for mut entry in entries {
+        let id = match entry.get("id").and_then(|v| v.as_u64()) {
+            Some(id) if (id as usize) < len => id as usize,
+            _ => continue,
+        };
+        slots[id] = Some(match entry.get("error") {
+            Some(error) if !error.is_null() => Err(anyhow::anyhow!("RPC error: {:?}", error)),
+            _ => Ok(entry.get_mut("result").map(serde_json::Value::take).unwrap_or(serde_json::Value::Null)),
+        });
+    }
+
+    Ok(slots
+        .into_iter()
+        .enumerate()
+        .map(|(id, slot)| slot.unwrap_or_else(|| Err(anyhow::anyhow!("No response for batch id {}", id))))
+        .collect())
+}
+
 impl ArchRpcClient {
-    pub fn new(_url: String) -> Self {
+    pub fn new(url: String) -> Self {
+        // Default settings name no certificate files, so building the client cannot fail
+        Self::with_pool(EndpointPool::single(url), &ArchNodeSettings::default())
+            .expect("Arch RPC client with default settings")
+    }
+
+    /// Client over every configured node endpoint, with background health checks when
+    /// more than one endpoint is configured. Fails when the `tls` settings are invalid
+    /// rather than connecting without the configured CA or client identity.
+    pub fn from_settings(settings: &ArchNodeSettings) -> Result<Self> {
+        let client = Self::with_pool(EndpointPool::new(settings.endpoints(), settings.max_lag_blocks), settings)?;
+        client.pool.spawn_health_checks(
+            client.client.clone(),
+            Duration::from_secs(settings.health_check_interval_seconds.max(1)),
+        );
+        Ok(client)
+    }
+
+    /// Builds the process-wide client returned by [`shared`](Self::shared) from the loaded
+    /// `arch_node` settings. Called once at startup, so invalid TLS settings stop the server
+    /// instead of failing a request.
+    pub fn init_shared(settings: &ArchNodeSettings) -> Result<()> {
+        let client = Self::from_settings(settings)?;
+        let _ = SHARED_CLIENT.set(client);
+        Ok(())
+    }
+
+    /// Process-wide client, so request handlers share one pool and its health state instead
+    /// of probing the nodes per request. When `init_shared` was not called, as for a router
+    /// built directly in tests, the client is built from the settings on first use, or from
+    /// the default settings when those cannot be loaded or are invalid.
+    pub fn shared() -> Self {
+        SHARED_CLIENT
+            .get_or_init(|| {
+                let built = crate::config::Settings::new()
+                    .map_err(anyhow::Error::from)
+                    .and_then(|settings| Self::from_settings(&settings.arch_node));
+                built.unwrap_or_else(|e| {
+                    warn!("Arch RPC client falls back to the default node settings: {}", e);
+                    Self::from_settings(&ArchNodeSettings::default()).expect("Arch RPC client with default settings")
+                })
+            })
+            .clone()
+    }
+
+    fn with_pool(pool: EndpointPool, settings: &ArchNodeSettings) -> Result<Self> {
         // Create a client with optimized settings for high-throughput indexing
+        let tls_config = tls::client_config(&settings.tls).context("invalid arch_node.tls settings")?;
         let client = Client::builder()
-            .danger_accept_invalid_certs(true)
             .timeout(Duration::from_secs(30))
             .pool_max_idle_per_host(100)
             .pool_idle_timeout(Duration::from_secs(90))
             .tcp_keepalive(Some(Duration::from_secs(60)))
+            .use_preconfigured_tls(tls_config)
             .build()
-            .unwrap_or_else(|_| Client::new());
-        
-        // Temporarily hardcode the beta network URL for testing
-        let _url = "http://44.196.173.35:8081".to_string();
-        info!("Initialized Arch RPC client for: {}", _url);
-        Self { client, url: _url }
+            .context("cannot build the Arch RPC HTTP client")?;
+
+        let limiter = LimiterConfig::bounded(
+            settings.min_concurrency,
+            settings.max_concurrency,
+            settings.initial_concurrency,
+            settings.latency_target_ms,
+        );
+        let retry = RetryPolicy::from_settings(&settings.retry);
+        info!(
+            "Initialized Arch RPC client for: {} (concurrency {} in {}..={}, {} attempts per call)",
+            pool.urls().join(", "),
+            limiter.initial,
+            limiter.min,
+            limiter.max,
+            retry.max_attempts
+        );
+        Ok(Self {
+            client,
+            pool: Arc::new(pool),
+            limiter: Arc::new(AdaptiveLimiter::new(limiter)),
+            retry,
+            breaker: Arc::new(CircuitBreaker::from_settings(&settings.retry)),
+        })
     }
 
-    pub async fn is_node_ready(&self) -> Result<bool> {
-        info!("Checking if Arch node is ready at: {}", self.url);
-        
-        let response = self.client
-            .post(&self.url)
-            .json(&json!({
-                "jsonrpc": "2.0",
-                "method": "is_node_ready",
-                "params": [],
-                "id": 1
-            }))
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            warn!("Node not ready, status: {}", response.status());
-            return Ok(false);
-        }
-
-        let json_response = response.json::<serde_json::Value>().await?;
-        let result = json_response["result"].as_bool().unwrap_or(false);
-        
-        info!("Node ready status: {}", result);
-        Ok(result)
+    pub fn pool(&self) -> &Arc<EndpointPool> {
+        &self.pool
     }
 
-    pub async fn get_block_count(&self) -> Result<i64> {
-        let response = self.client
-            .post(&self.url)
-            .json(&json!({
-                "jsonrpc": "2.0",
-                "method": "get_block_count",
-                "params": [],
-                "id": 1
-            }))
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            return Err(anyhow::anyhow!("HTTP error: {}", response.status()));
-        }
-
-        let json_response = response.json::<serde_json::Value>().await?;
-        
-        if let Some(error) = json_response.get("error") {
-            return Err(anyhow::anyhow!("RPC error: {:?}", error));
-        }
+    pub fn limiter(&self) -> &Arc<AdaptiveLimiter> {
+        &self.limiter
+    }
 
-        let result = json_response["result"].as_i64().unwrap_or(0);
-        Ok(result)
+    pub fn breaker(&self) -> &Arc<CircuitBreaker> {
+        &self.breaker
     }
 
-    pub async fn get_block_hash(&self, height: i64) -> Result<String> {
-        let mut attempts = 0;
-        let max_attempts = 5;
-        let base_delay = Duration::from_millis(100);
-
-        while attempts < max_attempts {
-            match self.client
-                .post(&self.url)
-                .json(&json!({
-                    "jsonrpc": "2.0",
-                    "method": "get_block_hash",
-                    "params": height,
-                    "id": 1
-                }))
-                .send()
-                .await
-            {
-                Ok(response) => {
-                    if !response.status().is_success() {
-                        warn!("HTTP error for height {}: {}", height, response.status());
-                        attempts += 1;
-                        if attempts < max_attempts {
-                            sleep(base_delay * attempts as u32).await;
-                            continue;
-                        }
-                        return Err(anyhow::anyhow!("HTTP error after {} attempts", max_attempts));
-                    }
-
-                    match response.json::<serde_json::Value>().await {
-                        Ok(json_response) => {
-                            if let Some(error) = json_response.get("error") {
-                                warn!("RPC error for height {}: {:?}", height, error);
-                                attempts += 1;
-                                if attempts < max_attempts {
-                                    sleep(base_delay * attempts as u32).await;
-                                    continue;
-                                }
-                                return Err(anyhow::anyhow!("RPC error for height {}: {:?}", height, error));
-                            }
-
-                            let result = json_response.get("result");
-                            match result {
-                                Some(result) => {
-                                    if let Some(hash) = result.as_str() {
-                                        return Ok(hash.to_string());
-                                    } else {
-                                        warn!("Unexpected result type for height {}: {:?}", height, result);
-                                        attempts += 1;
-                                        if attempts < max_attempts {
-                                            sleep(base_delay * attempts as u32).await;
-                                            continue;
-                                        }
-                                        return Err(anyhow::anyhow!("Invalid result type for height {}", height));
-                                    }
-                                },
-                                None => {
-                                    warn!("No result in response for height {}: {:?}", height, json_response);
-                                    attempts += 1;
-                                    if attempts < max_attempts {
-                                        sleep(base_delay * attempts as u32).await;
-                                        continue;
-                                    }
-                                    return Err(anyhow::anyhow!("No result in response for height {}", height));
-                                }
-                            }
-                        },
-                        Err(e) => {
-                            error!("JSON decode error for height {}: {}", height, e);
-                            attempts += 1;
-                            if attempts < max_attempts {
-                                sleep(base_delay * attempts as u32).await;
-                                continue;
-                            }
-                            return Err(anyhow::anyhow!("JSON decode error for height {}: {}", height, e));
-                        }
-                    }
-                },
-                Err(e) => {
-                    error!("Request error for height {}: {}", height, e);
-                    attempts += 1;
-                    if attempts < max_attempts {
-                        sleep(base_delay * attempts as u32).await;
-                        continue;
-                    }
-                    return Err(anyhow::anyhow!("Request error for height {}: {}", height, e));
+    /// Posts a JSON-RPC body to the next endpoint from the pool under an adaptive
+    /// concurrency permit and records the outcome with both, so failing nodes get ejected,
+    /// retries fail over to the others, and timeouts, HTTP 429/5xx or RPC errors shrink the
+    /// in-flight limit. The exchange is also written to the ARCH_RECORD_DIR fixtures when
+    /// recording is enabled.
+    async fn send<B: Serialize + ?Sized>(&self, body: &B) -> reqwest::Result<RpcReply> {
+        let permit = self.limiter.acquire().await;
+        let endpoint = self.pool.pick();
+        let result = match self.client.post(&endpoint.url).json(body).send().await {
+            Ok(response) => {
+                let status = response.status();
+                response
+                    .bytes()
+                    .await
+                    .map(|bytes| RpcReply { status, body: serde_json::from_slice(&bytes) })
+            }
+            Err(e) => Err(e),
+        };
+        let overloaded = match &result {
+            Ok(reply) => {
+                let status = reply.status();
+                if status.is_server_error() {
+                    self.pool.record_failure(endpoint);
+                } else {
+                    self.pool.record_success(endpoint);
                 }
+                status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS || reply.is_rpc_error()
+            }
+            Err(_) => {
+                self.pool.record_failure(endpoint);
+                true
+            }
+        };
+        self.limiter.release(permit, if overloaded { Outcome::Overloaded } else { Outcome::Success });
+        if let (Some(recorder), Ok(RpcReply { body: Ok(response), .. })) = (recorder::global(), &result) {
+            if let Ok(request) = serde_json::to_value(body) {
+                recorder.record_rpc(&request, response);
             }
         }
-
-        Err(anyhow::anyhow!("Failed to get block hash for height {} after {} attempts", height, max_attempts))
+        result
     }
 
-    pub async fn get_block(&self, hash: &str, height: i64) -> Result<Block> {
-        let mut attempts = 0;
-        let max_attempts = 5;
-        let base_delay = Duration::from_millis(200);
-
-        while attempts < max_attempts {
-            match self.client
-                .post(&self.url)
-                .json(&json!({
-                    "jsonrpc": "2.0",
-                    "method": "get_block",
-                    "params": [hash],
-                    "id": 1
-                }))
-                .send()
-                .await
-            {
-                Ok(response) => {
-                    if !response.status().is_success() {
-                        warn!("HTTP error for block {}: {}", hash, response.status());
-                        attempts += 1;
-                        if attempts < max_attempts {
-                            sleep(base_delay * attempts as u32).await;
-                            continue;
-                        }
-                        return Err(anyhow::anyhow!("HTTP error for block {}: {}", hash, response.status()));
-                    }
-
-                    match response.json::<serde_json::Value>().await {
-                        Ok(json_response) => {
-                            if let Some(error) = json_response.get("error") {
-                                error!("RPC error for block {}: {:?}", hash, error);
-                                attempts += 1;
-                                if attempts < max_attempts {
-                                    sleep(base_delay * attempts as u32).await;
-                                    continue;
-                                }
-                                return Err(anyhow::anyhow!("RPC error for block {}: {:?}", hash, error));
-                            }
-
-                            // Debug: Log the actual response structure
-                            info!("🔍 Raw RPC response for block {}: {:?}", hash, json_response["result"]);
-                            
-                            match serde_json::from_value::<BlockResponse>(json_response["result"].clone()) {
-                                Ok(block_response) => {
-                                    // Convert raw bytes to hex string for previous_block_hash
-                                    let previous_hash = hex::encode(&block_response.previous_block_hash);
-                                    
-                                    // Convert transactions to string format
-                                    let transaction_strings: Vec<String> = block_response.transactions
-                                        .iter()
-                                        .map(|tx| tx.to_string())
-                                        .collect();
-                                    
-                                    return Ok(Block {
-                                        height: block_response.block_height,
-                                        hash: hash.to_string(),
-                                        timestamp: block_response.timestamp,
-                                        bitcoin_block_height: block_response.bitcoin_block_height,
-                                        transactions: transaction_strings,
-                                        transaction_count: block_response.transactions.len() as i64,
-                                        previous_block_hash: Some(previous_hash),
-                                    });
-                                },
-                                Err(e) => {
-                                    error!("Block deserialization error for {}: {}", hash, e);
-                                    // Log the raw response for debugging
-                                    error!("🔍 Raw response that failed to deserialize: {:?}", json_response["result"]);
-                                    attempts += 1;
-                                    if attempts < max_attempts {
-                                        sleep(base_delay * attempts as u32).await;
-                                        continue;
-                                    }
-                                    return Err(anyhow::anyhow!("Block deserialization error for {}: {}", hash, e));
-                                }
-                            }
-                        },
-                        Err(e) => {
-                            error!("JSON decode error for block {}: {}", hash, e);
-                            attempts += 1;
-                            if attempts < max_attempts {
-                                sleep(base_delay * attempts as u32).await;
-                                continue;
-                            }
-                            return Err(anyhow::anyhow!("JSON decode error for block {}: {}", hash, e));
-                        }
-                    }
-                },
-                Err(e) => {
-                    error!("Request error for block {}: {}", hash, e);
-                    attempts += 1;
-                    if attempts < max_attempts {
-                        sleep(base_delay * attempts as u32).await;
-                        continue;
-                    }
-                    return Err(anyhow::anyhow!("Request error for block {}: {}", hash, e));
-                }
+    /// One JSON-RPC call under the retry policy and circuit breaker. `decode` runs inside
+    /// the retry loop, so a malformed `result` is retried like a transport error.
+    async fn call_as<T>(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+        decode: impl Fn(serde_json::Value) -> Result<T, RpcError>,
+    ) -> Result<T, RpcError> {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params,
+            "id": 1
+        });
+        let (body, decode) = (&body, &decode);
+        retry::with_retry(&self.retry, &self.breaker, method, move || async move {
+            let response = self.send(body).await?;
+            if !response.status().is_success() {
+                return Err(RpcError::Http(response.status()));
             }
-        }
-
-        Err(anyhow::anyhow!("Failed to get block after {} attempts", max_attempts))
+            let mut json_response = response.json().map_err(|e| RpcError::Decode(e.to_string()))?;
+            if let Some(error) = json_response.get("error").filter(|e| !e.is_null()) {
+                return Err(RpcError::from_rpc_error(error));
+            }
+            decode(json_response.get_mut("result").map(serde_json::Value::take).unwrap_or(serde_json::Value::Null))
+        })
+        .await
     }
 
-    pub async fn get_mempool_txids(&self) -> Result<Vec<String>> {
-        let response = self.client
-            .post(&self.url)
-            .json(&json!({
-                "jsonrpc": "2.0",
-                "method": "get_mempool_txids",
-                "params": [],
-                "id": 1
-            }))
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            return Err(anyhow::anyhow!("HTTP error: {}", response.status()));
-        }
+    /// Raw JSON-RPC call returning the `result` member.
+    pub async fn call(&self, method: &str, params: serde_json::Value) -> Result<serde_json::Value> {
+        Ok(self.call_as(method, params, Ok).await?)
+    }
 
-        let json_response = response.json::<serde_json::Value>().await?;
-        
-        if let Some(error) = json_response.get("error") {
-            return Err(anyhow::anyhow!("RPC error: {:?}", error));
-        }
+    pub async fn is_node_ready(&self) -> Result<bool> {
+        info!("Checking if Arch node is ready at: {}", self.pool.urls().join(", "));
 
-        let result = json_response["result"].as_array()
-            .ok_or_else(|| anyhow::anyhow!("Invalid response format"))?
-            .iter()
-            .filter_map(|v| v.as_str().map(|s| s.to_string()))
-            .collect();
+        let result = match self.call_as("is_node_ready", json!([]), |v| Ok(v.as_bool().unwrap_or(false))).await {
+            Ok(ready) => ready,
+            Err(RpcError::Http(status)) => {
+                warn!("Node not ready, status: {}", status);
+                false
+            }
+            Err(e) => return Err(e.into()),
+        };
 
+        info!("Node ready status: {}", result);
         Ok(result)
     }
 
-    pub async fn get_mempool_entry(&self, txid: &str) -> Result<Option<serde_json::Value>> {
-        let response = self.client
-            .post(&self.url)
-            .json(&json!({
-                "jsonrpc": "2.0",
-                "method": "get_mempool_entry",
-                "params": [txid],
-                "id": 1
-            }))
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            return Err(anyhow::anyhow!("HTTP error: {}", response.status()));
-        }
+    pub async fn get_block_count(&self) -> Result<i64> {
+        self.call_as("get_block_count", json!([]), |v| {
+            v.as_i64().ok_or_else(|| RpcError::Decode(format!("block count {}", v)))
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("get_block_count failed: {}", e))
+    }
 
-        let json_response = response.json::<serde_json::Value>().await?;
-        
-        if let Some(error) = json_response.get("error") {
-            return Err(anyhow::anyhow!("RPC error: {:?}", error));
-        }
+    pub async fn get_block_hash(&self, height: i64) -> Result<String> {
+        self.call_as("get_block_hash", json!(height), |v| {
+            v.as_str()
+                .map(|s| s.to_string())
+                .ok_or_else(|| RpcError::Decode(format!("block hash {}", v)))
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("get_block_hash failed for height {}: {}", height, e))
+    }
 
-        let result = json_response["result"].as_object().map(|obj| serde_json::Value::Object(obj.clone()));
-        Ok(result)
+    pub async fn get_block(&self, hash: &str, height: i64) -> Result<Block> {
+        self.call_as("get_block", json!([hash]), |v| {
+            serde_json::from_value::<BlockResponse>(v.clone())
+                .map(|b| block_from_response(hash, b))
+                .map_err(|e| {
+                    error!("🔍 Raw response that failed to deserialize: {:?}", v);
+                    RpcError::Decode(e.to_string())
+                })
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("get_block failed for {} (height {}): {}", hash, height, e))
+    }
+
+    pub async fn get_mempool_txids(&self) -> Result<Vec<String>> {
+        self.call_as("get_mempool_txids", json!([]), |v| {
+            v.as_array()
+                .map(|txids| txids.iter().filter_map(|t| t.as_str().map(|s| s.to_string())).collect())
+                .ok_or_else(|| RpcError::Decode("Invalid response format".to_string()))
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("get_mempool_txids failed: {}", e))
+    }
+
+    pub async fn get_mempool_entry(&self, txid: &str) -> Result<Option<serde_json::Value>> {
+        self.call_as("get_mempool_entry", json!([txid]), |v| {
+            Ok(v.as_object().map(|obj| serde_json::Value::Object(obj.clone())))
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("get_mempool_entry failed for {}: {}", txid, e))
     }
 
     pub async fn get_processed_transaction(&self, txid: &str) -> Result<ProcessedTransaction> {
-        let mut attempts = 0;
-        let max_attempts = 3;
-        let base_delay = Duration::from_millis(100);
-
-        while attempts < max_attempts {
-            match self.client
-                .post(&self.url)
-                .json(&json!({
+        self.call_as("get_processed_transaction", json!(txid), |v| {
+            serde_json::from_value::<ProcessedTransaction>(v).map_err(|e| RpcError::Decode(e.to_string()))
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("get_processed_transaction failed for {}: {}", txid, e))
+    }
+
+    /// Sends `calls` as JSON-RPC batch requests (at most `MAX_BATCH_SIZE` per POST) and returns
+    /// one result per call in input order. Transport failures fail the whole call; per-item RPC
+    /// errors are returned in place.
+    pub async fn call_batch(&self, calls: &[(&str, serde_json::Value)]) -> Result<Vec<Result<serde_json::Value>>> {
+        let mut results = Vec::with_capacity(calls.len());
+
+        for chunk in calls.chunks(MAX_BATCH_SIZE) {
+            let body: Vec<serde_json::Value> = chunk
+                .iter()
+                .enumerate()
+                .map(|(id, (method, params))| json!({
                     "jsonrpc": "2.0",
-                    "method": "get_processed_transaction",
-                    "params": txid,
-                    "id": 1
+                    "method": method,
+                    "params": params,
+                    "id": id
                 }))
-                .send()
-                .await
-            {
-                Ok(response) => {
-                    if !response.status().is_success() {
-                        warn!("HTTP error for tx {}: {}", txid, response.status());
-                        attempts += 1;
-                        if attempts < max_attempts {
-                            sleep(base_delay * attempts as u32).await;
-                            continue;
-                        }
-                        return Err(anyhow::anyhow!("HTTP error for tx {}: {}", txid, response.status()));
-                    }
-
-                    match response.json::<serde_json::Value>().await {
-                        Ok(json_response) => {
-                            if let Some(error) = json_response.get("error") {
-                                error!("RPC error for tx {}: {:?}", txid, error);
-                                attempts += 1;
-                                if attempts < max_attempts {
-                                    sleep(base_delay * attempts as u32).await;
-                                    continue;
-                                }
-                                return Err(anyhow::anyhow!("RPC error for tx {}: {:?}", txid, error));
-                            }
-
-                            match serde_json::from_value::<ProcessedTransaction>(json_response["result"].clone()) {
-                                Ok(tx) => return Ok(tx),
-                                Err(e) => {
-                                    error!("Transaction deserialization error for {}: {}", txid, e);
-                                    attempts += 1;
-                                    if attempts < max_attempts {
-                                        sleep(base_delay * attempts as u32).await;
-                                        continue;
-                                    }
-                                    return Err(anyhow::anyhow!("Transaction deserialization error for {}: {}", txid, e));
-                                }
-                            }
-                        },
-                        Err(e) => {
-                            error!("JSON decode error for tx {}: {}", txid, e);
-                            attempts += 1;
-                            if attempts < max_attempts {
-                                sleep(base_delay * attempts as u32).await;
-                                continue;
-                            }
-                            return Err(anyhow::anyhow!("JSON decode error for tx {}: {}", txid, e));
-                        }
-                    }
-                },
-                Err(e) => {
-                    error!("Request error for tx {}: {}", txid, e);
-                    attempts += 1;
-                    if attempts < max_attempts {
-                        sleep(base_delay * attempts as u32).await;
-                        continue;
-                    }
-                    return Err(anyhow::anyhow!("Request error for tx {}: {}", txid, e));
+                .collect();
+
+            let body = &body;
+            let response = retry::with_retry(&self.retry, &self.breaker, "batch", move || async move {
+                let response = self.send(body).await?;
+                if !response.status().is_success() {
+                    return Err(RpcError::Http(response.status()));
                 }
-            }
+                response.json().map_err(|e| RpcError::Decode(e.to_string()))
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!("Batch of {} calls failed: {}", chunk.len(), e))?;
+
+            results.extend(match_batch_response(chunk.len(), response)?);
         }
 
-        Err(anyhow::anyhow!("Failed to get transaction after {} attempts", max_attempts))
+        Ok(results)
+    }
+
+    /// Batched `get_block_hash` + `get_block` for many heights; one result per height.
+    pub async fn get_blocks_by_heights(&self, heights: &[i64]) -> Result<Vec<Result<Block>>> {
+        let hash_calls: Vec<(&str, serde_json::Value)> = heights
+            .iter()
+            .map(|h| ("get_block_hash", json!(h)))
+            .collect();
+        let hashes: Vec<Result<String>> = self
+            .call_batch(&hash_calls)
+            .await?
+            .into_iter()
+            .zip(heights)
+            .map(|(r, h)| {
+                r.and_then(|v| {
+                    v.as_str()
+                        .map(|s| s.to_string())
+                        .ok_or_else(|| anyhow::anyhow!("Invalid result type for height {}", h))
+                })
+            })
+            .collect();
+
+        let block_calls: Vec<(&str, serde_json::Value)> = hashes
+            .iter()
+            .filter_map(|r| r.as_ref().ok())
+            .map(|hash| ("get_block", json!([hash])))
+            .collect();
+        let mut blocks = self.call_batch(&block_calls).await?.into_iter();
+
+        let mut results = Vec::with_capacity(heights.len());
+        for hash in hashes {
+            results.push(match hash {
+                Ok(hash) => {
+                    let block = blocks
+                        .next()
+                        .ok_or_else(|| anyhow::anyhow!("Batch response count mismatch"))?;
+                    block.and_then(|v| {
+                        serde_json::from_value::<BlockResponse>(v)
+                            .map(|b| block_from_response(&hash, b))
+                            .map_err(|e| anyhow::anyhow!("Block deserialization error for {}: {}", hash, e))
+                    })
+                }
+                Err(e) => Err(e),
+            });
+        }
+
+        Ok(results)
+    }
+
+    /// Batched `get_processed_transaction`; one result per txid, in input order.
+    pub async fn get_processed_transactions(&self, txids: &[String]) -> Result<Vec<Result<ProcessedTransaction>>> {
+        let calls: Vec<(&str, serde_json::Value)> = txids
+            .iter()
+            .map(|t| ("get_processed_transaction", json!(t)))
+            .collect();
+
+        Ok(self
+            .call_batch(&calls)
+            .await?
+            .into_iter()
+            .zip(txids)
+            .map(|(r, txid)| {
+                r.and_then(|v| {
+                    serde_json::from_value::<ProcessedTransaction>(v)
+                        .map_err(|e| anyhow::anyhow!("Transaction deserialization error for {}: {}", txid, e))
+                })
+            })
+            .collect())
     }
 }
\ No newline at end of file
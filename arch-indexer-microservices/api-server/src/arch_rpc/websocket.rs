@@ -1,12 +1,14 @@
 use anyhow::Result;
 use futures_util::{SinkExt, StreamExt};
 use serde_json::{json, Value};
+use std::sync::Arc;
 use tokio::sync::mpsc;
-use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tokio_tungstenite::tungstenite::Message;
 use tracing::{error, info, warn};
 use url::Url;
 
-use crate::config::settings::WebSocketSettings;
+use crate::arch_rpc::{recorder, tls};
+use crate::config::settings::{TlsSettings, WebSocketSettings};
 
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct WebSocketEvent {
@@ -18,25 +20,41 @@ pub struct WebSocketEvent {
 #[derive(Debug)]
 pub struct WebSocketClient {
     settings: WebSocketSettings,
-    url: String,
+    urls: Vec<String>,
+    tls: Option<Arc<rustls::ClientConfig>>,
 }
 
 impl WebSocketClient {
     pub fn new(settings: WebSocketSettings, url: String) -> Self {
-        Self { settings, url }
+        Self { settings, urls: vec![url], tls: None }
+    }
+
+    /// Client that rotates through `urls` on each reconnect, failing over between nodes.
+    pub fn with_urls(settings: WebSocketSettings, urls: Vec<String>) -> Self {
+        Self { settings, urls, tls: None }
+    }
+
+    /// Uses the node TLS settings (CA bundle, mTLS identity) for `wss://` connections.
+    pub fn with_tls(mut self, tls: &TlsSettings) -> Result<Self> {
+        self.tls = Some(Arc::new(tls::client_config(tls)?));
+        Ok(self)
     }
 
     pub async fn start(&self, event_tx: mpsc::Sender<WebSocketEvent>) -> Result<()> {
-        let url = Url::parse(&self.url)?;
+        let urls = self.urls.iter().map(|u| Url::parse(u)).collect::<Result<Vec<Url>, _>>()?;
+        if urls.is_empty() {
+            return Err(anyhow::anyhow!("No WebSocket URLs configured"));
+        }
         
-        info!("Starting WebSocket client for: {}", self.url);
+        info!("Starting WebSocket client for: {}", self.urls.join(", "));
         
         let mut attempt_count = 0;
         loop {
+            let url = &urls[attempt_count % urls.len()];
             attempt_count += 1;
             info!("WebSocket connection attempt #{}", attempt_count);
             
-            match self.connect_and_subscribe(&url, &event_tx).await {
+            match self.connect_and_subscribe(url, &event_tx).await {
                 Ok(_) => {
                     info!("WebSocket connection closed, attempting to reconnect...");
                 }
@@ -71,11 +89,16 @@ impl WebSocketClient {
     ) -> Result<()> {
         info!("Connecting to WebSocket: {}", url);
         
+        let tls_config = match &self.tls {
+            Some(config) => Arc::clone(config),
+            None => Arc::new(tls::client_config(&TlsSettings::default())?),
+        };
+
         // Add timeout to prevent hanging
-        let connect_future = connect_async(url);
+        let connect_future = tls::connect_websocket(url, &tls_config);
         let timeout_duration = tokio::time::Duration::from_secs(30); // 30 second timeout
         
-        let (ws_stream, _) = tokio::time::timeout(timeout_duration, connect_future).await
+        let ws_stream = tokio::time::timeout(timeout_duration, connect_future).await
             .map_err(|_| anyhow::anyhow!("WebSocket connection timeout after {} seconds", timeout_duration.as_secs()))??;
             
         info!("✅ WebSocket connection established successfully!");
@@ -121,6 +144,9 @@ impl WebSocketClient {
             match msg {
                 Ok(Message::Text(text)) => {
                     if let Ok(event) = self.parse_event(&text) {
+                        if let Some(recorder) = recorder::global() {
+                            recorder.record_ws(&event);
+                        }
                         if let Err(e) = event_tx.send(event).await {
                             error!("Failed to send event to processor: {}", e);
                         }
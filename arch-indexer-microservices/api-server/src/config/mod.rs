@@ -6,6 +6,9 @@ pub use settings::{
     ApplicationSettings,
     DatabaseSettings,
     ArchNodeSettings,
+    ArchNodeEndpoint,
+    RpcRetrySettings,
+    TlsSettings,
     RedisSettings,
     IndexerSettings
 };
@@ -2,6 +2,9 @@ use config::{Config, ConfigError, Environment};
 use serde::Deserialize;
 use std::env;
 
+pub use node_rpc::settings::{ArchNodeEndpoint, RpcRetrySettings, TlsSettings};
+use node_rpc::settings::default_endpoint_weight;
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct Settings {
     pub database: DatabaseSettings,
@@ -56,12 +59,121 @@ pub struct ArchNodeSettings {
     pub url: String,
     #[serde(default = "default_websocket_url")]
     pub websocket_url: String,
+    /// Additional weighted endpoints; when empty, `url`/`websocket_url` form a single-node pool.
+    #[serde(default = "default_endpoints")]
+    pub endpoints: Vec<ArchNodeEndpoint>,
+    #[serde(default = "default_health_check_interval_seconds")]
+    pub health_check_interval_seconds: u64,
+    /// Endpoints further than this many blocks behind the best tip are ejected.
+    #[serde(default = "default_max_lag_blocks")]
+    pub max_lag_blocks: i64,
+    /// Bounds and starting point for the adaptive in-flight request limit.
+    #[serde(default = "default_min_concurrency")]
+    pub min_concurrency: usize,
+    #[serde(default = "default_max_concurrency")]
+    pub max_concurrency: usize,
+    #[serde(default = "default_initial_concurrency")]
+    pub initial_concurrency: usize,
+    /// Responses slower than this shrink the limit instead of growing it.
+    #[serde(default = "default_latency_target_ms")]
+    pub latency_target_ms: u64,
+    #[serde(default)]
+    pub retry: RpcRetrySettings,
+    #[serde(default)]
+    pub tls: TlsSettings,
+}
+
+impl ArchNodeSettings {
+    /// Effective endpoint list: configured `endpoints`, or the single `url`/`websocket_url`.
+    pub fn endpoints(&self) -> Vec<ArchNodeEndpoint> {
+        if !self.endpoints.is_empty() {
+            return self.endpoints.clone();
+        }
+        vec![ArchNodeEndpoint {
+            url: self.url.clone(),
+            websocket_url: Some(self.websocket_url.clone()),
+            weight: default_endpoint_weight(),
+        }]
+    }
+
+    /// WebSocket URLs in endpoint order, used to rotate on reconnect.
+    pub fn websocket_urls(&self) -> Vec<String> {
+        let urls: Vec<String> = self.endpoints().into_iter().filter_map(|e| e.websocket_url).collect();
+        if urls.is_empty() { vec![self.websocket_url.clone()] } else { urls }
+    }
+
+    /// The same node settings in the form the indexer library takes, for handlers that
+    /// write blocks through the indexer.
+    pub fn to_indexer(&self) -> arch_indexer::config::settings::ArchNodeSettings {
+        arch_indexer::config::settings::ArchNodeSettings {
+            url: self.url.clone(),
+            websocket_url: self.websocket_url.clone(),
+            endpoints: self.endpoints.clone(),
+            health_check_interval_seconds: self.health_check_interval_seconds,
+            max_lag_blocks: self.max_lag_blocks,
+            min_concurrency: self.min_concurrency,
+            max_concurrency: self.max_concurrency,
+            initial_concurrency: self.initial_concurrency,
+            latency_target_ms: self.latency_target_ms,
+            retry: self.retry.clone(),
+            tls: self.tls.clone(),
+        }
+    }
 }
 
 fn default_websocket_url() -> String {
     "ws://localhost:8081".to_string()
 }
 
+fn default_health_check_interval_seconds() -> u64 {
+    5
+}
+
+fn default_max_lag_blocks() -> i64 {
+    10
+}
+
+fn env_or<T: std::str::FromStr>(var: &str, default: T) -> T {
+    env::var(var).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+}
+
+fn default_min_concurrency() -> usize {
+    env_or("ARCH_RPC_MIN_CONCURRENCY", 1)
+}
+
+/// ARCH_MAX_CONCURRENCY is the fixed cap the indexer used before the limiter was adaptive.
+fn default_max_concurrency() -> usize {
+    env_or("ARCH_RPC_MAX_CONCURRENCY", env_or("ARCH_MAX_CONCURRENCY", 64))
+}
+
+fn default_initial_concurrency() -> usize {
+    8
+}
+
+fn default_latency_target_ms() -> u64 {
+    env_or("ARCH_RPC_LATENCY_TARGET_MS", 2_000)
+}
+
+/// Multi-node pool from ARCH_NODE_URLS (comma-separated), with optional aligned
+/// ARCH_NODE_WEBSOCKET_URLS and ARCH_NODE_WEIGHTS lists.
+fn default_endpoints() -> Vec<ArchNodeEndpoint> {
+    let urls = env::var("ARCH_NODE_URLS").unwrap_or_default();
+    let ws_urls = env::var("ARCH_NODE_WEBSOCKET_URLS").unwrap_or_default();
+    let weights = env::var("ARCH_NODE_WEIGHTS").unwrap_or_default();
+    let ws: Vec<&str> = ws_urls.split(',').map(|s| s.trim()).collect();
+    let weights: Vec<&str> = weights.split(',').map(|s| s.trim()).collect();
+    urls.split(',')
+        .map(|s| s.trim())
+        .enumerate()
+        .filter(|(_, url)| !url.is_empty())
+        .map(|(i, url)| ArchNodeEndpoint {
+            url: url.to_string(),
+            websocket_url: ws.get(i).filter(|s| !s.is_empty()).map(|s| s.to_string()),
+            weight: weights.get(i).and_then(|w| w.parse::<u32>().ok()).unwrap_or_else(default_endpoint_weight),
+        })
+        .collect()
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct RedisSettings {
     pub url: String,
@@ -76,6 +188,15 @@ impl Default for ArchNodeSettings {
         Self {
             url,
             websocket_url: default_websocket_url(),
+            endpoints: default_endpoints(),
+            health_check_interval_seconds: default_health_check_interval_seconds(),
+            max_lag_blocks: default_max_lag_blocks(),
+            min_concurrency: default_min_concurrency(),
+            max_concurrency: default_max_concurrency(),
+            initial_concurrency: default_initial_concurrency(),
+            latency_target_ms: default_latency_target_ms(),
+            retry: RpcRetrySettings::default(),
+            tls: TlsSettings::default(),
         }
     }
 }
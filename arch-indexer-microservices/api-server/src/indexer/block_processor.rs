@@ -1,26 +1,17 @@
 use anyhow::Result;
-use chrono::{Utc, DateTime};
-use dashmap::DashMap;
-use futures::stream;
-use futures::StreamExt;
 use std::sync::Arc;
-use tracing::error;
 use crate::arch_rpc::ArchRpcClient;
-use crate::arch_rpc::Block;
-use crate::db::models::Transaction;
 use sqlx::PgPool;
 use std::sync::atomic::{AtomicU64, AtomicI64};
 use std::time::{SystemTime, UNIX_EPOCH};
 use std::sync::atomic::Ordering;
 use std::time::Duration;
-use hex;
 use tracing::info;
-use crate::arch_rpc::ProcessedTransaction;
-use crate::utils::convert_arch_timestamp;
 
+/// Sync progress and mempool tracking for the API server. Blocks and transactions are
+/// written by the indexer alone, which applies their derived effects as it stores them.
 pub struct BlockProcessor {
     pub pool: PgPool,
-    block_cache: DashMap<i64, Block>,
     redis: redis::Client,
     pub arch_client: Arc<ArchRpcClient>,
     sync_start_time: AtomicU64,
@@ -33,7 +24,6 @@ impl BlockProcessor {
         info!("Initializing BlockProcessor...");
         Self {
             pool,
-            block_cache: DashMap::new(),
             redis,
             arch_client,
             sync_start_time: AtomicU64::new(SystemTime::now()
@@ -45,211 +35,6 @@ impl BlockProcessor {
         }
     }
 
-    fn normalize_program_id(program_id: &serde_json::Value) -> Option<String> {
-        match program_id {
-            serde_json::Value::String(s) => {
-                // If already hex, return as-is
-                if s.len() == 64 && s.chars().all(|c| c.is_ascii_hexdigit()) {
-                    return Some(s.to_lowercase());
-                }
-                // Try base58 decode
-                if let Ok(bytes) = bs58::decode(s).into_vec() {
-                    return Some(hex::encode(bytes));
-                }
-                None
-            },
-            serde_json::Value::Array(arr) => {
-                let bytes: Vec<u8> = arr.iter()
-                    .filter_map(|v| v.as_i64().map(|n| {
-                        if n < 0 { (n + 256) as u8 } else { n as u8 }
-                    }))
-                    .collect();
-                Some(hex::encode(bytes))
-            },
-            _ => None
-        }
-    }
-
-    pub async fn process_transaction(&self, transaction: &Transaction, tx: &mut sqlx::Transaction<'_, sqlx::Postgres>) -> Result<(), anyhow::Error> {
-        // Upsert transaction even if it already exists so that
-        // downstream program extraction logic always runs.
-
-        tracing::debug!("Processing transaction: {}", transaction.txid);
-        
-        let data_json = serde_json::to_value(&transaction.data)
-            .expect("Failed to serialize transaction data");
-        
-        let bitcoin_txids: Option<&[String]> = transaction.bitcoin_txids.as_deref();
-        let created_at_utc: DateTime<Utc> = transaction.created_at;
-        
-        // Insert the transaction
-        tracing::debug!("Inserting transaction into database: {}", transaction.txid);
-        match sqlx::query!(
-            r#"
-            INSERT INTO transactions (txid, block_height, data, status, bitcoin_txids, created_at)
-            VALUES ($1, $2, $3, $4, $5, $6)
-            ON CONFLICT (txid) DO UPDATE 
-            SET block_height = $2, data = $3, status = $4, bitcoin_txids = $5, created_at = $6
-            "#,
-            transaction.txid,
-            transaction.block_height,
-            data_json,
-            transaction.status,
-            bitcoin_txids,
-            created_at_utc
-        )
-        .execute(&mut **tx)
-        .await {
-            Ok(_) => tracing::debug!("Successfully inserted/updated transaction: {}", transaction.txid),
-            Err(e) => {
-                tracing::error!("Error inserting transaction {}: {}", transaction.txid, e);
-                return Err(e.into());
-            }
-        }
-
-        // Extract and process program IDs manually since we can't rely on trigger
-        tracing::debug!("Extracting program IDs for transaction: {}", transaction.txid);
-        if let Some(message) = transaction.data.get("message") {
-            if let Some(instructions) = message.get("instructions") {
-                if let Some(instructions_array) = instructions.as_array() {
-                    for (i, instruction) in instructions_array.iter().enumerate() {
-                        if let Some(program_id) = instruction.get("program_id") {
-                            tracing::debug!("Processing instruction {} with program_id: {:?}", i, program_id);
-                            if let Some(hex_program_id) = BlockProcessor::normalize_program_id(program_id) {
-                                tracing::debug!("Normalized program_id to hex: {:?}", hex_program_id);
-                                // Update programs table
-                                match sqlx::query!(
-                                    r#"
-                                    INSERT INTO programs (program_id)
-                                    VALUES ($1)
-                                    ON CONFLICT (program_id) 
-                                    DO UPDATE SET 
-                                        last_seen_at = CURRENT_TIMESTAMP,
-                                        transaction_count = programs.transaction_count + 1
-                                    "#,
-                                    hex_program_id
-                                )
-                                .execute(&mut **tx)
-                                .await {
-                                    Ok(_) => tracing::debug!("Successfully inserted/updated program: {:?}", hex_program_id),
-                                    Err(e) => tracing::warn!("Failed to insert program {:?}: {}", hex_program_id, e)
-                                };
-                                
-                                // Insert into transaction_programs
-                                match sqlx::query!(
-                                    r#"
-                                    INSERT INTO transaction_programs (txid, program_id)
-                                    VALUES ($1, $2)
-                                    ON CONFLICT DO NOTHING
-                                    "#,
-                                    transaction.txid,
-                                    hex_program_id
-                                )
-                                .execute(&mut **tx)
-                                .await {
-                                    Ok(_) => tracing::debug!("Successfully linked transaction {} to program {:?}", transaction.txid, hex_program_id),
-                                    Err(e) => tracing::warn!("Failed to link transaction to program: {}", e)
-                                };
-                            }
-                        }
-                    }
-                }
-            }
-        }
-
-        // Also record account participation explicitly to ensure account pages populate
-        if let Some(message) = transaction.data.get("message") {
-            let keys_opt = message.get("account_keys").or_else(|| message.get("keys"));
-            if let Some(keys) = keys_opt.and_then(|v| v.as_array()) {
-                for key_val in keys {
-                    if let Some(address_hex) = BlockProcessor::normalize_program_id(key_val) {
-                        // Best-effort insert; skip if table missing or any other error
-                        let _ = sqlx::query(
-                            r#"
-                            INSERT INTO account_participation (address_hex, txid, block_height, created_at)
-                            VALUES ($1, $2, $3, $4)
-                            ON CONFLICT (address_hex, txid) DO NOTHING
-                            "#
-                        )
-                        .bind(address_hex)
-                        .bind(&transaction.txid)
-                        .bind(transaction.block_height as i64)
-                        .bind(transaction.created_at)
-                        .execute(&mut **tx)
-                        .await;
-                    }
-                }
-            }
-        }
-
-        Ok(())
-    }
-
-    pub async fn process_transactions_batch(&self, transactions: Vec<Transaction>) -> Result<()> {
-        if transactions.is_empty() {
-            return Ok(());
-        }
-        
-        tracing::info!("Processing {} transactions in batch", transactions.len());
-        
-        // Use a single transaction for the entire batch
-        let mut tx = self.pool.begin().await?;
-        
-        // Check which transactions already exist
-        let txids: Vec<String> = transactions.iter().map(|t| t.txid.clone()).collect();
-        let existing_txids: Vec<String> = sqlx::query_scalar!(
-            "SELECT txid FROM transactions WHERE txid = ANY($1)",
-            &txids
-        )
-        .fetch_all(&mut *tx)
-        .await?;
-        
-        let existing_set: std::collections::HashSet<&str> = existing_txids.iter().map(|s| s.as_str()).collect();
-        
-        // Filter out existing transactions
-        let new_transactions: Vec<&Transaction> = transactions.iter()
-            .filter(|t| !existing_set.contains(t.txid.as_str()))
-            .collect();
-        
-        if new_transactions.is_empty() {
-            tracing::debug!("All {} transactions already exist", transactions.len());
-            tx.commit().await?;
-            return Ok(());
-        }
-        
-        tracing::info!("Inserting {} new transactions", new_transactions.len());
-        
-        // Build batch insert query
-        let mut query = String::from(
-            "INSERT INTO transactions (txid, block_height, data, status, bitcoin_txids, created_at) VALUES "
-        );
-        
-        let values: Vec<String> = new_transactions.iter()
-            .map(|tx| {
-                let data_json = serde_json::to_string(&tx.data).unwrap_or_else(|_| "{}".to_string());
-                let bitcoin_txids_str = tx.bitcoin_txids.as_ref()
-                    .map(|txids| format!("{{{}}}", txids.join(",")))
-                    .unwrap_or_else(|| "{}".to_string());
-                
-                format!(
-                    "('{}', {}, '{}', {}, '{}', '{}')",
-                    tx.txid, tx.block_height, data_json, tx.status, bitcoin_txids_str, tx.created_at
-                )
-            })
-            .collect();
-        
-        query.push_str(&values.join(","));
-        query.push_str(" ON CONFLICT (txid) DO NOTHING");
-        
-        // Execute batch insert
-        sqlx::query(&query).execute(&mut *tx).await?;
-        
-        tx.commit().await?;
-        tracing::info!("Successfully processed {} transactions in batch", new_transactions.len());
-        
-        Ok(())
-    }
-
     pub fn get_current_block_height(&self) -> i64 {
         self.current_block_height.load(Ordering::SeqCst)
     }
@@ -262,152 +47,11 @@ impl BlockProcessor {
         self.sync_start_time.load(Ordering::Relaxed)
     }
 
-    async fn fetch_blocks_batch(&self, heights: Vec<i64>) -> Result<Vec<(i64, Block)>> {
-        let retry_limit = 3;
-        let retry_delay = Duration::from_secs(1);
-        let backoff_multiplier = 2; // Each retry will wait longer
-
-        let futures: Vec<_> = heights
-            .into_iter()
-            .map(|height| {
-                let client = Arc::clone(&self.arch_client);
-                async move {
-                    let mut attempts = 0;
-                    let mut current_delay = retry_delay;
-
-                    loop {
-                        match async {
-                            let block_hash = client.get_block_hash(height).await?;
-                            let block = client.get_block(&block_hash, height).await?;
-                            Ok::<_, anyhow::Error>((height, block))
-                        }.await {
-                            Ok(result) => return Some(result),
-                            Err(e) => {
-                                attempts += 1;
-                                if attempts >= retry_limit {
-                                    error!(
-                                        "Failed to fetch block {} after {} attempts: {}",
-                                        height, retry_limit, e
-                                    );
-                                    return None;
-                                } else {
-                                    error!(
-                                        "Error fetching block {}: {}. Retrying {}/{} after {} ms",
-                                        height,
-                                        e,
-                                        attempts,
-                                        retry_limit,
-                                        current_delay.as_millis()
-                                    );
-                                    tokio::time::sleep(current_delay).await;
-                                    // Increase delay for next retry
-                                    current_delay *= backoff_multiplier;
-                                }
-                            }
-                        }
-                    }
-                }
-            })
-            .collect();
-
-        // Process up to 50 block requests concurrently
-        let results: Vec<_> = stream::iter(futures)
-            .buffer_unordered(50)
-            .filter_map(|result| async move { result })
-            .collect()
-            .await;
-
-        Ok(results)
-    }
-
-    pub async fn process_blocks_batch(&self, heights: Vec<i64>) -> Result<Vec<Block>> {
-        let mut processed_heights = std::collections::HashSet::new();
-        let mut results = Vec::new();
-
-        for height in heights {
-            if processed_heights.contains(&height) {
-                continue;
-            }
-
-            match self.process_block(height).await {
-                Ok(block) => {
-                    processed_heights.insert(height);
-                    results.push(block);
-                }
-                Err(e) => {
-                    error!("Failed to process block {}: {}", height, e);
-                    // Optionally break or continue based on error type
-                }
-            }
-        }
-
-        Ok(results)
-    }
-
     pub fn update_sync_metrics(&self, height: i64, block_time: Duration) {
         self.current_block_height.store(height, Ordering::Relaxed);
         self.average_block_time.store(block_time.as_millis() as u64, Ordering::Relaxed);
     }
 
-    async fn fetch_block_transactions(&self, height: i64) -> Result<Vec<Transaction>, anyhow::Error> {
-        tracing::debug!("Fetching transactions for height: {}", height);
-        
-        let block_hash = match self.arch_client.get_block_hash(height).await {
-            Ok(hash) => {
-                tracing::debug!("Retrieved block hash: {} for height: {}", hash, height);
-                hash
-            },
-            Err(e) => {
-                tracing::error!("Failed to get block hash for height {}: {}", height, e);
-                return Err(anyhow::anyhow!("Failed to get block hash: {}", e));
-            }
-        };
-        
-        let block = match self.arch_client.get_block(&block_hash, height).await {
-            Ok(block) => {
-                tracing::debug!("Retrieved block with {} transactions for height: {}", block.transactions.len(), height);
-                block
-            },
-            Err(e) => {
-                tracing::error!("Failed to get block for height {}: {}", height, e);
-                return Err(anyhow::anyhow!("Failed to get block: {}", e));
-            }
-        };
-        
-        let transactions: Vec<Transaction> = stream::iter(block.transactions)
-            .map(|txid| {
-                let client = Arc::clone(&self.arch_client);
-                let txid_clone = txid.clone();
-                async move {
-                    tracing::debug!("Fetching transaction: {}", txid_clone);
-                    match client.get_processed_transaction(&txid_clone).await {
-                        Ok(tx) => {
-                            tracing::debug!("Processed transaction: {}", txid_clone);
-                            Some(Transaction {
-                                txid: txid_clone,
-                                block_height: height,
-                                data: tx.runtime_transaction,
-                                status: tx.status,
-                                bitcoin_txids: tx.bitcoin_txids,
-                                created_at: chrono::Utc::now(),
-                            })
-                        },
-                        Err(e) => {
-                            tracing::warn!("Failed to fetch transaction {}: {}", txid_clone, e);
-                            None
-                        }
-                    }
-                }
-            })
-            .buffer_unordered(50) // Increased from 10 to 50
-            .filter_map(|result| async move { result })
-            .collect()
-            .await;
-
-        tracing::debug!("Completed fetching {} transactions for height: {}", transactions.len(), height);
-        Ok(transactions)
-    }
-
     pub async fn get_last_processed_height(&self) -> Result<Option<i64>> {
         let height = sqlx::query!(
             "SELECT MAX(height) as last_height FROM blocks"
@@ -419,332 +63,6 @@ impl BlockProcessor {
         Ok(height)
     }
 
-    pub async fn process_block(&self, height: i64) -> Result<Block, anyhow::Error> {
-        let start_time = std::time::Instant::now();
-
-        // Get block hash with detailed error handling (check cache first)
-        let block_hash = if let Some(cached_block) = self.block_cache.get(&height) {
-            tracing::debug!("Cache hit for block height {}", height);
-            cached_block.hash.clone()
-        } else {
-            tracing::debug!("Fetching block hash for height: {}", height);
-            match self.arch_client.get_block_hash(height).await {
-                Ok(hash) => {
-                    tracing::debug!("Retrieved block hash: {} for height {}", hash, height);
-                    hash
-                },
-                Err(e) => {
-                    tracing::error!("Failed to get block hash for height {}: {}", height, e);
-                    return Err(anyhow::anyhow!("Failed to get block hash: {}", e));
-                }
-            }
-        };
-
-        // Get block with detailed error handling (check cache first)
-        let block = if let Some(cached_block) = self.block_cache.get(&height) {
-            if cached_block.hash == block_hash {
-                tracing::debug!("Cache hit for block data at height {}", height);
-                cached_block.clone()
-            } else {
-                tracing::debug!("Cache miss - hash mismatch for height {}", height);
-                match self.arch_client.get_block(&block_hash, height).await {
-                    Ok(block) => {
-                        tracing::debug!("Retrieved block: height={}, tx_count={}", block.height, block.transactions.len());
-                        // Cache the block
-                        self.block_cache.insert(height, block.clone());
-                        block
-                    },
-                    Err(e) => {
-                        tracing::error!("Failed to get block for hash {}: {}", block_hash, e);
-                        return Err(anyhow::anyhow!("Failed to get block: {}", e));
-                    }
-                }
-            }
-        } else {
-            tracing::debug!("Fetching block data for hash: {}", block_hash);
-            match self.arch_client.get_block(&block_hash, height).await {
-                Ok(block) => {
-                    tracing::debug!("Retrieved block: height={}, tx_count={}", block.height, block.transactions.len());
-                    // Cache the block
-                    self.block_cache.insert(height, block.clone());
-                    block
-                },
-                Err(e) => {
-                    tracing::error!("Failed to get block for hash {}: {}", block_hash, e);
-                    return Err(anyhow::anyhow!("Failed to get block: {}", e));
-                }
-            }
-        };
-
-        // Fetch transactions with detailed error handling
-        tracing::debug!("Fetching transactions for block height: {}", height);
-        let transactions = match self.fetch_block_transactions(height).await {
-            Ok(txs) => {
-                tracing::debug!("Retrieved {} transactions for height {}", txs.len(), height);
-                txs
-            },
-            Err(e) => {
-                tracing::error!("Failed to fetch transactions for height {}: {}", height, e);
-                return Err(anyhow::anyhow!("Failed to fetch transactions: {}", e));
-            }
-        };
-        
-        // Start database transaction
-        let mut tx = match self.pool.begin().await {
-            Ok(tx) => tx,
-            Err(e) => {
-                tracing::error!("Failed to begin database transaction: {}", e);
-                return Err(anyhow::anyhow!("Database transaction error: {}", e));
-            }
-        };
-
-        tracing::debug!("Processing block: height={}, hash={}, tx_count={}", height, block_hash, block.transactions.len());
-
-        // Convert Arch timestamp to DateTime using centralized utility
-        let timestamp = convert_arch_timestamp(block.timestamp);
-
-        // Insert block with detailed error handling
-        match sqlx::query(
-            r#"
-            INSERT INTO blocks (height, hash, timestamp, bitcoin_block_height)
-            VALUES ($1, $2, $3, $4)
-            ON CONFLICT (height) DO UPDATE 
-            SET hash = $2, timestamp = $3, bitcoin_block_height = $4
-            "#
-        )
-        .bind(height)
-        .bind(&block_hash)
-        .bind(timestamp)
-        .bind(block.bitcoin_block_height.unwrap_or(0))
-        .execute(&mut *tx)
-        .await {
-            Ok(_) => tracing::debug!("Inserted/updated block {}", height),
-            Err(e) => {
-                tracing::error!("Failed to insert block {}: {}", height, e);
-                return Err(anyhow::anyhow!("Block insertion error: {}", e));
-            }
-        }
-
-        // Process transactions
-        if !transactions.is_empty() {
-            tracing::debug!("Processing {} transactions for block {}", transactions.len(), height);
-            for transaction in transactions.iter() {
-                if let Err(e) = self.process_transaction(transaction, &mut tx).await {
-                    tracing::error!("Failed to process transaction {}: {}", transaction.txid, e);
-                    return Err(anyhow::anyhow!("Transaction processing error: {}", e));
-                }
-            }
-        }
-
-        // Commit transaction
-        if let Err(e) = tx.commit().await {
-            tracing::error!("Failed to commit database transaction: {}", e);
-            return Err(anyhow::anyhow!("Failed to commit transaction: {}", e));
-        }
-
-        tracing::info!("Processed block {} with {} transactions", height, transactions.len());
-        
-        self.update_current_height(height);
-        self.update_sync_metrics(height, start_time.elapsed());
-        
-        Ok(block)
-    }
-
-    /// Process a Block object directly (for optimized sync)
-    pub async fn process_block_direct(&self, block: Block) -> Result<(), anyhow::Error> {
-        let start_time = std::time::Instant::now();
-        let height = block.height;
-        let block_hash = &block.hash;
-
-        tracing::debug!("Processing block directly: height={}, hash={}, tx_count={}", height, block_hash, block.transactions.len());
-
-        // Start database transaction
-        let mut tx = match self.pool.begin().await {
-            Ok(tx) => tx,
-            Err(e) => {
-                tracing::error!("Failed to begin database transaction: {}", e);
-                return Err(anyhow::anyhow!("Database transaction error: {}", e));
-            }
-        };
-
-        // Convert Arch timestamp to DateTime using centralized utility
-        let timestamp = convert_arch_timestamp(block.timestamp);
-
-        // Insert block with enhanced data
-        match sqlx::query(
-            r#"
-            INSERT INTO blocks (height, hash, timestamp, bitcoin_block_height)
-            VALUES ($1, $2, $3, $4)
-            ON CONFLICT (height) DO UPDATE 
-            SET hash = $2, timestamp = $3, bitcoin_block_height = $4
-            "#,
-        )
-        .bind(height)
-        .bind(block_hash)
-        .bind(timestamp)
-        .bind(block.bitcoin_block_height.unwrap_or(0))
-        .execute(&mut *tx)
-        .await {
-            Ok(_) => tracing::debug!("Inserted/updated block {}", height),
-            Err(e) => {
-                tracing::error!("Failed to insert block {}: {}", height, e);
-                return Err(anyhow::anyhow!("Block insertion error: {}", e));
-            }
-        }
-
-        // Process transactions if any
-        if !block.transactions.is_empty() {
-            tracing::debug!("Processing {} transactions for block {}", block.transactions.len(), height);
-            
-            // Fetch transaction details for each txid
-            for txid in &block.transactions {
-                if let Ok(transaction) = self.arch_client.get_processed_transaction(txid).await {
-                    if let Err(e) = self.process_transaction_direct(&transaction, &mut tx, txid, height).await {
-                        tracing::error!("Failed to process transaction {}: {}", txid, e);
-                        // Continue processing other transactions
-                    }
-                } else {
-                    tracing::warn!("Failed to fetch transaction details for {}", txid);
-                }
-            }
-        }
-
-        // Commit transaction
-        if let Err(e) = tx.commit().await {
-            tracing::error!("Failed to commit database transaction: {}", e);
-            return Err(anyhow::anyhow!("Failed to commit transaction: {}", e));
-        }
-
-        tracing::info!("Processed block {} with {} transactions", height, block.transactions.len());
-        
-        self.update_current_height(height);
-        self.update_sync_metrics(height, start_time.elapsed());
-        
-        Ok(())
-    }
-
-    /// Process a ProcessedTransaction directly (for optimized sync)
-    async fn process_transaction_direct(&self, transaction: &ProcessedTransaction, tx: &mut sqlx::Transaction<'_, sqlx::Postgres>, actual_txid: &str, block_height: i64) -> Result<(), anyhow::Error> {
-        // Use the actual transaction ID from the block data
-        let txid = actual_txid.to_string();
-
-        // Extract compute units from logs if available
-        let compute_units = if let Some(logs) = transaction.runtime_transaction.get("logs") {
-            if let Some(logs_array) = logs.as_array() {
-                logs_array.iter()
-                    .filter_map(|log| log.as_str())
-                    .find_map(|log| {
-                        if log.contains("Consumed") {
-                            log.split_whitespace()
-                                .filter_map(|word| word.parse::<i32>().ok())
-                                .next()
-                        } else {
-                            None
-                        }
-                    })
-            } else {
-                None
-            }
-        } else {
-            None
-        };
-
-        // Insert transaction with enhanced data
-        match sqlx::query!(
-            r#"
-            INSERT INTO transactions (txid, block_height, data, status, bitcoin_txids, created_at, logs, rollback_status, accounts_tags, compute_units_consumed)
-            VALUES ($1, $2, $3, $4, $5, CURRENT_TIMESTAMP, $6, $7, $8, $9)
-            ON CONFLICT (txid) DO UPDATE 
-            SET data = $3, status = $4, bitcoin_txids = $5, logs = $6, rollback_status = $7, accounts_tags = $8, compute_units_consumed = $9
-            "#,
-            txid,
-            block_height, // Use the actual block height from context
-            serde_json::to_value(&transaction.runtime_transaction)?,
-            serde_json::to_value(&transaction.status)?,
-            transaction.bitcoin_txids.as_ref().map(|txids| txids.as_slice()),
-            serde_json::to_value(&transaction.runtime_transaction.get("logs").unwrap_or(&serde_json::Value::Array(vec![])))?,
-            serde_json::to_value("NotRolledback")?, // Default rollback status
-            serde_json::to_value(&transaction.accounts_tags)?,
-            compute_units
-        )
-        .execute(&mut **tx)
-        .await {
-            Ok(_) => tracing::debug!("Inserted/updated transaction {}", txid),
-            Err(e) => {
-                tracing::error!("Failed to insert transaction {}: {}", txid, e);
-                return Err(anyhow::anyhow!("Transaction insertion error: {}", e));
-            }
-        }
-
-        // Process program IDs from accounts_tags
-        for account_tag in &transaction.accounts_tags {
-            if let Some(program_id) = account_tag.get("program_id").and_then(|id| id.as_str()) {
-                // Insert program
-                if let Err(e) = sqlx::query!(
-                    r#"
-                    INSERT INTO programs (program_id)
-                    VALUES ($1)
-                    ON CONFLICT (program_id) DO UPDATE SET 
-                        last_seen_at = CURRENT_TIMESTAMP,
-                        transaction_count = programs.transaction_count + 1
-                    "#,
-                    program_id
-                )
-                .execute(&mut **tx)
-                .await {
-                    tracing::error!("Failed to insert program {}: {}", program_id, e);
-                }
-
-                // Insert transaction-program relationship
-                if let Err(e) = sqlx::query!(
-                    r#"
-                    INSERT INTO transaction_programs (txid, program_id)
-                    VALUES ($1, $2)
-                    ON CONFLICT (txid, program_id) DO NOTHING
-                    "#,
-                    txid,
-                    program_id
-                )
-                .execute(&mut **tx)
-                .await {
-                    tracing::error!("Failed to insert transaction-program relationship: {}", e);
-                }
-            }
-        }
-
-        // Also record account participation explicitly to ensure account pages populate
-        if let Some(message) = transaction.runtime_transaction.get("message") {
-            let keys_opt = message.get("account_keys").or_else(|| message.get("keys"));
-            if let Some(keys) = keys_opt.and_then(|v| v.as_array()) {
-                for key_val in keys {
-                    // Normalize to hex whether it's base58 string or byte array
-                    let address_hex = match key_val {
-                        serde_json::Value::String(_) | serde_json::Value::Array(_) => {
-                            BlockProcessor::normalize_program_id(key_val)
-                        },
-                        _ => None,
-                    };
-                    if let Some(address_hex) = address_hex {
-                        let _ = sqlx::query(
-                            r#"
-                            INSERT INTO account_participation (address_hex, txid, block_height, created_at)
-                            VALUES ($1, $2, $3, CURRENT_TIMESTAMP)
-                            ON CONFLICT (address_hex, txid) DO NOTHING
-                            "#
-                        )
-                        .bind(address_hex)
-                        .bind(&txid)
-                        .bind(block_height as i64)
-                        .execute(&mut **tx)
-                        .await;
-                    }
-                }
-            }
-        }
-
-        Ok(())
-    }
-
     pub fn update_current_height(&self, height: i64) {
         self.current_block_height.store(height, Ordering::SeqCst);
     }
@@ -1,9 +1,5 @@
 pub mod block_processor;
-pub mod sync;
 pub mod realtime_processor;
-pub mod hybrid_sync;
 
 pub use block_processor::BlockProcessor;
-pub use sync::ChainSync;
 pub use realtime_processor::RealtimeProcessor;
-pub use hybrid_sync::HybridSync;
\ No newline at end of file
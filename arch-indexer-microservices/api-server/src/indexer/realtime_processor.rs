@@ -332,8 +332,8 @@ impl RealtimeProcessor {
                 info!("✅ Successfully fetched block data: height={}, tx_count={}", 
                       block.height, block.transaction_count);
                 
-                // Store the block in the database
-                self.store_block(&block).await?;
+                // The indexer stores the block with its transactions and derived rows; writing
+                // a bare blocks row here would race it and leave those columns empty
                 
                 // Previously we inserted placeholder tx rows here. Skip that to avoid polluting
                 // the transactions table; dedicated tx indexers should persist real txs.
@@ -341,9 +341,9 @@ impl RealtimeProcessor {
                     debug!("observed {} txs in block {} (skipping placeholder inserts)", block.transactions.len(), hash);
                 }
                 
-                info!("✅ Block {} fully processed and stored", hash);
+                info!("✅ Block {} processed; the indexer stores it", hash);
 
-                // After persistence, emit an enriched block event to UI clients with tx count and program counts
+                // Emit an enriched block event to UI clients with tx count and program counts
                 if let Some(server) = &self.websocket_server {
                     let (txs, prog, finalized) = self.load_block_activity(block.height).await.unwrap_or((block.transaction_count as i64, Vec::new(), false));
                     let mut obj = serde_json::Map::new();
@@ -361,7 +361,7 @@ impl RealtimeProcessor {
                         timestamp: Utc::now(),
                     };
                     let _ = server.broadcast_event(enriched).await;
-                    debug!("block summary post-store: height={}, txs={}", block.height, txs);
+                    debug!("block summary post-fetch: height={}, txs={}", block.height, txs);
                 }
             }
             Err(e) => {
@@ -458,15 +458,9 @@ impl RealtimeProcessor {
 
         info!("↩️ Processing rolledback transactions event: {:?}", transaction_hashes);
 
-        // Mark transactions as rolled back
-        for hash in transaction_hashes {
-            let _ = sqlx::query!(
-                "UPDATE transactions SET status = jsonb_set(status, '{rolled_back}', 'true') WHERE txid = $1",
-                hash
-            )
-            .execute(&*self.pool)
-            .await;
-        }
+        // The indexer records rollback state (rollback_status and transaction_rollbacks) and
+        // reverses derived rows; a second writer here would race it
+        info!("🔄 {} transactions rolled back; left to the indexer", transaction_hashes.len());
 
         Ok(())
     }
@@ -487,15 +481,9 @@ impl RealtimeProcessor {
 
         info!("🔄 Processing reapplied transactions event: {:?}", transaction_hashes);
 
-        // Mark transactions as reapplied
-        for hash in transaction_hashes {
-            let _ = sqlx::query!(
-                "UPDATE transactions SET status = jsonb_set(status, '{reapplied}', 'true') WHERE txid = $1",
-                hash
-            )
-            .execute(&*self.pool)
-            .await;
-        }
+        // The indexer records rollback state (rollback_status and transaction_rollbacks) and
+        // re-applies derived rows; a second writer here would race it
+        info!("🔄 {} transactions reapplied; left to the indexer", transaction_hashes.len());
 
         Ok(())
     }
@@ -545,24 +533,6 @@ impl RealtimeProcessor {
         Ok(latest_height + estimated_blocks)
     }
 
-    /// Store a complete block in the database
-    async fn store_block(&self, block: &crate::arch_rpc::Block) -> Result<()> {
-        sqlx::query!(
-            "INSERT INTO blocks (height, hash, timestamp, bitcoin_block_height) 
-             VALUES ($1, $2, $3, $4) 
-             ON CONFLICT (height) DO NOTHING",
-            block.height,
-            block.hash,
-            convert_arch_timestamp(block.timestamp),
-            block.bitcoin_block_height
-        )
-        .execute(&*self.pool)
-        .await?;
-
-        info!("✅ Block {} stored in database", block.hash);
-        Ok(())
-    }
-
     /// Store partial block data for later retry
     async fn store_partial_block(&self, hash: &str, timestamp: DateTime<Utc>, estimated_height: i64) -> Result<()> {
         // For now, just log the partial block instead of creating a new table
@@ -40,6 +40,9 @@ async fn main() -> Result<()> {
 
     info!("Loaded settings: {:?}", settings);
 
+    // Invalid Arch node TLS settings (CA bundle, client certificate or key) are fatal
+    ArchRpcClient::init_shared(&settings.arch_node)?;
+
     // Set up metrics
     let prometheus_handle = metrics::setup_metrics_recorder();
     info!("Prometheus metrics initialized");
@@ -199,18 +202,17 @@ $$;
     // Start real-time processor wired to the in-process websocket server.
     if settings.websocket.enabled && settings.indexer.enable_realtime {
         let ws_settings = settings.websocket.clone();
-        let node_ws_url = settings.arch_node.websocket_url.clone();
-        let arch_http_url = settings.arch_node.url.clone();
+        let node_ws_urls = settings.arch_node.websocket_urls();
         let pool_clone = Arc::new(pool.clone());
         let server_for_events = Arc::clone(&ws_server);
+        // Connect to Arch node WS → mpsc channel of events
+        let client = WebSocketClient::with_urls(ws_settings, node_ws_urls).with_tls(&settings.arch_node.tls)?;
+        let rpc_client = Arc::new(ArchRpcClient::from_settings(&settings.arch_node)?);
         tokio::spawn(async move {
-            // Connect to Arch node WS → mpsc channel of events
-            let client = WebSocketClient::new(ws_settings, node_ws_url);
             let (tx, rx) = mpsc::channel::<WebSocketEvent>(1000);
             tokio::spawn(async move { let _ = client.start(tx).await; });
 
             // Use the indexer's RealtimeProcessor to enrich and aggregate, then broadcast
-            let rpc_client = Arc::new(ArchRpcClient::new(arch_http_url));
             let processor = RealtimeProcessor::new(pool_clone, rpc_client, Some(server_for_events));
             if let Err(e) = processor.start(rx).await {
                 error!("Realtime processor ended with error: {:?}", e);
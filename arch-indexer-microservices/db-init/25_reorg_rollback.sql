@@ -0,0 +1,5 @@
+-- Reorg support: remember each block's parent hash so the indexer can detect forks.
+-- Balance effects of orphaned transactions are reverted by the indexer before it
+-- deletes them (see indexer/src/indexer/reorg.rs).
+
+ALTER TABLE blocks ADD COLUMN IF NOT EXISTS previous_block_hash TEXT;
@@ -0,0 +1,13 @@
+-- Token accounts, balances and mints are now derived by the indexer from typed APL token
+-- instructions (indexer/src/indexer/tokens.rs). Retire the positional token triggers,
+-- which guessed [account, mint, owner] from account order, and track the per-account
+-- state those instructions change.
+
+DROP TRIGGER IF EXISTS token_indexing_trigger ON transactions;
+DROP FUNCTION IF EXISTS populate_token_entities_from_tx();
+DROP TRIGGER IF EXISTS token_balances_trigger ON transactions;
+DROP FUNCTION IF EXISTS update_token_balances_from_transaction();
+
+ALTER TABLE token_accounts ADD COLUMN IF NOT EXISTS is_frozen BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE token_accounts ADD COLUMN IF NOT EXISTS is_closed BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE token_accounts ADD COLUMN IF NOT EXISTS close_authority_hex TEXT;
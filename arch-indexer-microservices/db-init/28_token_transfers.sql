@@ -0,0 +1,30 @@
+-- Token transfer ledger: one row per APL token transfer, mint or burn instruction, written
+-- by the indexer alongside the balance updates so transfers can be listed per mint and
+-- per wallet. Mints have no source and burns no destination.
+
+CREATE TABLE IF NOT EXISTS token_transfers (
+    txid TEXT NOT NULL REFERENCES transactions(txid) ON DELETE CASCADE,
+    instruction_index INTEGER NOT NULL,
+    block_height BIGINT NOT NULL,
+    block_time TIMESTAMPTZ,
+    kind TEXT NOT NULL CHECK (kind IN ('transfer', 'mint', 'burn')),
+    mint_address TEXT NOT NULL,
+    source_address TEXT,
+    destination_address TEXT,
+    -- owners of the source/destination token accounts when the instruction was indexed
+    source_owner TEXT,
+    destination_owner TEXT,
+    authority_address TEXT,
+    amount NUMERIC(65, 0) NOT NULL,
+    decimals INTEGER,
+    program_id TEXT NOT NULL,
+    PRIMARY KEY (txid, instruction_index)
+);
+
+-- Keyset pagination runs newest first on (block_height, txid, instruction_index)
+CREATE INDEX IF NOT EXISTS idx_token_transfers_mint ON token_transfers(mint_address, block_height DESC, txid DESC, instruction_index DESC);
+CREATE INDEX IF NOT EXISTS idx_token_transfers_source ON token_transfers(source_address, block_height DESC);
+CREATE INDEX IF NOT EXISTS idx_token_transfers_destination ON token_transfers(destination_address, block_height DESC);
+CREATE INDEX IF NOT EXISTS idx_token_transfers_source_owner ON token_transfers(source_owner, block_height DESC);
+CREATE INDEX IF NOT EXISTS idx_token_transfers_destination_owner ON token_transfers(destination_owner, block_height DESC);
+CREATE INDEX IF NOT EXISTS idx_token_transfers_block_time ON token_transfers(block_time);
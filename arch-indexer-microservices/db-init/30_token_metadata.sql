@@ -0,0 +1,18 @@
+-- Token metadata: name, symbol, URI, update authority and additional fields per mint,
+-- decoded by the indexer from token metadata instructions and metadata accounts.
+
+CREATE TABLE IF NOT EXISTS token_metadata (
+    mint_address TEXT PRIMARY KEY,
+    metadata_address TEXT NOT NULL,
+    name TEXT NOT NULL DEFAULT '',
+    symbol TEXT NOT NULL DEFAULT '',
+    uri TEXT NOT NULL DEFAULT '',
+    update_authority TEXT,
+    additional_metadata JSONB NOT NULL DEFAULT '{}'::jsonb,
+    block_height BIGINT,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_token_metadata_metadata_address ON token_metadata(metadata_address);
+CREATE INDEX IF NOT EXISTS idx_token_metadata_symbol ON token_metadata(LOWER(symbol));
+CREATE INDEX IF NOT EXISTS idx_token_metadata_name ON token_metadata(LOWER(name));
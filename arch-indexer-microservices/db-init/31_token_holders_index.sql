@@ -0,0 +1,5 @@
+-- Rich-list order for /api/tokens/:mint/holders: largest balances of a mint first, with
+-- the token account as keyset tie-breaker.
+
+CREATE INDEX IF NOT EXISTS idx_token_balances_mint_balance
+    ON token_balances(mint_address, balance DESC, account_address DESC);
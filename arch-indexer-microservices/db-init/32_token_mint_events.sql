@@ -0,0 +1,20 @@
+-- Token mint events: initializations, mints, burns and mint/freeze authority changes per
+-- instruction, with the mint's supply after each one. Written by the indexer alongside
+-- token_mints so supply can be charted over time and issuance audited.
+
+CREATE TABLE IF NOT EXISTS token_mint_events (
+    txid TEXT NOT NULL REFERENCES transactions(txid) ON DELETE CASCADE,
+    instruction_index INTEGER NOT NULL,
+    block_height BIGINT NOT NULL,
+    block_time TIMESTAMPTZ,
+    mint_address TEXT NOT NULL,
+    kind TEXT NOT NULL CHECK (kind IN ('initialize', 'mint', 'burn', 'set_mint_authority', 'set_freeze_authority')),
+    amount NUMERIC(65, 0),
+    supply_after NUMERIC(65, 0) NOT NULL,
+    previous_authority TEXT,
+    new_authority TEXT,
+    PRIMARY KEY (txid, instruction_index)
+);
+
+CREATE INDEX IF NOT EXISTS idx_token_mint_events_mint ON token_mint_events(mint_address, block_height DESC, txid DESC, instruction_index DESC);
+CREATE INDEX IF NOT EXISTS idx_token_mint_events_mint_time ON token_mint_events(mint_address, block_time);
@@ -0,0 +1,15 @@
+-- Native balance history: the lamport balance of an address after the last transaction of
+-- each block that moved its lamports, written by the indexer from native_balances so
+-- balance charts do not have to replay every transaction.
+
+CREATE TABLE IF NOT EXISTS native_balance_history (
+    address_hex TEXT NOT NULL,
+    block_height BIGINT NOT NULL,
+    block_time TIMESTAMPTZ,
+    balance NUMERIC(65, 0) NOT NULL,
+    txid TEXT,
+    PRIMARY KEY (address_hex, block_height)
+);
+
+CREATE INDEX IF NOT EXISTS idx_native_balance_history_time ON native_balance_history(address_hex, block_time);
+CREATE INDEX IF NOT EXISTS idx_native_balance_history_height ON native_balance_history(block_height);
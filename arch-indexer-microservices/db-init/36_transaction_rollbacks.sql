@@ -0,0 +1,13 @@
+-- Transaction rollbacks: every time the node rolls a transaction back or reapplies it, the
+-- indexer reverses or re-applies its derived effects and records the transition here. The
+-- latest row of a transaction tells whether it is currently rolled back.
+
+CREATE TABLE IF NOT EXISTS transaction_rollbacks (
+    id BIGSERIAL PRIMARY KEY,
+    txid TEXT NOT NULL REFERENCES transactions(txid) ON DELETE CASCADE,
+    action TEXT NOT NULL CHECK (action IN ('rolled_back', 'reapplied')),
+    block_height BIGINT,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_transaction_rollbacks_txid ON transaction_rollbacks(txid, id DESC);
@@ -0,0 +1,11 @@
+-- DKG events: distributed key generation progress reported by the node over websocket,
+-- stored as received by the legacy realtime path.
+
+CREATE TABLE IF NOT EXISTS dkg_events (
+    id BIGSERIAL PRIMARY KEY,
+    status TEXT,
+    data JSONB NOT NULL,
+    received_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_dkg_events_received_at ON dkg_events(received_at DESC);
@@ -0,0 +1,7 @@
+-- Order in which transactions had their derived effects applied, taken from a sequence
+-- each time the indexer applies them. A reorg rollback reverses effects in the reverse
+-- of this order. Transactions indexed before this column existed have no order and are
+-- reverted after the ones that do.
+
+CREATE SEQUENCE IF NOT EXISTS transaction_apply_order_seq;
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS apply_order BIGINT;
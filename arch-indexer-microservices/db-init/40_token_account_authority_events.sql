@@ -0,0 +1,16 @@
+-- Token account owner and close authority changes per instruction, with the value each one
+-- replaced. Written by the indexer when it applies a SetAuthority instruction so the change
+-- can be undone if the node rolls the transaction back.
+
+CREATE TABLE IF NOT EXISTS token_account_authority_events (
+    txid TEXT NOT NULL REFERENCES transactions(txid) ON DELETE CASCADE,
+    instruction_index INTEGER NOT NULL,
+    block_height BIGINT NOT NULL,
+    token_account TEXT NOT NULL,
+    kind TEXT NOT NULL CHECK (kind IN ('set_owner', 'set_close_authority')),
+    previous_authority TEXT,
+    new_authority TEXT,
+    PRIMARY KEY (txid, instruction_index)
+);
+
+CREATE INDEX IF NOT EXISTS idx_token_account_authority_events_account ON token_account_authority_events(token_account, block_height DESC);
@@ -0,0 +1,13 @@
+-- Latest state of each account, written with `account_history` by the account processors and
+-- restored from it when a reorg rolls states back. The indexer's bootstrap schema creates it
+-- too, but only on databases without `blocks`, so a database initialized from db-init needs
+-- it here.
+
+CREATE TABLE IF NOT EXISTS accounts (
+    pubkey TEXT PRIMARY KEY,
+    lamports BIGINT NOT NULL,
+    owner TEXT NOT NULL,
+    data BYTEA NOT NULL,
+    height BIGINT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
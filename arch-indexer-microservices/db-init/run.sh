@@ -20,12 +20,24 @@ echo "Truncating data tables (if present) to ensure fresh sync..."
 for t in \
   transaction_programs \
   programs \
+  instructions \
+  token_transfers \
+  token_mint_events \
+  token_account_authority_events \
+  native_transfers \
+  native_balance_history \
+  native_balances \
+  transaction_rollbacks \
   transactions \
   blocks \
   mempool_transactions \
+  dkg_events \
   token_balances \
   token_accounts \
   token_mints \
+  token_metadata \
+  accounts \
+  account_history \
   account_participation; do
   echo " - truncating $t (if exists)"
   # TRUNCATE does not support IF EXISTS; try and ignore missing-table errors
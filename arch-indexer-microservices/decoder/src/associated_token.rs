@@ -0,0 +1,34 @@
+use serde_json::json;
+
+use super::{Decoded, InstructionDecoder, APL_ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_BASE58};
+
+/// APL Associated Token Account program. Its only instruction (Create) carries no data.
+pub struct AssociatedTokenDecoder;
+
+impl InstructionDecoder for AssociatedTokenDecoder {
+    fn program_ids(&self) -> Vec<String> {
+        vec![APL_ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_BASE58.to_string()]
+    }
+
+    fn decode(&self, data: &[u8], accounts: &[String]) -> Option<Decoded> {
+        if !data.is_empty() {
+            return None;
+        }
+        let funder = accounts.first().cloned();
+        let associated_account = accounts.get(1).cloned();
+        let wallet = accounts.get(2).cloned();
+        let mint = accounts.get(3).cloned();
+        let system_program = accounts.get(4).cloned();
+        let token_program = accounts.get(5).cloned();
+        let decoded = json!({
+            "type": "create_associated_token_account",
+            "funder": funder,
+            "associated_account": associated_account,
+            "wallet": wallet,
+            "mint": mint,
+            "system_program": system_program,
+            "token_program": token_program,
+        });
+        Some(Decoded::new("Associated Token Account: Create", decoded))
+    }
+}
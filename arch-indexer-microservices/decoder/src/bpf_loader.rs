@@ -0,0 +1,76 @@
+use serde_json::json;
+
+use super::{u32_le, u64_le, Decoded, InstructionDecoder, BPF_LOADER_BASE58};
+
+/// BPF Loader (u32 LE tags): program deployment and management.
+pub struct BpfLoaderDecoder;
+
+impl InstructionDecoder for BpfLoaderDecoder {
+    fn program_ids(&self) -> Vec<String> {
+        vec![BPF_LOADER_BASE58.to_string()]
+    }
+
+    fn decode(&self, data: &[u8], _accounts: &[String]) -> Option<Decoded> {
+        if data.len() >= 4 {
+            let tag = u32_le(&data[0..4]).unwrap_or(9999);
+            let rest = &data[4..];
+            // 0: Write { offset: u64, bytes: Vec<u8> (len: u64, then bytes) }
+            if tag == 0 {
+                let mut offset_val: Option<u64> = None;
+                let mut bytes_hex: Option<String> = None;
+                if rest.len() >= 8 {
+                    let offset = u64_le(&rest[0..8]).unwrap_or(0);
+                    offset_val = Some(offset);
+                    if rest.len() >= 16 {
+                        let len = u64_le(&rest[8..16]).unwrap_or(0) as usize;
+                        bytes_hex = 16usize.checked_add(len).and_then(|end| rest.get(16..end)).map(hex::encode);
+                    }
+                }
+                let decoded = json!({
+                    "discriminator": {"type":"u32", "data": tag},
+                    "offset": offset_val,
+                    "bytes_hex": bytes_hex,
+                });
+                return Some(Decoded::new("BPF Loader: Write", decoded));
+            }
+            // 1: Truncate { new_size: u64 }
+            if tag == 1 {
+                let new_size = if rest.len() >= 8 { Some(u64_le(&rest[0..8]).unwrap_or(0)) } else { None };
+                let decoded = json!({
+                    "discriminator": {"type":"u32", "data": tag},
+                    "new_size": new_size
+                });
+                return Some(Decoded::new("BPF Loader: Truncate", decoded));
+            }
+            // 2: Deploy
+            if tag == 2 {
+                let decoded = json!({
+                    "discriminator": {"type":"u32", "data": tag}
+                });
+                return Some(Decoded::new("BPF Loader: Deploy", decoded));
+            }
+            // 3: Retract
+            if tag == 3 {
+                let decoded = json!({
+                    "discriminator": {"type":"u32", "data": tag}
+                });
+                return Some(Decoded::new("BPF Loader: Retract", decoded));
+            }
+            // 4: TransferAuthority
+            if tag == 4 {
+                let decoded = json!({
+                    "discriminator": {"type":"u32", "data": tag}
+                });
+                return Some(Decoded::new("BPF Loader: TransferAuthority", decoded));
+            }
+            // 5: Finalize
+            if tag == 5 {
+                let decoded = json!({
+                    "discriminator": {"type":"u32", "data": tag}
+                });
+                return Some(Decoded::new("BPF Loader: Finalize", decoded));
+            }
+        }
+        None
+    }
+}
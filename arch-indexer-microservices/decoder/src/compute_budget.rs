@@ -0,0 +1,43 @@
+use serde_json::json;
+
+use super::{u32_le, u64_le, Decoded, InstructionDecoder, COMPUTE_BUDGET_BASE58};
+
+/// Compute Budget program: heap frame requests and compute unit limit / price (u8 tags).
+pub struct ComputeBudgetDecoder;
+
+impl InstructionDecoder for ComputeBudgetDecoder {
+    fn program_ids(&self) -> Vec<String> {
+        vec![COMPUTE_BUDGET_BASE58.to_string()]
+    }
+
+    fn decode(&self, data: &[u8], _accounts: &[String]) -> Option<Decoded> {
+        if let Some((&tag, rest)) = data.split_first() {
+            // 1: RequestHeapFrame(u32 size)
+            if tag == 1 { if let Some(size) = u32_le(rest) {
+                let decoded = json!({
+                    "discriminator": {"type": "u8", "data": tag},
+                    "bytes": {"type": "u32", "data": size}
+                });
+                return Some(Decoded::new("Compute Budget: RequestHeapFrame", decoded)); }}
+            // 2: SetComputeUnitLimit(u32 units)
+            if tag == 2 && rest.len() >= 4 {
+                let units = u32_le(rest).unwrap_or(0);
+                let decoded = json!({
+                    "discriminator": {"type": "u8", "data": tag},
+                    "units": {"type": "u32", "data": units}
+                });
+                return Some(Decoded::new("Compute Budget: SetComputeUnitLimit", decoded));
+            }
+            // 3: SetComputeUnitPrice(u64 micro_lamports)
+            if tag == 3 && rest.len() >= 8 {
+                let price = u64_le(rest).unwrap_or(0);
+                let decoded = json!({
+                    "discriminator": {"type": "u8", "data": tag},
+                    "price_micro_lamports": {"type": "u64", "data": price}
+                });
+                return Some(Decoded::new("Compute Budget: SetComputeUnitPrice", decoded));
+            }
+        }
+        None
+    }
+}
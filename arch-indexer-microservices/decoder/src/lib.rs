@@ -0,0 +1,379 @@
+//! Instruction decoding shared by the indexer, which stores the results in the
+//! `instructions` table, and the API server, which falls back to it for transactions
+//! indexed before instructions were persisted.
+//!
+//! Decoders implement [`InstructionDecoder`] and are looked up by program id in a
+//! [`DecoderRegistry`]. The process-wide registry starts with the built-in programs;
+//! call [`register`] at startup to add decoders for other programs.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
+
+pub mod associated_token;
+pub mod bpf_loader;
+pub mod compute_budget;
+pub mod memo;
+pub mod stake;
+pub mod system;
+pub mod token;
+pub mod vote;
+
+pub const SYSTEM_PROGRAM_BASE58: &str = "11111111111111111111111111111111";
+/// Some nodes report the system program as `00..01` rather than all zeroes
+pub const SYSTEM_PROGRAM_ALT_BASE58: &str = "11111111111111111111111111111112";
+pub const VOTE_PROGRAM_BASE58: &str = "6pQdihBHh2RjkvynAKrVRXiJwWqQ3etv4FgsnKMz1yZ2";
+pub const STAKE_PROGRAM_BASE58: &str = "6cmmTNBUrk1xPATwae9QiZWQxrAqPx2G28xEG9vinJ3a";
+pub const COMPUTE_BUDGET_BASE58: &str = "ComputeBudget111111111111111111111111111111";
+pub const BPF_LOADER_BASE58: &str = "5UMKG6S4Dn9JLbJvCsE3qqPd2KcbpRypdNHG2KLP7Bex";
+pub const APL_TOKEN_PROGRAM_BASE58: &str = "5QSvph6op2FQj23To5H2LpD5unF1KXmVz29gFMoJTEoJ";
+pub const APL_ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_BASE58: &str = "5QVc8gaXMdjnfS8JS1K8NbQQVPhVHfVPY2asS8b1xY8g";
+pub const MEMO_PROGRAM_BASE58: &str = "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr";
+
+/// A successfully decoded instruction: display type such as `Token: Transfer` and its
+/// arguments. Arguments whose value is one of the instruction's accounts (e.g.
+/// `"destination": "<base58>"`) give that account its role.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decoded {
+    pub instruction_type: String,
+    pub args: Value,
+}
+
+impl Decoded {
+    pub fn new(instruction_type: impl Into<String>, args: Value) -> Self {
+        Self { instruction_type: instruction_type.into(), args }
+    }
+}
+
+/// Decodes the instructions of one or more programs.
+pub trait InstructionDecoder: Send + Sync {
+    /// Base58 ids of the programs this decoder understands.
+    fn program_ids(&self) -> Vec<String>;
+
+    /// Decodes raw instruction `data` given the instruction's accounts (base58, in
+    /// order). Returns `None` for instructions it does not recognise.
+    fn decode(&self, data: &[u8], accounts: &[String]) -> Option<Decoded>;
+}
+
+/// Instruction decoders keyed by base58 program id.
+#[derive(Default, Clone)]
+pub struct DecoderRegistry {
+    decoders: HashMap<String, Arc<dyn InstructionDecoder>>,
+}
+
+impl DecoderRegistry {
+    /// A registry with the System, Compute Budget, BPF Loader, APL Token, Associated
+    /// Token Account, Vote, Stake and Memo decoders.
+    pub fn with_builtin() -> Self {
+        let mut registry = Self::default();
+        registry.register(system::SystemDecoder);
+        registry.register(compute_budget::ComputeBudgetDecoder);
+        registry.register(bpf_loader::BpfLoaderDecoder);
+        registry.register(token::AplTokenDecoder);
+        registry.register(associated_token::AssociatedTokenDecoder);
+        registry.register(vote::VoteDecoder);
+        registry.register(stake::StakeDecoder);
+        registry.register(memo::MemoDecoder);
+        registry
+    }
+
+    /// Registers `decoder` for each of its program ids, replacing any decoder already
+    /// registered for them.
+    pub fn register(&mut self, decoder: impl InstructionDecoder + 'static) {
+        let decoder: Arc<dyn InstructionDecoder> = Arc::new(decoder);
+        for program_id in decoder.program_ids() {
+            self.decoders.insert(program_id, Arc::clone(&decoder));
+        }
+    }
+
+    pub fn get(&self, program_b58: &str) -> Option<&Arc<dyn InstructionDecoder>> {
+        self.decoders.get(program_b58)
+    }
+
+    pub fn decode(&self, program_b58: &str, data: &[u8], accounts: &[String]) -> Option<Decoded> {
+        self.get(program_b58)?.decode(data, accounts)
+    }
+
+    /// Decodes every instruction in a runtime transaction (`{"message": {...}}`). Returns
+    /// an empty list when the message has no account keys or instructions.
+    pub fn decode_transaction(&self, data: &Value) -> Vec<DecodedInstruction> {
+        let Some(message) = data.get("message") else { return Vec::new() };
+        let Some(keys) = message.get("account_keys").and_then(|a| a.as_array()) else { return Vec::new() };
+        let Some(instructions) = message.get("instructions").and_then(|a| a.as_array()) else { return Vec::new() };
+
+        let header = message.get("header");
+        let header_count = |name: &str| header.and_then(|h| h.get(name)).and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+        let n_req = header_count("num_required_signatures");
+        let writable_signed = n_req.saturating_sub(header_count("num_readonly_signed_accounts"));
+        let writable_unsigned = keys.len().saturating_sub(n_req).saturating_sub(header_count("num_readonly_unsigned_accounts"));
+        let is_writable = |i: usize| if i < n_req { i < writable_signed } else { i < n_req + writable_unsigned };
+
+        let mut out = Vec::with_capacity(instructions.len());
+        for (index, ins) in instructions.iter().enumerate() {
+            // Resolve program id: prefer explicit program_id, else program_id_index into account_keys
+            let program_val = if let Some(v) = ins.get("program_id") {
+                Some(v.clone())
+            } else if let Some(ix) = ins.get("program_id_index").and_then(|v| v.as_i64()) {
+                keys.get(ix as usize).cloned()
+            } else { None };
+            let program_id_hex = program_val.as_ref().map(key_to_hex).unwrap_or_default();
+            let program_id_base58 = program_val.as_ref().map(key_to_base58).unwrap_or_default();
+            let acc_idx: Vec<usize> = ins.get("accounts").and_then(|a| a.as_array()).map(|a| a.iter().filter_map(|v| v.as_i64().map(|n| n as usize)).collect()).unwrap_or_default();
+            let data: Vec<u8> = ins.get("data").and_then(|d| d.as_array()).map(|a| a.iter().filter_map(|v| v.as_i64().map(|n| n as u8)).collect()).unwrap_or_default();
+
+            let accounts: Vec<String> = acc_idx.iter().filter_map(|&i| keys.get(i).map(key_to_base58)).collect();
+            let (instruction_type, args) = match self.decode(&program_id_base58, &data, &accounts) {
+                Some(decoded) => (Some(decoded.instruction_type), Some(decoded.args)),
+                None => (None, None),
+            };
+            let account_roles = acc_idx
+                .iter()
+                .filter_map(|&i| {
+                    let key = keys.get(i)?;
+                    Some(AccountRole {
+                        address_hex: key_to_hex(key),
+                        role: role_of(args.as_ref(), &key_to_base58(key)),
+                        is_signer: i < n_req,
+                        is_writable: is_writable(i),
+                    })
+                })
+                .collect();
+
+            out.push(DecodedInstruction {
+                index,
+                program_id_hex,
+                program_id_base58,
+                accounts,
+                account_roles,
+                data,
+                instruction_type,
+                args,
+            });
+        }
+        out
+    }
+}
+
+static REGISTRY: OnceLock<RwLock<DecoderRegistry>> = OnceLock::new();
+
+fn registry() -> &'static RwLock<DecoderRegistry> {
+    REGISTRY.get_or_init(|| RwLock::new(DecoderRegistry::with_builtin()))
+}
+
+/// Adds `decoder` to the process-wide registry used when indexing and serving
+/// instructions. Call before the indexer or API server starts.
+pub fn register(decoder: impl InstructionDecoder + 'static) {
+    registry().write().unwrap().register(decoder);
+}
+
+/// Decodes one instruction with the process-wide registry.
+pub fn decode_instruction(program_b58: &str, data: &[u8], accounts: &[String]) -> Option<Decoded> {
+    registry().read().unwrap().decode(program_b58, data, accounts)
+}
+
+/// Decodes a runtime transaction with the process-wide registry.
+pub fn decode_transaction(data: &Value) -> Vec<DecodedInstruction> {
+    registry().read().unwrap().decode_transaction(data)
+}
+
+/// An account passed to an instruction, with the role the decoder gave it (e.g.
+/// `source`, `mint`) and the signer/writable flags from the message header.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountRole {
+    pub address_hex: String,
+    pub role: Option<String>,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+/// One instruction of a runtime transaction, decoded where the program is known.
+#[derive(Debug, Clone)]
+pub struct DecodedInstruction {
+    pub index: usize,
+    pub program_id_hex: String,
+    pub program_id_base58: String,
+    /// Base58 account addresses in instruction order
+    pub accounts: Vec<String>,
+    pub account_roles: Vec<AccountRole>,
+    pub data: Vec<u8>,
+    /// Display name such as `Token: Transfer`; `None` for programs we cannot decode
+    pub instruction_type: Option<String>,
+    pub args: Option<Value>,
+}
+
+fn key_to_bytes(v: &Value) -> Option<Vec<u8>> {
+    if let Some(arr) = v.as_array() {
+        return Some(arr.iter().filter_map(|x| x.as_i64().map(|n| n as u8)).collect());
+    }
+    if let Some(s) = v.as_str() {
+        if let Ok(bytes) = bs58::decode(s).into_vec() {
+            return Some(bytes);
+        }
+        let maybe_hex = s.len() >= 2 && s.len() % 2 == 0 && s.chars().all(|c| c.is_ascii_hexdigit());
+        if maybe_hex {
+            if let Ok(bytes) = hex::decode(s) {
+                return Some(bytes);
+            }
+        }
+    }
+    None
+}
+
+fn key_to_base58(v: &Value) -> String {
+    match key_to_bytes(v) {
+        Some(bytes) => bs58::encode(bytes).into_string(),
+        None => v.as_str().map(|s| s.to_string()).unwrap_or_default(),
+    }
+}
+
+pub fn key_to_hex(v: &Value) -> String {
+    match key_to_bytes(v) {
+        Some(bytes) => hex::encode(bytes),
+        None => v.as_str().map(|s| s.to_lowercase()).unwrap_or_default(),
+    }
+}
+
+/// The decoded argument naming `account` (e.g. `"destination": "<base58>"`), if any.
+fn role_of(args: Option<&Value>, account: &str) -> Option<String> {
+    args?.as_object()?
+        .iter()
+        .find(|(_, v)| match v {
+            Value::String(s) => s == account,
+            Value::Array(items) => items.iter().any(|item| item.as_str() == Some(account)),
+            _ => false,
+        })
+        .map(|(name, _)| name.clone())
+}
+
+/// Bytes a short_vec length prefix takes (LEB128-like, 7 bits per byte).
+fn shortvec_len(len: usize) -> usize {
+    let mut n = 0usize;
+    let mut v = len;
+    loop {
+        n += 1;
+        if v < 0x80 { break; }
+        v >>= 7;
+    }
+    n
+}
+
+/// Approximate wire size of a runtime transaction: signatures, the 3-byte header, account
+/// keys, recent blockhash and compiled instructions. The indexer stores the sum over a
+/// block's transactions as the block size.
+pub fn transaction_size(tx: &Value) -> i64 {
+    let sigs_len = tx.get("signatures").and_then(|a| a.as_array()).map(|a| a.len()).unwrap_or(0);
+    let sigs_size = shortvec_len(sigs_len) + sigs_len * 64;
+
+    let message = tx.get("message").unwrap_or(&Value::Null);
+    let header_size = 3usize;
+    let keys_len = message.get("account_keys").and_then(|a| a.as_array()).map(|a| a.len()).unwrap_or(0);
+    let keys_size = shortvec_len(keys_len) + keys_len * 32;
+    let recent_blockhash_size = 32usize;
+
+    let mut instr_size = 0usize;
+    if let Some(instrs) = message.get("instructions").and_then(|a| a.as_array()) {
+        instr_size += shortvec_len(instrs.len());
+        for ins in instrs {
+            let accounts_len = ins.get("accounts").and_then(|a| a.as_array()).map(|a| a.len()).unwrap_or(0);
+            let data_len = ins.get("data").and_then(|a| a.as_array()).map(|a| a.len()).unwrap_or(0);
+            instr_size += 1; // program_id_index
+            instr_size += shortvec_len(accounts_len) + accounts_len; // account indices (u8 each)
+            instr_size += shortvec_len(data_len) + data_len; // instruction data bytes
+        }
+    } else {
+        instr_size += shortvec_len(0);
+    }
+
+    (sigs_size + header_size + keys_size + recent_blockhash_size + instr_size) as i64
+}
+
+pub fn u32_le(bytes: &[u8]) -> Option<u32> { if bytes.len() >= 4 { Some(u32::from_le_bytes([bytes[0],bytes[1],bytes[2],bytes[3]])) } else { None } }
+pub fn u64_le(bytes: &[u8]) -> Option<u64> { if bytes.len() >= 8 { Some(u64::from_le_bytes([bytes[0],bytes[1],bytes[2],bytes[3],bytes[4],bytes[5],bytes[6],bytes[7]])) } else { None } }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn token_transfer_is_decoded_with_account_roles() {
+        let program = bs58::decode(APL_TOKEN_PROGRAM_BASE58).into_vec().unwrap();
+        let mut data = vec![3u8];
+        data.extend_from_slice(&500u64.to_le_bytes());
+        let tx = json!({
+            "message": {
+                "header": { "num_required_signatures": 1, "num_readonly_signed_accounts": 0, "num_readonly_unsigned_accounts": 1 },
+                "account_keys": [vec![1u8; 32], vec![2u8; 32], vec![3u8; 32], program],
+                "instructions": [{ "program_id_index": 3, "accounts": [1, 2, 0], "data": data }]
+            }
+        });
+        let decoded = decode_transaction(&tx);
+        assert_eq!(decoded.len(), 1);
+        let ix = &decoded[0];
+        assert_eq!(ix.instruction_type.as_deref(), Some("Token: Transfer"));
+        assert_eq!(ix.args.as_ref().unwrap()["amount"], json!(500));
+        let roles: Vec<_> = ix.account_roles.iter().map(|r| (r.role.as_deref(), r.is_signer, r.is_writable)).collect();
+        assert_eq!(roles, vec![(Some("from"), false, true), (Some("to"), false, true), (Some("authority"), true, true)]);
+    }
+
+    #[test]
+    fn transaction_size_counts_signatures_keys_and_instructions() {
+        let tx = json!({
+            "signatures": [vec![0u8; 64]],
+            "message": {
+                "account_keys": [vec![1u8; 32], vec![2u8; 32]],
+                "instructions": [{ "program_id_index": 1, "accounts": [0], "data": vec![9u8; 200] }]
+            }
+        });
+        // 1+64 signatures, 3 header, 1+64 keys, 32 blockhash, 1 + (1 + 1+1 + 2+200) instructions
+        assert_eq!(transaction_size(&tx), 65 + 3 + 65 + 32 + 206);
+        assert_eq!(transaction_size(&json!({})), 1 + 3 + 1 + 32 + 1);
+    }
+
+    struct EchoDecoder;
+
+    impl InstructionDecoder for EchoDecoder {
+        fn program_ids(&self) -> Vec<String> {
+            vec![bs58::encode([7u8; 32]).into_string()]
+        }
+
+        fn decode(&self, data: &[u8], accounts: &[String]) -> Option<Decoded> {
+            Some(Decoded::new("Echo: Call", json!({ "len": data.len(), "caller": accounts.first() })))
+        }
+    }
+
+    #[test]
+    fn registered_decoders_are_used_by_program_id() {
+        let mut registry = DecoderRegistry::with_builtin();
+        let program = bs58::encode([7u8; 32]).into_string();
+        assert!(registry.decode(&program, &[1, 2], &[]).is_none());
+        registry.register(EchoDecoder);
+        let decoded = registry.decode(&program, &[1, 2], &["caller".to_string()]).unwrap();
+        assert_eq!(decoded.instruction_type, "Echo: Call");
+        assert_eq!(decoded.args["caller"], json!("caller"));
+
+        let mut data = 4u32.to_le_bytes().to_vec();
+        data.extend_from_slice(&42u64.to_le_bytes());
+        let transfer = registry.decode(SYSTEM_PROGRAM_ALT_BASE58, &data, &["a".into(), "b".into()]).unwrap();
+        assert_eq!(transfer.instruction_type, "System Program: Transfer");
+    }
+
+    #[test]
+    fn oversized_write_lengths_are_not_sliced() {
+        let registry = DecoderRegistry::with_builtin();
+        let mut data = 1u32.to_le_bytes().to_vec();
+        data.extend_from_slice(&0u64.to_le_bytes());
+        data.extend_from_slice(&u64::MAX.to_le_bytes());
+        data.extend_from_slice(&[0xab; 4]);
+        let write = registry.decode(SYSTEM_PROGRAM_BASE58, &data, &["a".into()]).unwrap();
+        assert_eq!(write.instruction_type, "System Program: WriteBytes");
+        assert_eq!(write.args["bytes_hex"], json!(null));
+
+        let mut data = 0u32.to_le_bytes().to_vec();
+        data.extend_from_slice(&0u64.to_le_bytes());
+        data.extend_from_slice(&u64::MAX.to_le_bytes());
+        let write = registry.decode(BPF_LOADER_BASE58, &data, &[]).unwrap();
+        assert_eq!(write.args["bytes_hex"], json!(null));
+    }
+}
@@ -0,0 +1,17 @@
+use serde_json::json;
+
+use super::{Decoded, InstructionDecoder, MEMO_PROGRAM_BASE58};
+
+/// Memo program: the instruction data is the UTF-8 memo text.
+pub struct MemoDecoder;
+
+impl InstructionDecoder for MemoDecoder {
+    fn program_ids(&self) -> Vec<String> {
+        vec![MEMO_PROGRAM_BASE58.to_string()]
+    }
+
+    fn decode(&self, data: &[u8], _accounts: &[String]) -> Option<Decoded> {
+        let text = std::str::from_utf8(data).ok()?;
+        Some(Decoded::new("Memo: Write", json!({"memo": text})))
+    }
+}
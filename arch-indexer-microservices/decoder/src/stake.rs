@@ -0,0 +1,74 @@
+use serde_json::json;
+
+use super::{u32_le, u64_le, Decoded, InstructionDecoder, STAKE_PROGRAM_BASE58};
+
+/// Stake program. Instructions use the bincode layout shared with Solana's stake program:
+/// a u32 LE tag followed by the variant's fields.
+pub struct StakeDecoder;
+
+impl InstructionDecoder for StakeDecoder {
+    fn program_ids(&self) -> Vec<String> {
+        vec![STAKE_PROGRAM_BASE58.to_string()]
+    }
+
+    fn decode(&self, data: &[u8], accounts: &[String]) -> Option<Decoded> {
+        let tag = u32_le(data)?;
+        let pubkey_at = |offset: usize| data.get(offset..offset + 32).map(|b| bs58::encode(b).into_string());
+        let stake_account = accounts.first().cloned();
+        let (name, decoded) = match tag {
+            // 0: Initialize(Authorized { staker, withdrawer }, Lockup)
+            0 if data.len() >= 4 + 32 * 2 => ("Initialize", json!({
+                "discriminator": {"type":"u32", "data": tag},
+                "stake_account": stake_account,
+                "staker": pubkey_at(4),
+                "withdrawer": pubkey_at(36),
+            })),
+            // 1: Authorize(new_authority: Pubkey, StakeAuthorize: u32 — 0 staker, 1 withdrawer)
+            1 if data.len() >= 4 + 32 + 4 => ("Authorize", json!({
+                "discriminator": {"type":"u32", "data": tag},
+                "stake_account": stake_account,
+                "new_authority": pubkey_at(4),
+                "authority_type": {"type":"u32", "data": u32_le(&data[36..])},
+                "current_authority": accounts.last().cloned(),
+            })),
+            // 2: DelegateStake; accounts: [stake_account, vote_account, .., stake_authority]
+            2 => ("DelegateStake", json!({
+                "discriminator": {"type":"u32", "data": tag},
+                "stake_account": stake_account,
+                "vote_account": accounts.get(1),
+                "stake_authority": accounts.last().filter(|_| accounts.len() > 2),
+            })),
+            // 3: Split(lamports: u64); accounts: [stake_account, split_stake_account, stake_authority]
+            3 if data.len() >= 4 + 8 => ("Split", json!({
+                "discriminator": {"type":"u32", "data": tag},
+                "lamports": {"type":"u64", "data": u64_le(&data[4..])},
+                "stake_account": stake_account,
+                "split_stake_account": accounts.get(1),
+                "stake_authority": accounts.get(2),
+            })),
+            // 4: Withdraw(lamports: u64); accounts: [stake_account, recipient, .., withdraw_authority]
+            4 if data.len() >= 4 + 8 => ("Withdraw", json!({
+                "discriminator": {"type":"u32", "data": tag},
+                "lamports": {"type":"u64", "data": u64_le(&data[4..])},
+                "stake_account": stake_account,
+                "recipient": accounts.get(1),
+                "withdraw_authority": accounts.last().filter(|_| accounts.len() > 2),
+            })),
+            // 5: Deactivate; accounts: [stake_account, .., stake_authority]
+            5 => ("Deactivate", json!({
+                "discriminator": {"type":"u32", "data": tag},
+                "stake_account": stake_account,
+                "stake_authority": accounts.last().filter(|_| accounts.len() > 1),
+            })),
+            // 7: Merge; accounts: [destination_stake_account, source_stake_account, .., stake_authority]
+            7 => ("Merge", json!({
+                "discriminator": {"type":"u32", "data": tag},
+                "destination": stake_account,
+                "source": accounts.get(1),
+                "stake_authority": accounts.last().filter(|_| accounts.len() > 2),
+            })),
+            _ => return None,
+        };
+        Some(Decoded::new(format!("Stake Program: {}", name), decoded))
+    }
+}
@@ -0,0 +1,95 @@
+use serde_json::json;
+
+use super::{u32_le, u64_le, Decoded, InstructionDecoder, SYSTEM_PROGRAM_ALT_BASE58, SYSTEM_PROGRAM_BASE58};
+
+/// Arch System Program (u32 LE tags): account creation, transfers, ownership and data writes.
+pub struct SystemDecoder;
+
+impl InstructionDecoder for SystemDecoder {
+    fn program_ids(&self) -> Vec<String> {
+        vec![SYSTEM_PROGRAM_BASE58.to_string(), SYSTEM_PROGRAM_ALT_BASE58.to_string()]
+    }
+
+    fn decode(&self, data: &[u8], accounts: &[String]) -> Option<Decoded> {
+        if data.len() >= 4 {
+            let tag = u32_le(&data[0..4]).unwrap_or(9999);
+            // Arch: 3 => AssignOwnership { owner: Pubkey }
+            if tag == 3 && data.len() >= 4 + 32 {
+                let owner_b58 = bs58::encode(&data[4..36]).into_string();
+                let account = accounts.first().cloned();
+                let decoded = json!({
+                    "discriminator": {"type":"u32", "data": tag},
+                    "owner": owner_b58,
+                    "account": account,
+                });
+                return Some(Decoded::new("System Program: AssignOwnership", decoded));
+            }
+            // Arch: 0 => CreateAccount { lamports: u64, space: u64, owner: Pubkey(32) }
+            if tag == 0 && data.len() >= 4 + 8 + 8 + 32 {
+                let lamports = u64_le(&data[4..12]).unwrap_or(0);
+                let space = u64_le(&data[12..20]).unwrap_or(0);
+                let owner = bs58::encode(&data[20..52]).into_string();
+                let from = accounts.first().cloned(); // funder
+                let new_account = accounts.get(1).cloned();
+                let decoded = json!({
+                    "discriminator": {"type":"u32", "data": tag},
+                    "lamports": {"type":"u64", "data": lamports},
+                    "space": {"type":"u64", "data": space},
+                    "owner": owner,
+                    "funder": from,
+                    "new_account": new_account,
+                });
+                return Some(Decoded::new("System Program: CreateAccount", decoded));
+            }
+            // Arch: 4 => Transfer { lamports: u64 }
+            if tag == 4 && data.len() == 12 {
+                let lamports = u64_le(&data[4..12]).unwrap_or(0);
+                let src = accounts.first().cloned();
+                let dst = accounts.get(1).cloned();
+                let decoded = json!({
+                    "discriminator": {"type":"u32", "data": tag},
+                    "lamports": {"type":"u64", "data": lamports},
+                    "source": src,
+                    "destination": dst,
+                });
+                return Some(Decoded::new("System Program: Transfer", decoded));
+            }
+            // Arch: 2 => MakeExecutable (no payload)
+            if tag == 2 {
+                let account = accounts.first().cloned();
+                let decoded = json!({
+                    "discriminator": {"type":"u32", "data": tag},
+                    "account": account,
+                });
+                return Some(Decoded::new("System Program: MakeExecutable", decoded));
+            }
+            // Arch: 1 => WriteBytes { offset: u64, len: u64, bytes: [..] }
+            if tag == 1 && data.len() >= 4 + 8 + 8 {
+                let offset = u64_le(&data[4..12]).unwrap_or(0);
+                let len = u64_le(&data[12..20]).unwrap_or(0) as usize;
+                let bytes_hex = 20usize.checked_add(len).and_then(|end| data.get(20..end)).map(hex::encode);
+                let account = accounts.first().cloned();
+                let decoded = json!({
+                    "discriminator": {"type":"u32", "data": tag},
+                    "offset": {"type":"u64", "data": offset},
+                    "len": {"type":"u64", "data": len as u64},
+                    "bytes_hex": bytes_hex,
+                    "account": account,
+                });
+                return Some(Decoded::new("System Program: WriteBytes", decoded));
+            }
+            // 8: Allocate { space: u64 }
+            if tag == 8 && data.len() >= 4 + 8 {
+                let space = u64_le(&data[4..12]).unwrap_or(0);
+                let account = accounts.first().cloned();
+                let decoded = json!({
+                    "discriminator": {"type":"u32", "data": tag},
+                    "space": {"type":"u64", "data": space},
+                    "account": account,
+                });
+                return Some(Decoded::new("System Program: Allocate", decoded));
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,333 @@
+use serde_json::{json, Value};
+
+use super::{u64_le, Decoded, InstructionDecoder, APL_TOKEN_PROGRAM_BASE58};
+
+/// APL Token program (u8 tags, SPL Token compatible layout).
+pub struct AplTokenDecoder;
+
+impl InstructionDecoder for AplTokenDecoder {
+    fn program_ids(&self) -> Vec<String> {
+        vec![APL_TOKEN_PROGRAM_BASE58.to_string()]
+    }
+
+    fn decode(&self, data: &[u8], accounts: &[String]) -> Option<Decoded> {
+        if !data.is_empty() {
+            let tag = data[0];
+            // 0: InitializeMint { decimals, mint_authority, freeze_authority: COption<Pubkey> }
+            if tag == 0 && data.len() > 1 + 1 + 32 {
+                let decimals = data[1];
+                let mint_authority = bs58::encode(&data[2..34]).into_string();
+                let has_freeze = data[34] != 0;
+                let mut obj = serde_json::Map::new();
+                obj.insert("discriminator".to_string(), json!({"type":"u8", "data": tag}));
+                obj.insert("decimals".to_string(), json!({"type":"u8", "data": decimals}));
+                obj.insert("mint_authority".to_string(), json!(mint_authority));
+                if has_freeze && data.len() >= 1 + 1 + 32 + 1 + 32 {
+                    obj.insert("freeze_authority".to_string(), json!(bs58::encode(&data[35..67]).into_string()));
+                } else {
+                    obj.insert("freeze_authority".to_string(), json!(null));
+                }
+                return Some(Decoded::new("Token: InitializeMint", Value::Object(obj)));
+            }
+            // 1: InitializeAccount (no data)
+            if tag == 1 {
+                let decoded = json!({
+                    "discriminator": {"type":"u8", "data": tag},
+                    "account": accounts.first(),
+                    "mint": accounts.get(1),
+                    "owner": accounts.get(2),
+                });
+                return Some(Decoded::new("Token: InitializeAccount", decoded));
+            }
+            // 2: InitializeMultisig { m: u8 } ; accounts: [multisig, signer1..signerN]
+            if tag == 2 && data.len() >= 2 {
+                let m = data[1];
+                let multisig = accounts.first().cloned();
+                let signers: Vec<String> = if accounts.len() > 1 { accounts[1..].to_vec() } else { Vec::new() };
+                let decoded = json!({
+                    "discriminator": {"type":"u8", "data": tag},
+                    "m": {"type":"u8", "data": m},
+                    "multisig": multisig,
+                    "signers": signers,
+                });
+                return Some(Decoded::new("Token: InitializeMultisig", decoded));
+            }
+            // 3: Transfer { amount: u64 }, accounts: [source, destination, authority]
+            if tag == 3 && data.len() > 8 {
+                let amount = u64_le(&data[1..9]).unwrap_or(0);
+                let source = accounts.first().cloned();
+                let destination = accounts.get(1).cloned();
+                let authority = accounts.get(2).cloned();
+                let decoded = json!({
+                    "type": "transfer",
+                    "amount": amount,
+                    "from": source,
+                    "to": destination,
+                    "authority": authority,
+                });
+                return Some(Decoded::new("Token: Transfer", decoded));
+            }
+            // 4: Approve { amount: u64 }
+            if tag == 4 && data.len() > 8 {
+                let amount = u64_le(&data[1..9]).unwrap_or(0);
+                let source = accounts.first().cloned();
+                let delegate = accounts.get(2).cloned();
+                let decoded = json!({
+                    "discriminator": {"type":"u8", "data": tag},
+                    "amount": {"type":"u64", "data": amount},
+                    "source": source,
+                    "delegate": delegate,
+                });
+                return Some(Decoded::new("Token: Approve", decoded));
+            }
+            // 5: Revoke
+            if tag == 5 {
+                let source = accounts.first().cloned();
+                let decoded = json!({
+                    "discriminator": {"type":"u8", "data": tag},
+                    "source": source,
+                });
+                return Some(Decoded::new("Token: Revoke", decoded));
+            }
+            // 6: SetAuthority { authority_type: u8, new_authority: COption<Pubkey> }
+            if tag == 6 && data.len() >= 2 {
+                let authority_type = data[1];
+                let mut idx = 2usize;
+                let mut new_authority_str: Option<String> = None;
+                if data.len() > idx {
+                    let flag = data[idx];
+                    idx += 1;
+                    if flag == 1 && data.len() >= idx + 32 {
+                        new_authority_str = Some(bs58::encode(&data[idx..idx+32]).into_string());
+                    }
+                }
+                let owned = accounts.first().cloned();
+                let owner = accounts.get(1).cloned();
+                let decoded = json!({
+                    "discriminator": {"type":"u8", "data": tag},
+                    "authority_type": {"type":"u8", "data": authority_type},
+                    "new_authority": new_authority_str,
+                    "owned_account": owned,
+                    "current_authority": owner,
+                });
+                return Some(Decoded::new("Token: SetAuthority", decoded));
+            }
+            // 12: TransferChecked { amount: u64, decimals: u8 }
+            if tag == 12 && data.len() > 1 + 8 {
+                let amount = u64_le(&data[1..9]).unwrap_or(0);
+                let decimals = data[9];
+                let token = accounts.first().cloned();
+                let source = accounts.get(1).cloned();
+                let mint = accounts.get(2).cloned();
+                let destination = accounts.get(3).cloned();
+                let authority = accounts.get(4).cloned();
+                let decoded = json!({
+                    "discriminator": {"type":"u8", "data": tag},
+                    "amount": {"type":"u64", "data": amount},
+                    "decimals": {"type":"u8", "data": decimals},
+                    "token": token,
+                    "source": source,
+                    "mint": mint,
+                    "destination": destination,
+                    "authority": authority,
+                });
+                return Some(Decoded::new("Token: TransferChecked", decoded));
+            }
+            // 13: ApproveChecked { amount: u64, decimals: u8 }
+            if tag == 13 && data.len() > 1 + 8 {
+                let amount = u64_le(&data[1..9]).unwrap_or(0);
+                let decimals = data[9];
+                let source = accounts.first().cloned();
+                let mint = accounts.get(1).cloned();
+                let delegate = accounts.get(2).cloned();
+                let owner = accounts.get(3).cloned();
+                let decoded = json!({
+                    "discriminator": {"type":"u8", "data": tag},
+                    "amount": {"type":"u64", "data": amount},
+                    "decimals": {"type":"u8", "data": decimals},
+                    "source": source,
+                    "mint": mint,
+                    "delegate": delegate,
+                    "owner": owner,
+                });
+                return Some(Decoded::new("Token: ApproveChecked", decoded));
+            }
+            // 14: MintToChecked { amount: u64, decimals: u8 }
+            if tag == 14 && data.len() > 1 + 8 {
+                let amount = u64_le(&data[1..9]).unwrap_or(0);
+                let decimals = data[9];
+                let mint = accounts.first().cloned();
+                let destination = accounts.get(1).cloned();
+                let authority = accounts.get(2).cloned();
+                let decoded = json!({
+                    "discriminator": {"type":"u8", "data": tag},
+                    "amount": {"type":"u64", "data": amount},
+                    "decimals": {"type":"u8", "data": decimals},
+                    "mint": mint,
+                    "destination": destination,
+                    "authority": authority,
+                });
+                return Some(Decoded::new("Token: MintToChecked", decoded));
+            }
+            // 15: BurnChecked { amount: u64, decimals: u8 }
+            if tag == 15 && data.len() > 1 + 8 {
+                let amount = u64_le(&data[1..9]).unwrap_or(0);
+                let decimals = data[9];
+                let account = accounts.first().cloned();
+                let mint = accounts.get(1).cloned();
+                let owner = accounts.get(2).cloned();
+                let decoded = json!({
+                    "discriminator": {"type":"u8", "data": tag},
+                    "amount": {"type":"u64", "data": amount},
+                    "decimals": {"type":"u8", "data": decimals},
+                    "account": account,
+                    "mint": mint,
+                    "owner": owner,
+                });
+                return Some(Decoded::new("Token: BurnChecked", decoded));
+            }
+            // 7: MintTo { amount: u64 }
+            if tag == 7 && data.len() > 8 {
+                let amount = u64_le(&data[1..9]).unwrap_or(0);
+                let mint = accounts.first().cloned();
+                let dest = accounts.get(1).cloned();
+                let decoded = json!({
+                    "discriminator": {"type":"u8", "data": tag},
+                    "amount": {"type":"u64", "data": amount},
+                    "mint": mint,
+                    "destination": dest,
+                });
+                return Some(Decoded::new("Token: MintTo", decoded));
+            }
+            // 8: Burn { amount: u64 }
+            if tag == 8 && data.len() > 8 {
+                let amount = u64_le(&data[1..9]).unwrap_or(0);
+                let account = accounts.first().cloned();
+                let mint = accounts.get(1).cloned();
+                let decoded = json!({
+                    "discriminator": {"type":"u8", "data": tag},
+                    "amount": {"type":"u64", "data": amount},
+                    "account": account,
+                    "mint": mint,
+                });
+                return Some(Decoded::new("Token: Burn", decoded));
+            }
+            // 9: CloseAccount
+            if tag == 9 {
+                let account = accounts.first().cloned();
+                let destination = accounts.get(1).cloned();
+                let decoded = json!({
+                    "discriminator": {"type":"u8", "data": tag},
+                    "account": account,
+                    "destination": destination,
+                });
+                return Some(Decoded::new("Token: CloseAccount", decoded));
+            }
+            // 10: FreezeAccount
+            if tag == 10 {
+                let account = accounts.first().cloned();
+                let mint = accounts.get(1).cloned();
+                let decoded = json!({
+                    "discriminator": {"type":"u8", "data": tag},
+                    "account": account,
+                    "mint": mint,
+                });
+                return Some(Decoded::new("Token: FreezeAccount", decoded));
+            }
+            // 11: ThawAccount
+            if tag == 11 {
+                let account = accounts.first().cloned();
+                let mint = accounts.get(1).cloned();
+                let decoded = json!({
+                    "discriminator": {"type":"u8", "data": tag},
+                    "account": account,
+                    "mint": mint,
+                });
+                return Some(Decoded::new("Token: ThawAccount", decoded));
+            }
+            // 16: InitializeAccount2 { owner: Pubkey }
+            if tag == 16 && data.len() > 32 {
+                let owner = bs58::encode(&data[1..33]).into_string();
+                let account = accounts.first().cloned();
+                let mint = accounts.get(1).cloned();
+                let decoded = json!({
+                    "discriminator": {"type":"u8", "data": tag},
+                    "owner": owner,
+                    "account": account,
+                    "mint": mint,
+                });
+                return Some(Decoded::new("Token: InitializeAccount2", decoded));
+            }
+            // 17: InitializeAccount3 { owner: Pubkey }
+            if tag == 17 && data.len() > 32 {
+                let owner = bs58::encode(&data[1..33]).into_string();
+                let account = accounts.first().cloned();
+                let mint = accounts.get(1).cloned();
+                let decoded = json!({
+                    "discriminator": {"type":"u8", "data": tag},
+                    "owner": owner,
+                    "account": account,
+                    "mint": mint,
+                });
+                return Some(Decoded::new("Token: InitializeAccount3", decoded));
+            }
+            // 18: InitializeMint2 { decimals, mint_authority, freeze_authority: COption<Pubkey> }
+            if tag == 18 && data.len() > 1 + 1 + 32 {
+                let decimals = data[1];
+                let mint_authority = bs58::encode(&data[2..34]).into_string();
+                let has_freeze = data[34] != 0;
+                let mut obj = serde_json::Map::new();
+                obj.insert("discriminator".to_string(), json!({"type":"u8", "data": tag}));
+                obj.insert("decimals".to_string(), json!({"type":"u8", "data": decimals}));
+                obj.insert("mint_authority".to_string(), json!(mint_authority));
+                if has_freeze && data.len() >= 1 + 1 + 32 + 1 + 32 {
+                    obj.insert("freeze_authority".to_string(), json!(bs58::encode(&data[35..67]).into_string()));
+                } else {
+                    obj.insert("freeze_authority".to_string(), json!(null));
+                }
+                return Some(Decoded::new("Token: InitializeMint2", Value::Object(obj)));
+            }
+            // 19: GetAccountDataSize (no data)
+            if tag == 19 {
+                let mint = accounts.first().cloned();
+                let decoded = json!({
+                    "discriminator": {"type":"u8", "data": tag},
+                    "mint": mint,
+                });
+                return Some(Decoded::new("Token: GetAccountDataSize", decoded));
+            }
+            // 20: InitializeImmutableOwner (no data)
+            if tag == 20 {
+                let account = accounts.first().cloned();
+                let decoded = json!({
+                    "discriminator": {"type":"u8", "data": tag},
+                    "account": account,
+                });
+                return Some(Decoded::new("Token: InitializeImmutableOwner", decoded));
+            }
+            // 21: AmountToUiAmount { amount: u64 }
+            if tag == 21 && data.len() > 8 {
+                let amount = u64_le(&data[1..9]).unwrap_or(0);
+                let mint = accounts.first().cloned();
+                let decoded = json!({
+                    "discriminator": {"type":"u8", "data": tag},
+                    "amount": {"type":"u64", "data": amount},
+                    "mint": mint,
+                });
+                return Some(Decoded::new("Token: AmountToUiAmount", decoded));
+            }
+            // 22: UiAmountToAmount { ui_amount: string }
+            if tag == 22 && !data.is_empty() {
+                let ui_amount = match std::str::from_utf8(&data[1..]) { Ok(s) => s.to_string(), Err(_) => String::new() };
+                let mint = accounts.first().cloned();
+                let decoded = json!({
+                    "discriminator": {"type":"u8", "data": tag},
+                    "ui_amount": ui_amount,
+                    "mint": mint,
+                });
+                return Some(Decoded::new("Token: UiAmountToAmount", decoded));
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,68 @@
+use serde_json::json;
+
+use super::{u32_le, u64_le, Decoded, InstructionDecoder, VOTE_PROGRAM_BASE58};
+
+/// Vote program. Instructions use the bincode layout shared with Solana's vote program:
+/// a u32 LE tag followed by the variant's fields.
+pub struct VoteDecoder;
+
+impl InstructionDecoder for VoteDecoder {
+    fn program_ids(&self) -> Vec<String> {
+        vec![VOTE_PROGRAM_BASE58.to_string()]
+    }
+
+    fn decode(&self, data: &[u8], accounts: &[String]) -> Option<Decoded> {
+        let tag = u32_le(data)?;
+        let pubkey_at = |offset: usize| data.get(offset..offset + 32).map(|b| bs58::encode(b).into_string());
+        let vote_account = accounts.first().cloned();
+        let (name, decoded) = match tag {
+            // 0: InitializeAccount(VoteInit { node_pubkey, authorized_voter, authorized_withdrawer, commission })
+            0 if data.len() > 4 + 32 * 3 => ("InitializeAccount", json!({
+                "discriminator": {"type":"u32", "data": tag},
+                "vote_account": vote_account,
+                "node_pubkey": pubkey_at(4),
+                "authorized_voter": pubkey_at(36),
+                "authorized_withdrawer": pubkey_at(68),
+                "commission": {"type":"u8", "data": data[100]},
+            })),
+            // 1: Authorize(new_authority: Pubkey, VoteAuthorize: u32 — 0 voter, 1 withdrawer)
+            1 if data.len() >= 4 + 32 + 4 => ("Authorize", json!({
+                "discriminator": {"type":"u32", "data": tag},
+                "vote_account": vote_account,
+                "new_authority": pubkey_at(4),
+                "authority_type": {"type":"u32", "data": u32_le(&data[36..])},
+                "current_authority": accounts.last().cloned(),
+            })),
+            // 2: Vote(Vote { slots, hash, timestamp })
+            2 => ("Vote", json!({
+                "discriminator": {"type":"u32", "data": tag},
+                "vote_account": vote_account,
+                "vote_authority": accounts.last().cloned(),
+            })),
+            // 3: Withdraw(lamports: u64); accounts: [vote_account, recipient, withdraw_authority]
+            3 if data.len() >= 4 + 8 => ("Withdraw", json!({
+                "discriminator": {"type":"u32", "data": tag},
+                "lamports": {"type":"u64", "data": u64_le(&data[4..])},
+                "vote_account": vote_account,
+                "recipient": accounts.get(1),
+                "withdraw_authority": accounts.get(2),
+            })),
+            // 4: UpdateValidatorIdentity; accounts: [vote_account, new_identity, withdraw_authority]
+            4 => ("UpdateValidatorIdentity", json!({
+                "discriminator": {"type":"u32", "data": tag},
+                "vote_account": vote_account,
+                "new_identity": accounts.get(1),
+                "withdraw_authority": accounts.get(2),
+            })),
+            // 5: UpdateCommission(commission: u8); accounts: [vote_account, withdraw_authority]
+            5 if data.len() > 4 => ("UpdateCommission", json!({
+                "discriminator": {"type":"u32", "data": tag},
+                "commission": {"type":"u8", "data": data[4]},
+                "vote_account": vote_account,
+                "withdraw_authority": accounts.get(1),
+            })),
+            _ => return None,
+        };
+        Some(Decoded::new(format!("Vote Program: {}", name), decoded))
+    }
+}
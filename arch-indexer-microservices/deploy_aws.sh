@@ -54,8 +54,9 @@ build_push() {
 }
 
 # Build and push all images (linux/amd64)
-build_push api-server "$ECR_API" "$API_DIR" "$API_DIR/Dockerfile"
-build_push indexer    "$ECR_INDEXER" "$INDEXER_DIR" "$INDEXER_DIR/Dockerfile"
+# api-server and indexer build from the repository root to reach the shared decoder and node-rpc crates
+build_push api-server "$ECR_API" "$SCRIPT_DIR" "$API_DIR/Dockerfile"
+build_push indexer    "$ECR_INDEXER" "$SCRIPT_DIR" "$INDEXER_DIR/Dockerfile"
 build_push frontend   "$ECR_FRONTEND" "$FRONTEND_DIR" "$FRONTEND_DIR/Dockerfile"
 
 echo "\nDone. Images pushed:"
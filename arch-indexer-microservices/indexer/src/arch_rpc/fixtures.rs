@@ -0,0 +1,199 @@
+use anyhow::{Context, Result};
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::State;
+use axum::response::Response;
+use axum::routing::get;
+use axum::{Json, Router};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::recorder::{RpcRecord, RPC_FILE, WS_FILE};
+use super::WebSocketEvent;
+
+/// Recorded node traffic loaded back from an ARCH_RECORD_DIR directory, answering
+/// JSON-RPC calls by method and params the way the recording node did.
+#[derive(Debug, Default)]
+pub struct Fixtures {
+    calls: HashMap<String, RpcRecord>,
+    block_count: Option<i64>,
+    events: Vec<WebSocketEvent>,
+}
+
+/// Single-element param arrays and bare values are the same call (`["ab"]` vs `"ab"`).
+fn call_key(method: &str, params: &Value) -> String {
+    let params = match params {
+        Value::Array(items) if items.len() == 1 => &items[0],
+        other => other,
+    };
+    format!("{}:{}", method, params)
+}
+
+fn read_lines<T: serde::de::DeserializeOwned>(path: &Path) -> Result<Vec<T>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let text = std::fs::read_to_string(path).with_context(|| format!("cannot read {}", path.display()))?;
+    text.lines()
+        .filter(|l| !l.trim().is_empty())
+        .enumerate()
+        .map(|(i, l)| serde_json::from_str(l).with_context(|| format!("{}:{}", path.display(), i + 1)))
+        .collect()
+}
+
+impl Fixtures {
+    pub fn load(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref();
+        Ok(Self::from_records(read_lines(&dir.join(RPC_FILE))?, read_lines(&dir.join(WS_FILE))?))
+    }
+
+    pub fn from_records(records: Vec<RpcRecord>, events: Vec<WebSocketEvent>) -> Self {
+        let mut fixtures = Self { events, ..Default::default() };
+        let mut last_count = None;
+        let mut max_height = None;
+        for record in records {
+            match record.method.as_str() {
+                "get_block_count" => last_count = record.result.as_ref().and_then(Value::as_i64).or(last_count),
+                "get_block_hash" if record.result.is_some() => {
+                    max_height = max_height.max(record.params.as_i64());
+                }
+                _ => {}
+            }
+            // Later successful answers win over earlier errors for the same call
+            let key = call_key(&record.method, &record.params);
+            if record.result.is_some() || !fixtures.calls.contains_key(&key) {
+                fixtures.calls.insert(key, record);
+            }
+        }
+        // Report the highest recorded block as the tip so replays end where the recording did
+        fixtures.block_count = max_height.or(last_count);
+        fixtures
+    }
+
+    pub fn len(&self) -> usize {
+        self.calls.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.calls.is_empty()
+    }
+
+    pub fn events(&self) -> &[WebSocketEvent] {
+        &self.events
+    }
+
+    /// Answers a JSON-RPC request body, single call or batch.
+    pub fn respond(&self, request: &Value) -> Value {
+        match request {
+            Value::Array(calls) => Value::Array(calls.iter().map(|c| self.respond_one(c)).collect()),
+            call => self.respond_one(call),
+        }
+    }
+
+    fn respond_one(&self, call: &Value) -> Value {
+        let id = call.get("id").cloned().unwrap_or(Value::Null);
+        let method = call.get("method").and_then(Value::as_str).unwrap_or_default();
+        let params = call.get("params").cloned().unwrap_or(Value::Null);
+        let recorded = self.calls.get(&call_key(method, &params));
+        let (result, error) = match (method, recorded) {
+            ("get_block_count", _) if self.block_count.is_some() => (Some(json!(self.block_count)), None),
+            (_, Some(record)) => (record.result.clone(), record.error.clone()),
+            ("is_node_ready", None) => (Some(json!(true)), None),
+            (_, None) => (None, Some(json!({ "code": -32602, "message": format!("no fixture for {} {}", method, params) }))),
+        };
+        match error {
+            Some(error) => json!({ "jsonrpc": "2.0", "id": id, "error": error }),
+            None => json!({ "jsonrpc": "2.0", "id": id, "result": result.unwrap_or(Value::Null) }),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct MockNode {
+    fixtures: Fixtures,
+    event_interval: Duration,
+}
+
+/// Mock Arch node: JSON-RPC over `POST /` and WebSocket event replay on `GET /`. Every
+/// WebSocket connection receives the recorded events in order, `event_interval` apart.
+pub fn mock_node_router(fixtures: Fixtures, event_interval: Duration) -> Router {
+    Router::new()
+        .route("/", get(replay_events).post(answer_rpc))
+        .with_state(Arc::new(MockNode { fixtures, event_interval }))
+}
+
+async fn answer_rpc(State(node): State<Arc<MockNode>>, Json(request): Json<Value>) -> Json<Value> {
+    Json(node.fixtures.respond(&request))
+}
+
+async fn replay_events(State(node): State<Arc<MockNode>>, ws: WebSocketUpgrade) -> Response {
+    ws.on_upgrade(move |socket| replay(socket, node))
+}
+
+async fn replay(mut socket: WebSocket, node: Arc<MockNode>) {
+    for event in node.fixtures.events() {
+        let msg = json!({ "topic": event.topic, "data": event.data, "timestamp": event.timestamp });
+        if socket.send(Message::Text(msg.to_string())).await.is_err() {
+            return;
+        }
+        tokio::time::sleep(node.event_interval).await;
+    }
+    // Keep the connection open (ignoring subscribe requests) until the client leaves
+    while let Some(Ok(msg)) = socket.recv().await {
+        if matches!(msg, Message::Close(_)) {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arch_rpc::{ArchRpcClient, WebSocketClient};
+    use crate::config::settings::WebSocketSettings;
+
+    fn record(method: &str, params: Value, result: Value) -> RpcRecord {
+        RpcRecord { method: method.to_string(), params, result: Some(result), error: None }
+    }
+
+    #[tokio::test]
+    async fn mock_node_replays_recorded_rpc_and_events() {
+        let block = json!({
+            "bitcoin_block_height": 7,
+            "block_height": 2,
+            "previous_block_hash": [1, 2],
+            "timestamp": 1700000000,
+            "transactions": []
+        });
+        let fixtures = Fixtures::from_records(
+            vec![
+                record("get_block_count", json!([]), json!(1)),
+                record("get_block_hash", json!(2), json!("bb")),
+                record("get_block", json!(["bb"]), block),
+            ],
+            vec![WebSocketEvent { topic: "block".into(), data: json!({ "hash": "bb" }), timestamp: chrono::Utc::now() }],
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, mock_node_router(fixtures, Duration::ZERO)).await.unwrap();
+        });
+
+        let rpc = ArchRpcClient::new(format!("http://{}", addr));
+        assert_eq!(rpc.get_block_count().await.unwrap(), 2);
+        let hash = rpc.get_block_hash(2).await.unwrap();
+        let block = rpc.get_block(&hash, 2).await.unwrap();
+        assert_eq!((block.height, block.previous_block_hash.as_deref()), (2, Some("0102")));
+        let batch = rpc.get_blocks_by_heights(&[2, 3]).await.unwrap();
+        assert!(batch[0].is_ok() && batch[1].is_err());
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let ws = WebSocketClient::new(WebSocketSettings::default(), format!("ws://{}", addr));
+        let listener = tokio::spawn(async move { ws.connect_and_listen(tx).await });
+        let event = tokio::time::timeout(Duration::from_secs(5), rx.recv()).await.unwrap().unwrap();
+        assert_eq!(event.topic, "block");
+        listener.abort();
+    }
+}
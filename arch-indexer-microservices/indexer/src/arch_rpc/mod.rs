@@ -1,18 +1,56 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::sync::Arc;
 use std::time::Duration;
-use tracing::{error, info};
-use tokio::time::sleep;
+use tracing::info;
 
+pub mod fixtures;
 pub mod websocket;
+pub use node_rpc::{limiter, pool, recorder, retry, tls};
+pub use limiter::AdaptiveLimiter;
+pub use pool::EndpointPool;
+pub use retry::{CircuitBreaker, RetryPolicy, RpcError};
 pub use websocket::{WebSocketClient, WebSocketEvent};
 
+use crate::config::settings::ArchNodeSettings;
+use limiter::{LimiterConfig, Outcome};
+
+/// Maximum number of calls sent in a single JSON-RPC batch request.
+const MAX_BATCH_SIZE: usize = 100;
+
 #[derive(Debug, Clone)]
 pub struct ArchRpcClient {
 	client: Client,
-	url: String,
+	pool: Arc<EndpointPool>,
+	limiter: Arc<AdaptiveLimiter>,
+	retry: RetryPolicy,
+	breaker: Arc<CircuitBreaker>,
+}
+
+/// A JSON-RPC reply whose body has already been read, so the limiter can see RPC errors.
+#[derive(Debug)]
+struct RpcReply {
+	status: reqwest::StatusCode,
+	body: serde_json::Result<serde_json::Value>,
+}
+
+impl RpcReply {
+	fn status(&self) -> reqwest::StatusCode {
+		self.status
+	}
+
+	fn json(self) -> serde_json::Result<serde_json::Value> {
+		self.body
+	}
+
+	fn is_rpc_error(&self) -> bool {
+		match &self.body {
+			Ok(body) => body.get("error").map(|e| !e.is_null()).unwrap_or(false),
+			Err(_) => false,
+		}
+	}
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -46,200 +84,371 @@ pub struct ProcessedTransaction {
 	pub logs: Vec<String>,
 }
 
+/// An account as `read_account_info` returns it. `owner` is a pubkey in whatever encoding
+/// the node serializes it with (byte array or string).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AccountInfo {
+	#[serde(default)]
+	pub lamports: u64,
+	pub owner: serde_json::Value,
+	#[serde(default)]
+	pub data: Vec<u8>,
+	#[serde(default)]
+	pub utxo: String,
+	#[serde(default)]
+	pub is_executable: bool,
+}
+
+fn block_from_response(hash: &str, block_response: BlockResponse) -> Block {
+	// Convert transactions to hex strings when provided as byte arrays
+	let transaction_strings: Vec<String> = block_response
+		.transactions
+		.iter()
+		.map(|tx| {
+			if let Some(s) = tx.as_str() {
+				s.to_string()
+			} else if let Some(arr) = tx.as_array() {
+				let bytes: Vec<u8> = arr
+					.iter()
+					.filter_map(|v| v.as_i64().map(|n| if n < 0 { (n + 256) as u8 } else { n as u8 }))
+					.collect();
+				hex::encode(bytes)
+			} else {
+				tx.to_string()
+			}
+		})
+		.collect();
+	Block {
+		height: block_response.block_height,
+		hash: hash.to_string(),
+		timestamp: block_response.timestamp,
+		bitcoin_block_height: block_response.bitcoin_block_height,
+		transactions: transaction_strings,
+		transaction_count: block_response.transactions.len() as i64,
+		previous_block_hash: Some(hex::encode(&block_response.previous_block_hash)),
+	}
+}
+
+/// Matches a JSON-RPC batch response to the `len` requests it answers (ids `0..len`).
+/// Responses may arrive in any order; requests without a response yield an error.
+fn match_batch_response(len: usize, response: serde_json::Value) -> Result<Vec<Result<serde_json::Value>>> {
+	let entries = match response {
+		serde_json::Value::Array(entries) => entries,
+		other => {
+			// Nodes without batch support answer with a single error object
+			if let Some(error) = other.get("error") {
+				return Err(anyhow::anyhow!("RPC batch error: {:?}", error));
+			}
+			return Err(anyhow::anyhow!("Unexpected batch response: {}", other));
+		}
+	};
+	let mut slots: Vec<Option<Result<serde_json::Value>>> = (0..len).map(|_| None).collect();
+	for mut entry in entries {
+		let Some(id) = entry.get("id").and_then(|v| v.as_u64()).map(|n| n as usize) else { continue };
+		if id >= len { continue; }
+		slots[id] = Some(match entry.get("error") {
+			Some(error) if !error.is_null() => Err(anyhow::anyhow!("RPC error: {:?}", error)),
+			_ => Ok(entry.get_mut("result").map(serde_json::Value::take).unwrap_or(serde_json::Value::Null)),
+		});
+	}
+	Ok(slots
+		.into_iter()
+		.enumerate()
+		.map(|(id, slot)| slot.unwrap_or_else(|| Err(anyhow::anyhow!("No response for batch id {}", id))))
+		.collect())
+}
+
 impl ArchRpcClient {
 	pub fn new(url: String) -> Self {
+		// Default settings name no certificate files, so building the client cannot fail
+		Self::with_pool(EndpointPool::single(url), &ArchNodeSettings::default())
+			.expect("Arch RPC client with default settings")
+	}
+
+	/// Client over every configured node endpoint, with background health checks when
+	/// more than one endpoint is configured. Fails when the `tls` settings are invalid
+	/// rather than connecting without the configured CA or client identity.
+	pub fn from_settings(settings: &ArchNodeSettings) -> Result<Self> {
+		let client = Self::with_pool(EndpointPool::new(settings.endpoints(), settings.max_lag_blocks), settings)?;
+		client.pool.spawn_health_checks(
+			client.client.clone(),
+			Duration::from_secs(settings.health_check_interval_seconds.max(1)),
+		);
+		Ok(client)
+	}
+
+	fn with_pool(pool: EndpointPool, settings: &ArchNodeSettings) -> Result<Self> {
+		let tls_config = tls::client_config(&settings.tls).context("invalid arch_node.tls settings")?;
 		let client = Client::builder()
-			.danger_accept_invalid_certs(true)
 			.timeout(Duration::from_secs(30))
 			.pool_max_idle_per_host(100)
 			.pool_idle_timeout(Duration::from_secs(90))
 			.tcp_keepalive(Some(Duration::from_secs(60)))
+			.use_preconfigured_tls(tls_config)
 			.build()
-			.unwrap_or_else(|_| Client::new());
+			.context("cannot build the Arch RPC HTTP client")?;
 
-		info!("Initialized Arch RPC client for: {}", url);
-		Self { client, url }
+		let limiter = LimiterConfig::bounded(
+			settings.min_concurrency,
+			settings.max_concurrency,
+			settings.initial_concurrency,
+			settings.latency_target_ms,
+		);
+		let retry = RetryPolicy::from_settings(&settings.retry);
+		info!(
+			"Initialized Arch RPC client for: {} (concurrency {} in {}..={}, {} attempts per call)",
+			pool.urls().join(", "),
+			limiter.initial,
+			limiter.min,
+			limiter.max,
+			retry.max_attempts
+		);
+		Ok(Self {
+			client,
+			pool: Arc::new(pool),
+			limiter: Arc::new(AdaptiveLimiter::new(limiter)),
+			retry,
+			breaker: Arc::new(CircuitBreaker::from_settings(&settings.retry)),
+		})
 	}
 
-	pub async fn get_block_count(&self) -> Result<i64> {
-		let response = self.client
-			.post(&self.url)
-			.json(&json!({
-				"jsonrpc": "2.0",
-				"method": "get_block_count",
-				"params": [],
-				"id": 1
-			}))
-			.send()
-			.await?;
+	pub fn pool(&self) -> &Arc<EndpointPool> {
+		&self.pool
+	}
 
-		if !response.status().is_success() {
-			return Err(anyhow::anyhow!("HTTP error: {}", response.status()));
-		}
+	pub fn limiter(&self) -> &Arc<AdaptiveLimiter> {
+		&self.limiter
+	}
 
-		let json_response = response.json::<serde_json::Value>().await?;
-		if let Some(error) = json_response.get("error") {
-			return Err(anyhow::anyhow!("RPC error: {:?}", error));
-		}
-		Ok(json_response["result"].as_i64().unwrap_or(0))
+	pub fn breaker(&self) -> &Arc<CircuitBreaker> {
+		&self.breaker
 	}
 
-	pub async fn get_block_hash(&self, height: i64) -> Result<String> {
-		let mut attempts = 0;
-		let max_attempts = 5;
-		let base_delay = Duration::from_millis(100);
-		while attempts < max_attempts {
-			match self.client
-				.post(&self.url)
-				.json(&json!({
-					"jsonrpc": "2.0",
-					"method": "get_block_hash",
-					"params": height,
-					"id": 1
-				}))
-				.send()
-				.await
-			{
-				Ok(response) => {
-					if !response.status().is_success() {
-						attempts += 1;
-						if attempts < max_attempts { sleep(base_delay * attempts as u32).await; continue; }
-						return Err(anyhow::anyhow!("HTTP error after {} attempts", max_attempts));
-					}
-					let json_response = response.json::<serde_json::Value>().await?;
-					if let Some(error) = json_response.get("error") {
-						attempts += 1;
-						if attempts < max_attempts { sleep(base_delay * attempts as u32).await; continue; }
-						return Err(anyhow::anyhow!("RPC error for height {}: {:?}", height, error));
-					}
-					if let Some(hash) = json_response.get("result").and_then(|r| r.as_str()) {
-						return Ok(hash.to_string());
-					}
-					attempts += 1;
-					if attempts < max_attempts { sleep(base_delay * attempts as u32).await; continue; }
-					return Err(anyhow::anyhow!("Invalid result type for height {}", height));
-				},
-				Err(e) => {
-					attempts += 1;
-					if attempts < max_attempts { sleep(base_delay * attempts as u32).await; continue; }
-					return Err(anyhow::anyhow!("Request error for height {}: {}", height, e));
+	/// Posts a JSON-RPC body to the next endpoint from the pool under an adaptive
+	/// concurrency permit, and records the outcome with both: failing nodes get ejected,
+	/// and timeouts, HTTP 429/5xx or RPC errors shrink the in-flight limit.
+	async fn send<B: Serialize + ?Sized>(&self, body: &B) -> reqwest::Result<RpcReply> {
+		let permit = self.limiter.acquire().await;
+		let endpoint = self.pool.pick();
+		let result = match self.client.post(&endpoint.url).json(body).send().await {
+			Ok(response) => {
+				let status = response.status();
+				response.bytes().await.map(|bytes| RpcReply { status, body: serde_json::from_slice(&bytes) })
+			}
+			Err(e) => Err(e),
+		};
+		let overloaded = match &result {
+			Ok(reply) => {
+				let status = reply.status();
+				if status.is_server_error() {
+					self.pool.record_failure(endpoint);
+				} else {
+					self.pool.record_success(endpoint);
 				}
+				status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS || reply.is_rpc_error()
+			}
+			Err(_) => {
+				self.pool.record_failure(endpoint);
+				true
+			}
+		};
+		self.limiter.release(permit, if overloaded { Outcome::Overloaded } else { Outcome::Success });
+		if let (Some(recorder), Ok(RpcReply { body: Ok(response), .. })) = (recorder::global(), &result) {
+			if let Ok(request) = serde_json::to_value(body) {
+				recorder.record_rpc(&request, response);
 			}
 		}
-		Err(anyhow::anyhow!("Failed to get block hash after {} attempts", max_attempts))
+		result
+	}
+
+	/// One JSON-RPC call under the retry policy and circuit breaker. `decode` runs inside
+	/// the retry loop, so a malformed `result` is retried like a transport error.
+	async fn call_as<T>(
+		&self,
+		method: &str,
+		params: serde_json::Value,
+		decode: impl Fn(serde_json::Value) -> Result<T, RpcError>,
+	) -> Result<T, RpcError> {
+		let body = json!({
+			"jsonrpc": "2.0",
+			"method": method,
+			"params": params,
+			"id": 1
+		});
+		let (body, decode) = (&body, &decode);
+		retry::with_retry(&self.retry, &self.breaker, method, move || async move {
+			let reply = self.send(body).await?;
+			if !reply.status().is_success() {
+				return Err(RpcError::Http(reply.status()));
+			}
+			let mut response = reply.json().map_err(|e| RpcError::Decode(e.to_string()))?;
+			if let Some(error) = response.get("error").filter(|e| !e.is_null()) {
+				return Err(RpcError::from_rpc_error(error));
+			}
+			decode(response.get_mut("result").map(serde_json::Value::take).unwrap_or(serde_json::Value::Null))
+		})
+		.await
+	}
+
+	/// Raw JSON-RPC call returning the `result` member.
+	pub async fn call(&self, method: &str, params: serde_json::Value) -> Result<serde_json::Value> {
+		Ok(self.call_as(method, params, Ok).await?)
+	}
+
+	pub async fn get_block_count(&self) -> Result<i64> {
+		self.call_as("get_block_count", json!([]), |v| {
+			v.as_i64().ok_or_else(|| RpcError::Decode(format!("block count {}", v)))
+		})
+		.await
+		.map_err(|e| anyhow::anyhow!("get_block_count failed: {}", e))
+	}
+
+	pub async fn get_block_hash(&self, height: i64) -> Result<String> {
+		self.call_as("get_block_hash", json!(height), |v| {
+			v.as_str().map(|s| s.to_string()).ok_or_else(|| RpcError::Decode(format!("block hash {}", v)))
+		})
+		.await
+		.map_err(|e| anyhow::anyhow!("get_block_hash failed for height {}: {}", height, e))
 	}
 
 	pub async fn get_block(&self, hash: &str, _height: i64) -> Result<Block> {
-		let mut attempts = 0;
-		let max_attempts = 5;
-		let base_delay = Duration::from_millis(200);
-		while attempts < max_attempts {
-			match self.client
-				.post(&self.url)
-				.json(&json!({
+		self.call_as("get_block", json!([hash]), |v| {
+			serde_json::from_value::<BlockResponse>(v)
+				.map(|b| block_from_response(hash, b))
+				.map_err(|e| RpcError::Decode(e.to_string()))
+		})
+		.await
+		.map_err(|e| anyhow::anyhow!("get_block failed for {}: {}", hash, e))
+	}
+
+	/// Current state of the account with the given 32-byte pubkey.
+	pub async fn read_account_info(&self, pubkey: &[u8]) -> Result<AccountInfo> {
+		self.call_as("read_account_info", json!(pubkey), |v| {
+			serde_json::from_value::<AccountInfo>(v).map_err(|e| RpcError::Decode(e.to_string()))
+		})
+		.await
+		.map_err(|e| anyhow::anyhow!("read_account_info failed for {}: {}", hex::encode(pubkey), e))
+	}
+
+	pub async fn get_processed_transaction(&self, txid: &str) -> Result<ProcessedTransaction> {
+		self.call_as("get_processed_transaction", json!(txid), |v| {
+			serde_json::from_value::<ProcessedTransaction>(v).map_err(|e| RpcError::Decode(e.to_string()))
+		})
+		.await
+		.map_err(|e| anyhow::anyhow!("get_processed_transaction failed for {}: {}", txid, e))
+	}
+
+	/// Sends `calls` as JSON-RPC batch requests (at most `MAX_BATCH_SIZE` per POST) and returns
+	/// one result per call in input order. Transport failures fail the whole call; per-item RPC
+	/// errors are returned in place.
+	pub async fn call_batch(&self, calls: &[(&str, serde_json::Value)]) -> Result<Vec<Result<serde_json::Value>>> {
+		let mut results = Vec::with_capacity(calls.len());
+		for chunk in calls.chunks(MAX_BATCH_SIZE) {
+			let body: Vec<serde_json::Value> = chunk
+				.iter()
+				.enumerate()
+				.map(|(id, (method, params))| json!({
 					"jsonrpc": "2.0",
-					"method": "get_block",
-					"params": [hash],
-					"id": 1
+					"method": method,
+					"params": params,
+					"id": id
 				}))
-				.send()
-				.await
-			{
-				Ok(response) => {
-					if !response.status().is_success() {
-						attempts += 1; if attempts < max_attempts { sleep(base_delay * attempts as u32).await; continue; }
-						return Err(anyhow::anyhow!("HTTP error for block {}: {}", hash, response.status()));
-					}
-					let json_response = response.json::<serde_json::Value>().await?;
-					if let Some(error) = json_response.get("error") {
-						attempts += 1; if attempts < max_attempts { sleep(base_delay * attempts as u32).await; continue; }
-						return Err(anyhow::anyhow!("RPC error for block {}: {:?}", hash, error));
-					}
-					match serde_json::from_value::<BlockResponse>(json_response["result"].clone()) {
-						Ok(block_response) => {
-							// Convert transactions to hex strings when provided as byte arrays
-							let transaction_strings: Vec<String> = block_response
-								.transactions
-								.iter()
-								.map(|tx| {
-									if let Some(s) = tx.as_str() {
-										s.to_string()
-									} else if let Some(arr) = tx.as_array() {
-										let bytes: Vec<u8> = arr
-											.iter()
-											.filter_map(|v| v.as_i64().map(|n| if n < 0 { (n + 256) as u8 } else { n as u8 }))
-											.collect();
-										hex::encode(bytes)
-									} else {
-										tx.to_string()
-									}
-								})
-								.collect();
-							return Ok(Block {
-								height: block_response.block_height,
-								hash: hash.to_string(),
-								timestamp: block_response.timestamp,
-								bitcoin_block_height: block_response.bitcoin_block_height,
-								transactions: transaction_strings,
-								transaction_count: block_response.transactions.len() as i64,
-								previous_block_hash: Some(hex::encode(&block_response.previous_block_hash)),
-							});
-						},
-						Err(e) => {
-							attempts += 1; if attempts < max_attempts { sleep(base_delay * attempts as u32).await; continue; }
-							return Err(anyhow::anyhow!("Block deserialization error for {}: {}", hash, e));
-						}
-					}
-				},
-				Err(e) => {
-					attempts += 1; if attempts < max_attempts { sleep(base_delay * attempts as u32).await; continue; }
-					return Err(anyhow::anyhow!("Request error for block {}: {}", hash, e));
+				.collect();
+			let body = &body;
+			let response = retry::with_retry(&self.retry, &self.breaker, "batch", move || async move {
+				let reply = self.send(body).await?;
+				if !reply.status().is_success() {
+					return Err(RpcError::Http(reply.status()));
 				}
-			}
+				reply.json().map_err(|e| RpcError::Decode(e.to_string()))
+			})
+			.await
+			.map_err(|e| anyhow::anyhow!("Batch of {} calls failed: {}", chunk.len(), e))?;
+			results.extend(match_batch_response(chunk.len(), response)?);
 		}
-		Err(anyhow::anyhow!("Failed to get block after {} attempts", max_attempts))
+		Ok(results)
 	}
 
-	pub async fn get_processed_transaction(&self, txid: &str) -> Result<ProcessedTransaction> {
-		let mut attempts = 0;
-		let max_attempts = 3;
-		let base_delay = Duration::from_millis(100);
-		while attempts < max_attempts {
-			match self.client
-				.post(&self.url)
-				.json(&json!({
-					"jsonrpc": "2.0",
-					"method": "get_processed_transaction",
-					"params": txid,
-					"id": 1
-				}))
-				.send()
-				.await
-			{
-				Ok(response) => {
-					if !response.status().is_success() {
-						attempts += 1; if attempts < max_attempts { sleep(base_delay * attempts as u32).await; continue; }
-						return Err(anyhow::anyhow!("HTTP error for tx {}: {}", txid, response.status()));
-					}
-					let json_response = response.json::<serde_json::Value>().await?;
-					if let Some(error) = json_response.get("error") {
-						attempts += 1; if attempts < max_attempts { sleep(base_delay * attempts as u32).await; continue; }
-						return Err(anyhow::anyhow!("RPC error for tx {}: {:?}", txid, error));
-					}
-					match serde_json::from_value::<ProcessedTransaction>(json_response["result"].clone()) {
-						Ok(ptx) => return Ok(ptx),
-						Err(e) => {
-							attempts += 1; if attempts < max_attempts { sleep(base_delay * attempts as u32).await; continue; }
-							return Err(anyhow::anyhow!("Transaction deserialization error for {}: {}", txid, e));
-						}
-					}
-				},
-				Err(e) => {
-					attempts += 1; if attempts < max_attempts { sleep(base_delay * attempts as u32).await; continue; }
-					return Err(anyhow::anyhow!("Request error for tx {}: {}", txid, e));
+	/// Batched `get_block_hash` + `get_block` for many heights; one result per height.
+	pub async fn get_blocks_by_heights(&self, heights: &[i64]) -> Result<Vec<Result<Block>>> {
+		let hash_calls: Vec<(&str, serde_json::Value)> = heights.iter().map(|h| ("get_block_hash", json!(h))).collect();
+		let hashes: Vec<Result<String>> = self
+			.call_batch(&hash_calls)
+			.await?
+			.into_iter()
+			.zip(heights)
+			.map(|(r, h)| r.and_then(|v| v.as_str().map(|s| s.to_string()).ok_or_else(|| anyhow::anyhow!("Invalid result type for height {}", h))))
+			.collect();
+
+		let block_calls: Vec<(&str, serde_json::Value)> = hashes
+			.iter()
+			.filter_map(|r| r.as_ref().ok())
+			.map(|hash| ("get_block", json!([hash])))
+			.collect();
+		let mut blocks = self.call_batch(&block_calls).await?.into_iter();
+
+		let mut results = Vec::with_capacity(heights.len());
+		for hash in hashes {
+			results.push(match hash {
+				Ok(hash) => {
+					let block = blocks.next().ok_or_else(|| anyhow::anyhow!("Batch response count mismatch"))?;
+					block.and_then(|v| {
+						serde_json::from_value::<BlockResponse>(v)
+							.map(|b| block_from_response(&hash, b))
+							.map_err(|e| anyhow::anyhow!("Block deserialization error for {}: {}", hash, e))
+					})
 				}
-			}
+				Err(e) => Err(e),
+			});
 		}
-		Err(anyhow::anyhow!("Failed to get transaction after {} attempts", max_attempts))
+		Ok(results)
+	}
+
+	/// Batched `get_processed_transaction`; one result per txid, in input order.
+	pub async fn get_processed_transactions(&self, txids: &[String]) -> Result<Vec<Result<ProcessedTransaction>>> {
+		let calls: Vec<(&str, serde_json::Value)> = txids.iter().map(|t| ("get_processed_transaction", json!(t))).collect();
+		Ok(self
+			.call_batch(&calls)
+			.await?
+			.into_iter()
+			.zip(txids)
+			.map(|(r, txid)| r.and_then(|v| {
+				serde_json::from_value::<ProcessedTransaction>(v)
+					.map_err(|e| anyhow::anyhow!("Transaction deserialization error for {}: {}", txid, e))
+			}))
+			.collect())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn batch_response_is_matched_by_id() {
+		let response = json!([
+			{ "jsonrpc": "2.0", "id": 2, "result": "c" },
+			{ "jsonrpc": "2.0", "id": 0, "result": "a" },
+			{ "jsonrpc": "2.0", "id": 1, "error": { "code": -32000, "message": "not found" } }
+		]);
+		let results = match_batch_response(4, response).unwrap();
+		assert_eq!(results.len(), 4);
+		assert_eq!(results[0].as_ref().unwrap(), "a");
+		assert!(results[1].is_err());
+		assert_eq!(results[2].as_ref().unwrap(), "c");
+		assert!(results[3].is_err(), "missing ids must surface as errors");
+	}
+
+	#[test]
+	fn non_array_batch_response_is_an_error() {
+		let response = json!({ "jsonrpc": "2.0", "id": null, "error": { "code": -32600 } });
+		assert!(match_batch_response(1, response).is_err());
+	}
+
+	#[test]
+	fn invalid_tls_settings_fail_client_construction() {
+		let mut settings = ArchNodeSettings::default();
+		settings.tls.ca_cert_path = Some("/nonexistent/ca.pem".to_string());
+		assert!(ArchRpcClient::from_settings(&settings).is_err());
 	}
 }
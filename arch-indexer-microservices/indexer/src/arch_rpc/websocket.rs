@@ -1,12 +1,14 @@
 use anyhow::Result;
 use futures_util::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 use tokio::sync::mpsc;
-use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tokio_tungstenite::tungstenite::Message;
 use tracing::{error, info, warn};
 use url::Url;
 
-use crate::config::settings::WebSocketSettings;
+use crate::arch_rpc::{recorder, tls};
+use crate::config::settings::{TlsSettings, WebSocketSettings};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WebSocketEvent {
@@ -18,21 +20,41 @@ pub struct WebSocketEvent {
 #[derive(Debug, Clone)]
 pub struct WebSocketClient {
     settings: WebSocketSettings,
-    url: String,
+    urls: Vec<String>,
+    tls: Option<Arc<rustls::ClientConfig>>,
 }
 
 impl WebSocketClient {
-    pub fn new(settings: WebSocketSettings, url: String) -> Self { Self { settings, url } }
+    pub fn new(settings: WebSocketSettings, url: String) -> Self { Self { settings, urls: vec![url], tls: None } }
+
+    /// Client that rotates through `urls` on each reconnect, failing over between nodes.
+    pub fn with_urls(settings: WebSocketSettings, urls: Vec<String>) -> Self { Self { settings, urls, tls: None } }
+
+    /// Uses the node TLS settings (CA bundle, mTLS identity) for `wss://` connections.
+    pub fn with_tls(mut self, tls: &TlsSettings) -> Result<Self> {
+        self.tls = Some(Arc::new(tls::client_config(tls)?));
+        Ok(self)
+    }
 
     pub async fn connect_and_listen(&self, tx: mpsc::UnboundedSender<WebSocketEvent>) -> Result<()> {
-        let url = Url::parse(&self.url)?;
+        let urls = self.urls.iter().map(|u| Url::parse(u)).collect::<Result<Vec<Url>, _>>()?;
+        if urls.is_empty() {
+            return Err(anyhow::anyhow!("No WebSocket URLs configured"));
+        }
+        let tls_config = match &self.tls {
+            Some(config) => Arc::clone(config),
+            None => Arc::new(tls::client_config(&TlsSettings::default())?),
+        };
         let mut attempts = 0usize;
+        let mut next_url = 0usize;
 
         loop {
-            info!("WebSocket connecting to {} (attempt {} of {})", self.url, attempts + 1, self.settings.max_reconnect_attempts);
-            match connect_async(url.clone()).await {
-                Ok((mut ws_stream, _)) => {
-                    info!("WebSocket connected to {}", self.url);
+            let url = urls[next_url % urls.len()].clone();
+            next_url += 1;
+            info!("WebSocket connecting to {} (attempt {} of {})", url, attempts + 1, self.settings.max_reconnect_attempts);
+            match tls::connect_websocket(&url, &tls_config).await {
+                Ok(mut ws_stream) => {
+                    info!("WebSocket connected to {}", url);
                     attempts = 0; // reset on success
 
                     // Try to subscribe to standard topics if server expects a subscription
@@ -40,7 +62,7 @@ impl WebSocketClient {
                     let subscribe_msg = serde_json::json!({
                         "jsonrpc": "2.0",
                         "method": "subscribe",
-                        "params": ["blocks", "transactions", "accounts", "rolledback_transactions"],
+                        "params": ["blocks", "transactions", "accounts", "rolledback_transactions", "reapplied_transactions", "dkg"],
                         "id": 1
                     })
                     .to_string();
@@ -83,7 +105,11 @@ impl WebSocketClient {
                                     }
 
                                     if let Some(t) = topic {
-                                        let _ = tx.send(WebSocketEvent { topic: t, data: data.unwrap_or(serde_json::Value::Null), timestamp: chrono::Utc::now() });
+                                        let event = WebSocketEvent { topic: t, data: data.unwrap_or(serde_json::Value::Null), timestamp: chrono::Utc::now() };
+                                        if let Some(recorder) = recorder::global() {
+                                            recorder.record_ws(&event);
+                                        }
+                                        let _ = tx.send(event);
                                     } else {
                                         // unrecognized message shape; ignore
                                     }
@@ -0,0 +1,45 @@
+use anyhow::Result;
+use clap::Parser;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tracing::info;
+use tracing_subscriber::EnvFilter;
+
+use indexer::arch_rpc::fixtures::{mock_node_router, Fixtures};
+
+/// Serves JSON-RPC and WebSocket traffic recorded with ARCH_RECORD_DIR, so the indexer
+/// and API server can run end to end without a live Arch node.
+#[derive(Debug, Parser)]
+#[command(name = "mock-arch-node")]
+struct Args {
+    /// Directory containing rpc.jsonl and ws.jsonl fixtures
+    #[arg(long)]
+    fixtures: String,
+    /// Address for both JSON-RPC (POST /) and WebSocket (GET /)
+    #[arg(long, default_value = "127.0.0.1:8081")]
+    listen: SocketAddr,
+    /// Delay between replayed WebSocket events
+    #[arg(long, default_value_t = 10)]
+    event_interval_ms: u64,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::new(std::env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string())))
+        .init();
+
+    let args = Args::parse();
+    let fixtures = Fixtures::load(&args.fixtures)?;
+    info!(
+        "🧪 Mock Arch node serving {} recorded calls and {} events from {} on {}",
+        fixtures.len(),
+        fixtures.events().len(),
+        args.fixtures,
+        args.listen
+    );
+
+    let listener = tokio::net::TcpListener::bind(args.listen).await?;
+    axum::serve(listener, mock_node_router(fixtures, Duration::from_millis(args.event_interval_ms))).await?;
+    Ok(())
+}
@@ -2,6 +2,9 @@ use config::{Config, ConfigError, Environment};
 use serde::Deserialize;
 use std::env;
 
+pub use node_rpc::settings::{ArchNodeEndpoint, RpcRetrySettings, TlsSettings};
+use node_rpc::settings::default_endpoint_weight;
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct Settings {
     pub database: DatabaseSettings,
@@ -78,6 +81,28 @@ pub struct ArchNodeSettings {
     pub url: String,
     #[serde(default = "default_websocket_url")]
     pub websocket_url: String,
+    /// Additional weighted endpoints; when empty, `url`/`websocket_url` form a single-node pool.
+    #[serde(default)]
+    pub endpoints: Vec<ArchNodeEndpoint>,
+    #[serde(default = "default_health_check_interval_seconds")]
+    pub health_check_interval_seconds: u64,
+    /// Endpoints further than this many blocks behind the best tip are ejected.
+    #[serde(default = "default_max_lag_blocks")]
+    pub max_lag_blocks: i64,
+    /// Bounds and starting point for the adaptive in-flight request limit.
+    #[serde(default = "default_min_concurrency")]
+    pub min_concurrency: usize,
+    #[serde(default = "default_max_concurrency")]
+    pub max_concurrency: usize,
+    #[serde(default = "default_initial_concurrency")]
+    pub initial_concurrency: usize,
+    /// Responses slower than this shrink the limit instead of growing it.
+    #[serde(default = "default_latency_target_ms")]
+    pub latency_target_ms: u64,
+    #[serde(default)]
+    pub retry: RpcRetrySettings,
+    #[serde(default)]
+    pub tls: TlsSettings,
 }
 
 impl Default for ArchNodeSettings {
@@ -85,10 +110,46 @@ impl Default for ArchNodeSettings {
         Self {
             url: default_arch_node_url(),
             websocket_url: default_websocket_url(),
+            endpoints: Vec::new(),
+            health_check_interval_seconds: default_health_check_interval_seconds(),
+            max_lag_blocks: default_max_lag_blocks(),
+            min_concurrency: default_min_concurrency(),
+            max_concurrency: default_max_concurrency(),
+            initial_concurrency: default_initial_concurrency(),
+            latency_target_ms: default_latency_target_ms(),
+            retry: RpcRetrySettings::default(),
+            tls: TlsSettings::default(),
         }
     }
 }
 
+impl ArchNodeSettings {
+    /// Effective endpoint list: configured `endpoints`, or the single `url`/`websocket_url`.
+    pub fn endpoints(&self) -> Vec<ArchNodeEndpoint> {
+        if !self.endpoints.is_empty() {
+            return self.endpoints.clone();
+        }
+        vec![ArchNodeEndpoint {
+            url: self.url.clone(),
+            websocket_url: Some(self.websocket_url.clone()),
+            weight: default_endpoint_weight(),
+        }]
+    }
+
+    /// WebSocket URLs in endpoint order, used to rotate on reconnect.
+    pub fn websocket_urls(&self) -> Vec<String> {
+        let urls: Vec<String> = self.endpoints().into_iter().filter_map(|e| e.websocket_url).collect();
+        if urls.is_empty() { vec![self.websocket_url.clone()] } else { urls }
+    }
+}
+
+fn default_health_check_interval_seconds() -> u64 { 5 }
+fn default_max_lag_blocks() -> i64 { 10 }
+fn default_min_concurrency() -> usize { 1 }
+fn default_max_concurrency() -> usize { 64 }
+fn default_initial_concurrency() -> usize { 8 }
+fn default_latency_target_ms() -> u64 { 2000 }
+
 fn default_websocket_url() -> String {
     "ws://localhost:8081".to_string()
 }
@@ -178,6 +239,12 @@ impl Settings {
             .set_default("application.cors_allow_headers", default_cors_headers())?
             .set_default("arch_node.url", default_arch_node_url())?
             .set_default("arch_node.websocket_url", default_websocket_url())?
+            .set_default("arch_node.health_check_interval_seconds", default_health_check_interval_seconds() as i64)?
+            .set_default("arch_node.max_lag_blocks", default_max_lag_blocks())?
+            .set_default("arch_node.min_concurrency", default_min_concurrency() as i64)?
+            .set_default("arch_node.max_concurrency", default_max_concurrency() as i64)?
+            .set_default("arch_node.initial_concurrency", default_initial_concurrency() as i64)?
+            .set_default("arch_node.latency_target_ms", default_latency_target_ms() as i64)?
             .set_default("redis.url", default_redis_url())?
             .set_default("indexer.batch_size", default_batch_size() as i64)?
             .set_default("indexer.concurrent_batches", default_concurrent_batches() as i64)?
@@ -243,6 +310,48 @@ impl Settings {
             config = config.set_override("arch_node.websocket_url", arch_node_websocket_url)?;
         }
         
+        // Adaptive RPC concurrency; ARCH_MAX_CONCURRENCY is the pre-existing fixed cap
+        if let Ok(max) = env::var("ARCH_RPC_MAX_CONCURRENCY").or_else(|_| env::var("ARCH_MAX_CONCURRENCY")) {
+            if let Ok(max) = max.parse::<i64>() {
+                config = config.set_override("arch_node.max_concurrency", max)?;
+            }
+        }
+        if let Ok(min) = env::var("ARCH_RPC_MIN_CONCURRENCY") {
+            if let Ok(min) = min.parse::<i64>() {
+                config = config.set_override("arch_node.min_concurrency", min)?;
+            }
+        }
+        if let Ok(target) = env::var("ARCH_RPC_LATENCY_TARGET_MS") {
+            if let Ok(target) = target.parse::<i64>() {
+                config = config.set_override("arch_node.latency_target_ms", target)?;
+            }
+        }
+
+        for (var, key) in [
+            ("ARCH_RPC_MAX_ATTEMPTS", "arch_node.retry.max_attempts"),
+            ("ARCH_RPC_BASE_DELAY_MS", "arch_node.retry.base_delay_ms"),
+            ("ARCH_RPC_MAX_DELAY_MS", "arch_node.retry.max_delay_ms"),
+            ("ARCH_RPC_BREAKER_THRESHOLD", "arch_node.retry.breaker_failure_threshold"),
+            ("ARCH_RPC_BREAKER_OPEN_SECONDS", "arch_node.retry.breaker_open_seconds"),
+        ] {
+            if let Some(value) = env::var(var).ok().and_then(|v| v.parse::<i64>().ok()) {
+                config = config.set_override(key, value)?;
+            }
+        }
+
+        for (var, key) in [
+            ("ARCH_NODE_TLS_CA_CERT", "arch_node.tls.ca_cert_path"),
+            ("ARCH_NODE_TLS_CLIENT_CERT", "arch_node.tls.client_cert_path"),
+            ("ARCH_NODE_TLS_CLIENT_KEY", "arch_node.tls.client_key_path"),
+        ] {
+            if let Ok(path) = env::var(var) {
+                config = config.set_override(key, path)?;
+            }
+        }
+        if let Ok(insecure) = env::var("ARCH_NODE_TLS_INSECURE_SKIP_VERIFY") {
+            config = config.set_override("arch_node.tls.insecure_skip_verify", insecure == "true" || insecure == "1")?;
+        }
+
         if let Ok(enable_realtime) = env::var("ENABLE_REALTIME") {
             config = config.set_override("indexer.enable_realtime", enable_realtime)?;
         }
@@ -252,6 +361,31 @@ impl Settings {
         }
         
         let final_config = config.build()?;
-        final_config.try_deserialize()
+        let mut settings: Settings = final_config.try_deserialize()?;
+
+        // Multi-node pool: ARCH_NODE_URLS (comma-separated), with optional aligned
+        // ARCH_NODE_WEBSOCKET_URLS and ARCH_NODE_WEIGHTS lists
+        if let Ok(urls) = env::var("ARCH_NODE_URLS") {
+            let ws_urls = env::var("ARCH_NODE_WEBSOCKET_URLS").unwrap_or_default();
+            let weights = env::var("ARCH_NODE_WEIGHTS").unwrap_or_default();
+            settings.arch_node.endpoints = parse_endpoint_list(&urls, &ws_urls, &weights);
+        }
+        Ok(settings)
     }
 }
+
+/// Builds endpoints from comma-separated URL, WebSocket URL and weight lists aligned by position.
+pub fn parse_endpoint_list(urls: &str, ws_urls: &str, weights: &str) -> Vec<ArchNodeEndpoint> {
+    let ws: Vec<&str> = ws_urls.split(',').map(|s| s.trim()).collect();
+    let weights: Vec<&str> = weights.split(',').map(|s| s.trim()).collect();
+    urls.split(',')
+        .map(|s| s.trim())
+        .enumerate()
+        .filter(|(_, url)| !url.is_empty())
+        .map(|(i, url)| ArchNodeEndpoint {
+            url: url.to_string(),
+            websocket_url: ws.get(i).filter(|s| !s.is_empty()).map(|s| s.to_string()),
+            weight: weights.get(i).and_then(|w| w.parse::<u32>().ok()).unwrap_or_else(default_endpoint_weight),
+        })
+        .collect()
+}
@@ -0,0 +1,113 @@
+use anyhow::Result;
+use sqlx::PgPool;
+use std::collections::BTreeMap;
+
+use crate::indexer::metadata;
+
+/// One observed state of an account. `pubkey` is lowercase hex.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountState {
+    pub pubkey: String,
+    pub lamports: i64,
+    pub owner: String,
+    pub data: Vec<u8>,
+    pub height: i64,
+}
+
+/// 32-byte pubkey of an address given as hex or base58.
+pub fn address_bytes(address: &str) -> Option<Vec<u8>> {
+    let bytes = if address.len() == 64 && address.chars().all(|c| c.is_ascii_hexdigit()) {
+        hex::decode(address).ok()?
+    } else {
+        bs58::decode(address).into_vec().ok()?
+    };
+    (bytes.len() == 32).then_some(bytes)
+}
+
+/// Upserts the latest state of each account into `accounts`, keeps every state in
+/// `account_history` (the last state of an account within a height wins) and stores token
+/// metadata found in account data. Both the legacy and the Atlas runtime write accounts
+/// through here.
+pub async fn store_account_states(pool: &PgPool, states: &[AccountState]) -> Result<()> {
+    if states.is_empty() {
+        return Ok(());
+    }
+
+    let mut latest: BTreeMap<&str, &AccountState> = BTreeMap::new();
+    let mut history: BTreeMap<(&str, i64), &AccountState> = BTreeMap::new();
+    for state in states {
+        latest.insert(&state.pubkey, state);
+        history.insert((&state.pubkey, state.height), state);
+    }
+
+    let (mut pubkeys, mut lamports, mut owners, mut datas, mut heights) = (Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new());
+    for state in latest.values() {
+        pubkeys.push(state.pubkey.as_str());
+        lamports.push(state.lamports);
+        owners.push(state.owner.as_str());
+        datas.push(state.data.as_slice());
+        heights.push(state.height);
+    }
+    sqlx::query(
+        r#"
+        INSERT INTO accounts (pubkey, lamports, owner, data, height)
+        SELECT * FROM UNNEST($1::text[], $2::bigint[], $3::text[], $4::bytea[], $5::bigint[])
+        ON CONFLICT (pubkey) DO UPDATE
+        SET lamports = EXCLUDED.lamports, owner = EXCLUDED.owner, data = EXCLUDED.data, height = EXCLUDED.height,
+            updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(&pubkeys)
+    .bind(&lamports)
+    .bind(&owners)
+    .bind(&datas)
+    .bind(&heights)
+    .execute(pool)
+    .await?;
+
+    let (mut pubkeys, mut lamports, mut owners, mut datas, mut heights) = (Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new());
+    for state in history.values() {
+        pubkeys.push(state.pubkey.as_str());
+        lamports.push(state.lamports);
+        owners.push(state.owner.as_str());
+        datas.push(state.data.as_slice());
+        heights.push(state.height);
+    }
+    sqlx::query(
+        r#"
+        INSERT INTO account_history (pubkey, height, lamports, owner, data, is_deleted)
+        SELECT pubkey, height, lamports, owner, data, FALSE
+        FROM UNNEST($1::text[], $2::bigint[], $3::bigint[], $4::text[], $5::bytea[]) AS s(pubkey, height, lamports, owner, data)
+        ON CONFLICT (pubkey, height) DO UPDATE
+        SET lamports = EXCLUDED.lamports, owner = EXCLUDED.owner, data = EXCLUDED.data, is_deleted = FALSE,
+            recorded_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(&pubkeys)
+    .bind(&heights)
+    .bind(&lamports)
+    .bind(&owners)
+    .bind(&datas)
+    .execute(pool)
+    .await?;
+
+    for state in latest.values() {
+        if let Some(token_metadata) = metadata::unpack_account(&state.data) {
+            metadata::store_metadata_account(pool, &state.pubkey, &token_metadata, state.height).await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn addresses_are_accepted_as_hex_or_base58() {
+        let key = [0xabu8; 32];
+        assert_eq!(address_bytes(&hex::encode(key)), Some(key.to_vec()));
+        assert_eq!(address_bytes(&bs58::encode(key).into_string()), Some(key.to_vec()));
+        assert_eq!(address_bytes("abcd"), None);
+    }
+}
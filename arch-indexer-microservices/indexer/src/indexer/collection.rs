@@ -0,0 +1,297 @@
+//! Typed model of the instructions the indexer derives state from. The legacy path parses
+//! stored runtime transactions into it, and the Atlas pipeline uses [`ArchInstruction`] as
+//! its instruction decoder collection, so both read instructions the same way.
+
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+use decoder::compute_budget::ComputeBudgetDecoder;
+use decoder::system::SystemDecoder;
+use decoder::{
+    decode_transaction, Decoded, InstructionDecoder, APL_ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_BASE58, APL_TOKEN_PROGRAM_BASE58,
+    COMPUTE_BUDGET_BASE58, SYSTEM_PROGRAM_ALT_BASE58, SYSTEM_PROGRAM_BASE58,
+};
+use crate::indexer::programs::canonical_program_id;
+use crate::indexer::tokens::{token_effect, TokenEffect};
+
+/// Arch System Program instruction (u32 LE tag). Addresses are lowercase hex.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub enum SystemInstruction {
+    /// accounts: [funder, new account]
+    CreateAccount { funder: String, account: String, lamports: u64, space: u64, owner: String },
+    WriteBytes { account: String, offset: u64, len: u64 },
+    MakeExecutable { account: String },
+    AssignOwnership { account: String, owner: String },
+    /// accounts: [source, destination]
+    Transfer { source: String, destination: String, lamports: u64 },
+    Allocate { account: String, space: u64 },
+}
+
+impl SystemInstruction {
+    /// Reads the instruction through the decoder crate's [`SystemDecoder`], so the indexer
+    /// accepts exactly the data the explorer decodes.
+    pub fn unpack(data: &[u8], accounts: &[String]) -> Option<Self> {
+        let Decoded { instruction_type, args } = SystemDecoder.decode(data, accounts)?;
+        let account = |key: &str| arg_str(&args, key);
+        let instruction = match instruction_type.as_str() {
+            "System Program: CreateAccount" => SystemInstruction::CreateAccount {
+                funder: account("funder")?,
+                account: account("new_account")?,
+                lamports: arg_u64(&args, "lamports")?,
+                space: arg_u64(&args, "space")?,
+                owner: base58_to_hex(&account("owner")?)?,
+            },
+            "System Program: WriteBytes" => {
+                SystemInstruction::WriteBytes { account: account("account")?, offset: arg_u64(&args, "offset")?, len: arg_u64(&args, "len")? }
+            }
+            "System Program: MakeExecutable" => SystemInstruction::MakeExecutable { account: account("account")? },
+            "System Program: AssignOwnership" => {
+                SystemInstruction::AssignOwnership { account: account("account")?, owner: base58_to_hex(&account("owner")?)? }
+            }
+            "System Program: Transfer" => SystemInstruction::Transfer {
+                source: account("source")?,
+                destination: account("destination")?,
+                lamports: arg_u64(&args, "lamports")?,
+            },
+            "System Program: Allocate" => SystemInstruction::Allocate { account: account("account")?, space: arg_u64(&args, "space")? },
+            _ => return None,
+        };
+        Some(instruction)
+    }
+}
+
+/// Compute Budget program instruction (u8 tag).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub enum ComputeBudgetInstruction {
+    RequestHeapFrame { bytes: u32 },
+    SetComputeUnitLimit { units: u32 },
+    SetComputeUnitPrice { micro_lamports: u64 },
+}
+
+impl ComputeBudgetInstruction {
+    /// Reads the instruction through the decoder crate's [`ComputeBudgetDecoder`].
+    pub fn unpack(data: &[u8]) -> Option<Self> {
+        let Decoded { instruction_type, args } = ComputeBudgetDecoder.decode(data, &[])?;
+        let instruction = match instruction_type.as_str() {
+            "Compute Budget: RequestHeapFrame" => ComputeBudgetInstruction::RequestHeapFrame { bytes: arg_u64(&args, "bytes")?.try_into().ok()? },
+            "Compute Budget: SetComputeUnitLimit" => {
+                ComputeBudgetInstruction::SetComputeUnitLimit { units: arg_u64(&args, "units")?.try_into().ok()? }
+            }
+            "Compute Budget: SetComputeUnitPrice" => {
+                ComputeBudgetInstruction::SetComputeUnitPrice { micro_lamports: arg_u64(&args, "price_micro_lamports")? }
+            }
+            _ => return None,
+        };
+        Some(instruction)
+    }
+}
+
+/// A `{"type": .., "data": n}` integer argument of a decoder's output.
+fn arg_u64(args: &JsonValue, key: &str) -> Option<u64> {
+    args.get(key)?.get("data")?.as_u64()
+}
+
+/// A string argument (an account or base58 key) of a decoder's output.
+fn arg_str(args: &JsonValue, key: &str) -> Option<String> {
+    args.get(key)?.as_str().map(str::to_string)
+}
+
+fn base58_to_hex(value: &str) -> Option<String> {
+    bs58::decode(value).into_vec().ok().map(hex::encode)
+}
+
+/// APL Associated Token Account program instruction. Its only instruction carries no data.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub enum AssociatedTokenAccountInstruction {
+    /// accounts: [funder, associated account, wallet, mint, system program, token program]
+    Create { funder: String, associated_account: String, wallet: String, mint: String },
+}
+
+impl AssociatedTokenAccountInstruction {
+    pub fn unpack(data: &[u8], accounts: &[String]) -> Option<Self> {
+        if !data.is_empty() {
+            return None;
+        }
+        let account = |i: usize| accounts.get(i).cloned();
+        Some(AssociatedTokenAccountInstruction::Create {
+            funder: account(0)?,
+            associated_account: account(1)?,
+            wallet: account(2)?,
+            mint: account(3)?,
+        })
+    }
+}
+
+/// Program an [`ArchInstruction`] belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum ArchInstructionType {
+    System,
+    ComputeBudget,
+    Token,
+    AssociatedTokenAccount,
+}
+
+/// A decoded instruction of one of the programs above.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub enum ArchInstruction {
+    System(SystemInstruction),
+    ComputeBudget(ComputeBudgetInstruction),
+    /// The state change an APL token instruction implies; `None` for approvals, multisig
+    /// setup and other instructions that change no indexed state
+    Token(Option<TokenEffect>),
+    AssociatedTokenAccount(AssociatedTokenAccountInstruction),
+}
+
+impl ArchInstruction {
+    /// Decodes instruction `data` of `program_id_base58`, given its accounts (hex, in
+    /// instruction order). Aliases such as the legacy APL token id are read as the program
+    /// they stand for. Returns `None` for other programs and for data they reject.
+    pub fn parse(program_id_base58: &str, data: &[u8], accounts: &[String]) -> Option<Self> {
+        let program_hex = hex::encode(bs58::decode(program_id_base58).into_vec().ok()?);
+        let program_id = bs58::encode(hex::decode(canonical_program_id(&program_hex)).ok()?).into_string();
+        match program_id.as_str() {
+            SYSTEM_PROGRAM_BASE58 | SYSTEM_PROGRAM_ALT_BASE58 => SystemInstruction::unpack(data, accounts).map(ArchInstruction::System),
+            COMPUTE_BUDGET_BASE58 => ComputeBudgetInstruction::unpack(data).map(ArchInstruction::ComputeBudget),
+            APL_TOKEN_PROGRAM_BASE58 => {
+                // Any data the token program accepts is a token instruction, even without an effect
+                apl_token::instruction::TokenInstruction::unpack(data).ok()?;
+                Some(ArchInstruction::Token(token_effect(data, accounts)))
+            }
+            APL_ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_BASE58 => {
+                AssociatedTokenAccountInstruction::unpack(data, accounts).map(ArchInstruction::AssociatedTokenAccount)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn instruction_type(&self) -> ArchInstructionType {
+        match self {
+            ArchInstruction::System(_) => ArchInstructionType::System,
+            ArchInstruction::ComputeBudget(_) => ArchInstructionType::ComputeBudget,
+            ArchInstruction::Token(_) => ArchInstructionType::Token,
+            ArchInstruction::AssociatedTokenAccount(_) => ArchInstructionType::AssociatedTokenAccount,
+        }
+    }
+}
+
+/// An [`ArchInstruction`] together with its position in the transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedInstruction {
+    pub index: usize,
+    pub program_id_hex: String,
+    pub instruction: ArchInstruction,
+}
+
+/// The instructions of a runtime transaction that belong to the programs above, in
+/// instruction order.
+pub fn parse_transaction(data: &JsonValue) -> Vec<ParsedInstruction> {
+    decode_transaction(data)
+        .into_iter()
+        .filter_map(|ix| {
+            let accounts: Vec<String> = ix.account_roles.iter().map(|r| r.address_hex.clone()).collect();
+            let instruction = ArchInstruction::parse(&ix.program_id_base58, &ix.data, &accounts)?;
+            Some(ParsedInstruction { index: ix.index, program_id_hex: ix.program_id_hex, instruction })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn instructions_of_each_program_are_typed() {
+        let key = |b: &str| bs58::decode(b).into_vec().unwrap();
+        let mut transfer = 4u32.to_le_bytes().to_vec();
+        transfer.extend_from_slice(&42u64.to_le_bytes());
+        let mut limit = vec![2u8];
+        limit.extend_from_slice(&200_000u32.to_le_bytes());
+        let tx = json!({"message": {
+            "header": {"num_required_signatures": 1, "num_readonly_signed_accounts": 0, "num_readonly_unsigned_accounts": 4},
+            "account_keys": [
+                vec![1u8; 32], vec![2u8; 32], vec![3u8; 32],
+                key(SYSTEM_PROGRAM_BASE58), key(COMPUTE_BUDGET_BASE58), key(APL_TOKEN_PROGRAM_BASE58), key(APL_ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_BASE58),
+            ],
+            "instructions": [
+                {"program_id_index": 4, "accounts": [], "data": limit},
+                {"program_id_index": 3, "accounts": [0, 1], "data": transfer},
+                {"program_id_index": 6, "accounts": [0, 1, 0, 2, 3, 5], "data": []},
+                {"program_id_index": 5, "accounts": [1, 0], "data": [5]},
+                {"program_id_index": 5, "accounts": [1], "data": [99]},
+            ]
+        }});
+
+        let (a, b, c) = (hex::encode([1u8; 32]), hex::encode([2u8; 32]), hex::encode([3u8; 32]));
+        let parsed: Vec<(usize, ArchInstruction)> = parse_transaction(&tx).into_iter().map(|p| (p.index, p.instruction)).collect();
+        assert_eq!(
+            parsed,
+            vec![
+                (0, ArchInstruction::ComputeBudget(ComputeBudgetInstruction::SetComputeUnitLimit { units: 200_000 })),
+                (1, ArchInstruction::System(SystemInstruction::Transfer { source: a.clone(), destination: b.clone(), lamports: 42 })),
+                (
+                    2,
+                    ArchInstruction::AssociatedTokenAccount(AssociatedTokenAccountInstruction::Create {
+                        funder: a.clone(),
+                        associated_account: b,
+                        wallet: a,
+                        mint: c,
+                    })
+                ),
+                (3, ArchInstruction::Token(None)),
+            ]
+        );
+        assert_eq!(parsed[3].1.instruction_type(), ArchInstructionType::Token);
+    }
+
+    #[test]
+    fn system_and_compute_budget_data_is_read_as_the_decoder_crate_reads_it() {
+        let accounts = vec![hex::encode([1u8; 32]), hex::encode([2u8; 32])];
+        let mut transfer = 4u32.to_le_bytes().to_vec();
+        transfer.extend_from_slice(&42u64.to_le_bytes());
+        let mut padded = transfer.clone();
+        padded.push(0);
+        for data in [&transfer, &padded] {
+            assert_eq!(
+                SystemInstruction::unpack(data, &accounts).is_some(),
+                decoder::decode_instruction(SYSTEM_PROGRAM_BASE58, data, &accounts).is_some()
+            );
+        }
+        assert_eq!(SystemInstruction::unpack(&padded, &accounts), None);
+
+        let mut create = 0u32.to_le_bytes().to_vec();
+        create.extend_from_slice(&900u64.to_le_bytes());
+        create.extend_from_slice(&165u64.to_le_bytes());
+        create.extend_from_slice(&[7u8; 32]);
+        assert_eq!(
+            SystemInstruction::unpack(&create, &accounts),
+            Some(SystemInstruction::CreateAccount {
+                funder: accounts[0].clone(),
+                account: accounts[1].clone(),
+                lamports: 900,
+                space: 165,
+                owner: hex::encode([7u8; 32]),
+            })
+        );
+
+        let mut price = vec![3u8];
+        price.extend_from_slice(&5u64.to_le_bytes());
+        assert_eq!(
+            ComputeBudgetInstruction::unpack(&price),
+            Some(ComputeBudgetInstruction::SetComputeUnitPrice { micro_lamports: 5 })
+        );
+        assert_eq!(ComputeBudgetInstruction::unpack(&[2u8, 1, 2]), None);
+    }
+
+    #[test]
+    fn legacy_token_program_id_is_read_as_the_token_program() {
+        use crate::indexer::programs::LEGACY_APL_TOKEN_PROGRAM_BASE58;
+
+        let (account, mint, owner) = (hex::encode([1u8; 32]), hex::encode([2u8; 32]), hex::encode([3u8; 32]));
+        let accounts = vec![account.clone(), mint.clone(), owner.clone()];
+        assert_eq!(
+            ArchInstruction::parse(LEGACY_APL_TOKEN_PROGRAM_BASE58, &[1], &accounts),
+            Some(ArchInstruction::Token(Some(TokenEffect::InitializeAccount { account, mint, owner })))
+        );
+    }
+}
@@ -0,0 +1,114 @@
+use anyhow::Result;
+use serde_json::Value as JsonValue;
+use sqlx::{Postgres, Transaction};
+
+use crate::indexer::{instructions, metadata, native, participation, programs, tokens};
+
+/// Writes every table derived from one runtime transaction: instructions, native transfers,
+/// balances and balance history, program links and counters, account participation, token
+/// state and token metadata, and marks the transaction's effects as applied. The
+/// `transactions` row must already be written in `tx`; both the legacy and the Atlas runtime
+/// call this so they produce the same rows. A transaction whose stored status is not
+/// processed changed no state, so only its instructions, program links and participation
+/// are written.
+pub async fn apply_transaction(
+    tx: &mut Transaction<'_, Postgres>,
+    txid: &str,
+    data: &JsonValue,
+    accounts_tags: Option<&[JsonValue]>,
+) -> Result<()> {
+    instructions::store_instructions(&mut **tx, txid, data).await?;
+    let program_ids = programs::transaction_program_ids(data, accounts_tags);
+    programs::store_transaction_programs(&mut **tx, txid, &program_ids).await?;
+    participation::store_account_participation(&mut **tx, txid, data).await?;
+
+    if stored_as_processed(tx, txid).await? {
+        native::store_native_transfers(&mut **tx, txid, data).await?;
+        native::apply_native_balances(&mut **tx, data).await?;
+        native::record_native_balance_history(tx, txid, data).await?;
+        // Token accounts, balances and mints from typed APL token instructions
+        tokens::apply_token_instructions(tx, txid, data).await?;
+        // Token names, symbols and URIs from token metadata instructions
+        metadata::apply_metadata_instructions(tx, txid, data).await?;
+    }
+    set_effects_applied(tx, txid, true).await
+}
+
+/// Whether a transaction status as the node reports it means the transaction executed.
+/// Failed and queued transactions are not; statuses that carry no kind, as in legacy rows,
+/// count as processed.
+pub fn is_processed(status: &JsonValue) -> bool {
+    let kind = match status {
+        JsonValue::String(kind) => Some(kind.as_str()),
+        JsonValue::Object(obj) if obj.contains_key("Failed") => return false,
+        JsonValue::Object(obj) => obj.get("type").and_then(|kind| kind.as_str()),
+        _ => None,
+    };
+    kind.is_none_or(|kind| kind.eq_ignore_ascii_case("processed"))
+}
+
+async fn stored_as_processed(tx: &mut Transaction<'_, Postgres>, txid: &str) -> Result<bool> {
+    let status: Option<JsonValue> = sqlx::query_scalar("SELECT status FROM transactions WHERE txid = $1")
+        .bind(txid)
+        .fetch_optional(&mut **tx)
+        .await?;
+    Ok(status.is_none_or(|status| is_processed(&status)))
+}
+
+async fn set_effects_applied(tx: &mut Transaction<'_, Postgres>, txid: &str, applied: bool) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE transactions
+        SET effects_applied = $2,
+            apply_order = CASE WHEN $2 THEN nextval('transaction_apply_order_seq') ELSE apply_order END
+        WHERE txid = $1
+        "#,
+    )
+    .bind(txid)
+    .bind(applied)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// Reverses the balance effects `apply_transaction` applied for the same transaction, which
+/// are none when its stored status is not processed. Rows keyed by txid go away with the
+/// `transactions` row via `ON DELETE CASCADE`.
+pub async fn revert_transaction(tx: &mut Transaction<'_, Postgres>, txid: &str, data: &JsonValue) -> Result<()> {
+    if !stored_as_processed(tx, txid).await? {
+        return Ok(());
+    }
+    tokens::revert_token_instructions(tx, txid, data).await?;
+    native::revert_native_balances(&mut **tx, data).await?;
+    Ok(())
+}
+
+/// Tables holding rows keyed by the txid they were derived from.
+const TXID_KEYED_TABLES: [&str; 6] = [
+    "instructions",
+    "native_transfers",
+    "token_transfers",
+    "token_mint_events",
+    "token_account_authority_events",
+    "account_participation",
+];
+
+/// Reverses everything `apply_transaction` wrote for a transaction whose row stays stored,
+/// as when the node rolls it back: balance effects, program counters and the rows derived
+/// from it, and clears its applied mark. `apply_transaction` restores them if the
+/// transaction is reapplied.
+pub async fn unapply_transaction(tx: &mut Transaction<'_, Postgres>, txid: &str, data: &JsonValue) -> Result<()> {
+    revert_transaction(tx, txid, data).await?;
+    if stored_as_processed(tx, txid).await? {
+        native::revert_native_balance_history(tx, txid, data).await?;
+        tokens::revert_mint_event_supply(tx, txid).await?;
+    }
+    programs::remove_transaction_programs(&mut **tx, txid).await?;
+    for table in TXID_KEYED_TABLES {
+        sqlx::query(&format!("DELETE FROM {} WHERE txid = $1", table))
+            .bind(txid)
+            .execute(&mut **tx)
+            .await?;
+    }
+    set_effects_applied(tx, txid, false).await
+}
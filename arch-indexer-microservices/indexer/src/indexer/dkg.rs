@@ -0,0 +1,17 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde_json::Value as JsonValue;
+use sqlx::PgExecutor;
+
+/// Stores a DKG (distributed key generation) event as the node sent it, with its `status`
+/// pulled out for filtering.
+pub async fn store_dkg_event<'c>(executor: impl PgExecutor<'c>, data: &JsonValue, received_at: DateTime<Utc>) -> Result<()> {
+    let status = data.get("status").and_then(|s| s.as_str());
+    sqlx::query("INSERT INTO dkg_events (status, data, received_at) VALUES ($1, $2, $3)")
+        .bind(status)
+        .bind(data)
+        .bind(received_at)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
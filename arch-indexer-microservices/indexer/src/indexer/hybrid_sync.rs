@@ -1,46 +1,45 @@
 use anyhow::Result;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
 use tokio::time::Duration;
-use tracing::{error, info};
+use tracing::{debug, error, info};
 
 use crate::config::Settings;
 use sqlx::{PgPool, Row};
 
-use crate::arch_rpc::ArchRpcClient;
+use crate::arch_rpc::{ArchRpcClient, Block, ProcessedTransaction};
 use crate::arch_rpc::websocket::WebSocketClient;
+use decoder::key_to_hex;
+use crate::indexer::accounts::{self, AccountState};
+use crate::indexer::{derived, dkg, reorg, rollback};
 use crate::utils::convert_arch_timestamp;
-use serde_json::Value as JsonValue;
 use bs58;
 use hex;
 
-fn try_hex_to_base58(hex_str: &str) -> String {
-    if hex_str.is_empty() { return String::new(); }
-    if let Ok(bytes) = hex::decode(hex_str) {
-        bs58::encode(bytes).into_string()
-    } else {
-        String::new()
-    }
-}
-
 #[derive(Debug, Clone)]
 pub struct HybridSync {
     settings: Arc<Settings>,
     pool: Arc<PgPool>,
+    rpc: Arc<ArchRpcClient>,
     current_height: Arc<AtomicI64>,
     is_realtime_active: Arc<AtomicBool>,
     last_realtime_update: Arc<AtomicI64>,
 }
 
 impl HybridSync {
-    pub fn new(settings: Arc<Settings>, pool: Arc<PgPool>) -> Self {
-        Self {
+    pub fn new(settings: Arc<Settings>, pool: Arc<PgPool>) -> Result<Self> {
+        let rpc = Arc::new(ArchRpcClient::from_settings(&settings.arch_node)?);
+        Ok(Self {
             settings,
             pool,
+            rpc,
             current_height: Arc::new(AtomicI64::new(0)),
             is_realtime_active: Arc::new(AtomicBool::new(false)),
             last_realtime_update: Arc::new(AtomicI64::new(0)),
-        }
+        })
     }
 
     pub fn is_websocket_enabled(&self) -> bool {
@@ -107,15 +106,15 @@ impl HybridSync {
 
     async fn start_realtime_sync(&self) -> Result<()> {
         info!("🔄 Starting start_realtime_sync method...");
-        let websocket_url = self.settings.arch_node.websocket_url.clone();
+        let websocket_urls = self.settings.arch_node.websocket_urls();
         let websocket_settings = self.settings.websocket.clone();
-        let rpc = Arc::new(ArchRpcClient::new(self.settings.arch_node.url.clone()));
+        let rpc = Arc::clone(&self.rpc);
         let pool = Arc::clone(&self.pool);
         let is_realtime_active = Arc::clone(&self.is_realtime_active);
         let last_realtime_update = Arc::clone(&self.last_realtime_update);
 
         let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<crate::arch_rpc::websocket::WebSocketEvent>();
-        let ws_client = WebSocketClient::new(websocket_settings, websocket_url);
+        let ws_client = WebSocketClient::with_urls(websocket_settings, websocket_urls).with_tls(&self.settings.arch_node.tls)?;
 
         // Connection task
         tokio::spawn(async move {
@@ -126,6 +125,7 @@ impl HybridSync {
 
         // Event processor
         tokio::spawn(async move {
+            let mut buffer = RealtimeBuffer::default();
             while let Some(evt) = rx.recv().await {
                 // Mark realtime active
                 is_realtime_active.store(true, Ordering::Relaxed);
@@ -135,6 +135,7 @@ impl HybridSync {
                 let topic_norm = match evt.topic.as_str() {
                     "blocks" => "block",
                     "transactions" => "transaction",
+                    "accounts" => "account_update",
                     other => other,
                 };
 
@@ -146,26 +147,18 @@ impl HybridSync {
                             // Height may not be present; attempt to get height from tip for now
                             // or we could ignore and let bulk catch up. We'll attempt fetch by hash only.
                             match rpc.get_block(hash, 0).await {
-                                Ok(block) => {
-                                    // Convert Arch timestamp to DateTime using centralized utility
-                                    let timestamp = convert_arch_timestamp(block.timestamp);
-                                    if let Err(e) = sqlx::query(
-                                        r#"
-                                        INSERT INTO blocks (height, hash, timestamp, bitcoin_block_height)
-                                        VALUES ($1, $2, $3, $4)
-                                        ON CONFLICT (height) DO UPDATE 
-                                        SET hash = EXCLUDED.hash, timestamp = EXCLUDED.timestamp, bitcoin_block_height = EXCLUDED.bitcoin_block_height
-                                        "#,
-                                    )
-                                    .bind(block.height)
-                                    .bind(&block.hash)
-                                    .bind(timestamp)
-                                    .bind(block.bitcoin_block_height.unwrap_or(0))
-                                    .execute(&*pool)
-                                    .await {
-                                        error!("Realtime block upsert failed: {}", e);
+                                Ok(block) => match store_realtime_block(&pool, &block).await {
+                                    // Transactions announced before their block are stored now
+                                    Ok(()) => {
+                                        for (txid, processed) in buffer.block_seen(&block) {
+                                            match store_realtime_transaction(&pool, &txid, &processed, block.height).await {
+                                                Ok(()) => info!("✅ Realtime transaction persisted: {}", txid),
+                                                Err(e) => error!("Realtime transaction {} failed: {}", txid, e),
+                                            }
+                                        }
                                     }
-                                }
+                                    Err(e) => error!("Realtime block insert failed: {}", e),
+                                },
                                 Err(e) => error!("Realtime failed to fetch block by hash: {}", e),
                             }
                         }
@@ -174,67 +167,46 @@ impl HybridSync {
                         if let Some(hash) = evt.data.get("hash").and_then(|v| v.as_str()) {
                             info!("📨 Realtime transaction event: {}", hash);
                             match rpc.get_processed_transaction(hash).await {
-                                Ok(processed) => {
-                                    let data = match serde_json::to_value(&processed.runtime_transaction) { Ok(v)=>v, Err(_)=>serde_json::Value::Null };
-                                    let status = match serde_json::to_value(&processed.status) { Ok(v)=>v, Err(_)=>serde_json::Value::Null };
-                                    let bitcoin_txids: Option<&[String]> = processed.bitcoin_txids.as_deref();
-                                    // Extract logs from runtime or struct field
-                                    let logs: Vec<String> = if let Some(arr) = processed.runtime_transaction.get("logs").and_then(|v| v.as_array()) {
-                                        arr.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect()
-                                    } else { processed.logs.clone() };
-                                    let logs_json = serde_json::to_value(&logs).unwrap_or(serde_json::Value::Array(vec![]));
-                                    if let Err(e) = sqlx::query(
-                                        r#"
-                                        INSERT INTO transactions (txid, block_height, data, status, bitcoin_txids, logs, created_at)
-                                        VALUES ($1, COALESCE((SELECT MAX(height) FROM blocks), 0), $2, $3, $4, $5, CURRENT_TIMESTAMP)
-                                        ON CONFLICT (txid) DO UPDATE SET data = $2, status = $3, bitcoin_txids = $4, logs = $5
-                                        "#,
-                                    )
-                                    .bind(hash)
-                                    .bind(&data)
-                                    .bind(status)
-                                    .bind(bitcoin_txids)
-                                    .bind(&logs_json)
-                                    .execute(&*pool)
-                                    .await {
-                                        error!("Realtime tx upsert failed: {}", e);
-                                    }
-
-                                    // Extract and upsert program IDs
-                                    let pids = extract_program_ids(&data, Some(&processed.accounts_tags));
-                                    info!("↳ programs in tx {}: {}", hash, pids.len());
-                                    for pid in pids {
-                                        if let Err(e) = sqlx::query(
-                                            r#"
-                                            INSERT INTO programs (program_id, first_seen_at, last_seen_at, transaction_count)
-                                            VALUES ($1, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP, 1)
-                                            ON CONFLICT (program_id) DO UPDATE
-                                            SET last_seen_at = CURRENT_TIMESTAMP,
-                                                transaction_count = programs.transaction_count + 1
-                                            "#
-                                        )
-                                        .bind(&pid)
-                                        .execute(&*pool)
-                                        .await { error!("programs upsert failed: {}", e); }
-
-                                        if let Err(e) = sqlx::query(
-                                            r#"
-                                            INSERT INTO transaction_programs (txid, program_id)
-                                            VALUES ($1, $2)
-                                            ON CONFLICT DO NOTHING
-                                            "#
-                                        )
-                                        .bind(hash)
-                                        .bind(&pid)
-                                        .execute(&*pool)
-                                        .await { error!("transaction_programs insert failed: {}", e); }
-                                    }
-                                    info!("✅ Realtime transaction persisted: {}", hash);
-                                }
+                                Ok(processed) => match buffer.transaction_seen(hash, processed) {
+                                    Some((height, processed)) => match store_realtime_transaction(&pool, hash, &processed, height).await {
+                                        Ok(()) => info!("✅ Realtime transaction persisted: {}", hash),
+                                        Err(e) => error!("Realtime transaction {} failed: {}", hash, e),
+                                    },
+                                    None => debug!("⏳ Realtime transaction {} waits for its block", hash),
+                                },
                                 Err(e) => error!("Realtime failed to fetch transaction {}: {}", hash, e),
                             }
                         }
                     }
+                    "account_update" => {
+                        if let Some(account) = evt.data.get("account").and_then(|v| v.as_str()) {
+                            let txid = evt.data.get("transaction_hash").and_then(|v| v.as_str());
+                            if let Err(e) = store_account_update(&pool, &rpc, account, txid).await {
+                                error!("Realtime account update failed for {}: {}", account, e);
+                            }
+                        }
+                    }
+                    "rolledback_transactions" | "reapplied_transactions" => {
+                        let txids: Vec<String> = evt
+                            .data
+                            .get("transaction_hashes")
+                            .and_then(|v| v.as_array())
+                            .map(|arr| arr.iter().filter_map(|h| h.as_str().map(|s| s.to_string())).collect())
+                            .unwrap_or_default();
+                        let result = if topic_norm == "rolledback_transactions" {
+                            rollback::rollback_transactions(&pool, &txids).await
+                        } else {
+                            rollback::reapply_transactions(&pool, &txids).await
+                        };
+                        if let Err(e) = result {
+                            error!("Realtime {} failed for {:?}: {}", topic_norm, txids, e);
+                        }
+                    }
+                    "dkg" => {
+                        if let Err(e) = dkg::store_dkg_event(&*pool, &evt.data, evt.timestamp).await {
+                            error!("Realtime DKG event insert failed: {}", e);
+                        }
+                    }
                     other => {
                         // Ignore other topics, but log once at debug
                         tracing::debug!("Ignoring realtime topic: {}", other);
@@ -251,10 +223,25 @@ impl HybridSync {
         info!("🔄 Starting start_traditional_sync method...");
         let pool = Arc::clone(&self.pool);
         let settings = Arc::clone(&self.settings);
+        let rpc = Arc::clone(&self.rpc);
 
         tokio::spawn(async move {
-            let rpc = Arc::new(ArchRpcClient::new(settings.arch_node.url.clone()));
-            info!("🌐 Bulk sync using RPC endpoint: {}", settings.arch_node.url);
+            info!("🌐 Bulk sync using RPC endpoints: {}", rpc.pool().urls().join(", "));
+
+            // Bounded fetch pipeline shared by the prefix backfill, the heal pass and the bulk
+            // loop: up to `batch_size * concurrent_batches` heights are fetched on spawned
+            // tasks ahead of the writer, which still commits them strictly in height order.
+            // ARCH_BULK_BATCH_SIZE overrides indexer.batch_size for backwards compatibility;
+            // the client's adaptive limiter decides how many of those requests are actually
+            // in flight against the node.
+            let batch_size: i64 = std::env::var("ARCH_BULK_BATCH_SIZE")
+                .ok()
+                .and_then(|v| v.parse::<i64>().ok())
+                .filter(|&n| n > 0 && n <= 1000)
+                .unwrap_or(settings.indexer.batch_size as i64)
+                .clamp(1, 1000);
+            let concurrent_batches = settings.indexer.concurrent_batches.max(1);
+            let window = batch_size as usize * concurrent_batches;
 
             // Optional prefix backfill on startup: fill [0, DB_MIN-1] if DB_MIN > 0
             let enable_prefix = std::env::var("ARCH_BACKFILL_PREFIX_ON_START").ok().unwrap_or_else(|| "1".to_string()) == "1";
@@ -270,12 +257,25 @@ impl HybridSync {
                             while cursor <= end {
                                 let hi = (cursor + chunk - 1).min(end);
                                 info!("🧩 Prefix backfill chunk {}..{}", cursor, hi);
-                                for h in cursor..=hi {
-                                    if let Err(e) = process_block_via_rpc(&pool, &rpc, h).await {
-                                        error!("Prefix backfill: block {} failed: {}", h, e);
+                                let mut next_cursor = hi + 1;
+                                let mut fetches = FetchPipeline::spawn(&rpc, (cursor..=hi).collect(), window);
+                                while let Some((h, fetched)) = fetches.next().await {
+                                    let outcome = match fetched {
+                                        Ok(fetched) => write_fetched_block(&pool, &rpc, fetched).await,
+                                        Err(e) => Err(e),
+                                    };
+                                    match outcome {
+                                        Ok(BlockOutcome::Indexed) => {}
+                                        Ok(BlockOutcome::Reorged { fork_height }) => {
+                                            // Everything from the fork up was rolled back; re-index from there
+                                            info!("🔀 Prefix backfill resuming at fork height {}", fork_height);
+                                            next_cursor = fork_height;
+                                            break;
+                                        }
+                                        Err(e) => error!("Prefix backfill: block {} failed: {}", h, e),
                                     }
                                 }
-                                cursor = hi + 1;
+                                cursor = next_cursor;
                             }
                             info!("✅ Prefix backfill complete up to {}", end);
                         }
@@ -312,17 +312,29 @@ impl HybridSync {
                             .await;
                             let rows = match rows { Ok(r) => r, Err(e) => { error!("heal: query heights {}..{} failed: {}", cursor, end, e); break; } };
                             let present: std::collections::HashSet<i64> = rows.iter().map(|r| r.get::<i64, _>("height")).collect();
-                            let mut missing_in_chunk: i64 = 0;
-                            for h in cursor..=end {
-                                if !present.contains(&h) {
-                                    missing_in_chunk += 1;
-                                    if let Err(e) = process_block_via_rpc(&pool, &rpc, h).await {
-                                        error!("heal: block {} failed: {}", h, e);
-                                    } else { healed += 1; }
+                            let missing: Vec<i64> = (cursor..=end).filter(|h| !present.contains(h)).collect();
+                            let missing_in_chunk = missing.len();
+                            let mut next_cursor = end + 1;
+                            let mut fetches = FetchPipeline::spawn(&rpc, missing, window);
+                            while let Some((h, fetched)) = fetches.next().await {
+                                let outcome = match fetched {
+                                    Ok(fetched) => write_fetched_block(&pool, &rpc, fetched).await,
+                                    Err(e) => Err(e),
+                                };
+                                match outcome {
+                                    Ok(BlockOutcome::Indexed) => healed += 1,
+                                    Ok(BlockOutcome::Reorged { fork_height }) => {
+                                        // Stored heights from the fork up were rolled back, so the
+                                        // chunk is re-scanned from there
+                                        info!("🔀 Heal pass resuming at fork height {}", fork_height);
+                                        next_cursor = fork_height;
+                                        break;
+                                    }
+                                    Err(e) => error!("heal: block {} failed: {}", h, e),
                                 }
                             }
                             if missing_in_chunk > 0 { info!("🧩 Healed {} missing blocks in chunk {}..{} (total healed: {})", missing_in_chunk, cursor, end, healed); }
-                            cursor = end + 1;
+                            cursor = next_cursor;
                         }
                         info!("✅ Heal pass complete. Total healed: {}", healed);
                     }
@@ -330,7 +342,13 @@ impl HybridSync {
             } else {
                 info!("🧩 Heal pass disabled (ARCH_HEAL_MISSING_ON_START!=1)");
             }
-            let mut start_height = last_height.unwrap_or(-1) + 1;
+            // Re-read the stored tip: a reorg found while healing may have rewound it
+            let stored_max: Option<i64> = sqlx::query_scalar("SELECT MAX(height) FROM blocks")
+                .fetch_optional(&*pool)
+                .await
+                .ok()
+                .flatten();
+            let mut start_height = stored_max.unwrap_or(-1) + 1;
 
             // Fetch current tip
             let mut tip = match rpc.get_block_count().await {
@@ -368,33 +386,57 @@ impl HybridSync {
                 }
             }
 
-            info!("📈 Bulk sync starting at {} up to {}", start_height, tip);
+            // The tip is re-read after every chunk of heights, so a long catch-up keeps
+            // following the node instead of stopping at the tip it started with.
+            let chunk = batch_size * concurrent_batches as i64;
 
+            info!(
+                "📈 Bulk sync starting at {} up to {} (batch_size={}, concurrent_batches={}, in-flight={})",
+                start_height, tip, batch_size, concurrent_batches, window
+            );
+
+            let mut failures: u32 = 0;
             loop {
-                if start_height > tip { // refresh tip and wait briefly
-                    match rpc.get_block_count().await { Ok(h) => tip = h, Err(e) => error!("get_block_count error: {}", e) }
+                match rpc.get_block_count().await { Ok(h) => tip = h, Err(e) => error!("get_block_count error: {}", e) }
+                if start_height > tip { // caught up; wait briefly for new blocks
                     tokio::time::sleep(Duration::from_secs(5)).await;
                     continue;
                 }
 
-                // Process in batches (configurable via ARCH_BULK_BATCH_SIZE)
-                let batch_size: i64 = std::env::var("ARCH_BULK_BATCH_SIZE")
-                    .ok()
-                    .and_then(|v| v.parse::<i64>().ok())
-                    .filter(|&n| n > 0 && n <= 1000)
-                    .unwrap_or(25);
-                let end = (start_height + batch_size as i64 - 1).min(tip);
-                info!("📦 Processing blocks {}..{}", start_height, end);
-
-                for h in start_height..=end {
-                    if let Err(e) = process_block_via_rpc(&pool, &rpc, h).await {
-                        error!("Block {} failed: {}", h, e);
-                        // backoff before retrying next iteration
-                        tokio::time::sleep(Duration::from_secs(2)).await;
+                let end = tip.min(start_height + chunk - 1);
+                let mut next_height = end + 1;
+                let mut fetches = FetchPipeline::spawn(&rpc, (start_height..=end).collect(), window);
+
+                while let Some((h, fetched)) = fetches.next().await {
+                    if (h - start_height) % batch_size == 0 {
+                        info!("📦 Processing blocks {}..{}", h, (h + batch_size - 1).min(end));
+                    }
+                    let outcome = match fetched {
+                        Ok(fetched) => write_fetched_block(&pool, &rpc, fetched).await,
+                        Err(e) => Err(e),
+                    };
+                    match outcome {
+                        Ok(BlockOutcome::Indexed) => failures = 0,
+                        Ok(BlockOutcome::Reorged { fork_height }) => {
+                            // Drop in-flight fetches and re-index the canonical chain from the fork point
+                            info!("🔀 Resuming bulk sync at fork height {}", fork_height);
+                            next_height = fork_height;
+                            break;
+                        }
+                        Err(e) => {
+                            // Retry this height before moving past it: drop in-flight fetches
+                            // and restart the stream here after a backoff
+                            failures += 1;
+                            let backoff = Duration::from_secs(2u64.pow(failures.min(5)));
+                            error!("Block {} failed (attempt {}), retrying in {:?}: {}", h, failures, backoff, e);
+                            tokio::time::sleep(backoff).await;
+                            next_height = h;
+                            break;
+                        }
                     }
                 }
 
-                start_height = end + 1;
+                start_height = next_height;
             }
         });
 
@@ -414,455 +456,342 @@ impl HybridSync {
     }
 }
 
-fn extract_program_ids(data: &JsonValue, accounts_tags: Option<&[JsonValue]>) -> Vec<String> {
-    let mut ids: Vec<String> = Vec::new();
-    // Preload account_keys as hex strings if available
-    let mut account_keys_hex: Vec<String> = Vec::new();
-    if let Some(keys) = data
-        .get("message")
-        .and_then(|m| m.get("account_keys"))
-        .and_then(|v| v.as_array())
-    {
-        for k in keys {
-            // account key may be array of numbers (bytes)
-            if let Some(arr) = k.as_array() {
-                let bytes: Vec<u8> = arr.iter().filter_map(|x| x.as_u64().map(|n| n as u8)).collect();
-                account_keys_hex.push(hex::encode(bytes));
-            } else if let Some(s) = k.as_str() { // base58 or hex string
-                // If it's hex already, keep; otherwise try base58 decode
-                if s.chars().all(|c| c.is_ascii_hexdigit()) && s.len() >= 2 {
-                    account_keys_hex.push(s.to_string());
-                } else if let Ok(bytes) = bs58::decode(s).into_vec() {
-                    account_keys_hex.push(hex::encode(bytes));
-                }
+/// Outcome of indexing a single height in the bulk path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockOutcome {
+    Indexed,
+    /// The block did not extend the stored chain; rows from `fork_height` up were rolled back.
+    Reorged { fork_height: i64 },
+}
+
+/// A block and its processed transactions, fetched from the node but not yet persisted.
+struct FetchedBlock {
+    height: i64,
+    hash: String,
+    block: Block,
+    transactions: Vec<(String, ProcessedTransaction)>,
+}
+
+/// Fetches heights on spawned tasks so fetching keeps going while the caller writes. At most
+/// `window` fetches run ahead of the height the caller is waiting on, and results come back
+/// in the order the heights were given. Dropping the pipeline aborts the fetches still running.
+struct FetchPipeline {
+    rx: mpsc::Receiver<(i64, AbortOnDrop<Result<FetchedBlock>>)>,
+    _producer: AbortOnDrop<()>,
+}
+
+/// A spawned task that is aborted when its handle is dropped.
+struct AbortOnDrop<T>(JoinHandle<T>);
+
+impl<T> Drop for AbortOnDrop<T> {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+impl FetchPipeline {
+    fn spawn(rpc: &Arc<ArchRpcClient>, heights: Vec<i64>, window: usize) -> Self {
+        let (tx, rx) = mpsc::channel(window.max(1));
+        let rpc = Arc::clone(rpc);
+        let producer = tokio::spawn(async move {
+            for h in heights {
+                // Wait for room in the window before starting the next fetch
+                let Ok(slot) = tx.reserve().await else { return };
+                let rpc = Arc::clone(&rpc);
+                let fetch = tokio::spawn(async move { fetch_block_via_rpc(&rpc, h).await });
+                slot.send((h, AbortOnDrop(fetch)));
             }
-        }
+        });
+        Self { rx, _producer: AbortOnDrop(producer) }
     }
 
-    if let Some(msg) = data.get("message") {
-        // Support both "instructions" and "compiled_instructions" shapes
-        let inst_array_opt = msg.get("instructions").and_then(|v| v.as_array())
-            .or_else(|| msg.get("compiled_instructions").and_then(|v| v.as_array()));
-        if let Some(instructions) = inst_array_opt {
-            for ins in instructions {
-                if let Some(pid) = ins.get("program_id").and_then(|v| v.as_str()) {
-                    // normalize program_id string (hex or base58) to hex
-                    let hex_pid = if pid.chars().all(|c| c.is_ascii_hexdigit()) && pid.len() >= 2 {
-                        pid.to_string()
-                    } else if let Ok(bytes) = bs58::decode(pid).into_vec() {
-                        hex::encode(bytes)
-                    } else { pid.to_string() };
-                    ids.push(hex_pid);
-                    continue;
-                }
-                if let Some(idx) = ins.get("program_id_index").and_then(|v| v.as_u64()) {
-                    let i = idx as usize;
-                    if i < account_keys_hex.len() {
-                        ids.push(account_keys_hex[i].clone());
-                    }
-                }
+    async fn next(&mut self) -> Option<(i64, Result<FetchedBlock>)> {
+        let (h, mut fetch) = self.rx.recv().await?;
+        let fetched = match (&mut fetch.0).await {
+            Ok(fetched) => fetched,
+            Err(e) => Err(anyhow::anyhow!("fetch task for block {} failed: {}", h, e)),
+        };
+        Some((h, fetched))
+    }
+}
+
+async fn fetch_block_via_rpc(rpc: &Arc<ArchRpcClient>, height: i64) -> Result<FetchedBlock> {
+    let hash = rpc.get_block_hash(height).await?;
+    let block = rpc.get_block(&hash, height).await?;
+    let mut transactions = Vec::with_capacity(block.transactions.len());
+    if !block.transactions.is_empty() {
+        // One batched round-trip per chunk; items (or whole batches) that fail fall back to
+        // the single-call path, which has its own retries.
+        let batched = match rpc.get_processed_transactions(&block.transactions).await {
+            Ok(results) => results,
+            Err(e) => {
+                tracing::warn!("Batch tx fetch for block {} failed, falling back to single calls: {}", height, e);
+                block.transactions.iter().map(|_| Err(anyhow::anyhow!("batch unavailable"))).collect()
             }
+        };
+        for (txid, result) in block.transactions.iter().zip(batched) {
+            let processed = match result {
+                Ok(processed) => processed,
+                Err(_) => rpc.get_processed_transaction(txid).await?,
+            };
+            transactions.push((txid.clone(), processed));
         }
     }
-    if let Some(tags) = accounts_tags {
-        for tag in tags {
-            if let Some(pid) = tag.get("program_id").and_then(|v| v.as_str()) {
-                // normalize to hex
-                let hex_pid = if pid.chars().all(|c| c.is_ascii_hexdigit()) && pid.len() >= 2 {
-                    pid.to_string()
-                } else if let Ok(bytes) = bs58::decode(pid).into_vec() {
-                    hex::encode(bytes)
-                } else { pid.to_string() };
-                ids.push(hex_pid);
+    Ok(FetchedBlock { height, hash, block, transactions })
+}
+
+/// Persists the state of the account an `account_update` event names. The event carries no
+/// state, so it is read from the node; the height is that of the transaction that touched
+/// the account, or the highest stored block when that transaction is not stored yet.
+async fn store_account_update(pool: &PgPool, rpc: &ArchRpcClient, account: &str, txid: Option<&str>) -> Result<()> {
+    let pubkey = accounts::address_bytes(account).ok_or_else(|| anyhow::anyhow!("invalid account address {}", account))?;
+    let info = rpc.read_account_info(&pubkey).await?;
+    let height: i64 = sqlx::query_scalar(
+        "SELECT COALESCE((SELECT block_height FROM transactions WHERE txid = $1), (SELECT MAX(height) FROM blocks), 0)",
+    )
+    .bind(txid)
+    .fetch_one(pool)
+    .await?;
+    let state = AccountState {
+        pubkey: hex::encode(&pubkey),
+        lamports: info.lamports as i64,
+        owner: key_to_hex(&info.owner),
+        data: info.data,
+        height,
+    };
+    accounts::store_account_states(pool, &[state]).await
+}
+
+/// Most realtime entries kept by [`RealtimeBuffer`] on each side.
+const REALTIME_BUFFER_LIMIT: usize = 10_000;
+
+/// Pairs realtime transactions with the realtime block that includes them, whichever
+/// arrives first, so a transaction is only stored once its height is known. Both sides are
+/// bounded and drop their oldest entries; the bulk path indexes whatever is dropped when it
+/// reaches the block.
+#[derive(Default)]
+struct RealtimeBuffer {
+    /// Transactions whose block has not been seen, oldest first
+    transactions: VecDeque<(String, ProcessedTransaction)>,
+    /// Height of each transaction of the blocks seen, and the txids in the order they came
+    heights: HashMap<String, i64>,
+    seen: VecDeque<String>,
+}
+
+impl RealtimeBuffer {
+    /// Remembers the block's transactions and returns the buffered ones it includes.
+    fn block_seen(&mut self, block: &Block) -> Vec<(String, ProcessedTransaction)> {
+        for txid in &block.transactions {
+            if self.heights.insert(txid.clone(), block.height).is_none() {
+                self.seen.push_back(txid.clone());
             }
         }
+        while self.seen.len() > REALTIME_BUFFER_LIMIT {
+            if let Some(txid) = self.seen.pop_front() {
+                self.heights.remove(&txid);
+            }
+        }
+        let (ready, waiting): (Vec<_>, VecDeque<_>) = std::mem::take(&mut self.transactions)
+            .into_iter()
+            .partition(|(txid, _)| self.heights.contains_key(txid));
+        self.transactions = waiting;
+        ready
+    }
+
+    /// Returns the transaction with its height when its block has been seen; buffers it
+    /// otherwise.
+    fn transaction_seen(&mut self, txid: &str, processed: ProcessedTransaction) -> Option<(i64, ProcessedTransaction)> {
+        if let Some(&height) = self.heights.get(txid) {
+            return Some((height, processed));
+        }
+        self.transactions.retain(|(pending, _)| pending != txid);
+        self.transactions.push_back((txid.to_string(), processed));
+        if self.transactions.len() > REALTIME_BUFFER_LIMIT {
+            self.transactions.pop_front();
+        }
+        None
     }
-    ids.sort();
-    ids.dedup();
-    ids
 }
 
-async fn process_block_via_rpc(pool: &PgPool, rpc: &Arc<ArchRpcClient>, height: i64) -> Result<()> {
-    let hash = rpc.get_block_hash(height).await?;
-    let block = rpc.get_block(&hash, height).await?;
+/// Stores the header of a block announced on the realtime stream. Heights already stored
+/// are left alone: a block from a competing fork must not replace the stored hash, the bulk
+/// path detects the reorg when it reaches this height and rolls it back.
+async fn store_realtime_block(pool: &PgPool, block: &Block) -> Result<()> {
+    // Convert Arch timestamp to DateTime using centralized utility
+    let timestamp = convert_arch_timestamp(block.timestamp);
+    sqlx::query(
+        r#"
+        INSERT INTO blocks (height, hash, timestamp, bitcoin_block_height, previous_block_hash)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (height) DO NOTHING
+        "#,
+    )
+    .bind(block.height)
+    .bind(&block.hash)
+    .bind(timestamp)
+    .bind(block.bitcoin_block_height.unwrap_or(0))
+    .bind(block.previous_block_hash.as_deref())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Persists a transaction announced on the realtime stream at the height of the realtime
+/// block that includes it, whose header is stored first. The row and its derived effects
+/// are written in one DB transaction, and the effects are applied only when the row is
+/// new; a row the bulk path already wrote keeps its height and effects.
+async fn store_realtime_transaction(pool: &PgPool, txid: &str, processed: &ProcessedTransaction, height: i64) -> Result<()> {
+    let data = serde_json::to_value(&processed.runtime_transaction)?;
+    let status = serde_json::to_value(&processed.status)?;
+    let bitcoin_txids: Option<&[String]> = processed.bitcoin_txids.as_deref();
+    // Extract logs from runtime or struct field
+    let logs: Vec<String> = if let Some(arr) = processed.runtime_transaction.get("logs").and_then(|v| v.as_array()) {
+        arr.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect()
+    } else { processed.logs.clone() };
+    let logs_json = serde_json::to_value(&logs).unwrap_or(serde_json::Value::Array(vec![]));
+
+    let mut tx = pool.begin().await?;
+    let inserted: bool = sqlx::query_scalar(
+        r#"
+        INSERT INTO transactions (txid, block_height, data, status, bitcoin_txids, logs, accounts_tags, created_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, CURRENT_TIMESTAMP)
+        ON CONFLICT (txid) DO UPDATE SET data = $3, status = $4, bitcoin_txids = $5, logs = $6, accounts_tags = $7
+        RETURNING (xmax = 0) AS inserted
+        "#,
+    )
+    .bind(txid)
+    .bind(height)
+    .bind(&data)
+    .bind(&status)
+    .bind(bitcoin_txids)
+    .bind(&logs_json)
+    .bind(serde_json::Value::Array(processed.accounts_tags.clone()))
+    .fetch_one(&mut *tx)
+    .await?;
+    if inserted {
+        derived::apply_transaction(&mut tx, txid, &data, Some(&processed.accounts_tags)).await?;
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Fetches and stores one height through the same write path as the bulk sync, so the
+/// block's transactions and their derived rows land together. When the block does not
+/// extend the stored chain, the orphaned rows are rolled back and the height is written
+/// once more; heights between the fork and this one are left for the heal pass.
+pub async fn index_block(pool: &PgPool, rpc: &Arc<ArchRpcClient>, height: i64) -> Result<BlockOutcome> {
+    let fetched = fetch_block_via_rpc(rpc, height).await?;
+    match write_fetched_block(pool, rpc, fetched).await? {
+        BlockOutcome::Indexed => Ok(BlockOutcome::Indexed),
+        BlockOutcome::Reorged { fork_height } => {
+            let fetched = fetch_block_via_rpc(rpc, height).await?;
+            write_fetched_block(pool, rpc, fetched).await?;
+            Ok(BlockOutcome::Reorged { fork_height })
+        }
+    }
+}
+
+async fn write_fetched_block(pool: &PgPool, rpc: &Arc<ArchRpcClient>, fetched: FetchedBlock) -> Result<BlockOutcome> {
+    let FetchedBlock { height, hash, block, transactions } = fetched;
+
+    // Make sure this block builds on the parent we stored; otherwise roll back to the fork
+    if let Some(fork_height) = reorg::find_fork_height(pool, rpc, height, block.previous_block_hash.as_deref()).await? {
+        reorg::rollback_from_height(pool, fork_height).await?;
+        return Ok(BlockOutcome::Reorged { fork_height });
+    }
+    // A different block stored at this height (the realtime path stores the blocks it is
+    // told about, possibly from a competing fork) is rolled back with everything above it
+    if reorg::replaces_stored_block(pool, height, &hash).await? {
+        reorg::rollback_from_height(pool, height).await?;
+        return Ok(BlockOutcome::Reorged { fork_height: height });
+    }
 
     // Convert Arch timestamp to DateTime using centralized utility
     let timestamp = convert_arch_timestamp(block.timestamp);
 
-    // Insert block
+    let block_size_bytes: i64 = transactions.iter().map(|(_, p)| decoder::transaction_size(&p.runtime_transaction)).sum();
+
+    // The block, its transactions and their derived effects are written in one DB
+    // transaction, so a failed write leaves the height missing for the heal pass
+    let mut tx = pool.begin().await?;
     sqlx::query(
         r#"
-        INSERT INTO blocks (height, hash, timestamp, bitcoin_block_height)
-        VALUES ($1, $2, $3, $4)
+        INSERT INTO blocks (height, hash, timestamp, bitcoin_block_height, previous_block_hash, block_size_bytes, transaction_count)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
         ON CONFLICT (height) DO UPDATE 
-        SET hash = EXCLUDED.hash, timestamp = EXCLUDED.timestamp, bitcoin_block_height = EXCLUDED.bitcoin_block_height
+        SET hash = EXCLUDED.hash, timestamp = EXCLUDED.timestamp, bitcoin_block_height = EXCLUDED.bitcoin_block_height,
+            previous_block_hash = EXCLUDED.previous_block_hash, block_size_bytes = EXCLUDED.block_size_bytes,
+            transaction_count = EXCLUDED.transaction_count
         "#,
     )
     .bind(height)
     .bind(&hash)
     .bind(timestamp)
     .bind(block.bitcoin_block_height.unwrap_or(0))
-    .execute(pool)
+    .bind(block.previous_block_hash.as_deref())
+    .bind(block_size_bytes)
+    .bind(block.transaction_count)
+    .execute(&mut *tx)
     .await?;
 
-    // Fetch transactions and insert
-    let txids = block.transactions.clone();
-    if !txids.is_empty() {
-        let mut tx = pool.begin().await?;
-        for txid in txids {
-            let processed = rpc.get_processed_transaction(&txid).await?;
-            let data = serde_json::to_value(&processed.runtime_transaction)?;
-            let status = serde_json::to_value(&processed.status)?;
-            let bitcoin_txids = processed.bitcoin_txids.as_ref().map(|v| v.as_slice());
-            // Extract logs from runtime or struct field
-            let logs: Vec<String> = if let Some(arr) = processed.runtime_transaction.get("logs").and_then(|v| v.as_array()) {
-                arr.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect()
-            } else { processed.logs.clone() };
-            let logs_json = serde_json::to_value(&logs).unwrap_or(serde_json::Value::Array(vec![]));
-            sqlx::query(
-                r#"
-                INSERT INTO transactions (txid, block_height, data, status, bitcoin_txids, logs, created_at)
-                VALUES ($1, $2, $3, $4, $5, $6, CURRENT_TIMESTAMP)
-                ON CONFLICT (txid) DO UPDATE 
-                SET block_height = $2, data = $3, status = $4, bitcoin_txids = $5, logs = $6
-                "#,
-            )
-            .bind(&txid)
-            .bind(height)
-            .bind(&data)
-            .bind(&status)
-            .bind(bitcoin_txids)
-            .bind(&logs_json)
-            .execute(&mut *tx)
-            .await?;
-            tracing::info!("📥 Inserted/updated transaction {} at height {}", txid, height);
-
-            // Extract and upsert program IDs
-            let pids = extract_program_ids(&data, Some(&processed.accounts_tags));
-            for pid in pids {
-                sqlx::query(
-                    r#"
-                    INSERT INTO programs (program_id, first_seen_at, last_seen_at, transaction_count)
-                    VALUES ($1, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP, 1)
-                    ON CONFLICT (program_id) DO UPDATE
-                    SET last_seen_at = CURRENT_TIMESTAMP,
-                        transaction_count = programs.transaction_count + 1
-                    "#
-                )
-                .bind(&pid)
-                .execute(&mut *tx)
-                .await?;
-                tracing::info!("📥 Upserted program {} due to tx {}", pid, txid);
-
-                sqlx::query(
-                    r#"
-                    INSERT INTO transaction_programs (txid, program_id)
-                    VALUES ($1, $2)
-                    ON CONFLICT DO NOTHING
-                    "#
-                )
-                .bind(&txid)
-                .bind(&pid)
-                .execute(&mut *tx)
-                .await?;
-                tracing::info!("🔗 Linked tx {} -> program {}", txid, pid);
-            }
-
-            // Populate account participation: account_keys and instruction.accounts
-            if let Some(message) = data.get("message") {
-                // account_keys array
-                if let Some(keys) = message.get("account_keys").and_then(|v| v.as_array()) {
-                    for k in keys {
-                        let addr_hex = if let Some(arr) = k.as_array() {
-                            let bytes: Vec<u8> = arr.iter().filter_map(|x| x.as_u64().map(|n| n as u8)).collect();
-                            hex::encode(bytes)
-                        } else if let Some(s) = k.as_str() {
-                            if s.chars().all(|c| c.is_ascii_hexdigit()) && s.len() >= 2 {
-                                s.to_string()
-                            } else if let Ok(bytes) = bs58::decode(s).into_vec() { hex::encode(bytes) } else { continue }
-                        } else { continue };
-                        sqlx::query(
-                            r#"INSERT INTO account_participation(address_hex, txid, block_height, created_at)
-                               VALUES ($1, $2, $3, CURRENT_TIMESTAMP)
-                               ON CONFLICT DO NOTHING"#
-                        )
-                        .bind(&addr_hex)
-                        .bind(&txid)
-                        .bind(height)
-                        .execute(&mut *tx)
-                        .await.ok();
-                    }
-                }
-
-                // instruction.accounts indexes
-                if let Some(instructions) = message.get("instructions").and_then(|v| v.as_array()) {
-                    if let Some(keys) = message.get("account_keys").and_then(|v| v.as_array()) {
-                        for inst in instructions {
-                            if let Some(accs) = inst.get("accounts").and_then(|v| v.as_array()) {
-                                for idx_v in accs {
-                                    if let Some(i) = idx_v.as_u64().map(|n| n as usize) {
-                                        if let Some(k) = keys.get(i) {
-                                            let addr_hex = if let Some(arr) = k.as_array() {
-                                                let bytes: Vec<u8> = arr.iter().filter_map(|x| x.as_u64().map(|n| n as u8)).collect();
-                                                hex::encode(bytes)
-                                            } else if let Some(s) = k.as_str() {
-                                                if s.chars().all(|c| c.is_ascii_hexdigit()) && s.len() >= 2 { s.to_string() } else if let Ok(bytes) = bs58::decode(s).into_vec() { hex::encode(bytes) } else { continue }
-                                            } else { continue };
-                                            sqlx::query(
-                                                r#"INSERT INTO account_participation(address_hex, txid, block_height, created_at)
-                                                   VALUES ($1, $2, $3, CURRENT_TIMESTAMP)
-                                                   ON CONFLICT DO NOTHING"#
-                                            )
-                                            .bind(&addr_hex)
-                                            .bind(&txid)
-                                            .bind(height)
-                                            .execute(&mut *tx)
-                                            .await.ok();
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
+    for (txid, processed) in transactions {
+        let data = serde_json::to_value(&processed.runtime_transaction)?;
+        let status = serde_json::to_value(&processed.status)?;
+        let bitcoin_txids = processed.bitcoin_txids.as_ref().map(|v| v.as_slice());
+        // Extract logs from runtime or struct field
+        let logs: Vec<String> = if let Some(arr) = processed.runtime_transaction.get("logs").and_then(|v| v.as_array()) {
+            arr.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect()
+        } else { processed.logs.clone() };
+        let logs_json = serde_json::to_value(&logs).unwrap_or(serde_json::Value::Array(vec![]));
+        // Derived effects are applied once: re-indexing a height (heal, prefix backfill,
+        // realtime overlap) must not apply balances twice. A stored row whose effects are
+        // not applied gets them now unless the node rolled it back, and one stored at another
+        // height or with a status that settled differently has its effects unapplied as
+        // stored and re-applied as the block reports it.
+        let stored: Option<(i64, serde_json::Value, serde_json::Value, bool)> = sqlx::query_as(
+            "SELECT block_height, data, status, effects_applied FROM transactions WHERE txid = $1 FOR UPDATE",
+        )
+        .bind(&txid)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let apply = match &stored {
+            None => true,
+            Some((_, _, _, false)) => !rollback::is_rolled_back(&mut tx, &txid).await?,
+            Some((stored_height, stored_data, stored_status, true))
+                if *stored_height != height || derived::is_processed(stored_status) != derived::is_processed(&status) =>
+            {
+                derived::unapply_transaction(&mut tx, &txid, stored_data).await?;
+                true
             }
+            Some(_) => false,
+        };
+        sqlx::query(
+            r#"
+            INSERT INTO transactions (txid, block_height, data, status, bitcoin_txids, logs, accounts_tags, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, CURRENT_TIMESTAMP)
+            ON CONFLICT (txid) DO UPDATE 
+            SET block_height = $2, data = $3, status = $4, bitcoin_txids = $5, logs = $6, accounts_tags = $7
+            "#,
+        )
+        .bind(&txid)
+        .bind(height)
+        .bind(&data)
+        .bind(&status)
+        .bind(bitcoin_txids)
+        .bind(&logs_json)
+        .bind(serde_json::Value::Array(processed.accounts_tags.clone()))
+        .execute(&mut *tx)
+        .await?;
+        tracing::info!("📥 Inserted/updated transaction {} at height {}", txid, height);
 
-            // Token entities seeding: detect Token program instructions [acct, mint, owner]
-            if let Some(instructions) = data.get("message").and_then(|m| m.get("instructions")).and_then(|v| v.as_array()) {
-                // Find token program id in either explicit field or by name
-                for inst in instructions {
-                    // Resolve program id either from explicit field or via program_id_index into account_keys
-                    let program_hex = if let Some(pid_str) = inst.get("program_id").and_then(|v| v.as_str()) {
-                        if pid_str.chars().all(|c| c.is_ascii_hexdigit()) && pid_str.len() >= 2 {
-                            pid_str.to_string()
-                        } else if let Ok(bytes) = bs58::decode(pid_str).into_vec() { hex::encode(bytes) } else { continue }
-                    } else if let Some(idx) = inst.get("program_id_index").and_then(|v| v.as_u64()) {
-                        if let Some(keys) = data.get("message").and_then(|m| m.get("account_keys")).and_then(|v| v.as_array()) {
-                            if let Some(k) = keys.get(idx as usize) {
-                                if let Some(arr) = k.as_array() {
-                                    hex::encode(arr.iter().filter_map(|x| x.as_u64().map(|n| n as u8)).collect::<Vec<u8>>())
-                                } else if let Some(s) = k.as_str() {
-                                    if s.chars().all(|c| c.is_ascii_hexdigit()) && s.len() >= 2 { s.to_string() } else if let Ok(bytes) = bs58::decode(s).into_vec() { hex::encode(bytes) } else { continue }
-                                } else { continue }
-                            } else { continue }
-                        } else { continue }
-                    } else { continue };
-                    // If this looks like token program (either ours or SPL), process accounts
-                    if program_hex.len() == 64 {
-                        if let Some(accs) = inst.get("accounts").and_then(|v| v.as_array()) {
-                            // Expect [account, mint, owner] shape
-                            if accs.len() >= 3 {
-                                if let Some(keys) = data.get("message").and_then(|m| m.get("account_keys")).and_then(|v| v.as_array()) {
-                                    let idxs: Vec<usize> = accs.iter().filter_map(|x| x.as_u64().map(|n| n as usize)).collect();
-                                    if idxs.len() >= 3 && idxs[0] < keys.len() && idxs[1] < keys.len() && idxs[2] < keys.len() {
-                                        let to_hex = |k: &serde_json::Value| -> Option<String> {
-                                            if let Some(arr) = k.as_array() {
-                                                Some(hex::encode(arr.iter().filter_map(|x| x.as_u64().map(|n| n as u8)).collect::<Vec<u8>>()))
-                                            } else if let Some(s) = k.as_str() {
-                                                if s.chars().all(|c| c.is_ascii_hexdigit()) && s.len() >= 2 { Some(s.to_string()) } else if let Ok(bytes) = bs58::decode(s).into_vec() { Some(hex::encode(bytes)) } else { None }
-                                            } else { None }
-                                        };
-                                        let acct_hex = to_hex(&keys[idxs[0]]);
-                                        let mint_hex = to_hex(&keys[idxs[1]]);
-                                        let owner_hex = to_hex(&keys[idxs[2]]);
-                                        if let (Some(acct), Some(mint)) = (acct_hex, mint_hex) {
-                                            // upsert token_account
-                                            sqlx::query("SELECT upsert_token_account($1, $2, $3, $4)")
-                                                .bind(&acct)
-                                                .bind(&mint)
-                                                .bind(owner_hex.as_deref())
-                                                .bind(&program_hex)
-                                                .execute(&mut *tx)
-                                                .await.ok();
-                                            // seed token_balances
-                                            sqlx::query(
-                                                r#"INSERT INTO token_balances (account_address, mint_address, balance, decimals, owner_address, program_id)
-                                                   VALUES ($1, $2, 0, 0, $3, $4)
-                                                   ON CONFLICT (account_address, mint_address) DO UPDATE SET last_updated = CURRENT_TIMESTAMP"#
-                                            )
-                                            .bind(&acct)
-                                            .bind(&mint)
-                                            .bind(owner_hex.as_deref())
-                                            .bind(&program_hex)
-                                            .execute(&mut *tx)
-                                            .await.ok();
-                                        }
-                                    }
-                                }
-                            }
-
-                            // Additionally handle Token instructions to ensure balances/decimals are maintained
-                            if let Some(data_bytes) = inst.get("data").and_then(|v| v.as_array()) {
-                                let tag = data_bytes.get(0).and_then(|v| v.as_u64()).unwrap_or(255) as u8;
-                                // InitializeMint / InitializeMint2 provide decimals (byte 1)
-                                if tag == 0 || tag == 18 {
-                                    if let Some(dec_u64) = data_bytes.get(1).and_then(|v| v.as_u64()) {
-                                        if let Some(keys) = data.get("message").and_then(|m| m.get("account_keys")).and_then(|v| v.as_array()) {
-                                            if let Some(mint_idx) = accs.get(0).and_then(|x| x.as_u64()).map(|n| n as usize) {
-                                                if let Some(k) = keys.get(mint_idx) {
-                                                    let mint_hex = if let Some(arr) = k.as_array() {
-                                                        hex::encode(arr.iter().filter_map(|x| x.as_u64().map(|n| n as u8)).collect::<Vec<u8>>())
-                                                    } else if let Some(s) = k.as_str() {
-                                                        if s.chars().all(|c| c.is_ascii_hexdigit()) && s.len() >= 2 { s.to_string() } else { bs58::decode(s).into_vec().ok().map(hex::encode).unwrap_or_default() }
-                                                    } else { String::new() };
-                                                    if !mint_hex.is_empty() {
-                                                        let _ = sqlx::query(
-                                                            r#"INSERT INTO token_mints (mint_address, program_id, decimals)
-                                                               VALUES ($1, $2, $3)
-                                                               ON CONFLICT (mint_address) DO UPDATE SET decimals = EXCLUDED.decimals, last_seen_at = CURRENT_TIMESTAMP"#
-                                                        )
-                                                        .bind(&mint_hex)
-                                                        .bind(&program_hex)
-                                                        .bind(dec_u64 as i32)
-                                                        .execute(&mut *tx)
-                                                        .await;
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-                                if tag == 3 || tag == 12 { // Transfer or TransferChecked
-                                    println!("Transfer or TransferChecked");
-                                    if let Some(keys) = data.get("message").and_then(|m| m.get("account_keys")).and_then(|v| v.as_array()) {                                        
-                                        let idxs: Vec<usize> = accs.iter().filter_map(|x| x.as_u64().map(|n| n as usize)).collect();
-                                        let to_hex = |k: &serde_json::Value| -> Option<String> {
-                                            if let Some(arr) = k.as_array() {
-                                                Some(hex::encode(arr.iter().filter_map(|x| x.as_u64().map(|n| n as u8)).collect::<Vec<u8>>()))
-                                            } else if let Some(s) = k.as_str() {
-                                                if s.chars().all(|c| c.is_ascii_hexdigit()) && s.len() >= 2 { Some(s.to_string()) } else if let Ok(bytes) = bs58::decode(s).into_vec() { Some(hex::encode(bytes)) } else { None }
-                                            } else { None }
-                                        };
-                                        // Parse amount little-endian from instruction bytes [1..9]
-                                        let mut amount: i128 = 0;
-                                        if data_bytes.len() >= 9 {
-                                            let mut le: u64 = 0;
-                                            for i in 0..8 { le |= (data_bytes[i+1].as_u64().unwrap_or(0) as u64) << (8*i); }
-                                            amount = le as i128;
-                                        }
-                                        // TransferChecked includes decimals after amount (byte 9)
-                                        let mut inst_decimals: Option<i32> = None;
-                                        if tag == 12 && data_bytes.len() >= 10 {
-                                            inst_decimals = Some(data_bytes[9].as_u64().unwrap_or(0) as i32);
-                                        }
-                                        // Determine source/dest and mint
-                                        let (src_hex_opt, dst_hex_opt, mint_hex_opt) = if tag == 3 {
-                                            // Transfer: [source, destination, authority]
-                                            let src = idxs.get(0).and_then(|i| keys.get(*i)).and_then(to_hex);
-                                            let dst = idxs.get(1).and_then(|i| keys.get(*i)).and_then(to_hex);
-                                            // Try to resolve mint from token_accounts by source
-                                            let mint_from_db: Option<String> = if let Some(ref src_hex) = src {
-                                                sqlx::query_scalar("SELECT mint_address_hex FROM token_accounts WHERE token_account_hex = $1")
-                                                    .bind(src_hex)
-                                                    .fetch_optional(&mut *tx)
-                                                    .await
-                                                    .ok()
-                                                    .flatten()
-                                            } else { None };
-                                            (src, dst, mint_from_db)
-                                        } else { // 12 TransferChecked: commonly [source, mint, destination, ...]
-                                            let src = idxs.get(0).and_then(|i| keys.get(*i)).and_then(to_hex);
-                                            let mint = idxs.get(1).and_then(|i| keys.get(*i)).and_then(to_hex);
-                                            let dst = idxs.get(2).and_then(|i| keys.get(*i)).and_then(to_hex);
-                                            (src, dst, mint)
-                                        };
-                                        println!("src_hex_opt: {:?}", src_hex_opt);
-                                        println!("dst_hex_opt: {:?}", dst_hex_opt);
-                                        println!("mint_hex_opt: {:?}", mint_hex_opt);
-                                        println!("program_hex: {:?}", program_hex);
-                                        if let (Some(src_hex), Some(dst_hex), Some(mint_hex)) = (src_hex_opt, dst_hex_opt, mint_hex_opt) {
-                                            // Log when this is an APL Token transfer
-                                            if let Ok(apl_bytes) = bs58::decode("5QSvph6op2FQj23To5H2LpD5unF1KXmVz29gFMoJTEoJ").into_vec() {
-                                                let apl_hex = hex::encode(apl_bytes);
-                                                if apl_hex == program_hex {
-                                                    let mint_b58 = try_hex_to_base58(&mint_hex);
-                                                    let src_b58 = try_hex_to_base58(&src_hex);
-                                                    let dst_b58 = try_hex_to_base58(&dst_hex);
-                                                    tracing::info!(
-                                                        "💸 APL token transfer: tx {} amount {} mint {} src {} -> dst {}",
-                                                        txid, amount, if mint_b58.is_empty() { mint_hex.clone() } else { mint_b58 }, if src_b58.is_empty() { src_hex.clone() } else { src_b58 }, if dst_b58.is_empty() { dst_hex.clone() } else { dst_b58 }
-                                                    );
-                                                }
-                                            }
-                                            // Ensure token_accounts rows exist for src/dst (owner unknown here)
-                                            sqlx::query("SELECT upsert_token_account($1, $2, NULL, $3)")
-                                                .bind(&src_hex)
-                                                .bind(&mint_hex)
-                                                .bind(&program_hex)
-                                                .execute(&mut *tx)
-                                                .await.ok();
-                                            sqlx::query("SELECT upsert_token_account($1, $2, NULL, $3)")
-                                                .bind(&dst_hex)
-                                                .bind(&mint_hex)
-                                                .bind(&program_hex)
-                                                .execute(&mut *tx)
-                                                .await.ok();
-                                            // Seed balances rows for src and dst
-                                            for acct in [&src_hex, &dst_hex] {
-                                                sqlx::query(
-                                                    r#"INSERT INTO token_balances (account_address, mint_address, balance, decimals, owner_address, program_id)
-                                                       VALUES ($1, $2, 0, 0, NULL, $3)
-                                                       ON CONFLICT (account_address, mint_address) DO UPDATE SET last_updated = CURRENT_TIMESTAMP"#
-                                                )
-                                                .bind(acct)
-                                                .bind(&mint_hex)
-                                                .bind(&program_hex)
-                                                .execute(&mut *tx)
-                                                .await.ok();
-                                            }
-                                            // If decimals available, set on balances and mints
-                                            if let Some(d) = inst_decimals {
-                                                let _ = sqlx::query("UPDATE token_balances SET decimals = $3 WHERE (account_address = $1 AND mint_address = $2) OR (account_address = $4 AND mint_address = $2)")
-                                                    .bind(&src_hex)
-                                                    .bind(&mint_hex)
-                                                    .bind(d)
-                                                    .bind(&dst_hex)
-                                                    .execute(&mut *tx)
-                                                    .await;
-                                                let _ = sqlx::query(
-                                                    r#"INSERT INTO token_mints (mint_address, program_id, decimals)
-                                                       VALUES ($1, $2, $3)
-                                                       ON CONFLICT (mint_address) DO UPDATE SET decimals = EXCLUDED.decimals, last_seen_at = CURRENT_TIMESTAMP"#
-                                                )
-                                                .bind(&mint_hex)
-                                                .bind(&program_hex)
-                                                .bind(d)
-                                                .execute(&mut *tx)
-                                                .await;
-                                            }
-                                            // Apply balance deltas using NUMERIC arithmetic
-                                            if amount > 0 {
-                                                let _ = sqlx::query(
-                                                    "UPDATE token_balances SET balance = GREATEST(balance - ($3)::numeric, 0), last_updated = CURRENT_TIMESTAMP WHERE account_address = $1 AND mint_address = $2"
-                                                )
-                                                .bind(&src_hex)
-                                                .bind(&mint_hex)
-                                                .bind(amount.to_string())
-                                                .execute(&mut *tx)
-                                                .await;
-                                                let _ = sqlx::query(
-                                                    "UPDATE token_balances SET balance = balance + ($3)::numeric, last_updated = CURRENT_TIMESTAMP WHERE account_address = $1 AND mint_address = $2"
-                                                )
-                                                .bind(&dst_hex)
-                                                .bind(&mint_hex)
-                                                .bind(amount.to_string())
-                                                .execute(&mut *tx)
-                                                .await;
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+        if apply {
+            derived::apply_transaction(&mut tx, &txid, &data, Some(&processed.accounts_tags)).await?;
         }
-        tx.commit().await?;
     }
+    tx.commit().await?;
 
     info!("✅ Processed block {} ({} txs)", height, block.transaction_count);
-    Ok(())
+    Ok(BlockOutcome::Indexed)
 }
 
 #[derive(Debug, Clone)]
@@ -871,3 +800,336 @@ pub enum RealtimeStatus {
     TransactionReceived { hash: String, timestamp: i64 },
     ConnectionStatus { connected: bool },
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arch_rpc::fixtures::{mock_node_router, Fixtures};
+    use crate::arch_rpc::recorder::RpcRecord;
+    use serde_json::{json, Value};
+    use std::collections::BTreeMap;
+
+    const HEIGHT: i64 = 900_000_000;
+
+    /// Database tests index the same heights, so they run one at a time.
+    static DB: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+    async fn process_block_via_rpc(pool: &PgPool, rpc: &Arc<ArchRpcClient>, height: i64) -> Result<BlockOutcome> {
+        let fetched = fetch_block_via_rpc(rpc, height).await?;
+        write_fetched_block(pool, rpc, fetched).await
+    }
+
+    fn record(method: &str, params: Value, result: Value) -> RpcRecord {
+        RpcRecord { method: method.to_string(), params, result: Some(result), error: None }
+    }
+
+    fn block(height: i64, previous: &[u8], txid: &str) -> Value {
+        json!({
+            "bitcoin_block_height": 7,
+            "block_height": height,
+            "previous_block_hash": previous,
+            "timestamp": 1700000000,
+            "transactions": [txid]
+        })
+    }
+
+    /// A System transfer of `lamports` from the 0x71.. account to the 0x72.. account.
+    fn transfer(lamports: u64, status: Value) -> Value {
+        let mut data = 4u32.to_le_bytes().to_vec();
+        data.extend_from_slice(&lamports.to_le_bytes());
+        json!({
+            "runtime_transaction": {
+                "version": 0,
+                "signatures": [],
+                "message": {
+                    "header": { "num_required_signatures": 1, "num_readonly_signed_accounts": 0, "num_readonly_unsigned_accounts": 1 },
+                    "account_keys": [vec![0x71u8; 32], vec![0x72u8; 32], vec![0u8; 32]],
+                    "instructions": [{ "program_id_index": 2, "accounts": [0, 1], "data": data }]
+                }
+            },
+            "status": status,
+            "bitcoin_txids": null,
+            "accounts_tags": []
+        })
+    }
+
+    /// Serves `records` from a mock node and returns a client for it.
+    async fn mock_node(records: Vec<RpcRecord>) -> Arc<ArchRpcClient> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let fixtures = Fixtures::from_records(records, Vec::new());
+        tokio::spawn(async move {
+            axum::serve(listener, mock_node_router(fixtures, Duration::ZERO)).await.unwrap();
+        });
+        Arc::new(ArchRpcClient::new(format!("http://{}", addr)))
+    }
+
+    async fn cleanup(pool: &PgPool) {
+        reorg::rollback_from_height(pool, HEIGHT).await.unwrap();
+        sqlx::query("DELETE FROM native_balances WHERE address_hex = ANY($1)")
+            .bind(vec![hex::encode([0x71u8; 32]), hex::encode([0x72u8; 32])])
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    fn realtime_block(height: i64, txids: &[&str]) -> Block {
+        Block {
+            hash: format!("rt{}", height),
+            height,
+            timestamp: 0,
+            bitcoin_block_height: None,
+            transactions: txids.iter().map(|t| t.to_string()).collect(),
+            transaction_count: txids.len() as i64,
+            previous_block_hash: None,
+        }
+    }
+
+    fn processed() -> ProcessedTransaction {
+        serde_json::from_value(transfer(1, json!({ "type": "processed" }))).unwrap()
+    }
+
+    #[test]
+    fn realtime_transactions_wait_for_their_block() {
+        let mut buffer = RealtimeBuffer::default();
+
+        // Transaction first: held until the block that includes it is seen
+        assert!(buffer.transaction_seen("a", processed()).is_none());
+        assert!(buffer.transaction_seen("b", processed()).is_none());
+        let ready = buffer.block_seen(&realtime_block(10, &["a"]));
+        assert_eq!(ready.iter().map(|(txid, _)| txid.as_str()).collect::<Vec<_>>(), vec!["a"]);
+
+        // Block first: the transaction is returned at once with the block's height
+        assert!(buffer.block_seen(&realtime_block(11, &["c"])).is_empty());
+        assert_eq!(buffer.transaction_seen("c", processed()).map(|(height, _)| height), Some(11));
+
+        // A transaction no block has included stays buffered
+        assert_eq!(buffer.block_seen(&realtime_block(12, &[])).len(), 0);
+        assert_eq!(buffer.transactions.len(), 1);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database initialized from db-init"]
+    async fn blocks_from_the_mock_node_are_indexed_once() {
+        let _db = DB.lock().await;
+        let pool = PgPool::connect(&std::env::var("DATABASE_URL").unwrap()).await.unwrap();
+        cleanup(&pool).await;
+
+        let rpc = mock_node(vec![
+            record("get_block_hash", json!(HEIGHT), json!("e2e0")),
+            record("get_block_hash", json!(HEIGHT + 1), json!("e2e1")),
+            record("get_block_hash", json!(HEIGHT + 2), json!("e2e2")),
+            record("get_block", json!(["e2e0"]), block(HEIGHT, &[], "e2etx0")),
+            record("get_block", json!(["e2e1"]), block(HEIGHT + 1, &[0xe2, 0xe0], "e2etx1")),
+            record("get_block", json!(["e2e2"]), block(HEIGHT + 2, &[0xe2, 0xe1], "e2etx2")),
+            record("get_processed_transaction", json!("e2etx0"), transfer(100, json!({ "type": "processed" }))),
+            record("get_processed_transaction", json!("e2etx1"), transfer(50, json!({ "type": "processed" }))),
+            record(
+                "get_processed_transaction",
+                json!("e2etx2"),
+                transfer(25, json!({ "type": "failed", "message": "insufficient lamports" })),
+            ),
+        ])
+        .await;
+
+        let state = |pool: PgPool| async move {
+            let balance: String = sqlx::query_scalar("SELECT balance::text FROM native_balances WHERE address_hex = $1")
+                .bind(hex::encode([0x72u8; 32]))
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+            let history: Vec<(i64, String)> = sqlx::query_as(
+                "SELECT block_height, balance::text FROM native_balance_history WHERE address_hex = $1 ORDER BY block_height",
+            )
+            .bind(hex::encode([0x72u8; 32]))
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+            let rows: (i64, i64, i64, i64) = sqlx::query_as(
+                r#"
+                SELECT (SELECT COUNT(*) FROM blocks WHERE height >= $1 AND transaction_count = 1 AND block_size_bytes > 0),
+                       (SELECT COUNT(*) FROM transactions WHERE block_height >= $1 AND effects_applied),
+                       (SELECT COUNT(*) FROM native_transfers n JOIN transactions t USING (txid) WHERE t.block_height >= $1),
+                       (SELECT COUNT(*) FROM account_participation WHERE txid = 'e2etx1' AND block_height <> $1 + 1)
+                "#,
+            )
+            .bind(HEIGHT)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+            (balance, history, rows)
+        };
+        let expected = (
+            "150".to_string(),
+            vec![(HEIGHT, "100".to_string()), (HEIGHT + 1, "150".to_string())],
+            (2, 2, 2, 0),
+        );
+
+        // The second block and its transaction arrive on the realtime stream first and are
+        // stored at the block's height; the bulk path then fills in the block without applying
+        // the transaction's effects again
+        assert_eq!(process_block_via_rpc(&pool, &rpc, HEIGHT).await.unwrap(), BlockOutcome::Indexed);
+        let realtime_block = rpc.get_block("e2e1", HEIGHT + 1).await.unwrap();
+        store_realtime_block(&pool, &realtime_block).await.unwrap();
+        let processed = rpc.get_processed_transaction("e2etx1").await.unwrap();
+        store_realtime_transaction(&pool, "e2etx1", &processed, realtime_block.height).await.unwrap();
+        assert_eq!(process_block_via_rpc(&pool, &rpc, HEIGHT + 1).await.unwrap(), BlockOutcome::Indexed);
+        assert_eq!(state(pool.clone()).await, expected);
+
+        // Re-indexing a stored height (heal, prefix backfill) must not apply its effects again
+        assert_eq!(process_block_via_rpc(&pool, &rpc, HEIGHT).await.unwrap(), BlockOutcome::Indexed);
+        assert_eq!(state(pool.clone()).await, expected);
+
+        // A block from a competing fork stored at a height is rolled back when the bulk path
+        // reaches that height, and the canonical block is indexed in its place
+        sqlx::query("UPDATE blocks SET hash = 'e2eorphan' WHERE height = $1").bind(HEIGHT + 1).execute(&pool).await.unwrap();
+        assert_eq!(
+            process_block_via_rpc(&pool, &rpc, HEIGHT + 1).await.unwrap(),
+            BlockOutcome::Reorged { fork_height: HEIGHT + 1 }
+        );
+        assert_eq!(process_block_via_rpc(&pool, &rpc, HEIGHT + 1).await.unwrap(), BlockOutcome::Indexed);
+        assert_eq!(state(pool.clone()).await, expected);
+
+        // A failed transfer is stored and applied as a row, but moves no balance, writes no
+        // ledger or history, and has nothing to revert when its block is rolled back
+        assert_eq!(process_block_via_rpc(&pool, &rpc, HEIGHT + 2).await.unwrap(), BlockOutcome::Indexed);
+        assert_eq!(state(pool.clone()).await, (expected.0.clone(), expected.1.clone(), (3, 3, 2, 0)));
+        reorg::rollback_from_height(&pool, HEIGHT + 2).await.unwrap();
+        assert_eq!(state(pool.clone()).await, expected);
+
+        cleanup(&pool).await;
+    }
+
+    fn account(byte: u8, lamports: i64, height: i64) -> AccountState {
+        AccountState { pubkey: hex::encode([byte; 32]), lamports, owner: hex::encode([0u8; 32]), data: vec![byte], height }
+    }
+
+    /// Rows the indexer keeps for the test heights and the 0x71..0x73 accounts, one line each.
+    async fn chain_state(pool: &PgPool) -> BTreeMap<&'static str, Vec<String>> {
+        let addresses: Vec<String> = [0x71u8, 0x72, 0x73].iter().map(|b| hex::encode([*b; 32])).collect();
+        let queries = [
+            ("blocks", "SELECT concat_ws(' ', height, hash, previous_block_hash, transaction_count) FROM blocks WHERE height >= $1"),
+            ("transactions", "SELECT concat_ws(' ', txid, block_height, effects_applied) FROM transactions WHERE block_height >= $1"),
+            ("accounts", "SELECT concat_ws(' ', pubkey, lamports, owner, encode(data, 'hex'), height) FROM accounts WHERE pubkey = ANY($2)"),
+            ("account_history", "SELECT concat_ws(' ', pubkey, height, lamports, is_deleted) FROM account_history WHERE pubkey = ANY($2)"),
+            ("native_balances", "SELECT concat_ws(' ', address_hex, balance) FROM native_balances WHERE address_hex = ANY($2)"),
+            (
+                "native_balance_history",
+                "SELECT concat_ws(' ', address_hex, block_height, balance, txid) FROM native_balance_history WHERE address_hex = ANY($2)",
+            ),
+            (
+                "native_transfers",
+                "SELECT concat_ws(' ', txid, instruction_index, block_height, lamports) FROM native_transfers WHERE block_height >= $1",
+            ),
+            (
+                "instructions",
+                "SELECT concat_ws(' ', txid, instruction_index, block_height, instruction_type) FROM instructions WHERE block_height >= $1",
+            ),
+            (
+                "account_participation",
+                "SELECT concat_ws(' ', address_hex, txid, block_height) FROM account_participation WHERE block_height >= $1",
+            ),
+            (
+                "transaction_programs",
+                "SELECT concat_ws(' ', txid, program_id) FROM transaction_programs JOIN transactions USING (txid) WHERE block_height >= $1",
+            ),
+            ("programs", "SELECT concat_ws(' ', program_id, transaction_count) FROM programs WHERE program_id = $3"),
+        ];
+        let mut state = BTreeMap::new();
+        for (table, sql) in queries {
+            let mut rows: Vec<String> = sqlx::query_scalar(sql)
+                .bind(HEIGHT)
+                .bind(&addresses)
+                .bind(hex::encode([0u8; 32]))
+                .fetch_all(pool)
+                .await
+                .unwrap();
+            rows.sort();
+            state.insert(table, rows);
+        }
+        state
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database initialized from db-init"]
+    async fn replacing_the_tip_matches_indexing_the_canonical_chain() {
+        let _db = DB.lock().await;
+        let pool = PgPool::connect(&std::env::var("DATABASE_URL").unwrap()).await.unwrap();
+        cleanup(&pool).await;
+
+        // Both nodes agree on the first block; the first node's next block is orphaned by the
+        // two blocks the second node builds on it
+        let processed = || json!({ "type": "processed" });
+        let common = vec![
+            record("get_block_hash", json!(HEIGHT), json!("c000")),
+            record("get_block", json!(["c000"]), block(HEIGHT, &[], "reorgtx0")),
+            record("get_processed_transaction", json!("reorgtx0"), transfer(100, processed())),
+        ];
+        let orphan_node = mock_node(
+            [
+                common.clone(),
+                vec![
+                    record("get_block_hash", json!(HEIGHT + 1), json!("0f01")),
+                    record("get_block", json!(["0f01"]), block(HEIGHT + 1, &[0xc0, 0x00], "reorgtxorphan")),
+                    record("get_processed_transaction", json!("reorgtxorphan"), transfer(70, processed())),
+                ],
+            ]
+            .concat(),
+        )
+        .await;
+        let canonical_node = mock_node(
+            [
+                common,
+                vec![
+                    record("get_block_hash", json!(HEIGHT + 1), json!("c001")),
+                    record("get_block_hash", json!(HEIGHT + 2), json!("c002")),
+                    record("get_block", json!(["c001"]), block(HEIGHT + 1, &[0xc0, 0x00], "reorgtx1")),
+                    record("get_block", json!(["c002"]), block(HEIGHT + 2, &[0xc0, 0x01], "reorgtx2")),
+                    record("get_processed_transaction", json!("reorgtx1"), transfer(30, processed())),
+                    record("get_processed_transaction", json!("reorgtx2"), transfer(20, processed())),
+                ],
+            ]
+            .concat(),
+        )
+        .await;
+        // Account states the account processor records along each chain
+        let canonical_states = [vec![account(0x72, 100, HEIGHT)], vec![], vec![account(0x72, 150, HEIGHT + 2)]];
+        let orphan_states = vec![account(0x72, 170, HEIGHT + 1), account(0x73, 5, HEIGHT + 1)];
+
+        for (height, states) in [(HEIGHT, &canonical_states[0]), (HEIGHT + 1, &orphan_states)] {
+            assert_eq!(process_block_via_rpc(&pool, &orphan_node, height).await.unwrap(), BlockOutcome::Indexed);
+            accounts::store_account_states(&pool, states).await.unwrap();
+        }
+
+        // The canonical tip does not extend the stored one: the orphan is rolled back, its
+        // account states fall back to the last ones below the fork and accounts it created go
+        assert_eq!(
+            process_block_via_rpc(&pool, &canonical_node, HEIGHT + 2).await.unwrap(),
+            BlockOutcome::Reorged { fork_height: HEIGHT + 1 }
+        );
+        let state = chain_state(&pool).await;
+        assert_eq!(state["blocks"].len(), 1);
+        let restored = format!("{} 100 {} 72 {}", hex::encode([0x72u8; 32]), hex::encode([0u8; 32]), HEIGHT);
+        assert_eq!(state["accounts"], vec![restored]);
+
+        for height in [HEIGHT + 1, HEIGHT + 2] {
+            assert_eq!(process_block_via_rpc(&pool, &canonical_node, height).await.unwrap(), BlockOutcome::Indexed);
+            accounts::store_account_states(&pool, &canonical_states[(height - HEIGHT) as usize]).await.unwrap();
+        }
+        let reorged = chain_state(&pool).await;
+        assert_eq!(
+            reorged["native_balances"],
+            vec![format!("{} -150", hex::encode([0x71u8; 32])), format!("{} 150", hex::encode([0x72u8; 32]))]
+        );
+
+        // The same rows as indexing the canonical chain without ever seeing the orphan
+        cleanup(&pool).await;
+        for (height, states) in (HEIGHT..).zip(&canonical_states) {
+            assert_eq!(process_block_via_rpc(&pool, &canonical_node, height).await.unwrap(), BlockOutcome::Indexed);
+            accounts::store_account_states(&pool, states).await.unwrap();
+        }
+        assert_eq!(chain_state(&pool).await, reorged);
+
+        cleanup(&pool).await;
+    }
+}
@@ -0,0 +1,56 @@
+use anyhow::Result;
+use serde_json::Value as JsonValue;
+use sqlx::PgExecutor;
+
+use decoder::decode_transaction;
+
+/// Decodes the instructions of a stored transaction and upserts one `instructions` row
+/// per instruction. The block height is taken from the `transactions` row, so the
+/// transaction must be written first; rows are removed with it via `ON DELETE CASCADE`.
+pub async fn store_instructions<'c>(executor: impl PgExecutor<'c>, txid: &str, data: &JsonValue) -> Result<usize> {
+    let decoded = decode_transaction(data);
+    if decoded.is_empty() {
+        return Ok(0);
+    }
+
+    let mut indexes = Vec::with_capacity(decoded.len());
+    let mut programs = Vec::with_capacity(decoded.len());
+    let mut types = Vec::with_capacity(decoded.len());
+    let mut args = Vec::with_capacity(decoded.len());
+    let mut accounts = Vec::with_capacity(decoded.len());
+    let mut payloads = Vec::with_capacity(decoded.len());
+    for ix in decoded {
+        indexes.push(ix.index as i32);
+        programs.push(ix.program_id_hex);
+        types.push(ix.instruction_type);
+        args.push(ix.args.unwrap_or(JsonValue::Null));
+        accounts.push(serde_json::to_value(&ix.account_roles)?);
+        payloads.push(ix.data);
+    }
+
+    let written = sqlx::query(
+        r#"
+        INSERT INTO instructions (txid, instruction_index, block_height, program_id, instruction_type, args, accounts, data)
+        SELECT t.txid, i.idx, t.block_height, i.program_id, i.instruction_type, NULLIF(i.args, 'null'::jsonb), i.accounts, i.data
+        FROM transactions t
+        CROSS JOIN UNNEST($2::int[], $3::text[], $4::text[], $5::jsonb[], $6::jsonb[], $7::bytea[])
+            AS i(idx, program_id, instruction_type, args, accounts, data)
+        WHERE t.txid = $1
+        ON CONFLICT (txid, instruction_index) DO UPDATE
+        SET block_height = EXCLUDED.block_height, program_id = EXCLUDED.program_id,
+            instruction_type = EXCLUDED.instruction_type, args = EXCLUDED.args,
+            accounts = EXCLUDED.accounts, data = EXCLUDED.data
+        "#,
+    )
+    .bind(txid)
+    .bind(&indexes)
+    .bind(&programs)
+    .bind(&types)
+    .bind(&args)
+    .bind(&accounts)
+    .bind(&payloads)
+    .execute(executor)
+    .await?
+    .rows_affected();
+    Ok(written as usize)
+}
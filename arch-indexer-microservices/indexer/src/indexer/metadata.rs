@@ -0,0 +1,281 @@
+use anyhow::Result;
+use serde_json::Value as JsonValue;
+use sqlx::{PgExecutor, Postgres, Transaction};
+use tracing::debug;
+
+use decoder::decode_transaction;
+
+// First 8 bytes of sha256("spl_token_metadata_interface:<name>"), the instruction and TLV
+// discriminators of the token metadata interface implemented by apl-token-metadata.
+const INITIALIZE: [u8; 8] = [210, 225, 30, 162, 88, 184, 77, 141];
+const UPDATE_FIELD: [u8; 8] = [221, 233, 49, 45, 181, 202, 220, 200];
+const REMOVE_KEY: [u8; 8] = [234, 18, 32, 56, 89, 141, 37, 181];
+const UPDATE_AUTHORITY: [u8; 8] = [215, 228, 166, 228, 84, 100, 86, 123];
+const STATE: [u8; 8] = [112, 132, 90, 90, 11, 88, 157, 87];
+
+/// Field targeted by an `UpdateField` instruction; `Key` is an additional metadata entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MetadataField {
+    Name,
+    Symbol,
+    Uri,
+    Key(String),
+}
+
+/// A token metadata instruction. Addresses are lowercase hex.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MetadataInstruction {
+    Initialize { name: String, symbol: String, uri: String },
+    UpdateField { field: MetadataField, value: String },
+    RemoveKey { key: String },
+    UpdateAuthority { new_authority: Option<String> },
+}
+
+/// Metadata as stored in a metadata account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenMetadata {
+    pub update_authority: Option<String>,
+    pub mint: String,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub additional_metadata: Vec<(String, String)>,
+}
+
+/// Borsh reader over instruction and account data.
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        if self.data.len() < n {
+            return None;
+        }
+        let (head, rest) = self.data.split_at(n);
+        self.data = rest;
+        Some(head)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|b| b[0])
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take(4).map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+    }
+
+    fn string(&mut self) -> Option<String> {
+        let len = self.u32()? as usize;
+        String::from_utf8(self.take(len)?.to_vec()).ok()
+    }
+
+    /// `OptionalNonZeroPubkey`: 32 bytes, all zeros meaning none
+    fn optional_pubkey(&mut self) -> Option<Option<String>> {
+        let key = self.take(32)?;
+        Some(key.iter().any(|b| *b != 0).then(|| hex::encode(key)))
+    }
+}
+
+/// Unpacks token metadata instruction data. `Emit` and unknown discriminators yield `None`.
+pub fn unpack_instruction(data: &[u8]) -> Option<MetadataInstruction> {
+    let discriminator: [u8; 8] = data.get(..8)?.try_into().ok()?;
+    let mut r = Reader { data: &data[8..] };
+    let instruction = match discriminator {
+        INITIALIZE => MetadataInstruction::Initialize { name: r.string()?, symbol: r.string()?, uri: r.string()? },
+        UPDATE_FIELD => {
+            let field = match r.u8()? {
+                0 => MetadataField::Name,
+                1 => MetadataField::Symbol,
+                2 => MetadataField::Uri,
+                3 => MetadataField::Key(r.string()?),
+                _ => return None,
+            };
+            MetadataInstruction::UpdateField { field, value: r.string()? }
+        }
+        REMOVE_KEY => {
+            let _idempotent = r.u8()?;
+            MetadataInstruction::RemoveKey { key: r.string()? }
+        }
+        UPDATE_AUTHORITY => MetadataInstruction::UpdateAuthority { new_authority: r.optional_pubkey()? },
+        _ => return None,
+    };
+    Some(instruction)
+}
+
+/// Reads the metadata entry of a TLV-encoded account (`[discriminator][u32 length][value]`),
+/// either a dedicated metadata account or a mint carrying its own metadata.
+pub fn unpack_account(data: &[u8]) -> Option<TokenMetadata> {
+    let start = data.windows(8).position(|w| w == STATE)? + 8;
+    let mut r = Reader { data: &data[start..] };
+    let len = r.u32()? as usize;
+    let mut r = Reader { data: r.take(len)? };
+    let update_authority = r.optional_pubkey()?;
+    let mint = hex::encode(r.take(32)?);
+    let (name, symbol, uri) = (r.string()?, r.string()?, r.string()?);
+    let additional_metadata = (0..r.u32()?).map(|_| Some((r.string()?, r.string()?))).collect::<Option<Vec<_>>>()?;
+    Some(TokenMetadata { update_authority, mint, name, symbol, uri, additional_metadata })
+}
+
+/// Applies the metadata instructions of one runtime transaction to `token_metadata`, keyed
+/// by mint. Initialize accounts are `[metadata, update_authority, mint, mint_authority]`;
+/// later instructions address the row by its metadata account (first account).
+pub async fn apply_metadata_instructions(tx: &mut Transaction<'_, Postgres>, txid: &str, data: &JsonValue) -> Result<usize> {
+    let mut applied = 0;
+    for ix in decode_transaction(data) {
+        let Some(instruction) = unpack_instruction(&ix.data) else { continue };
+        let accounts: Vec<String> = ix.account_roles.into_iter().map(|r| r.address_hex).collect();
+        let Some(metadata) = accounts.first() else { continue };
+        debug!("token metadata {:?} in {} (metadata account {})", instruction, txid, metadata);
+
+        match &instruction {
+            MetadataInstruction::Initialize { name, symbol, uri } => {
+                let (Some(authority), Some(mint)) = (accounts.get(1), accounts.get(2)) else { continue };
+                sqlx::query(
+                    r#"
+                    INSERT INTO token_metadata (mint_address, metadata_address, name, symbol, uri, update_authority, additional_metadata, block_height, updated_at)
+                    SELECT $1, $2, $3, $4, $5, $6, '{}'::jsonb, t.block_height, CURRENT_TIMESTAMP
+                    FROM transactions t WHERE t.txid = $7
+                    ON CONFLICT (mint_address) DO UPDATE
+                    SET metadata_address = EXCLUDED.metadata_address, name = EXCLUDED.name, symbol = EXCLUDED.symbol,
+                        uri = EXCLUDED.uri, update_authority = EXCLUDED.update_authority,
+                        additional_metadata = '{}'::jsonb, block_height = EXCLUDED.block_height, updated_at = CURRENT_TIMESTAMP
+                    "#,
+                )
+                .bind(mint)
+                .bind(metadata)
+                .bind(name)
+                .bind(symbol)
+                .bind(uri)
+                .bind(authority)
+                .bind(txid)
+                .execute(&mut **tx)
+                .await?;
+            }
+            MetadataInstruction::UpdateField { field, value } => {
+                let (set, key) = match field {
+                    MetadataField::Name => ("name = $3", None),
+                    MetadataField::Symbol => ("symbol = $3", None),
+                    MetadataField::Uri => ("uri = $3", None),
+                    MetadataField::Key(key) => ("additional_metadata = additional_metadata || jsonb_build_object($4::text, $3::text)", Some(key)),
+                };
+                update_metadata(tx, txid, metadata, set, Some(value), key).await?;
+            }
+            MetadataInstruction::RemoveKey { key } => {
+                update_metadata(tx, txid, metadata, "additional_metadata = additional_metadata - $4::text", None, Some(key)).await?;
+            }
+            MetadataInstruction::UpdateAuthority { new_authority } => {
+                update_metadata(tx, txid, metadata, "update_authority = $3", new_authority.as_ref(), None).await?;
+            }
+        }
+        applied += 1;
+    }
+    Ok(applied)
+}
+
+/// Updates the `token_metadata` row of metadata account `$2` with `set`, which may use the
+/// `$3` value and `$4` additional-metadata key, stamped with the height of transaction `$1`.
+async fn update_metadata(
+    tx: &mut Transaction<'_, Postgres>,
+    txid: &str,
+    metadata: &str,
+    set: &str,
+    value: Option<&String>,
+    key: Option<&String>,
+) -> Result<()> {
+    let sql = format!(
+        "UPDATE token_metadata SET {}, \
+         block_height = COALESCE((SELECT block_height FROM transactions WHERE txid = $1), block_height), \
+         updated_at = CURRENT_TIMESTAMP \
+         WHERE metadata_address = $2",
+        set
+    );
+    sqlx::query(&sql).bind(txid).bind(metadata).bind(value).bind(key).execute(&mut **tx).await?;
+    Ok(())
+}
+
+/// Upserts the metadata read from a metadata account's data, replacing whatever the
+/// instruction path derived for the same mint.
+pub async fn store_metadata_account<'c>(executor: impl PgExecutor<'c>, address_hex: &str, metadata: &TokenMetadata, height: i64) -> Result<()> {
+    let additional: serde_json::Map<String, JsonValue> =
+        metadata.additional_metadata.iter().map(|(k, v)| (k.clone(), JsonValue::String(v.clone()))).collect();
+    sqlx::query(
+        r#"
+        INSERT INTO token_metadata (mint_address, metadata_address, name, symbol, uri, update_authority, additional_metadata, block_height, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, CURRENT_TIMESTAMP)
+        ON CONFLICT (mint_address) DO UPDATE
+        SET metadata_address = EXCLUDED.metadata_address, name = EXCLUDED.name, symbol = EXCLUDED.symbol,
+            uri = EXCLUDED.uri, update_authority = EXCLUDED.update_authority,
+            additional_metadata = EXCLUDED.additional_metadata, block_height = EXCLUDED.block_height, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(&metadata.mint)
+    .bind(address_hex)
+    .bind(&metadata.name)
+    .bind(&metadata.symbol)
+    .bind(&metadata.uri)
+    .bind(&metadata.update_authority)
+    .bind(JsonValue::Object(additional))
+    .bind(height)
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn borsh_str(out: &mut Vec<u8>, s: &str) {
+        out.extend_from_slice(&(s.len() as u32).to_le_bytes());
+        out.extend_from_slice(s.as_bytes());
+    }
+
+    #[test]
+    fn instructions_and_account_state_are_unpacked() {
+        let mut init = INITIALIZE.to_vec();
+        for s in ["Arch Dollar", "ADOL", "https://example.com/adol.json"] {
+            borsh_str(&mut init, s);
+        }
+        assert_eq!(
+            unpack_instruction(&init),
+            Some(MetadataInstruction::Initialize {
+                name: "Arch Dollar".into(),
+                symbol: "ADOL".into(),
+                uri: "https://example.com/adol.json".into()
+            })
+        );
+
+        let mut update = UPDATE_FIELD.to_vec();
+        update.push(3);
+        borsh_str(&mut update, "website");
+        borsh_str(&mut update, "arch.network");
+        assert_eq!(
+            unpack_instruction(&update),
+            Some(MetadataInstruction::UpdateField { field: MetadataField::Key("website".into()), value: "arch.network".into() })
+        );
+
+        let mut revoke = UPDATE_AUTHORITY.to_vec();
+        revoke.extend_from_slice(&[0u8; 32]);
+        assert_eq!(unpack_instruction(&revoke), Some(MetadataInstruction::UpdateAuthority { new_authority: None }));
+        assert_eq!(unpack_instruction(&init[..12]), None);
+
+        let mut value = [5u8; 32].to_vec();
+        value.extend_from_slice(&[6u8; 32]);
+        for s in ["Arch Dollar", "ADOL", "https://example.com/adol.json"] {
+            borsh_str(&mut value, s);
+        }
+        value.extend_from_slice(&1u32.to_le_bytes());
+        borsh_str(&mut value, "website");
+        borsh_str(&mut value, "arch.network");
+        let mut account = STATE.to_vec();
+        account.extend_from_slice(&(value.len() as u32).to_le_bytes());
+        account.extend_from_slice(&value);
+
+        let metadata = unpack_account(&account).unwrap();
+        assert_eq!(metadata.update_authority, Some(hex::encode([5u8; 32])));
+        assert_eq!(metadata.mint, hex::encode([6u8; 32]));
+        assert_eq!(metadata.symbol, "ADOL");
+        assert_eq!(metadata.additional_metadata, vec![("website".to_string(), "arch.network".to_string())]);
+    }
+}
@@ -1,3 +1,15 @@
+pub mod accounts;
+pub mod collection;
+pub mod derived;
+pub mod dkg;
 pub mod hybrid_sync;
+pub mod instructions;
+pub mod metadata;
+pub mod native;
+pub mod participation;
+pub mod programs;
+pub mod reorg;
+pub mod rollback;
+pub mod tokens;
 
-pub use hybrid_sync::HybridSync;
+pub use hybrid_sync::{index_block, BlockOutcome, HybridSync};
@@ -0,0 +1,268 @@
+use anyhow::Result;
+use serde_json::Value as JsonValue;
+use sqlx::{PgExecutor, Postgres, Transaction};
+use std::collections::BTreeMap;
+
+use crate::indexer::collection::{parse_transaction, ArchInstruction, SystemInstruction};
+
+/// How lamports moved: a System `Transfer`, or the funding of a new account by `CreateAccount`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NativeTransferKind {
+    Transfer,
+    CreateFunding,
+}
+
+impl NativeTransferKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NativeTransferKind::Transfer => "transfer",
+            NativeTransferKind::CreateFunding => "create_funding",
+        }
+    }
+}
+
+/// One lamport movement between two accounts (hex addresses).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NativeTransfer {
+    pub index: usize,
+    pub kind: NativeTransferKind,
+    pub source: String,
+    pub destination: String,
+    pub lamports: u64,
+}
+
+/// System Program transfers (tag 4) and account creations (tag 0) of a runtime transaction,
+/// in instruction order. Accounts are `[source|funder, destination|new account, ...]`.
+pub fn native_transfers(data: &JsonValue) -> Vec<NativeTransfer> {
+    parse_transaction(data)
+        .into_iter()
+        .filter_map(|parsed| {
+            let (kind, source, destination, lamports) = match parsed.instruction {
+                ArchInstruction::System(SystemInstruction::CreateAccount { funder, account, lamports, .. }) => {
+                    (NativeTransferKind::CreateFunding, funder, account, lamports)
+                }
+                ArchInstruction::System(SystemInstruction::Transfer { source, destination, lamports }) => {
+                    (NativeTransferKind::Transfer, source, destination, lamports)
+                }
+                _ => return None,
+            };
+            Some(NativeTransfer { index: parsed.index, kind, source, destination, lamports })
+        })
+        .collect()
+}
+
+/// Upserts one `native_transfers` row per System transfer or account creation of a stored
+/// transaction. Like `store_instructions`, the block height comes from the `transactions`
+/// row and rows are removed with it via `ON DELETE CASCADE`.
+pub async fn store_native_transfers<'c>(executor: impl PgExecutor<'c>, txid: &str, data: &JsonValue) -> Result<usize> {
+    let transfers = native_transfers(data);
+    if transfers.is_empty() {
+        return Ok(0);
+    }
+
+    let mut indexes = Vec::with_capacity(transfers.len());
+    let mut kinds = Vec::with_capacity(transfers.len());
+    let mut sources = Vec::with_capacity(transfers.len());
+    let mut destinations = Vec::with_capacity(transfers.len());
+    let mut lamports = Vec::with_capacity(transfers.len());
+    for t in transfers {
+        indexes.push(t.index as i32);
+        kinds.push(t.kind.as_str());
+        sources.push(t.source);
+        destinations.push(t.destination);
+        lamports.push(t.lamports.to_string());
+    }
+
+    let written = sqlx::query(
+        r#"
+        INSERT INTO native_transfers (txid, instruction_index, block_height, block_time, kind, source_address, destination_address, lamports)
+        SELECT t.txid, i.idx, t.block_height, b.timestamp, i.kind, i.source, i.destination, i.lamports::numeric
+        FROM transactions t
+        LEFT JOIN blocks b ON b.height = t.block_height
+        CROSS JOIN UNNEST($2::int[], $3::text[], $4::text[], $5::text[], $6::text[])
+            AS i(idx, kind, source, destination, lamports)
+        WHERE t.txid = $1
+        ON CONFLICT (txid, instruction_index) DO UPDATE
+        SET block_height = EXCLUDED.block_height, block_time = EXCLUDED.block_time, kind = EXCLUDED.kind,
+            source_address = EXCLUDED.source_address, destination_address = EXCLUDED.destination_address,
+            lamports = EXCLUDED.lamports
+        "#,
+    )
+    .bind(txid)
+    .bind(&indexes)
+    .bind(&kinds)
+    .bind(&sources)
+    .bind(&destinations)
+    .bind(&lamports)
+    .execute(executor)
+    .await?
+    .rows_affected();
+    Ok(written as usize)
+}
+
+/// Net lamport change per address (hex) from a transaction's System transfers and account
+/// creations. An address that sends to itself nets to zero but is still listed.
+pub fn native_balance_deltas(data: &JsonValue) -> BTreeMap<String, i128> {
+    let mut deltas = BTreeMap::new();
+    for t in native_transfers(data) {
+        *deltas.entry(t.source).or_insert(0i128) -= t.lamports as i128;
+        *deltas.entry(t.destination).or_insert(0i128) += t.lamports as i128;
+    }
+    deltas
+}
+
+/// Applies a transaction's lamport movements to `native_balances`.
+pub async fn apply_native_balances<'c>(executor: impl PgExecutor<'c>, data: &JsonValue) -> Result<usize> {
+    adjust_native_balances(executor, data, 1).await
+}
+
+/// Reverses what `apply_native_balances` did for the same transaction, e.g. when its block
+/// is orphaned by a reorg.
+pub async fn revert_native_balances<'c>(executor: impl PgExecutor<'c>, data: &JsonValue) -> Result<usize> {
+    adjust_native_balances(executor, data, -1).await
+}
+
+async fn adjust_native_balances<'c>(executor: impl PgExecutor<'c>, data: &JsonValue, direction: i128) -> Result<usize> {
+    let deltas = native_balance_deltas(data);
+    if deltas.is_empty() {
+        return Ok(0);
+    }
+
+    let (addresses, amounts): (Vec<String>, Vec<String>) =
+        deltas.into_iter().map(|(address, delta)| (address, (delta * direction).to_string())).unzip();
+    let written = sqlx::query(
+        r#"
+        INSERT INTO native_balances (address_hex, balance, updated_at)
+        SELECT d.address, d.delta::numeric, CURRENT_TIMESTAMP
+        FROM UNNEST($1::text[], $2::text[]) AS d(address, delta)
+        ON CONFLICT (address_hex) DO UPDATE
+        SET balance = native_balances.balance + EXCLUDED.balance, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(&addresses)
+    .bind(&amounts)
+    .execute(executor)
+    .await?
+    .rows_affected();
+    Ok(written as usize)
+}
+
+/// Records the post-transaction balance of every address moved by a System transfer in
+/// `native_balance_history`, one row per address and block (the last transaction of the
+/// block names the row). The balance is the address's latest history row at or below the
+/// transaction's height plus this transaction's delta, so it does not depend on which other
+/// blocks happen to be indexed already; rows of later blocks indexed earlier are shifted by
+/// the same delta. Must run in the DB transaction that writes the transaction row.
+pub async fn record_native_balance_history(tx: &mut Transaction<'_, Postgres>, txid: &str, data: &JsonValue) -> Result<usize> {
+    let (addresses, deltas) = history_deltas(data, 1);
+    if addresses.is_empty() {
+        return Ok(0);
+    }
+
+    let written = sqlx::query(
+        r#"
+        INSERT INTO native_balance_history (address_hex, block_height, block_time, balance, txid)
+        SELECT d.address, t.block_height, b.timestamp,
+               COALESCE(
+                   (SELECT h.balance FROM native_balance_history h
+                    WHERE h.address_hex = d.address AND h.block_height <= t.block_height
+                    ORDER BY h.block_height DESC LIMIT 1),
+                   0
+               ) + d.delta::numeric,
+               t.txid
+        FROM transactions t
+        LEFT JOIN blocks b ON b.height = t.block_height
+        CROSS JOIN UNNEST($2::text[], $3::text[]) AS d(address, delta)
+        WHERE t.txid = $1
+        ON CONFLICT (address_hex, block_height) DO UPDATE
+        SET block_time = EXCLUDED.block_time, balance = EXCLUDED.balance, txid = EXCLUDED.txid
+        "#,
+    )
+    .bind(txid)
+    .bind(&addresses)
+    .bind(&deltas)
+    .execute(&mut **tx)
+    .await?
+    .rows_affected();
+
+    shift_native_balance_history(tx, txid, &addresses, &deltas, ">").await?;
+    Ok(written as usize)
+}
+
+/// Takes a transaction's delta back out of the history rows at and above its height, as
+/// when the node rolls it back. The rows stay: other transactions of the same block may
+/// have moved the same address.
+pub async fn revert_native_balance_history(tx: &mut Transaction<'_, Postgres>, txid: &str, data: &JsonValue) -> Result<u64> {
+    let (addresses, deltas) = history_deltas(data, -1);
+    if addresses.is_empty() {
+        return Ok(0);
+    }
+    shift_native_balance_history(tx, txid, &addresses, &deltas, ">=").await
+}
+
+fn history_deltas(data: &JsonValue, direction: i128) -> (Vec<String>, Vec<String>) {
+    native_balance_deltas(data).into_iter().map(|(address, delta)| (address, (delta * direction).to_string())).unzip()
+}
+
+async fn shift_native_balance_history(
+    tx: &mut Transaction<'_, Postgres>,
+    txid: &str,
+    addresses: &[String],
+    deltas: &[String],
+    heights: &str,
+) -> Result<u64> {
+    let shifted = sqlx::query(&format!(
+        r#"
+        UPDATE native_balance_history h
+        SET balance = h.balance + d.delta::numeric
+        FROM transactions t, UNNEST($2::text[], $3::text[]) AS d(address, delta)
+        WHERE t.txid = $1 AND h.address_hex = d.address AND h.block_height {} t.block_height
+          AND d.delta::numeric <> 0
+        "#,
+        heights
+    ))
+    .bind(txid)
+    .bind(addresses)
+    .bind(deltas)
+    .execute(&mut **tx)
+    .await?
+    .rows_affected();
+    Ok(shifted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use decoder::SYSTEM_PROGRAM_BASE58;
+    use serde_json::json;
+
+    #[test]
+    fn transfers_account_creations_and_balance_deltas_are_extracted() {
+        let system = bs58::decode(SYSTEM_PROGRAM_BASE58).into_vec().unwrap();
+        let mut transfer = 4u32.to_le_bytes().to_vec();
+        transfer.extend_from_slice(&2_500u64.to_le_bytes());
+        let mut create = 0u32.to_le_bytes().to_vec();
+        create.extend_from_slice(&900u64.to_le_bytes());
+        create.extend_from_slice(&165u64.to_le_bytes());
+        create.extend_from_slice(&[7u8; 32]);
+        let tx = json!({"message": {
+            "header": {"num_required_signatures": 1, "num_readonly_signed_accounts": 0, "num_readonly_unsigned_accounts": 1},
+            "account_keys": [vec![1u8; 32], vec![2u8; 32], system],
+            "instructions": [
+                {"program_id_index": 2, "accounts": [0, 1], "data": transfer},
+                {"program_id_index": 2, "accounts": [1], "data": [2, 0, 0, 0]},
+                {"program_id_index": 2, "accounts": [0, 1], "data": create},
+            ]
+        }});
+
+        let (a, b) = (hex::encode([1u8; 32]), hex::encode([2u8; 32]));
+        assert_eq!(
+            native_transfers(&tx),
+            vec![
+                NativeTransfer { index: 0, kind: NativeTransferKind::Transfer, source: a.clone(), destination: b.clone(), lamports: 2_500 },
+                NativeTransfer { index: 2, kind: NativeTransferKind::CreateFunding, source: a.clone(), destination: b.clone(), lamports: 900 },
+            ]
+        );
+        assert_eq!(native_balance_deltas(&tx), BTreeMap::from([(a, -3_400), (b, 3_400)]));
+    }
+}
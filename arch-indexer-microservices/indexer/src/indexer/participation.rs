@@ -0,0 +1,62 @@
+use anyhow::Result;
+use serde_json::Value as JsonValue;
+use sqlx::PgExecutor;
+
+use decoder::key_to_hex;
+
+/// Addresses (hex, sorted, deduplicated) that take part in a runtime transaction. Every
+/// instruction account indexes into `account_keys`, so the keys alone cover them.
+pub fn account_participants(data: &JsonValue) -> Vec<String> {
+    let keys = data
+        .get("message")
+        .and_then(|m| m.get("account_keys").or_else(|| m.get("keys")))
+        .and_then(|v| v.as_array());
+    let mut addresses: Vec<String> = keys.into_iter().flatten().map(key_to_hex).filter(|a| !a.is_empty()).collect();
+    addresses.sort();
+    addresses.dedup();
+    addresses
+}
+
+/// Upserts one `account_participation` row per participant of a stored transaction. The
+/// block height and time come from the `transactions` row, so a transaction first seen in
+/// realtime is moved to its real height when the block path writes it.
+pub async fn store_account_participation<'c>(executor: impl PgExecutor<'c>, txid: &str, data: &JsonValue) -> Result<usize> {
+    let addresses = account_participants(data);
+    if addresses.is_empty() {
+        return Ok(0);
+    }
+
+    let written = sqlx::query(
+        r#"
+        INSERT INTO account_participation (address_hex, txid, block_height, created_at)
+        SELECT a.address, t.txid, t.block_height, t.created_at
+        FROM transactions t
+        CROSS JOIN UNNEST($2::text[]) AS a(address)
+        WHERE t.txid = $1
+        ON CONFLICT (address_hex, txid) DO UPDATE
+        SET block_height = EXCLUDED.block_height
+        "#,
+    )
+    .bind(txid)
+    .bind(&addresses)
+    .execute(executor)
+    .await?
+    .rows_affected();
+    Ok(written as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn participants_are_hex_and_deduplicated() {
+        let payer = vec![9u8; 32];
+        let tx = json!({"message": {
+            "account_keys": [payer.clone(), bs58::encode(&payer).into_string(), vec![3u8; 32]],
+            "instructions": [{"program_id_index": 2, "accounts": [0, 1], "data": []}]
+        }});
+        assert_eq!(account_participants(&tx), vec![hex::encode([3u8; 32]), hex::encode([9u8; 32])]);
+    }
+}
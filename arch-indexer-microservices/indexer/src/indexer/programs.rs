@@ -0,0 +1,160 @@
+use anyhow::Result;
+use serde_json::Value as JsonValue;
+use sqlx::PgExecutor;
+
+use decoder::{key_to_hex, APL_ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_BASE58, APL_TOKEN_PROGRAM_BASE58};
+
+/// Program id the APL token program was deployed under before it moved to its label id
+pub(crate) const LEGACY_APL_TOKEN_PROGRAM_BASE58: &str = "7ZMyUmgbNckx7G5BCrdmX2XUasjDAk5uhcMpDbUDxHQ3";
+const SPL_TOKEN_PROGRAM_BASE58: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+const SPL_ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_BASE58: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
+
+fn base58_to_hex(id: &str) -> String {
+    hex::encode(bs58::decode(id).into_vec().unwrap_or_default())
+}
+
+/// Maps aliases of well-known programs onto one id (hex): the legacy APL token id, and
+/// ids that are ASCII labels such as `apl-token...` or `spl-associated-token-account...`.
+/// Any other id is returned lowercased.
+pub fn canonical_program_id(program_hex: &str) -> String {
+    let program_hex = program_hex.to_lowercase();
+    if program_hex == base58_to_hex(LEGACY_APL_TOKEN_PROGRAM_BASE58) {
+        return base58_to_hex(APL_TOKEN_PROGRAM_BASE58);
+    }
+    let label = hex::decode(&program_hex).ok().and_then(|bytes| String::from_utf8(bytes).ok()).unwrap_or_default();
+    let has_label = |dashed: &str| label.starts_with(dashed) || label.starts_with(&dashed.replace('-', "_"));
+    if has_label("spl-token") {
+        base58_to_hex(SPL_TOKEN_PROGRAM_BASE58)
+    } else if has_label("apl-token") {
+        base58_to_hex(APL_TOKEN_PROGRAM_BASE58)
+    } else if has_label("spl-associated-token-account") {
+        base58_to_hex(SPL_ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_BASE58)
+    } else if has_label("apl-associated-token-account") {
+        base58_to_hex(APL_ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_BASE58)
+    } else {
+        program_hex
+    }
+}
+
+/// Canonical ids (hex, sorted, deduplicated) of the programs a runtime transaction invokes,
+/// plus any program named in the node's `accounts_tags`. Instructions may carry either
+/// `program_id_index` into `account_keys` or an explicit `program_id`.
+pub fn transaction_program_ids(data: &JsonValue, accounts_tags: Option<&[JsonValue]>) -> Vec<String> {
+    let message = data.get("message");
+    let keys = message
+        .and_then(|m| m.get("account_keys").or_else(|| m.get("keys")))
+        .and_then(|v| v.as_array());
+    let instructions = message
+        .and_then(|m| m.get("instructions").or_else(|| m.get("compiled_instructions")))
+        .and_then(|v| v.as_array());
+
+    let mut ids: Vec<String> = Vec::new();
+    for ins in instructions.into_iter().flatten() {
+        let program = match ins.get("program_id") {
+            Some(JsonValue::Object(obj)) => obj.get("pubkey"),
+            Some(v) => Some(v),
+            None => ins
+                .get("program_id_index")
+                .and_then(|v| v.as_u64())
+                .and_then(|i| keys.and_then(|k| k.get(i as usize))),
+        };
+        if let Some(program) = program {
+            ids.push(key_to_hex(program));
+        }
+    }
+    for tag in accounts_tags.into_iter().flatten() {
+        if let Some(program) = tag.get("program_id") {
+            ids.push(key_to_hex(program));
+        }
+    }
+
+    let mut ids: Vec<String> = ids.into_iter().filter(|id| !id.is_empty()).map(|id| canonical_program_id(&id)).collect();
+    ids.sort();
+    ids.dedup();
+    ids
+}
+
+/// Links a stored transaction to the programs it invokes and upserts those programs.
+/// `transaction_count` only grows for links that did not exist yet, so writing the same
+/// transaction again (e.g. from both the realtime and the block path) counts it once.
+pub async fn store_transaction_programs<'c>(executor: impl PgExecutor<'c>, txid: &str, program_ids: &[String]) -> Result<usize> {
+    if program_ids.is_empty() {
+        return Ok(0);
+    }
+
+    let written = sqlx::query(
+        r#"
+        WITH linked AS (
+            INSERT INTO transaction_programs (txid, program_id)
+            SELECT $1, p FROM UNNEST($2::text[]) AS p
+            ON CONFLICT DO NOTHING
+            RETURNING program_id
+        )
+        INSERT INTO programs (program_id, first_seen_at, last_seen_at, transaction_count)
+        SELECT p, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP, CASE WHEN l.program_id IS NULL THEN 0 ELSE 1 END
+        FROM UNNEST($2::text[]) AS p
+        LEFT JOIN linked l ON l.program_id = p
+        ON CONFLICT (program_id) DO UPDATE
+        SET last_seen_at = CURRENT_TIMESTAMP,
+            transaction_count = programs.transaction_count + EXCLUDED.transaction_count
+        "#,
+    )
+    .bind(txid)
+    .bind(program_ids)
+    .execute(executor)
+    .await?
+    .rows_affected();
+    Ok(written as usize)
+}
+
+/// Removes a transaction's program links and takes it out of those programs'
+/// `transaction_count`, undoing `store_transaction_programs` for a transaction that stays
+/// stored (e.g. one the node rolled back).
+pub async fn remove_transaction_programs<'c>(executor: impl PgExecutor<'c>, txid: &str) -> Result<usize> {
+    let removed = sqlx::query(
+        r#"
+        WITH unlinked AS (
+            DELETE FROM transaction_programs WHERE txid = $1
+            RETURNING program_id
+        )
+        UPDATE programs p
+        SET transaction_count = GREATEST(p.transaction_count - 1, 0)
+        FROM unlinked u
+        WHERE p.program_id = u.program_id
+        "#,
+    )
+    .bind(txid)
+    .execute(executor)
+    .await?
+    .rows_affected();
+    Ok(removed as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn program_ids_are_resolved_and_canonicalized() {
+        let apl_token = base58_to_hex(APL_TOKEN_PROGRAM_BASE58);
+        let label = b"apl-token-v2\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0".to_vec();
+        let tx = json!({"message": {
+            "account_keys": [vec![1u8; 32], label, bs58::decode(LEGACY_APL_TOKEN_PROGRAM_BASE58).into_vec().unwrap()],
+            "instructions": [
+                {"program_id_index": 1, "accounts": [0], "data": []},
+                {"program_id_index": 2, "accounts": [0], "data": []},
+                {"program_id": "11111111111111111111111111111111", "accounts": [], "data": []},
+            ]
+        }});
+        let tags = [json!({"program_id": "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr"})];
+
+        let mut expected = vec![
+            apl_token,
+            hex::encode([0u8; 32]),
+            base58_to_hex("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr"),
+        ];
+        expected.sort();
+        assert_eq!(transaction_program_ids(&tx, Some(&tags)), expected);
+    }
+}
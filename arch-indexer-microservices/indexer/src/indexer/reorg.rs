@@ -0,0 +1,216 @@
+use anyhow::Result;
+use serde_json::Value as JsonValue;
+use sqlx::{PgPool, Postgres, Row, Transaction};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::arch_rpc::ArchRpcClient;
+use crate::indexer::{derived, metadata};
+
+/// Rows removed while rolling the stored chain back to a common ancestor.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RollbackSummary {
+    pub blocks: u64,
+    pub transactions: u64,
+}
+
+fn max_reorg_depth() -> i64 {
+    std::env::var("ARCH_MAX_REORG_DEPTH")
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(1_000)
+}
+
+async fn stored_hash(pool: &PgPool, height: i64) -> Result<Option<String>> {
+    let hash: Option<String> = sqlx::query_scalar("SELECT hash FROM blocks WHERE height = $1")
+        .bind(height)
+        .fetch_optional(pool)
+        .await?;
+    Ok(hash)
+}
+
+/// Whether a block other than `hash` is already stored at `height`.
+pub async fn replaces_stored_block(pool: &PgPool, height: i64, hash: &str) -> Result<bool> {
+    Ok(stored_hash(pool, height).await?.is_some_and(|stored| !stored.eq_ignore_ascii_case(hash)))
+}
+
+/// Checks a freshly fetched block against the parent we have stored at `height - 1`.
+///
+/// Returns `None` when the parent matches (or is not indexed yet). On a mismatch, walks
+/// back using the node's canonical hashes until it finds a stored block that agrees and
+/// returns the first height above that common ancestor.
+pub async fn find_fork_height(
+    pool: &PgPool,
+    rpc: &Arc<ArchRpcClient>,
+    height: i64,
+    previous_block_hash: Option<&str>,
+) -> Result<Option<i64>> {
+    if height == 0 { return Ok(None); }
+    let Some(prev) = previous_block_hash.filter(|s| !s.is_empty()) else { return Ok(None) };
+    let Some(parent) = stored_hash(pool, height - 1).await? else { return Ok(None) };
+    if parent.eq_ignore_ascii_case(prev) {
+        return Ok(None);
+    }
+
+    warn!(
+        "🔀 Reorg detected at height {}: stored parent {} != previous_block_hash {}",
+        height, parent, prev
+    );
+
+    let max_depth = max_reorg_depth();
+    let mut h = height - 1;
+    while h >= 0 {
+        if height - h > max_depth {
+            return Err(anyhow::anyhow!(
+                "Reorg at height {} is deeper than ARCH_MAX_REORG_DEPTH={}",
+                height,
+                max_depth
+            ));
+        }
+        // Gaps carry no data to roll back; keep walking until a stored block is found
+        if let Some(stored) = stored_hash(pool, h).await? {
+            let canonical = rpc.get_block_hash(h).await?;
+            if stored.eq_ignore_ascii_case(&canonical) {
+                info!("🔀 Common ancestor found at height {}", h);
+                return Ok(Some(h + 1));
+            }
+        }
+        h -= 1;
+    }
+    Ok(Some(0))
+}
+
+/// Deletes every block at or above `fork_height` together with its transactions and
+/// derived rows, reverting balance effects so the canonical chain can be re-indexed.
+///
+/// Native balance and token instruction effects are reversed with
+/// `derived::revert_transaction` for transactions whose effects are applied, in the reverse
+/// of the order they were applied in. Account states and token
+/// metadata written at or above the fork are dropped too (see `restore_accounts_below`).
+pub async fn rollback_from_height(pool: &PgPool, fork_height: i64) -> Result<RollbackSummary> {
+    let mut tx = pool.begin().await?;
+
+    let rows = sqlx::query(
+        r#"
+        SELECT txid, data FROM transactions
+        WHERE block_height >= $1 AND effects_applied
+        ORDER BY block_height DESC, apply_order DESC NULLS LAST
+        "#,
+    )
+    .bind(fork_height)
+    .fetch_all(&mut *tx)
+    .await?;
+    for row in &rows {
+        let txid: String = row.try_get("txid")?;
+        let data: JsonValue = row.try_get("data")?;
+        derived::revert_transaction(&mut tx, &txid, &data).await?;
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE programs p
+        SET transaction_count = GREATEST(p.transaction_count - c.cnt, 0)
+        FROM (
+            SELECT tp.program_id, COUNT(*) AS cnt
+            FROM transaction_programs tp
+            JOIN transactions t ON t.txid = tp.txid
+            WHERE t.block_height >= $1
+            GROUP BY tp.program_id
+        ) c
+        WHERE p.program_id = c.program_id
+        "#,
+    )
+    .bind(fork_height)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query("DELETE FROM account_participation WHERE block_height >= $1")
+        .bind(fork_height)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("DELETE FROM native_balance_history WHERE block_height >= $1")
+        .bind(fork_height)
+        .execute(&mut *tx)
+        .await?;
+    restore_accounts_below(&mut tx, fork_height).await?;
+    sqlx::query(
+        "DELETE FROM transaction_programs WHERE txid IN (SELECT txid FROM transactions WHERE block_height >= $1)",
+    )
+    .bind(fork_height)
+    .execute(&mut *tx)
+    .await?;
+    let transactions = sqlx::query("DELETE FROM transactions WHERE block_height >= $1")
+        .bind(fork_height)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    let blocks = sqlx::query("DELETE FROM blocks WHERE height >= $1")
+        .bind(fork_height)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+    tx.commit().await?;
+    warn!(
+        "🔀 Rolled back {} blocks and {} transactions from height {}",
+        blocks, transactions, fork_height
+    );
+    Ok(RollbackSummary { blocks, transactions })
+}
+
+/// Drops the account states and token metadata recorded at or above `fork_height`. Each
+/// affected account falls back to its latest state below the fork in `account_history`
+/// (and the token metadata held in it); accounts first seen in orphaned blocks, and
+/// metadata with no earlier metadata account state, are removed until the canonical chain
+/// writes them again.
+async fn restore_accounts_below(tx: &mut Transaction<'_, Postgres>, fork_height: i64) -> Result<()> {
+    let metadata_addresses: Vec<String> =
+        sqlx::query_scalar("DELETE FROM token_metadata WHERE block_height >= $1 RETURNING metadata_address")
+            .bind(fork_height)
+            .fetch_all(&mut **tx)
+            .await?;
+    let mut pubkeys: Vec<String> = sqlx::query_scalar("DELETE FROM account_history WHERE height >= $1 RETURNING pubkey")
+        .bind(fork_height)
+        .fetch_all(&mut **tx)
+        .await?;
+    pubkeys.sort();
+    pubkeys.dedup();
+
+    sqlx::query("DELETE FROM accounts WHERE height >= $1")
+        .bind(fork_height)
+        .execute(&mut **tx)
+        .await?;
+    sqlx::query(
+        r#"
+        INSERT INTO accounts (pubkey, lamports, owner, data, height)
+        SELECT pubkey, lamports, owner, data, height
+        FROM (
+            SELECT DISTINCT ON (pubkey) pubkey, lamports, owner, data, height, is_deleted
+            FROM account_history
+            WHERE pubkey = ANY($1)
+            ORDER BY pubkey, height DESC
+        ) latest
+        WHERE NOT is_deleted AND lamports IS NOT NULL AND owner IS NOT NULL AND data IS NOT NULL
+        ON CONFLICT (pubkey) DO UPDATE
+        SET lamports = EXCLUDED.lamports, owner = EXCLUDED.owner, data = EXCLUDED.data, height = EXCLUDED.height,
+            updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(&pubkeys)
+    .execute(&mut **tx)
+    .await?;
+
+    for address in &metadata_addresses {
+        let latest: Option<(Vec<u8>, i64)> = sqlx::query_as(
+            "SELECT data, height FROM account_history WHERE pubkey = $1 AND NOT is_deleted AND data IS NOT NULL ORDER BY height DESC LIMIT 1",
+        )
+        .bind(address)
+        .fetch_optional(&mut **tx)
+        .await?;
+        if let Some((token_metadata, height)) = latest.and_then(|(data, height)| Some((metadata::unpack_account(&data)?, height))) {
+            metadata::store_metadata_account(&mut **tx, address, &token_metadata, height).await?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,127 @@
+use anyhow::Result;
+use serde_json::Value as JsonValue;
+use sqlx::{PgPool, Postgres, Row, Transaction};
+use tracing::info;
+
+use crate::indexer::derived;
+
+/// A rollback state transition, as recorded in `transaction_rollbacks`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RollbackAction {
+    RolledBack,
+    Reapplied,
+}
+
+impl RollbackAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RollbackAction::RolledBack => "rolled_back",
+            RollbackAction::Reapplied => "reapplied",
+        }
+    }
+}
+
+/// Reverses the derived effects of stored transactions the node rolled back, newest first,
+/// and records the rollback. Transactions that are not stored or whose effects are not
+/// applied are skipped, so a repeated event changes nothing. Returns the txids that were
+/// rolled back.
+pub async fn rollback_transactions(pool: &PgPool, txids: &[String]) -> Result<Vec<String>> {
+    transition(pool, txids, RollbackAction::RolledBack).await
+}
+
+/// Re-applies the derived effects of stored transactions the node reapplied whose effects
+/// are not applied, oldest first, and records the reapply. Program links are re-derived
+/// from the stored `accounts_tags`. Returns the txids that were reapplied.
+pub async fn reapply_transactions(pool: &PgPool, txids: &[String]) -> Result<Vec<String>> {
+    transition(pool, txids, RollbackAction::Reapplied).await
+}
+
+/// Whether the node's latest rollback event for the transaction rolled it back: its effects
+/// are unapplied on purpose, and only a reapply event restores them.
+pub async fn is_rolled_back(tx: &mut Transaction<'_, Postgres>, txid: &str) -> Result<bool> {
+    let action: Option<String> =
+        sqlx::query_scalar("SELECT action FROM transaction_rollbacks WHERE txid = $1 ORDER BY id DESC LIMIT 1")
+            .bind(txid)
+            .fetch_optional(&mut **tx)
+            .await?;
+    Ok(action.as_deref() == Some(RollbackAction::RolledBack.as_str()))
+}
+
+/// Moves `txids` into the `action` state in one DB transaction. Only transactions whose
+/// effects are applied are rolled back, and only those whose effects are not applied are
+/// reapplied; `effects_applied` is the state. Every transition is recorded in
+/// `transaction_rollbacks`, and `status.rolled_back` mirrors the state for readers of the
+/// transaction row.
+async fn transition(pool: &PgPool, txids: &[String], action: RollbackAction) -> Result<Vec<String>> {
+    if txids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let (applied, order) = match action {
+        RollbackAction::RolledBack => (true, "DESC"),
+        RollbackAction::Reapplied => (false, "ASC"),
+    };
+
+    let mut tx = pool.begin().await?;
+    let rows = sqlx::query(&format!(
+        r#"
+        SELECT t.txid, t.data, t.accounts_tags
+        FROM transactions t
+        WHERE t.txid = ANY($1) AND t.effects_applied = $2
+        ORDER BY t.block_height {order}, array_position($1, t.txid) {order}
+        FOR UPDATE OF t
+        "#
+    ))
+    .bind(txids)
+    .bind(applied)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let mut changed = Vec::with_capacity(rows.len());
+    for row in &rows {
+        let txid: String = row.try_get("txid")?;
+        let data: JsonValue = row.try_get("data")?;
+        match action {
+            RollbackAction::RolledBack => derived::unapply_transaction(&mut tx, &txid, &data).await?,
+            RollbackAction::Reapplied => {
+                // Program links come from the stored tags too, as when the row was indexed
+                let accounts_tags: Option<JsonValue> = row.try_get("accounts_tags").ok().flatten();
+                let accounts_tags = accounts_tags.as_ref().and_then(|tags| tags.as_array()).map(|tags| tags.as_slice());
+                derived::apply_transaction(&mut tx, &txid, &data, accounts_tags).await?
+            }
+        }
+        changed.push(txid);
+    }
+    if changed.is_empty() {
+        return Ok(changed);
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO transaction_rollbacks (txid, action, block_height)
+        SELECT t.txid, $2, t.block_height
+        FROM transactions t
+        WHERE t.txid = ANY($1)
+        "#,
+    )
+    .bind(&changed)
+    .bind(action.as_str())
+    .execute(&mut *tx)
+    .await?;
+
+    // Statuses the node reports as plain strings cannot carry the flag
+    let set_flag = match action {
+        RollbackAction::RolledBack => "jsonb_set(status, '{rolled_back}', 'true')",
+        RollbackAction::Reapplied => "status - 'rolled_back'",
+    };
+    sqlx::query(&format!(
+        "UPDATE transactions SET status = {} WHERE txid = ANY($1) AND jsonb_typeof(status) = 'object'",
+        set_flag
+    ))
+    .bind(&changed)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    info!("↩️ {} {} transaction(s)", action.as_str(), changed.len());
+    Ok(changed)
+}
@@ -0,0 +1,950 @@
+use anyhow::Result;
+use apl_token::instruction::{AuthorityType, TokenInstruction};
+use arch_program::program_option::COption;
+use arch_program::pubkey::Pubkey;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use sqlx::{Postgres, Transaction};
+use tracing::{debug, info};
+
+use crate::indexer::collection::{parse_transaction, ArchInstruction};
+
+/// Which authority a `SetAuthority` instruction replaces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum AuthorityKind {
+    MintTokens,
+    FreezeAccount,
+    AccountOwner,
+    CloseAccount,
+}
+
+/// State change implied by one APL token instruction. Addresses are lowercase hex.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub enum TokenEffect {
+    InitializeMint { mint: String, decimals: u8, mint_authority: String, freeze_authority: Option<String> },
+    InitializeAccount { account: String, mint: String, owner: String },
+    /// `mint` is only carried by `TransferChecked`; a plain `Transfer` resolves it from `token_accounts`
+    Transfer { source: String, destination: String, mint: Option<String>, authority: Option<String>, amount: u64, decimals: Option<u8> },
+    MintTo { mint: String, account: String, authority: Option<String>, amount: u64, decimals: Option<u8> },
+    Burn { account: String, mint: String, authority: Option<String>, amount: u64, decimals: Option<u8> },
+    CloseAccount { account: String },
+    /// `target` is the mint for mint/freeze authorities and the token account otherwise
+    SetAuthority { target: String, kind: AuthorityKind, new_authority: Option<String> },
+    SetFrozen { account: String, mint: String, frozen: bool },
+}
+
+fn coption_hex(value: COption<Pubkey>) -> Option<String> {
+    match value {
+        COption::Some(pubkey) => Some(hex::encode(pubkey)),
+        COption::None => None,
+    }
+}
+
+/// Unpacks APL token instruction data with the program's own `TokenInstruction` and maps
+/// it onto the accounts (hex, instruction order) it touches. Instructions that do not
+/// change indexed state (Approve, Revoke, InitializeMultisig, ...) yield `None`.
+pub fn token_effect(data: &[u8], accounts: &[String]) -> Option<TokenEffect> {
+    let account = |i: usize| accounts.get(i).cloned();
+    let effect = match TokenInstruction::unpack(data).ok()? {
+        TokenInstruction::InitializeMint { decimals, mint_authority, freeze_authority }
+        | TokenInstruction::InitializeMint2 { decimals, mint_authority, freeze_authority } => TokenEffect::InitializeMint {
+            mint: account(0)?,
+            decimals,
+            mint_authority: hex::encode(mint_authority),
+            freeze_authority: coption_hex(freeze_authority),
+        },
+        // accounts: [account, mint, owner, ...]
+        TokenInstruction::InitializeAccount => TokenEffect::InitializeAccount { account: account(0)?, mint: account(1)?, owner: account(2)? },
+        // accounts: [account, mint, ...]; the owner travels in the instruction data
+        TokenInstruction::InitializeAccount2 { owner } | TokenInstruction::InitializeAccount3 { owner } => {
+            TokenEffect::InitializeAccount { account: account(0)?, mint: account(1)?, owner: hex::encode(owner) }
+        }
+        // accounts: [source, destination, authority]
+        TokenInstruction::Transfer { amount } => {
+            TokenEffect::Transfer { source: account(0)?, destination: account(1)?, mint: None, authority: account(2), amount, decimals: None }
+        }
+        // accounts: [source, mint, destination, authority]
+        TokenInstruction::TransferChecked { amount, decimals } => TokenEffect::Transfer {
+            source: account(0)?,
+            destination: account(2)?,
+            mint: Some(account(1)?),
+            authority: account(3),
+            amount,
+            decimals: Some(decimals),
+        },
+        // accounts: [mint, destination, authority]
+        TokenInstruction::MintTo { amount } => {
+            TokenEffect::MintTo { mint: account(0)?, account: account(1)?, authority: account(2), amount, decimals: None }
+        }
+        TokenInstruction::MintToChecked { amount, decimals } => {
+            TokenEffect::MintTo { mint: account(0)?, account: account(1)?, authority: account(2), amount, decimals: Some(decimals) }
+        }
+        // accounts: [account, mint, owner]
+        TokenInstruction::Burn { amount } => {
+            TokenEffect::Burn { account: account(0)?, mint: account(1)?, authority: account(2), amount, decimals: None }
+        }
+        TokenInstruction::BurnChecked { amount, decimals } => {
+            TokenEffect::Burn { account: account(0)?, mint: account(1)?, authority: account(2), amount, decimals: Some(decimals) }
+        }
+        // accounts: [account, destination, owner]
+        TokenInstruction::CloseAccount => TokenEffect::CloseAccount { account: account(0)? },
+        // accounts: [mint or account, current authority]
+        TokenInstruction::SetAuthority { authority_type, new_authority } => TokenEffect::SetAuthority {
+            target: account(0)?,
+            kind: match authority_type {
+                AuthorityType::MintTokens => AuthorityKind::MintTokens,
+                AuthorityType::FreezeAccount => AuthorityKind::FreezeAccount,
+                AuthorityType::AccountOwner => AuthorityKind::AccountOwner,
+                AuthorityType::CloseAccount => AuthorityKind::CloseAccount,
+            },
+            new_authority: coption_hex(new_authority),
+        },
+        // accounts: [account, mint, freeze authority]
+        TokenInstruction::FreezeAccount => TokenEffect::SetFrozen { account: account(0)?, mint: account(1)?, frozen: true },
+        TokenInstruction::ThawAccount => TokenEffect::SetFrozen { account: account(0)?, mint: account(1)?, frozen: false },
+        _ => return None,
+    };
+    Some(effect)
+}
+
+/// A token effect together with the instruction it came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenInstructionEffect {
+    pub index: usize,
+    pub program_id_hex: String,
+    pub effect: TokenEffect,
+}
+
+/// Token effects of every APL token instruction in a runtime transaction, in
+/// instruction order.
+pub fn token_effects(data: &JsonValue) -> Vec<TokenInstructionEffect> {
+    parse_transaction(data)
+        .into_iter()
+        .filter_map(|parsed| match parsed.instruction {
+            ArchInstruction::Token(Some(effect)) => {
+                Some(TokenInstructionEffect { index: parsed.index, program_id_hex: parsed.program_id_hex, effect })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Applies the token instructions of a transaction to `token_accounts`, `token_balances`
+/// and `token_mints`, records transfers, mints and burns in `token_transfers`, and supply
+/// and mint authority changes in `token_mint_events`. Runs inside the block's DB
+/// transaction after the transaction row is written, so the effects commit with it.
+pub async fn apply_token_instructions(tx: &mut Transaction<'_, Postgres>, txid: &str, data: &JsonValue) -> Result<usize> {
+    let effects = token_effects(data);
+    for located in &effects {
+        apply_effect(tx, txid, located).await?;
+    }
+    Ok(effects.len())
+}
+
+/// Reverses the balance, supply and account-state effects of a transaction's token
+/// instructions, newest first. Transfers are undone from the `token_transfers` row written
+/// when they were applied, so a transfer skipped then (its mint was unknown) is skipped
+/// here too, and authority changes from the previous authority recorded in
+/// `token_mint_events` (mint and freeze) or `token_account_authority_events` (account owner
+/// and close); this runs before those rows are deleted. Initializations are kept.
+pub async fn revert_token_instructions(tx: &mut Transaction<'_, Postgres>, txid: &str, data: &JsonValue) -> Result<()> {
+    for TokenInstructionEffect { index, effect, .. } in token_effects(data).iter().rev() {
+        match effect {
+            TokenEffect::Transfer { source, destination, amount, .. } => {
+                let recorded: Option<String> = sqlx::query_scalar(
+                    "SELECT mint_address FROM token_transfers WHERE txid = $1 AND instruction_index = $2 AND kind = 'transfer'",
+                )
+                .bind(txid)
+                .bind(*index as i32)
+                .fetch_optional(&mut **tx)
+                .await?;
+                let Some(mint) = recorded else { continue };
+                debit(tx, destination, &mint, *amount).await?;
+                credit(tx, source, &mint, *amount).await?;
+            }
+            TokenEffect::MintTo { mint, account, amount, .. } => {
+                debit(tx, account, mint, *amount).await?;
+                adjust_supply(tx, mint, *amount, false).await?;
+            }
+            TokenEffect::Burn { account, mint, amount, .. } => {
+                credit(tx, account, mint, *amount).await?;
+                adjust_supply(tx, mint, *amount, true).await?;
+            }
+            TokenEffect::CloseAccount { account } => {
+                sqlx::query("UPDATE token_accounts SET is_closed = FALSE WHERE token_account_hex = $1")
+                    .bind(account)
+                    .execute(&mut **tx)
+                    .await?;
+            }
+            TokenEffect::SetFrozen { account, frozen, .. } => {
+                sqlx::query("UPDATE token_accounts SET is_frozen = $2 WHERE token_account_hex = $1")
+                    .bind(account)
+                    .bind(!frozen)
+                    .execute(&mut **tx)
+                    .await?;
+            }
+            TokenEffect::SetAuthority { kind: kind @ (AuthorityKind::MintTokens | AuthorityKind::FreezeAccount), .. } => {
+                let column = if *kind == AuthorityKind::MintTokens { "mint_authority" } else { "freeze_authority" };
+                sqlx::query(&format!(
+                    r#"
+                    UPDATE token_mints m
+                    SET {column} = e.previous_authority, last_seen_at = CURRENT_TIMESTAMP
+                    FROM token_mint_events e
+                    WHERE e.txid = $1 AND e.instruction_index = $2 AND m.mint_address = e.mint_address
+                    "#
+                ))
+                .bind(txid)
+                .bind(*index as i32)
+                .execute(&mut **tx)
+                .await?;
+            }
+            TokenEffect::SetAuthority { kind: AuthorityKind::AccountOwner, .. } => {
+                let previous: Option<(String, Option<String>)> = sqlx::query_as(
+                    "SELECT token_account, previous_authority FROM token_account_authority_events WHERE txid = $1 AND instruction_index = $2",
+                )
+                .bind(txid)
+                .bind(*index as i32)
+                .fetch_optional(&mut **tx)
+                .await?;
+                let Some((account, owner)) = previous else { continue };
+                sqlx::query("UPDATE token_accounts SET owner_address_hex = $2, last_seen_at = CURRENT_TIMESTAMP WHERE token_account_hex = $1")
+                    .bind(&account)
+                    .bind(owner.as_deref())
+                    .execute(&mut **tx)
+                    .await?;
+                sqlx::query("UPDATE token_balances SET owner_address = $2, last_updated = CURRENT_TIMESTAMP WHERE account_address = $1")
+                    .bind(&account)
+                    .bind(owner.as_deref())
+                    .execute(&mut **tx)
+                    .await?;
+            }
+            TokenEffect::SetAuthority { kind: AuthorityKind::CloseAccount, .. } => {
+                sqlx::query(
+                    r#"
+                    UPDATE token_accounts a
+                    SET close_authority_hex = e.previous_authority, last_seen_at = CURRENT_TIMESTAMP
+                    FROM token_account_authority_events e
+                    WHERE e.txid = $1 AND e.instruction_index = $2 AND a.token_account_hex = e.token_account
+                    "#,
+                )
+                .bind(txid)
+                .bind(*index as i32)
+                .execute(&mut **tx)
+                .await?;
+            }
+            TokenEffect::InitializeMint { .. } | TokenEffect::InitializeAccount { .. } => {}
+        }
+    }
+    Ok(())
+}
+
+async fn apply_effect(tx: &mut Transaction<'_, Postgres>, txid: &str, located: &TokenInstructionEffect) -> Result<()> {
+    let program = located.program_id_hex.as_str();
+    match &located.effect {
+        TokenEffect::InitializeMint { mint, decimals, mint_authority, freeze_authority } => {
+            sqlx::query(
+                r#"INSERT INTO token_mints (mint_address, program_id, decimals, mint_authority, freeze_authority)
+                   VALUES ($1, $2, $3, $4, $5)
+                   ON CONFLICT (mint_address) DO UPDATE SET decimals = EXCLUDED.decimals,
+                       mint_authority = EXCLUDED.mint_authority, freeze_authority = EXCLUDED.freeze_authority,
+                       last_seen_at = CURRENT_TIMESTAMP"#,
+            )
+            .bind(mint)
+            .bind(program)
+            .bind(*decimals as i32)
+            .bind(mint_authority)
+            .bind(freeze_authority.as_deref())
+            .execute(&mut **tx)
+            .await?;
+            record_decimals(tx, mint, program, *decimals).await?;
+            let event = MintEvent { kind: "initialize", mint, amount: None, previous_authority: None, new_authority: Some(mint_authority.as_str()) };
+            record_mint_event(tx, txid, located, &event).await?;
+        }
+        TokenEffect::InitializeAccount { account, mint, owner } => {
+            ensure_mint(tx, mint, program).await?;
+            upsert_account(tx, account, mint, Some(owner), program).await?;
+            sqlx::query("UPDATE token_accounts SET is_closed = FALSE, is_frozen = FALSE WHERE token_account_hex = $1")
+                .bind(account)
+                .execute(&mut **tx)
+                .await?;
+            ensure_balance(tx, account, mint, program).await?;
+        }
+        TokenEffect::Transfer { source, destination, mint, authority, amount, decimals } => {
+            let Some(mint) = resolve_mint(tx, source, mint.as_deref()).await? else {
+                debug!("Skipping token transfer in {}: mint of source {} is unknown", txid, source);
+                return Ok(());
+            };
+            info!(
+                "💸 APL token transfer: tx {} amount {} mint {} src {} -> dst {}",
+                txid, amount, hex_to_base58(&mint), hex_to_base58(source), hex_to_base58(destination)
+            );
+            for acct in [source, destination] {
+                upsert_account(tx, acct, &mint, None, program).await?;
+                ensure_balance(tx, acct, &mint, program).await?;
+            }
+            if let Some(d) = decimals {
+                record_decimals(tx, &mint, program, *d).await?;
+            }
+            debit(tx, source, &mint, *amount).await?;
+            credit(tx, destination, &mint, *amount).await?;
+            let entry = LedgerEntry {
+                kind: "transfer",
+                mint: &mint,
+                source: Some(source),
+                destination: Some(destination),
+                authority: authority.as_deref(),
+                amount: *amount,
+                decimals: *decimals,
+            };
+            record_transfer(tx, txid, located, &entry).await?;
+        }
+        TokenEffect::MintTo { mint, account, authority, amount, decimals } => {
+            ensure_mint(tx, mint, program).await?;
+            upsert_account(tx, account, mint, None, program).await?;
+            ensure_balance(tx, account, mint, program).await?;
+            if let Some(d) = decimals {
+                record_decimals(tx, mint, program, *d).await?;
+            }
+            credit(tx, account, mint, *amount).await?;
+            adjust_supply(tx, mint, *amount, true).await?;
+            let event = MintEvent { kind: "mint", mint, amount: Some(*amount), previous_authority: None, new_authority: None };
+            record_mint_event(tx, txid, located, &event).await?;
+            let entry = LedgerEntry {
+                kind: "mint",
+                mint,
+                source: None,
+                destination: Some(account),
+                authority: authority.as_deref(),
+                amount: *amount,
+                decimals: *decimals,
+            };
+            record_transfer(tx, txid, located, &entry).await?;
+        }
+        TokenEffect::Burn { account, mint, authority, amount, decimals } => {
+            ensure_mint(tx, mint, program).await?;
+            ensure_balance(tx, account, mint, program).await?;
+            if let Some(d) = decimals {
+                record_decimals(tx, mint, program, *d).await?;
+            }
+            debit(tx, account, mint, *amount).await?;
+            adjust_supply(tx, mint, *amount, false).await?;
+            let event = MintEvent { kind: "burn", mint, amount: Some(*amount), previous_authority: None, new_authority: None };
+            record_mint_event(tx, txid, located, &event).await?;
+            let entry = LedgerEntry {
+                kind: "burn",
+                mint,
+                source: Some(account),
+                destination: None,
+                authority: authority.as_deref(),
+                amount: *amount,
+                decimals: *decimals,
+            };
+            record_transfer(tx, txid, located, &entry).await?;
+        }
+        TokenEffect::CloseAccount { account } => {
+            // Only empty accounts can be closed, so the balance row is zeroed rather than removed
+            sqlx::query("UPDATE token_accounts SET is_closed = TRUE, last_seen_at = CURRENT_TIMESTAMP WHERE token_account_hex = $1")
+                .bind(account)
+                .execute(&mut **tx)
+                .await?;
+            sqlx::query("UPDATE token_balances SET balance = 0, last_updated = CURRENT_TIMESTAMP WHERE account_address = $1")
+                .bind(account)
+                .execute(&mut **tx)
+                .await?;
+        }
+        TokenEffect::SetAuthority { target, kind, new_authority } => {
+            let previous_sql = match kind {
+                AuthorityKind::MintTokens => "SELECT mint_authority FROM token_mints WHERE mint_address = $1",
+                AuthorityKind::FreezeAccount => "SELECT freeze_authority FROM token_mints WHERE mint_address = $1",
+                AuthorityKind::AccountOwner => "SELECT owner_address_hex FROM token_accounts WHERE token_account_hex = $1",
+                AuthorityKind::CloseAccount => "SELECT close_authority_hex FROM token_accounts WHERE token_account_hex = $1",
+            };
+            let previous_authority =
+                sqlx::query_scalar::<_, Option<String>>(previous_sql).bind(target).fetch_optional(&mut **tx).await?.flatten();
+            let sql = match kind {
+                AuthorityKind::MintTokens => "UPDATE token_mints SET mint_authority = $2, last_seen_at = CURRENT_TIMESTAMP WHERE mint_address = $1",
+                AuthorityKind::FreezeAccount => "UPDATE token_mints SET freeze_authority = $2, last_seen_at = CURRENT_TIMESTAMP WHERE mint_address = $1",
+                AuthorityKind::AccountOwner => "UPDATE token_accounts SET owner_address_hex = $2, last_seen_at = CURRENT_TIMESTAMP WHERE token_account_hex = $1",
+                AuthorityKind::CloseAccount => "UPDATE token_accounts SET close_authority_hex = $2, last_seen_at = CURRENT_TIMESTAMP WHERE token_account_hex = $1",
+            };
+            sqlx::query(sql)
+                .bind(target)
+                .bind(new_authority.as_deref())
+                .execute(&mut **tx)
+                .await?;
+            if *kind == AuthorityKind::AccountOwner {
+                sqlx::query("UPDATE token_balances SET owner_address = $2, last_updated = CURRENT_TIMESTAMP WHERE account_address = $1")
+                    .bind(target)
+                    .bind(new_authority.as_deref())
+                    .execute(&mut **tx)
+                    .await?;
+            }
+            let event_kind = match kind {
+                AuthorityKind::MintTokens => "set_mint_authority",
+                AuthorityKind::FreezeAccount => "set_freeze_authority",
+                AuthorityKind::AccountOwner => "set_owner",
+                AuthorityKind::CloseAccount => "set_close_authority",
+            };
+            match kind {
+                AuthorityKind::MintTokens | AuthorityKind::FreezeAccount => {
+                    let event = MintEvent {
+                        kind: event_kind,
+                        mint: target,
+                        amount: None,
+                        previous_authority: previous_authority.as_deref(),
+                        new_authority: new_authority.as_deref(),
+                    };
+                    record_mint_event(tx, txid, located, &event).await?;
+                }
+                AuthorityKind::AccountOwner | AuthorityKind::CloseAccount => {
+                    sqlx::query(
+                        r#"
+                        INSERT INTO token_account_authority_events (txid, instruction_index, block_height, token_account, kind,
+                            previous_authority, new_authority)
+                        SELECT t.txid, $2, t.block_height, $3, $4, $5, $6
+                        FROM transactions t
+                        WHERE t.txid = $1
+                        ON CONFLICT (txid, instruction_index) DO UPDATE
+                        SET block_height = EXCLUDED.block_height, token_account = EXCLUDED.token_account, kind = EXCLUDED.kind,
+                            previous_authority = EXCLUDED.previous_authority, new_authority = EXCLUDED.new_authority
+                        "#,
+                    )
+                    .bind(txid)
+                    .bind(located.index as i32)
+                    .bind(target)
+                    .bind(event_kind)
+                    .bind(previous_authority.as_deref())
+                    .bind(new_authority.as_deref())
+                    .execute(&mut **tx)
+                    .await?;
+                }
+            }
+        }
+        TokenEffect::SetFrozen { account, mint, frozen } => {
+            upsert_account(tx, account, mint, None, program).await?;
+            sqlx::query("UPDATE token_accounts SET is_frozen = $2 WHERE token_account_hex = $1")
+                .bind(account)
+                .bind(*frozen)
+                .execute(&mut **tx)
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// One `token_transfers` row: a transfer, mint (no source) or burn (no destination).
+struct LedgerEntry<'a> {
+    kind: &'static str,
+    mint: &'a str,
+    source: Option<&'a str>,
+    destination: Option<&'a str>,
+    authority: Option<&'a str>,
+    amount: u64,
+    decimals: Option<u8>,
+}
+
+/// Upserts the ledger row, taking block height and time from the stored transaction and
+/// the owners of both token accounts as of this instruction.
+async fn record_transfer(
+    tx: &mut Transaction<'_, Postgres>,
+    txid: &str,
+    located: &TokenInstructionEffect,
+    entry: &LedgerEntry<'_>,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO token_transfers (txid, instruction_index, block_height, block_time, kind, mint_address,
+            source_address, destination_address, source_owner, destination_owner, authority_address,
+            amount, decimals, program_id)
+        SELECT t.txid, $2, t.block_height, b.timestamp, $3, $4, $5, $6,
+            (SELECT owner_address_hex FROM token_accounts WHERE token_account_hex = $5),
+            (SELECT owner_address_hex FROM token_accounts WHERE token_account_hex = $6),
+            $7, ($8)::numeric, COALESCE($9::int, (SELECT decimals FROM token_mints WHERE mint_address = $4)), $10
+        FROM transactions t
+        LEFT JOIN blocks b ON b.height = t.block_height
+        WHERE t.txid = $1
+        ON CONFLICT (txid, instruction_index) DO UPDATE
+        SET block_height = EXCLUDED.block_height, block_time = EXCLUDED.block_time, kind = EXCLUDED.kind,
+            mint_address = EXCLUDED.mint_address, source_address = EXCLUDED.source_address,
+            destination_address = EXCLUDED.destination_address, source_owner = EXCLUDED.source_owner,
+            destination_owner = EXCLUDED.destination_owner, authority_address = EXCLUDED.authority_address,
+            amount = EXCLUDED.amount, decimals = EXCLUDED.decimals, program_id = EXCLUDED.program_id
+        "#,
+    )
+    .bind(txid)
+    .bind(located.index as i32)
+    .bind(entry.kind)
+    .bind(entry.mint)
+    .bind(entry.source)
+    .bind(entry.destination)
+    .bind(entry.authority)
+    .bind(entry.amount.to_string())
+    .bind(entry.decimals.map(i32::from))
+    .bind(&located.program_id_hex)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// One `token_mint_events` row: an initialization, mint, burn or mint/freeze authority change.
+struct MintEvent<'a> {
+    kind: &'static str,
+    mint: &'a str,
+    amount: Option<u64>,
+    previous_authority: Option<&'a str>,
+    new_authority: Option<&'a str>,
+}
+
+impl MintEvent<'_> {
+    /// How much the instruction moved the mint's supply.
+    fn supply_delta(&self) -> i128 {
+        let amount = self.amount.unwrap_or(0) as i128;
+        match self.kind {
+            "mint" => amount,
+            "burn" => -amount,
+            _ => 0,
+        }
+    }
+}
+
+/// Upserts the mint event with the mint's supply after this instruction: the supply after
+/// the mint's previous event in `(block_height, txid, instruction_index)` order plus this
+/// instruction's delta, and carries the delta into the mint's later events, so the supply
+/// history can be read without replaying every mint and burn.
+async fn record_mint_event(
+    tx: &mut Transaction<'_, Postgres>,
+    txid: &str,
+    located: &TokenInstructionEffect,
+    event: &MintEvent<'_>,
+) -> Result<()> {
+    let delta = event.supply_delta().to_string();
+    sqlx::query(
+        r#"
+        INSERT INTO token_mint_events (txid, instruction_index, block_height, block_time, mint_address, kind,
+            amount, supply_after, previous_authority, new_authority)
+        SELECT t.txid, $2, t.block_height, b.timestamp, $3, $4, ($5)::numeric,
+            COALESCE(
+                (SELECT e.supply_after FROM token_mint_events e
+                 WHERE e.mint_address = $3
+                   AND (e.block_height, e.txid, e.instruction_index) < (t.block_height, t.txid, $2)
+                 ORDER BY e.block_height DESC, e.txid DESC, e.instruction_index DESC LIMIT 1),
+                0
+            ) + ($8)::numeric,
+            $6, $7
+        FROM transactions t
+        LEFT JOIN blocks b ON b.height = t.block_height
+        WHERE t.txid = $1
+        ON CONFLICT (txid, instruction_index) DO UPDATE
+        SET block_height = EXCLUDED.block_height, block_time = EXCLUDED.block_time,
+            mint_address = EXCLUDED.mint_address, kind = EXCLUDED.kind, amount = EXCLUDED.amount,
+            supply_after = EXCLUDED.supply_after, previous_authority = EXCLUDED.previous_authority,
+            new_authority = EXCLUDED.new_authority
+        "#,
+    )
+    .bind(txid)
+    .bind(located.index as i32)
+    .bind(event.mint)
+    .bind(event.kind)
+    .bind(event.amount.map(|a| a.to_string()))
+    .bind(event.previous_authority)
+    .bind(event.new_authority)
+    .bind(&delta)
+    .execute(&mut **tx)
+    .await?;
+
+    if event.supply_delta() != 0 {
+        sqlx::query(
+            r#"
+            UPDATE token_mint_events e
+            SET supply_after = e.supply_after + ($4)::numeric
+            FROM transactions t
+            WHERE t.txid = $1 AND e.mint_address = $2
+              AND (e.block_height, e.txid, e.instruction_index) > (t.block_height, t.txid, $3)
+            "#,
+        )
+        .bind(txid)
+        .bind(event.mint)
+        .bind(located.index as i32)
+        .bind(&delta)
+        .execute(&mut **tx)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Takes a transaction's mints and burns back out of the `supply_after` of its mints'
+/// later events from other transactions, as when the node rolls it back. Runs before the
+/// transaction's own events are deleted.
+pub async fn revert_mint_event_supply(tx: &mut Transaction<'_, Postgres>, txid: &str) -> Result<u64> {
+    let shifted = sqlx::query(
+        r#"
+        UPDATE token_mint_events e
+        SET supply_after = e.supply_after - s.delta
+        FROM (
+            SELECT later.txid, later.instruction_index,
+                   SUM(CASE WHEN r.kind = 'mint' THEN r.amount ELSE -r.amount END) AS delta
+            FROM token_mint_events r
+            JOIN token_mint_events later
+              ON later.mint_address = r.mint_address
+             AND (later.block_height, later.txid, later.instruction_index) > (r.block_height, r.txid, r.instruction_index)
+            WHERE r.txid = $1 AND r.kind IN ('mint', 'burn') AND later.txid <> $1
+            GROUP BY later.txid, later.instruction_index
+        ) s
+        WHERE e.txid = s.txid AND e.instruction_index = s.instruction_index AND s.delta <> 0
+        "#,
+    )
+    .bind(txid)
+    .execute(&mut **tx)
+    .await?
+    .rows_affected();
+    Ok(shifted)
+}
+
+async fn resolve_mint(tx: &mut Transaction<'_, Postgres>, account: &str, mint: Option<&str>) -> Result<Option<String>> {
+    if let Some(mint) = mint {
+        return Ok(Some(mint.to_string()));
+    }
+    let mint: Option<String> = sqlx::query_scalar("SELECT mint_address_hex FROM token_accounts WHERE token_account_hex = $1")
+        .bind(account)
+        .fetch_optional(&mut **tx)
+        .await?;
+    Ok(mint)
+}
+
+/// Records the account's mint and program; an unknown (`None`) owner keeps the stored one.
+async fn upsert_account(tx: &mut Transaction<'_, Postgres>, account: &str, mint: &str, owner: Option<&str>, program: &str) -> Result<()> {
+    sqlx::query(
+        r#"INSERT INTO token_accounts (token_account_hex, mint_address_hex, owner_address_hex, program_id_hex, last_seen_at)
+           VALUES ($1, $2, $3, $4, CURRENT_TIMESTAMP)
+           ON CONFLICT (token_account_hex) DO UPDATE SET
+               mint_address_hex = EXCLUDED.mint_address_hex,
+               owner_address_hex = COALESCE(EXCLUDED.owner_address_hex, token_accounts.owner_address_hex),
+               program_id_hex = EXCLUDED.program_id_hex,
+               last_seen_at = CURRENT_TIMESTAMP"#,
+    )
+    .bind(account)
+    .bind(mint)
+    .bind(owner)
+    .bind(program)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+async fn ensure_mint(tx: &mut Transaction<'_, Postgres>, mint: &str, program: &str) -> Result<()> {
+    sqlx::query("INSERT INTO token_mints (mint_address, program_id) VALUES ($1, $2) ON CONFLICT (mint_address) DO UPDATE SET last_seen_at = CURRENT_TIMESTAMP")
+        .bind(mint)
+        .bind(program)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+/// Seeds a zero balance row, taking decimals from the mint and the owner from `token_accounts`.
+async fn ensure_balance(tx: &mut Transaction<'_, Postgres>, account: &str, mint: &str, program: &str) -> Result<()> {
+    sqlx::query(
+        r#"INSERT INTO token_balances (account_address, mint_address, balance, decimals, owner_address, program_id)
+           VALUES ($1, $2, 0,
+                   COALESCE((SELECT decimals FROM token_mints WHERE mint_address = $2), 0),
+                   (SELECT owner_address_hex FROM token_accounts WHERE token_account_hex = $1), $3)
+           ON CONFLICT (account_address, mint_address) DO UPDATE SET
+               owner_address = COALESCE(EXCLUDED.owner_address, token_balances.owner_address),
+               last_updated = CURRENT_TIMESTAMP"#,
+    )
+    .bind(account)
+    .bind(mint)
+    .bind(program)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+async fn record_decimals(tx: &mut Transaction<'_, Postgres>, mint: &str, program: &str, decimals: u8) -> Result<()> {
+    sqlx::query(
+        r#"INSERT INTO token_mints (mint_address, program_id, decimals)
+           VALUES ($1, $2, $3)
+           ON CONFLICT (mint_address) DO UPDATE SET decimals = EXCLUDED.decimals, last_seen_at = CURRENT_TIMESTAMP"#,
+    )
+    .bind(mint)
+    .bind(program)
+    .bind(decimals as i32)
+    .execute(&mut **tx)
+    .await?;
+    sqlx::query("UPDATE token_balances SET decimals = $2 WHERE mint_address = $1 AND decimals <> $2")
+        .bind(mint)
+        .bind(decimals as i32)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+async fn credit(tx: &mut Transaction<'_, Postgres>, account: &str, mint: &str, amount: u64) -> Result<()> {
+    sqlx::query(
+        "UPDATE token_balances SET balance = balance + ($3)::numeric, last_updated = CURRENT_TIMESTAMP WHERE account_address = $1 AND mint_address = $2",
+    )
+    .bind(account)
+    .bind(mint)
+    .bind(amount.to_string())
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// Not clamped at zero, like `native_balances`: history indexed from mid-chain can leave a
+/// balance short, and a clamp would make reverting the debit credit more than was taken.
+async fn debit(tx: &mut Transaction<'_, Postgres>, account: &str, mint: &str, amount: u64) -> Result<()> {
+    sqlx::query(
+        "UPDATE token_balances SET balance = balance - ($3)::numeric, last_updated = CURRENT_TIMESTAMP WHERE account_address = $1 AND mint_address = $2",
+    )
+    .bind(account)
+    .bind(mint)
+    .bind(amount.to_string())
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+async fn adjust_supply(tx: &mut Transaction<'_, Postgres>, mint: &str, amount: u64, increase: bool) -> Result<()> {
+    let sql = if increase {
+        "UPDATE token_mints SET supply = supply + ($2)::numeric, last_seen_at = CURRENT_TIMESTAMP WHERE mint_address = $1"
+    } else {
+        "UPDATE token_mints SET supply = supply - ($2)::numeric, last_seen_at = CURRENT_TIMESTAMP WHERE mint_address = $1"
+    };
+    sqlx::query(sql)
+        .bind(mint)
+        .bind(amount.to_string())
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+fn hex_to_base58(hex_str: &str) -> String {
+    hex::decode(hex_str).map(|b| bs58::encode(b).into_string()).unwrap_or_else(|_| hex_str.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::PgPool;
+
+    const HEIGHT: i64 = 900_000_000;
+
+    fn accounts(n: usize) -> Vec<String> {
+        (1..=n).map(|i| hex::encode([i as u8; 32])).collect()
+    }
+
+    /// Stores a block and an empty transaction at `height`, for effects to take their
+    /// height and time from.
+    async fn seed_transaction(tx: &mut Transaction<'_, Postgres>, txid: &str, height: i64) {
+        sqlx::query("INSERT INTO blocks (height, hash, timestamp) VALUES ($1, $2, CURRENT_TIMESTAMP) ON CONFLICT (height) DO NOTHING")
+            .bind(height)
+            .bind(format!("tokens{}", height))
+            .execute(&mut **tx)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO transactions (txid, block_height, data, status) VALUES ($1, $2, '{}'::jsonb, '{\"type\": \"processed\"}'::jsonb)")
+            .bind(txid)
+            .bind(height)
+            .execute(&mut **tx)
+            .await
+            .unwrap();
+    }
+
+    fn located(effect: TokenEffect) -> TokenInstructionEffect {
+        TokenInstructionEffect { index: 0, program_id_hex: hex::encode([0xa1u8; 32]), effect }
+    }
+
+    fn mint_to(amount: u64) -> TokenInstructionEffect {
+        located(TokenEffect::MintTo {
+            mint: hex::encode([0xb1u8; 32]),
+            account: hex::encode([0xb2u8; 32]),
+            authority: None,
+            amount,
+            decimals: None,
+        })
+    }
+
+    fn burn(amount: u64) -> TokenInstructionEffect {
+        located(TokenEffect::Burn {
+            account: hex::encode([0xb2u8; 32]),
+            mint: hex::encode([0xb1u8; 32]),
+            authority: None,
+            amount,
+            decimals: None,
+        })
+    }
+
+    async fn supply_history(tx: &mut Transaction<'_, Postgres>) -> Vec<(String, String)> {
+        sqlx::query_as(
+            "SELECT txid, supply_after::text FROM token_mint_events WHERE mint_address = $1 ORDER BY block_height, txid, instruction_index",
+        )
+        .bind(hex::encode([0xb1u8; 32]))
+        .fetch_all(&mut **tx)
+        .await
+        .unwrap()
+    }
+
+    fn history(rows: &[(&str, &str)]) -> Vec<(String, String)> {
+        rows.iter().map(|(txid, supply)| (txid.to_string(), supply.to_string())).collect()
+    }
+
+    /// Mint 100, mint 50 and burn 30 at consecutive heights, applied in `order`.
+    async fn apply_mint_events(tx: &mut Transaction<'_, Postgres>, order: [usize; 3]) {
+        let events = [("tokens-mint-a", mint_to(100)), ("tokens-mint-b", mint_to(50)), ("tokens-mint-c", burn(30))];
+        for (i, (txid, _)) in events.iter().enumerate() {
+            seed_transaction(tx, txid, HEIGHT + i as i64).await;
+        }
+        for i in order {
+            let (txid, effect) = &events[i];
+            apply_effect(tx, txid, effect).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database initialized from db-init"]
+    async fn mint_events_carry_the_supply_in_chain_order() {
+        let pool = PgPool::connect(&std::env::var("DATABASE_URL").unwrap()).await.unwrap();
+        let expected = history(&[("tokens-mint-a", "100"), ("tokens-mint-b", "150"), ("tokens-mint-c", "120")]);
+
+        // Nothing is committed; each transaction is rolled back when dropped
+        let mut tx = pool.begin().await.unwrap();
+        apply_mint_events(&mut tx, [0, 1, 2]).await;
+        assert_eq!(supply_history(&mut tx).await, expected);
+        drop(tx);
+
+        // Heights indexed out of order (realtime blocks first, gaps healed later) end with the
+        // same supply history
+        let mut tx = pool.begin().await.unwrap();
+        apply_mint_events(&mut tx, [2, 0, 1]).await;
+        assert_eq!(supply_history(&mut tx).await, expected);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database initialized from db-init"]
+    async fn reverting_a_mint_event_shifts_only_later_supply() {
+        let pool = PgPool::connect(&std::env::var("DATABASE_URL").unwrap()).await.unwrap();
+        let mut tx = pool.begin().await.unwrap();
+        apply_mint_events(&mut tx, [0, 1, 2]).await;
+
+        // The middle mint is rolled back: the burn after it loses the 50, the mint before keeps its supply
+        assert_eq!(revert_mint_event_supply(&mut tx, "tokens-mint-b").await.unwrap(), 1);
+        sqlx::query("DELETE FROM transactions WHERE txid = $1").bind("tokens-mint-b").execute(&mut *tx).await.unwrap();
+        assert_eq!(supply_history(&mut tx).await, history(&[("tokens-mint-a", "100"), ("tokens-mint-c", "70")]));
+    }
+
+    /// Owner and close authority of a token account, and the owner on its balance row.
+    async fn owner_state(tx: &mut Transaction<'_, Postgres>, account: &str) -> (Option<String>, Option<String>, Option<String>) {
+        sqlx::query_as(
+            r#"SELECT a.owner_address_hex, a.close_authority_hex, b.owner_address
+               FROM token_accounts a JOIN token_balances b ON b.account_address = a.token_account_hex
+               WHERE a.token_account_hex = $1"#,
+        )
+        .bind(account)
+        .fetch_one(&mut **tx)
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL pointing at a database initialized from db-init"]
+    async fn reverting_an_owner_change_restores_owner_and_close_authority() {
+        let pool = PgPool::connect(&std::env::var("DATABASE_URL").unwrap()).await.unwrap();
+        let mut tx = pool.begin().await.unwrap();
+        let (account, owner, close_authority, new_owner) =
+            (hex::encode([0xc1u8; 32]), hex::encode([0xc3u8; 32]), hex::encode([0xc4u8; 32]), hex::encode([0xc5u8; 32]));
+        seed_transaction(&mut tx, "tokens-owner", HEIGHT).await;
+        sqlx::query(
+            "INSERT INTO token_accounts (token_account_hex, mint_address_hex, owner_address_hex, program_id_hex, close_authority_hex) VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(&account)
+        .bind(hex::encode([0xb1u8; 32]))
+        .bind(&owner)
+        .bind(hex::encode([0xa1u8; 32]))
+        .bind(&close_authority)
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO token_balances (account_address, mint_address, balance, owner_address, program_id) VALUES ($1, $2, 5, $3, $4)")
+            .bind(&account)
+            .bind(hex::encode([0xb1u8; 32]))
+            .bind(&owner)
+            .bind(hex::encode([0xa1u8; 32]))
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+
+        // SetAuthority { AccountOwner, new_authority: Some(new_owner) }, signed by the current owner
+        let mut ix = vec![6u8, 2, 1];
+        ix.extend_from_slice(&[0xc5u8; 32]);
+        let data = serde_json::json!({
+            "version": 0,
+            "signatures": [],
+            "message": {
+                "header": { "num_required_signatures": 1, "num_readonly_signed_accounts": 0, "num_readonly_unsigned_accounts": 1 },
+                "account_keys": [vec![0xc1u8; 32], vec![0xc3u8; 32], bs58::decode(decoder::APL_TOKEN_PROGRAM_BASE58).into_vec().unwrap()],
+                "instructions": [{ "program_id_index": 2, "accounts": [0, 1], "data": ix }]
+            }
+        });
+
+        assert_eq!(apply_token_instructions(&mut tx, "tokens-owner", &data).await.unwrap(), 1);
+        assert_eq!(owner_state(&mut tx, &account).await, (Some(new_owner.clone()), Some(close_authority.clone()), Some(new_owner.clone())));
+        let event: (String, Option<String>, Option<String>) = sqlx::query_as(
+            "SELECT kind, previous_authority, new_authority FROM token_account_authority_events WHERE txid = 'tokens-owner'",
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .unwrap();
+        assert_eq!(event, ("set_owner".to_string(), Some(owner.clone()), Some(new_owner)));
+
+        revert_token_instructions(&mut tx, "tokens-owner", &data).await.unwrap();
+        assert_eq!(owner_state(&mut tx, &account).await, (Some(owner.clone()), Some(close_authority), Some(owner)));
+    }
+
+    #[test]
+    fn transfer_checked_carries_mint_and_decimals() {
+        let mut data = vec![12u8];
+        data.extend_from_slice(&1_500u64.to_le_bytes());
+        data.push(6);
+        let accts = accounts(4);
+        assert_eq!(
+            token_effect(&data, &accts),
+            Some(TokenEffect::Transfer {
+                source: accts[0].clone(),
+                destination: accts[2].clone(),
+                mint: Some(accts[1].clone()),
+                authority: Some(accts[3].clone()),
+                amount: 1_500,
+                decimals: Some(6),
+            })
+        );
+    }
+
+    #[test]
+    fn initialize_account3_takes_owner_from_data() {
+        let owner = [9u8; 32];
+        let mut data = vec![18u8];
+        data.extend_from_slice(&owner);
+        let accts = accounts(2);
+        assert_eq!(
+            token_effect(&data, &accts),
+            Some(TokenEffect::InitializeAccount { account: accts[0].clone(), mint: accts[1].clone(), owner: hex::encode(owner) })
+        );
+    }
+
+    #[test]
+    fn set_authority_and_freeze_are_typed() {
+        // SetAuthority { AccountOwner, new_authority: None }
+        let accts = accounts(2);
+        assert_eq!(
+            token_effect(&[6, 2, 0], &accts),
+            Some(TokenEffect::SetAuthority { target: accts[0].clone(), kind: AuthorityKind::AccountOwner, new_authority: None })
+        );
+        assert_eq!(
+            token_effect(&[10], &accounts(3)),
+            Some(TokenEffect::SetFrozen { account: accts[0].clone(), mint: accts[1].clone(), frozen: true })
+        );
+        // Revoke does not change indexed state; missing accounts are not guessed
+        assert_eq!(token_effect(&[5], &accounts(2)), None);
+        assert_eq!(token_effect(&[9], &[]), None);
+    }
+}
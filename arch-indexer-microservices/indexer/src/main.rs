@@ -94,7 +94,7 @@ async fn main() -> Result<()> {
                 let ws_url = ws_url_env.as_deref().unwrap_or(ws_fallback);
                 let rocks_path = std::env::var("ATLAS_CHECKPOINT_PATH").unwrap_or_else(|_| "./.atlas_checkpoints".to_string());
                 info!("🧪 INDEXER_RUNTIME=atlas; starting syncing pipeline (rpc={}, ws={})", rpc_url, ws_url);
-                if let Err(e) = pipeline_atlas::run_syncing_pipeline(rpc_url, ws_url, &rocks_path, std::sync::Arc::new(pool)).await {
+                if let Err(e) = pipeline_atlas::run_syncing_pipeline(&settings.arch_node, rpc_url, ws_url, &rocks_path, std::sync::Arc::new(pool)).await {
                     error!("Atlas syncing pipeline failed: {}", e);
                     std::process::exit(1);
                 }
@@ -105,7 +105,7 @@ async fn main() -> Result<()> {
                 let hybrid_sync = HybridSync::new(
                     std::sync::Arc::new(settings),
                     std::sync::Arc::new(pool),
-                );
+                )?;
                 info!("🚀 Starting legacy indexer...");
                 if let Err(e) = hybrid_sync.start().await {
                     error!("❌ Indexer failed to start: {}", e);
@@ -119,7 +119,7 @@ async fn main() -> Result<()> {
             let hybrid_sync = HybridSync::new(
                 std::sync::Arc::new(settings),
                 std::sync::Arc::new(pool),
-            );
+            )?;
             info!("🚀 Starting indexer...");
             if let Err(e) = hybrid_sync.start().await {
                 error!("❌ Indexer failed to start: {}", e);
@@ -154,11 +154,16 @@ async fn bootstrap_schema_if_missing(pool: &PgPool) -> Result<()> {
     // Minimal base schema sufficient for the indexer to operate. Full migrations
     // can still be applied later, but this prevents startup failures on fresh DBs.
     const BASE_SCHEMA: &str = r#"
+        CREATE SEQUENCE IF NOT EXISTS transaction_apply_order_seq;
+
         CREATE TABLE IF NOT EXISTS blocks (
             height BIGINT PRIMARY KEY,
             hash TEXT NOT NULL,
             timestamp TIMESTAMPTZ NOT NULL,
-            bitcoin_block_height BIGINT
+            bitcoin_block_height BIGINT,
+            previous_block_hash TEXT,
+            block_size_bytes BIGINT,
+            transaction_count BIGINT
         );
 
         CREATE TABLE IF NOT EXISTS transactions (
@@ -167,6 +172,10 @@ async fn bootstrap_schema_if_missing(pool: &PgPool) -> Result<()> {
             data JSONB NOT NULL,
             status JSONB NOT NULL DEFAULT '0'::jsonb,
             bitcoin_txids TEXT[] DEFAULT '{}',
+            logs JSONB DEFAULT '[]'::jsonb,
+            accounts_tags JSONB DEFAULT '[]'::jsonb,
+            effects_applied BOOLEAN NOT NULL DEFAULT FALSE,
+            apply_order BIGINT,
             created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
             FOREIGN KEY (block_height) REFERENCES blocks(height)
         );
@@ -180,6 +189,39 @@ async fn bootstrap_schema_if_missing(pool: &PgPool) -> Result<()> {
             updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
         );
 
+        CREATE TABLE IF NOT EXISTS account_history (
+            pubkey TEXT NOT NULL,
+            height BIGINT NOT NULL,
+            lamports BIGINT,
+            owner TEXT,
+            data BYTEA,
+            data_hash TEXT GENERATED ALWAYS AS (encode(sha256(data), 'hex')) STORED,
+            is_deleted BOOLEAN NOT NULL DEFAULT FALSE,
+            recorded_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (pubkey, height)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_account_history_height ON account_history(height);
+
+        CREATE TABLE IF NOT EXISTS transaction_rollbacks (
+            id BIGSERIAL PRIMARY KEY,
+            txid TEXT NOT NULL REFERENCES transactions(txid) ON DELETE CASCADE,
+            action TEXT NOT NULL CHECK (action IN ('rolled_back', 'reapplied')),
+            block_height BIGINT,
+            recorded_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_transaction_rollbacks_txid ON transaction_rollbacks(txid, id DESC);
+
+        CREATE TABLE IF NOT EXISTS dkg_events (
+            id BIGSERIAL PRIMARY KEY,
+            status TEXT,
+            data JSONB NOT NULL,
+            received_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_dkg_events_received_at ON dkg_events(received_at DESC);
+
         CREATE INDEX IF NOT EXISTS idx_transactions_block_height ON transactions(block_height);
         CREATE INDEX IF NOT EXISTS idx_blocks_bitcoin_block_height ON blocks(bitcoin_block_height);
         CREATE INDEX IF NOT EXISTS idx_blocks_timestamp ON blocks(timestamp);
@@ -199,6 +241,177 @@ async fn bootstrap_schema_if_missing(pool: &PgPool) -> Result<()> {
         );
 
         CREATE INDEX IF NOT EXISTS idx_transaction_programs_program_id ON transaction_programs(program_id);
+
+        CREATE TABLE IF NOT EXISTS account_participation (
+            address_hex TEXT NOT NULL,
+            txid TEXT NOT NULL REFERENCES transactions(txid) ON DELETE CASCADE,
+            block_height BIGINT NOT NULL,
+            created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (address_hex, txid)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_account_participation_address ON account_participation(address_hex);
+        CREATE INDEX IF NOT EXISTS idx_account_participation_created_at ON account_participation(created_at DESC);
+
+        CREATE TABLE IF NOT EXISTS instructions (
+            txid TEXT NOT NULL REFERENCES transactions(txid) ON DELETE CASCADE,
+            instruction_index INTEGER NOT NULL,
+            block_height BIGINT NOT NULL,
+            program_id TEXT NOT NULL,
+            instruction_type TEXT,
+            args JSONB,
+            accounts JSONB NOT NULL DEFAULT '[]'::jsonb,
+            data BYTEA NOT NULL DEFAULT ''::bytea,
+            created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (txid, instruction_index)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_instructions_type ON instructions(instruction_type);
+        CREATE INDEX IF NOT EXISTS idx_instructions_program_type ON instructions(program_id, instruction_type);
+        CREATE INDEX IF NOT EXISTS idx_instructions_block_height ON instructions(block_height);
+
+        CREATE TABLE IF NOT EXISTS token_accounts (
+            token_account_hex TEXT PRIMARY KEY,
+            mint_address_hex TEXT NOT NULL,
+            owner_address_hex TEXT,
+            program_id_hex TEXT NOT NULL,
+            is_frozen BOOLEAN NOT NULL DEFAULT FALSE,
+            is_closed BOOLEAN NOT NULL DEFAULT FALSE,
+            close_authority_hex TEXT,
+            last_seen_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+        );
+
+        CREATE TABLE IF NOT EXISTS token_balances (
+            id SERIAL PRIMARY KEY,
+            account_address TEXT NOT NULL,
+            mint_address TEXT NOT NULL,
+            balance NUMERIC(65, 0) NOT NULL DEFAULT 0,
+            decimals INTEGER NOT NULL DEFAULT 0,
+            owner_address TEXT,
+            program_id TEXT NOT NULL,
+            last_updated TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE(account_address, mint_address)
+        );
+
+        CREATE TABLE IF NOT EXISTS token_mints (
+            mint_address TEXT PRIMARY KEY,
+            program_id TEXT NOT NULL,
+            decimals INTEGER NOT NULL DEFAULT 0,
+            supply NUMERIC(65, 0) NOT NULL DEFAULT 0,
+            is_frozen BOOLEAN DEFAULT FALSE,
+            mint_authority TEXT,
+            freeze_authority TEXT,
+            first_seen_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            last_seen_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_token_accounts_owner ON token_accounts(owner_address_hex);
+        CREATE INDEX IF NOT EXISTS idx_token_accounts_mint ON token_accounts(mint_address_hex);
+        CREATE INDEX IF NOT EXISTS idx_token_balances_account ON token_balances(account_address);
+        CREATE INDEX IF NOT EXISTS idx_token_balances_mint ON token_balances(mint_address);
+        CREATE INDEX IF NOT EXISTS idx_token_balances_mint_balance ON token_balances(mint_address, balance DESC, account_address DESC);
+
+        CREATE TABLE IF NOT EXISTS token_transfers (
+            txid TEXT NOT NULL REFERENCES transactions(txid) ON DELETE CASCADE,
+            instruction_index INTEGER NOT NULL,
+            block_height BIGINT NOT NULL,
+            block_time TIMESTAMPTZ,
+            kind TEXT NOT NULL CHECK (kind IN ('transfer', 'mint', 'burn')),
+            mint_address TEXT NOT NULL,
+            source_address TEXT,
+            destination_address TEXT,
+            source_owner TEXT,
+            destination_owner TEXT,
+            authority_address TEXT,
+            amount NUMERIC(65, 0) NOT NULL,
+            decimals INTEGER,
+            program_id TEXT NOT NULL,
+            PRIMARY KEY (txid, instruction_index)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_token_transfers_mint ON token_transfers(mint_address, block_height DESC, txid DESC, instruction_index DESC);
+        CREATE INDEX IF NOT EXISTS idx_token_transfers_source ON token_transfers(source_address, block_height DESC);
+        CREATE INDEX IF NOT EXISTS idx_token_transfers_destination ON token_transfers(destination_address, block_height DESC);
+        CREATE INDEX IF NOT EXISTS idx_token_transfers_source_owner ON token_transfers(source_owner, block_height DESC);
+        CREATE INDEX IF NOT EXISTS idx_token_transfers_destination_owner ON token_transfers(destination_owner, block_height DESC);
+
+        CREATE TABLE IF NOT EXISTS token_mint_events (
+            txid TEXT NOT NULL REFERENCES transactions(txid) ON DELETE CASCADE,
+            instruction_index INTEGER NOT NULL,
+            block_height BIGINT NOT NULL,
+            block_time TIMESTAMPTZ,
+            mint_address TEXT NOT NULL,
+            kind TEXT NOT NULL CHECK (kind IN ('initialize', 'mint', 'burn', 'set_mint_authority', 'set_freeze_authority')),
+            amount NUMERIC(65, 0),
+            supply_after NUMERIC(65, 0) NOT NULL,
+            previous_authority TEXT,
+            new_authority TEXT,
+            PRIMARY KEY (txid, instruction_index)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_token_mint_events_mint ON token_mint_events(mint_address, block_height DESC, txid DESC, instruction_index DESC);
+        CREATE INDEX IF NOT EXISTS idx_token_mint_events_mint_time ON token_mint_events(mint_address, block_time);
+
+        CREATE TABLE IF NOT EXISTS token_account_authority_events (
+            txid TEXT NOT NULL REFERENCES transactions(txid) ON DELETE CASCADE,
+            instruction_index INTEGER NOT NULL,
+            block_height BIGINT NOT NULL,
+            token_account TEXT NOT NULL,
+            kind TEXT NOT NULL CHECK (kind IN ('set_owner', 'set_close_authority')),
+            previous_authority TEXT,
+            new_authority TEXT,
+            PRIMARY KEY (txid, instruction_index)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_token_account_authority_events_account ON token_account_authority_events(token_account, block_height DESC);
+
+        CREATE TABLE IF NOT EXISTS native_balances (
+            address_hex TEXT PRIMARY KEY,
+            balance NUMERIC(65, 0) NOT NULL DEFAULT 0,
+            updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+        );
+
+        CREATE TABLE IF NOT EXISTS native_balance_history (
+            address_hex TEXT NOT NULL,
+            block_height BIGINT NOT NULL,
+            block_time TIMESTAMPTZ,
+            balance NUMERIC(65, 0) NOT NULL,
+            txid TEXT,
+            PRIMARY KEY (address_hex, block_height)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_native_balance_history_time ON native_balance_history(address_hex, block_time);
+        CREATE INDEX IF NOT EXISTS idx_native_balance_history_height ON native_balance_history(block_height);
+
+        CREATE TABLE IF NOT EXISTS native_transfers (
+            txid TEXT NOT NULL REFERENCES transactions(txid) ON DELETE CASCADE,
+            instruction_index INTEGER NOT NULL,
+            block_height BIGINT NOT NULL,
+            block_time TIMESTAMPTZ,
+            kind TEXT NOT NULL CHECK (kind IN ('transfer', 'create_funding')),
+            source_address TEXT NOT NULL,
+            destination_address TEXT NOT NULL,
+            lamports NUMERIC(20, 0) NOT NULL,
+            PRIMARY KEY (txid, instruction_index)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_native_transfers_source ON native_transfers(source_address, block_height DESC);
+        CREATE INDEX IF NOT EXISTS idx_native_transfers_destination ON native_transfers(destination_address, block_height DESC);
+
+        CREATE TABLE IF NOT EXISTS token_metadata (
+            mint_address TEXT PRIMARY KEY,
+            metadata_address TEXT NOT NULL,
+            name TEXT NOT NULL DEFAULT '',
+            symbol TEXT NOT NULL DEFAULT '',
+            uri TEXT NOT NULL DEFAULT '',
+            update_authority TEXT,
+            additional_metadata JSONB NOT NULL DEFAULT '{}'::jsonb,
+            block_height BIGINT,
+            updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_token_metadata_metadata_address ON token_metadata(metadata_address);
         CREATE INDEX IF NOT EXISTS idx_accounts_owner ON accounts(owner);
         CREATE INDEX IF NOT EXISTS idx_accounts_height ON accounts(height);
     "#;
@@ -220,16 +433,36 @@ async fn bootstrap_schema_if_missing(pool: &PgPool) -> Result<()> {
 async fn reset_database(pool: &PgPool) -> Result<()> {
     let mut tx = pool.begin().await?;
 
-    // Drop trigger if present
+    // Drop legacy derivation triggers if present
     let drops = [
         "DROP TRIGGER IF EXISTS transaction_programs_trigger ON transactions",
+        "DROP TRIGGER IF EXISTS account_participation_trigger ON transactions",
+        "DROP TRIGGER IF EXISTS native_balances_trigger ON transactions",
         "DROP FUNCTION IF EXISTS update_transaction_programs()",
+        "DROP FUNCTION IF EXISTS populate_account_participation()",
+        "DROP FUNCTION IF EXISTS populate_native_balances_from_tx()",
         "DROP FUNCTION IF EXISTS normalize_program_id(text)",
         "DROP FUNCTION IF EXISTS decode_base58(text)",
+        "DROP TABLE IF EXISTS instructions",
+        "DROP TABLE IF EXISTS token_transfers",
+        "DROP TABLE IF EXISTS token_mint_events",
+        "DROP TABLE IF EXISTS token_account_authority_events",
+        "DROP TABLE IF EXISTS native_transfers",
+        "DROP TABLE IF EXISTS native_balance_history",
+        "DROP TABLE IF EXISTS native_balances",
+        "DROP TABLE IF EXISTS account_participation",
+        "DROP VIEW IF EXISTS account_token_balances",
+        "DROP TABLE IF EXISTS token_balances",
+        "DROP TABLE IF EXISTS token_accounts",
+        "DROP TABLE IF EXISTS token_metadata",
+        "DROP TABLE IF EXISTS token_mints",
+        "DROP TABLE IF EXISTS transaction_rollbacks",
+        "DROP TABLE IF EXISTS dkg_events",
         "DROP TABLE IF EXISTS transaction_programs",
         "DROP TABLE IF EXISTS programs",
         "DROP TABLE IF EXISTS transactions",
-        "DROP TABLE IF EXISTS blocks"
+        "DROP TABLE IF EXISTS blocks",
+        "DROP SEQUENCE IF EXISTS transaction_apply_order_seq"
     ];
 
     for stmt in drops.iter() {
@@ -17,11 +17,38 @@ use std::fs;
 use std::path::PathBuf;
 use sqlx::{PgPool, QueryBuilder};
 use tokio_postgres::{NoTls};
-use std::collections::{VecDeque, HashSet};
+use std::collections::{BTreeMap, BTreeSet, VecDeque, HashSet};
 use std::sync::atomic::{AtomicI64, Ordering};
 use std::time::{Duration, Instant};
 use tracing::info;
 
+use crate::arch_rpc::ArchRpcClient;
+use crate::arch_rpc::limiter::LimiterConfig;
+use crate::config::settings::ArchNodeSettings;
+use crate::indexer::collection::{ArchInstruction, ArchInstructionType};
+
+/// Atlas decodes System, Compute Budget, APL Token and ATA instructions into the same typed
+/// model the legacy path derives its tables from.
+impl core::collection::InstructionDecoderCollection for ArchInstruction {
+    type InstructionType = ArchInstructionType;
+    fn parse_instruction(instruction: &arch_program::instruction::Instruction) -> Option<core::instruction::DecodedInstruction<Self>> {
+        let accounts: Vec<String> = instruction.accounts.iter().map(|meta| hex::encode(meta.pubkey)).collect();
+        let program_id = bs58::encode(instruction.program_id).into_string();
+        let data = ArchInstruction::parse(&program_id, &instruction.data, &accounts)?;
+        Some(core::instruction::DecodedInstruction { program_id: instruction.program_id, accounts: instruction.accounts.clone(), data })
+    }
+    fn get_type(&self) -> Self::InstructionType { self.instruction_type() }
+}
+
+fn instruction_type_metric(kind: ArchInstructionType) -> &'static str {
+    match kind {
+        ArchInstructionType::System => "ix_decoded_system",
+        ArchInstructionType::ComputeBudget => "ix_decoded_compute_budget",
+        ArchInstructionType::Token => "ix_decoded_token",
+        ArchInstructionType::AssociatedTokenAccount => "ix_decoded_associated_token_account",
+    }
+}
+
 struct PromMetrics;
 
 #[async_trait]
@@ -73,7 +100,18 @@ pub async fn run_minimal_pipeline() -> Result<()> {
 }
 
 /// Run full syncing pipeline using Atlas SyncingDatasource wired to Arch RPC + WS and RocksDB checkpoint.
-pub async fn run_syncing_pipeline(rpc_url: &str, ws_url: &str, rocks_path: &str, db_pool: Arc<PgPool>) -> Result<()> {
+/// The indexer's own RPC calls (tip polling, rollback pubkey resolution) go through one
+/// `ArchRpcClient` built from `arch_node`, so they share its TLS, endpoint pool, retry and
+/// circuit-breaker configuration with the legacy path.
+pub async fn run_syncing_pipeline(
+    arch_node: &ArchNodeSettings,
+    rpc_url: &str,
+    ws_url: &str,
+    rocks_path: &str,
+    db_pool: Arc<PgPool>,
+) -> Result<()> {
+    let rpc = Arc::new(ArchRpcClient::from_settings(arch_node)?);
+
     // Simple file-based checkpoint store compatible with atlas-arch::sync::CheckpointStore
     struct FileCheckpointStore { path: PathBuf }
     #[async_trait]
@@ -154,11 +192,17 @@ pub async fn run_syncing_pipeline(rpc_url: &str, ws_url: &str, rocks_path: &str,
     };
 
     // Datasource implementations
-    // Read tuning knobs from env with sensible defaults
-    let max_concurrency = std::env::var("ARCH_MAX_CONCURRENCY")
-        .ok()
-        .and_then(|v| v.parse::<usize>().ok())
-        .unwrap_or(64);
+    // The Atlas datasource runs its own HTTP client and only takes a fixed in-flight cap, so
+    // it is given the adaptive limiter's upper bound (arch_node.max_concurrency, which
+    // ARCH_MAX_CONCURRENCY still sets) and the calls the indexer makes itself go through the
+    // limiter of the shared `rpc` client. Other tuning knobs come from env with sensible defaults.
+    let max_concurrency = LimiterConfig::bounded(
+        arch_node.min_concurrency,
+        arch_node.max_concurrency,
+        arch_node.initial_concurrency,
+        arch_node.latency_target_ms,
+    )
+    .max;
     let batch_emit_size = std::env::var("ARCH_BULK_BATCH_SIZE")
         .ok()
         .and_then(|v| v.parse::<usize>().ok())
@@ -212,16 +256,6 @@ pub async fn run_syncing_pipeline(rpc_url: &str, ws_url: &str, rocks_path: &str,
     );
 
 
-    // Transaction bridge processor wiring
-    // Define a minimal instruction decoder collection (no-op)
-    #[derive(Clone, Debug, PartialEq, Eq, Hash, serde::Serialize)]
-    struct EmptyCollection;
-    impl core::collection::InstructionDecoderCollection for EmptyCollection {
-        type InstructionType = ();
-        fn parse_instruction(_instruction: &arch_program::instruction::Instruction) -> Option<core::instruction::DecodedInstruction<Self>> { None }
-        fn get_type(&self) -> Self::InstructionType { () }
-    }
-
     // Helper: open a raw tokio-postgres connection (COPY support)
     async fn open_copy_conn() -> anyhow::Result<tokio_postgres::Client> {
         let db_url = std::env::var("DATABASE_URL")?;
@@ -235,14 +269,40 @@ pub async fn run_syncing_pipeline(rpc_url: &str, ws_url: &str, rocks_path: &str,
     // Provide AccountDatasource to enable account refresh after rollback/reapply
     let account_provider = atlas_arch_rpc_datasource::ArchRpcClient::new(rpc_url);
 
-    // Processor that writes transactions into the existing DB
+    // A transaction row as both runtimes store it
+    struct TransactionRow {
+        txid: String,
+        block_height: i64,
+        data: serde_json::Value,
+        status: serde_json::Value,
+        bitcoin_txids: Vec<String>,
+        logs: serde_json::Value,
+        /// The node's `accounts_tags`, kept so program links match the legacy path and can
+        /// be re-derived from the stored row on reapply
+        accounts_tags: serde_json::Value,
+    }
+
+    // COPY text format: escape backslashes and the row/column delimiters
+    fn copy_text(value: &str) -> String {
+        value.replace('\\', "\\\\").replace('\n', "\\n").replace('\r', "\\r").replace('\t', "\\t")
+    }
+
+    fn text_array_literal(items: &[String]) -> String {
+        let quoted: Vec<String> = items.iter().map(|s| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))).collect();
+        format!("{{{}}}", quoted.join(","))
+    }
+
+    // Processor that writes transactions and, in the same DB transaction, every table the
+    // legacy path derives from them. A transaction delivered again (e.g. where backfill hands
+    // over to the live stream) is upserted but its effects are not applied a second time.
     struct TransactionDbProcessor {
         pool: Arc<PgPool>,
+        use_copy_bulk: bool,
     }
 
     #[async_trait::async_trait]
     impl core::processor::Processor for TransactionDbProcessor {
-        type InputType = core::transaction::TransactionProcessorInputType<EmptyCollection, ()>;
+        type InputType = core::transaction::TransactionProcessorInputType<ArchInstruction, ()>;
         type OutputType = ();
 
         async fn process(
@@ -250,83 +310,154 @@ pub async fn run_syncing_pipeline(rpc_url: &str, ws_url: &str, rocks_path: &str,
             data: Vec<Self::InputType>,
             metrics: Arc<core::metrics::MetricsCollection>,
         ) -> core::error::IndexerResult<Self::OutputType> {
-            let mut tx = self.pool.begin().await.map_err(|e| core::error::Error::Custom(format!("db begin: {}", e)))?;
+            if data.is_empty() { return Ok(()); }
 
-            // Batch upsert transactions with one statement or COPY bulk path
-            if !data.is_empty() && !std::env::var("ATLAS_USE_COPY_BULK").ok().as_deref().eq(&Some("1")) {
-                let mut qb = QueryBuilder::<sqlx::Postgres>::new(
-                    "INSERT INTO transactions (txid, block_height, data, status, bitcoin_txids) VALUES ",
-                );
-                qb.push_values(data.iter(), |mut b, (meta, _parsed, _matched)| {
-                    let status_json = serde_json::to_value(&meta.status)
-                        .unwrap_or(serde_json::json!(null));
-                    let data_json = serde_json::json!({
+            // One row per txid, in delivery order so derived balances follow the chain
+            let mut seen: HashSet<String> = HashSet::new();
+            let rows: Vec<TransactionRow> = data
+                .iter()
+                .filter(|(meta, _parsed, _matched)| seen.insert(meta.id.clone()))
+                .map(|(meta, _parsed, _matched)| TransactionRow {
+                    txid: meta.id.clone(),
+                    block_height: meta.block_height as i64,
+                    data: serde_json::json!({
                         "id": meta.id,
                         "block_height": meta.block_height,
                         "message": meta.message,
                         "rollback_status": meta.rollback_status,
-                    });
-                    let bitcoin_txids: Vec<String> = meta
-                        .bitcoin_txid
-                        .as_ref()
-                        .map(|s| vec![s.to_string()])
-                        .unwrap_or_default();
-                    b.push_bind(&meta.id)
-                        .push_bind(meta.block_height as i64)
-                        .push_bind(data_json)
-                        .push_bind(status_json)
-                        .push_bind(bitcoin_txids);
+                    }),
+                    status: serde_json::to_value(&meta.status).unwrap_or(serde_json::json!(null)),
+                    bitcoin_txids: meta.bitcoin_txid.as_ref().map(|s| vec![s.to_string()]).unwrap_or_default(),
+                    logs: serde_json::to_value(&meta.logs).unwrap_or(serde_json::json!([])),
+                    accounts_tags: serde_json::to_value(&meta.accounts_tags).unwrap_or(serde_json::json!([])),
+                })
+                .collect();
+
+            let mut tx = self.pool.begin().await.map_err(|e| core::error::Error::Custom(format!("db begin: {}", e)))?;
+
+            let upserted: Result<Vec<(String, bool, bool)>, sqlx::Error> = if !self.use_copy_bulk {
+                // Batch upsert transactions with one statement
+                let mut qb = QueryBuilder::<sqlx::Postgres>::new(
+                    "INSERT INTO transactions (txid, block_height, data, status, bitcoin_txids, logs, accounts_tags) ",
+                );
+                qb.push_values(rows.iter(), |mut b, row| {
+                    b.push_bind(&row.txid)
+                        .push_bind(row.block_height)
+                        .push_bind(&row.data)
+                        .push_bind(&row.status)
+                        .push_bind(&row.bitcoin_txids)
+                        .push_bind(&row.logs)
+                        .push_bind(&row.accounts_tags);
                 });
-                qb.push(" ON CONFLICT (txid) DO UPDATE SET block_height = EXCLUDED.block_height, data = EXCLUDED.data, status = EXCLUDED.status, bitcoin_txids = EXCLUDED.bitcoin_txids");
-                if let Err(e) = qb.build().execute(&mut *tx).await {
+                qb.push(" ON CONFLICT (txid) DO UPDATE SET block_height = EXCLUDED.block_height, data = EXCLUDED.data, status = EXCLUDED.status, bitcoin_txids = EXCLUDED.bitcoin_txids, logs = EXCLUDED.logs, accounts_tags = EXCLUDED.accounts_tags");
+                qb.push(" RETURNING txid, (xmax = 0) AS inserted, effects_applied");
+                qb.build_query_as().fetch_all(&mut *tx).await
+            } else {
+                // COPY into temp staging on the same connection, then upsert from it
+                let mut buf = String::new();
+                for row in &rows {
+                    let fields = [
+                        copy_text(&row.txid),
+                        row.block_height.to_string(),
+                        copy_text(&row.data.to_string()),
+                        copy_text(&row.status.to_string()),
+                        copy_text(&text_array_literal(&row.bitcoin_txids)),
+                        copy_text(&row.logs.to_string()),
+                        copy_text(&row.accounts_tags.to_string()),
+                    ];
+                    buf.push_str(&fields.join("\t"));
+                    buf.push('\n');
+                }
+                sqlx::query("CREATE TEMP TABLE IF NOT EXISTS tmp_transactions (txid text, block_height bigint, data jsonb, status jsonb, bitcoin_txids text[], logs jsonb, accounts_tags jsonb) ON COMMIT DROP")
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|e| core::error::Error::Custom(format!("tmp table: {}", e)))?;
+                let mut copy = tx
+                    .copy_in_raw("COPY tmp_transactions (txid, block_height, data, status, bitcoin_txids, logs, accounts_tags) FROM STDIN")
+                    .await
+                    .map_err(|e| core::error::Error::Custom(format!("copy in: {}", e)))?;
+                copy.send(buf.as_bytes()).await.map_err(|e| core::error::Error::Custom(format!("copy write: {}", e)))?;
+                copy.finish().await.map_err(|e| core::error::Error::Custom(format!("copy finish: {}", e)))?;
+                sqlx::query_as(
+                    "INSERT INTO transactions (txid, block_height, data, status, bitcoin_txids, logs, accounts_tags) SELECT txid, block_height, data, status, bitcoin_txids, logs, accounts_tags FROM tmp_transactions ON CONFLICT (txid) DO UPDATE SET block_height = EXCLUDED.block_height, data = EXCLUDED.data, status = EXCLUDED.status, bitcoin_txids = EXCLUDED.bitcoin_txids, logs = EXCLUDED.logs, accounts_tags = EXCLUDED.accounts_tags RETURNING txid, (xmax = 0) AS inserted, effects_applied",
+                )
+                .fetch_all(&mut *tx)
+                .await
+            };
+            let upserted = match upserted {
+                Ok(upserted) => upserted,
+                Err(e) => {
                     let _ = metrics.increment_counter("tx_write_failed", 1).await;
                     return Err(core::error::Error::Custom(format!("tx upsert failed: {}", e)));
-                } else {
-                    let _ = metrics.increment_counter("tx_write_success", data.len() as u64).await;
                 }
-            } else if !data.is_empty() {
-                // COPY into temp staging and upsert within a single transaction
-                let mut client = open_copy_conn().await.map_err(|e| core::error::Error::Custom(format!("copy conn: {}", e)))?;
-                let transaction = client.transaction().await.map_err(|e| core::error::Error::Custom(format!("copy tx begin: {}", e)))?;
-                transaction.batch_execute("CREATE TEMP TABLE IF NOT EXISTS tmp_transactions (txid text, block_height bigint, data jsonb, status jsonb, bitcoin_txids text[]) ON COMMIT DROP;").await.map_err(|e| core::error::Error::Custom(format!("tmp table: {}", e)))?;
-                let sink = transaction.copy_in("COPY tmp_transactions (txid, block_height, data, status, bitcoin_txids) FROM STDIN BINARY").await.map_err(|e| core::error::Error::Custom(format!("copy in: {}", e)))?;
-                let writer = tokio_postgres::binary_copy::BinaryCopyInWriter::new(sink, &[tokio_postgres::types::Type::TEXT, tokio_postgres::types::Type::INT8, tokio_postgres::types::Type::JSONB, tokio_postgres::types::Type::JSONB, tokio_postgres::types::Type::TEXT_ARRAY]);
-                let mut writer = std::pin::pin!(writer);
-                for (meta, _parsed, _matched) in &data {
-                    let status_json: serde_json::Value = serde_json::to_value(&meta.status).unwrap_or(serde_json::json!(null));
-                    let data_json: serde_json::Value = serde_json::json!({
-                        "id": meta.id,
-                        "block_height": meta.block_height,
-                        "message": meta.message,
-                        "rollback_status": meta.rollback_status,
-                    });
-                    let bitcoin_txids: Vec<String> = meta
-                        .bitcoin_txid
-                        .as_ref()
-                        .map(|s| vec![s.to_string()])
-                        .unwrap_or_default();
-                    use tokio_postgres::types::ToSql;
-                    let txid: String = meta.id.clone();
-                    let height_i64: i64 = meta.block_height as i64;
-                    let json_data: serde_json::Value = data_json;
-                    let json_status: serde_json::Value = status_json;
-                    let txids_arr: Vec<String> = bitcoin_txids;
-                    let params: [&(dyn ToSql + Sync); 5] = [
-                        &txid,
-                        &height_i64,
-                        &json_data,
-                        &json_status,
-                        &txids_arr,
-                    ];
-                    writer.as_mut().write(&params).await.map_err(|e| core::error::Error::Custom(format!("copy write: {}", e)))?;
+            };
+            let mut inserted: HashSet<String> =
+                upserted.iter().filter(|(_, inserted, _)| *inserted).map(|(txid, _, _)| txid.clone()).collect();
+            // Rows stored earlier without their effects (and not rolled back by the node) get
+            // them now, like new ones
+            let unapplied: HashSet<String> =
+                upserted.into_iter().filter(|(_, _, applied)| !*applied).map(|(txid, _, _)| txid).collect();
+
+            // Instructions, native transfers and token effects are persisted from the same
+            // `ArchInstruction` model the collection decoded, inside this DB transaction so
+            // they never land without (or twice for) their transaction row
+            let mut applied = 0u64;
+            for row in rows.iter().filter(|row| unapplied.contains(&row.txid)) {
+                if !inserted.contains(&row.txid)
+                    && crate::indexer::rollback::is_rolled_back(&mut tx, &row.txid)
+                        .await
+                        .map_err(|e| core::error::Error::Custom(format!("rollback state {}: {}", row.txid, e)))?
+                {
+                    continue;
                 }
-                writer.as_mut().finish().await.map_err(|e| core::error::Error::Custom(format!("copy finish: {}", e)))?;
-                transaction.batch_execute("INSERT INTO transactions (txid, block_height, data, status, bitcoin_txids) SELECT txid, block_height, data, status, bitcoin_txids FROM tmp_transactions ON CONFLICT (txid) DO UPDATE SET block_height = EXCLUDED.block_height, data = EXCLUDED.data, status = EXCLUDED.status, bitcoin_txids = EXCLUDED.bitcoin_txids;").await.map_err(|e| core::error::Error::Custom(format!("copy upsert: {}", e)))?;
-                transaction.commit().await.map_err(|e| core::error::Error::Custom(format!("copy tx commit: {}", e)))?;
-                let _ = metrics.increment_counter("tx_write_success", data.len() as u64).await;
+                applied += 1;
+                let accounts_tags = row.accounts_tags.as_array().map(|tags| tags.as_slice());
+                crate::indexer::derived::apply_transaction(&mut tx, &row.txid, &row.data, accounts_tags)
+                    .await
+                    .map_err(|e| core::error::Error::Custom(format!("derive {}: {}", row.txid, e)))?;
+            }
+
+            // Each newly written transaction adds to its block's stored count and size
+            let mut per_block: BTreeMap<i64, (i64, i64)> = BTreeMap::new();
+            for row in rows.iter().filter(|row| inserted.contains(&row.txid)) {
+                let entry = per_block.entry(row.block_height).or_default();
+                entry.0 += 1;
+                entry.1 += decoder::transaction_size(&row.data);
+            }
+            if !per_block.is_empty() {
+                let (heights, (counts, sizes)): (Vec<i64>, (Vec<i64>, Vec<i64>)) = per_block.into_iter().unzip();
+                sqlx::query(
+                    r#"
+                    UPDATE blocks b
+                    SET transaction_count = COALESCE(b.transaction_count, 0) + d.count,
+                        block_size_bytes = COALESCE(b.block_size_bytes, 0) + d.size
+                    FROM UNNEST($1::bigint[], $2::bigint[], $3::bigint[]) AS d(height, count, size)
+                    WHERE b.height = d.height
+                    "#,
+                )
+                .bind(&heights)
+                .bind(&counts)
+                .bind(&sizes)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| core::error::Error::Custom(format!("block totals: {}", e)))?;
             }
 
             tx.commit().await.map_err(|e| core::error::Error::Custom(format!("db commit: {}", e)))?;
+            let _ = metrics.increment_counter("tx_write_success", rows.len() as u64).await;
+            let _ = metrics.increment_counter("tx_derived", applied).await;
+
+            // Count decoded instructions per program, once per newly written transaction
+            let mut decoded: BTreeMap<&'static str, u64> = BTreeMap::new();
+            for (meta, parsed, _matched) in &data {
+                if !inserted.remove(&meta.id) { continue; }
+                for (_ix_meta, ix) in parsed {
+                    *decoded.entry(instruction_type_metric(ix.data.instruction_type())).or_insert(0) += 1;
+                }
+            }
+            for (key, n) in decoded {
+                let _ = metrics.increment_counter(key, n).await;
+            }
             Ok(())
         }
     }
@@ -338,13 +469,13 @@ pub async fn run_syncing_pipeline(rpc_url: &str, ws_url: &str, rocks_path: &str,
         .shutdown_strategy(ShutdownStrategy::Immediate);
 
     // Register transaction bridge
-    let tx_processor = TransactionDbProcessor { pool: db_pool.clone() };
-    pipeline_builder = pipeline_builder.transaction::<EmptyCollection, ()>(tx_processor, None);
+    let tx_processor = TransactionDbProcessor { pool: db_pool.clone(), use_copy_bulk };
+    pipeline_builder = pipeline_builder.transaction::<ArchInstruction, ()>(tx_processor, None);
 
     // Set up a live tip-height poller for accurate rate/ETA reporting
     let tip_height: Arc<AtomicI64> = Arc::new(AtomicI64::new(0));
     {
-        let rpc = crate::arch_rpc::ArchRpcClient::new(rpc_url.to_string());
+        let rpc = Arc::clone(&rpc);
         let tip_clone = tip_height.clone();
         tokio::spawn(async move {
             loop {
@@ -356,6 +487,29 @@ pub async fn run_syncing_pipeline(rpc_url: &str, ws_url: &str, rocks_path: &str,
         });
     }
 
+    // A blocks row as the legacy path writes it: hex hashes, the node timestamp truncated to
+    // seconds and a bitcoin height of 0 when the node reports none. The transaction count and
+    // size are added by the transaction processor as the block's transactions are written.
+    struct BlockRow {
+        height: i64,
+        hash: String,
+        timestamp: chrono::DateTime<chrono::Utc>,
+        bitcoin_block_height: i64,
+        previous_block_hash: Option<String>,
+    }
+
+    impl BlockRow {
+        fn from_details(b: &core::datasource::BlockDetails) -> Self {
+            BlockRow {
+                height: b.height as i64,
+                hash: b.block_hash.map(|h| hex::encode(h.to_array())).unwrap_or_default(),
+                timestamp: crate::utils::convert_arch_timestamp(b.block_time.unwrap_or(0)),
+                bitcoin_block_height: b.bitcoin_block_height.map(|h| h as i64).unwrap_or(0),
+                previous_block_hash: b.previous_block_hash.map(|h| hex::encode(h.to_array())),
+            }
+        }
+    }
+
     // BlockDetails processor → blocks table
     struct BlockDetailsDbProcessor {
         pool: Arc<PgPool>,
@@ -381,66 +535,53 @@ pub async fn run_syncing_pipeline(rpc_url: &str, ws_url: &str, rocks_path: &str,
             metrics: Arc<core::metrics::MetricsCollection>,
         ) -> core::error::IndexerResult<Self::OutputType> {
             let mut tx = self.pool.begin().await.map_err(|e| core::error::Error::Custom(format!("db begin: {}", e)))?;
-            let mut max_height_in_batch: i64 = -1;
-            let mut min_height_in_batch: i64 = i64::MAX;
-            if !data.is_empty() && !self.use_copy_bulk {
+            let rows: Vec<BlockRow> = data.iter().map(BlockRow::from_details).collect();
+            let max_height_in_batch: i64 = rows.iter().map(|r| r.height).max().unwrap_or(-1);
+            let min_height_in_batch: i64 = rows.iter().map(|r| r.height).min().unwrap_or(i64::MAX);
+            const BLOCK_UPSERT: &str = " ON CONFLICT (height) DO UPDATE SET hash = EXCLUDED.hash, timestamp = EXCLUDED.timestamp, bitcoin_block_height = EXCLUDED.bitcoin_block_height, previous_block_hash = EXCLUDED.previous_block_hash";
+            if !rows.is_empty() && !self.use_copy_bulk {
                 // Batch upsert blocks with one statement
                 let mut qb = QueryBuilder::<sqlx::Postgres>::new(
-                    "INSERT INTO blocks (height, hash, timestamp) VALUES ",
+                    "INSERT INTO blocks (height, hash, timestamp, bitcoin_block_height, previous_block_hash) ",
                 );
-                for b in &data {
-                    let micros: i64 = b.block_time.unwrap_or(0);
-                    let secs_f64: f64 = (micros as f64) / 1_000_000_f64;
-                    let hash = b.block_hash.map(|h| format!("{:?}", h)).unwrap_or_default();
-                    qb.push("(")
-                        .push_bind(b.height as i64)
-                        .push(", ")
-                        .push_bind(hash)
-                        .push(", to_timestamp(")
-                        .push_bind(secs_f64)
-                        .push(") )");
-                    qb.separated(',');
-                    let h_i64 = b.height as i64;
-                    if h_i64 > max_height_in_batch { max_height_in_batch = h_i64; }
-                    if h_i64 < min_height_in_batch { min_height_in_batch = h_i64; }
-                }
-                qb.push(" ON CONFLICT (height) DO UPDATE SET hash = EXCLUDED.hash, timestamp = EXCLUDED.timestamp");
+                qb.push_values(rows.iter(), |mut b, row| {
+                    b.push_bind(row.height)
+                        .push_bind(&row.hash)
+                        .push_bind(row.timestamp)
+                        .push_bind(row.bitcoin_block_height)
+                        .push_bind(&row.previous_block_hash);
+                });
+                qb.push(BLOCK_UPSERT);
                 if let Err(e) = qb.build().execute(&mut *tx).await {
                     let _ = metrics.increment_counter("block_write_failed", 1).await;
                     return Err(core::error::Error::Custom(format!("block upsert failed: {}", e)));
                 } else {
-                    let _ = metrics.increment_counter("block_write_success", data.len() as u64).await;
+                    let _ = metrics.increment_counter("block_write_success", rows.len() as u64).await;
                 }
-            } else if !data.is_empty() {
+            } else if !rows.is_empty() {
                 // COPY bulk for blocks within a single transaction
                 let mut client = open_copy_conn().await.map_err(|e| core::error::Error::Custom(format!("copy conn: {}", e)))?;
                 let transaction = client.transaction().await.map_err(|e| core::error::Error::Custom(format!("copy tx begin: {}", e)))?;
-                transaction.batch_execute("CREATE TEMP TABLE IF NOT EXISTS tmp_blocks (height bigint, hash text, ts_seconds double precision) ON COMMIT DROP;").await.map_err(|e| core::error::Error::Custom(format!("tmp table: {}", e)))?;
-                let sink = transaction.copy_in("COPY tmp_blocks (height, hash, ts_seconds) FROM STDIN BINARY").await.map_err(|e| core::error::Error::Custom(format!("copy in: {}", e)))?;
-                let writer = tokio_postgres::binary_copy::BinaryCopyInWriter::new(sink, &[tokio_postgres::types::Type::INT8, tokio_postgres::types::Type::TEXT, tokio_postgres::types::Type::FLOAT8]);
+                transaction.batch_execute("CREATE TEMP TABLE IF NOT EXISTS tmp_blocks (height bigint, hash text, ts_seconds bigint, bitcoin_block_height bigint, previous_block_hash text) ON COMMIT DROP;").await.map_err(|e| core::error::Error::Custom(format!("tmp table: {}", e)))?;
+                let sink = transaction.copy_in("COPY tmp_blocks (height, hash, ts_seconds, bitcoin_block_height, previous_block_hash) FROM STDIN BINARY").await.map_err(|e| core::error::Error::Custom(format!("copy in: {}", e)))?;
+                use tokio_postgres::types::{ToSql, Type};
+                let writer = tokio_postgres::binary_copy::BinaryCopyInWriter::new(sink, &[Type::INT8, Type::TEXT, Type::INT8, Type::INT8, Type::TEXT]);
                 let mut writer = std::pin::pin!(writer);
-                for b in &data {
-                    let micros: i64 = b.block_time.unwrap_or(0);
-                    let secs_f64: f64 = (micros as f64) / 1_000_000_f64;
-                    let hash = b.block_hash.map(|h| format!("{:?}", h)).unwrap_or_default();
-                    use tokio_postgres::types::ToSql;
-                    let height_i64: i64 = b.height as i64;
-                    let hash_owned: String = hash;
-                    let secs: f64 = secs_f64;
-                    let params: [&(dyn ToSql + Sync); 3] = [
-                        &height_i64,
-                        &hash_owned,
+                for row in &rows {
+                    let secs: i64 = row.timestamp.timestamp();
+                    let params: [&(dyn ToSql + Sync); 5] = [
+                        &row.height,
+                        &row.hash,
                         &secs,
+                        &row.bitcoin_block_height,
+                        &row.previous_block_hash,
                     ];
                     writer.as_mut().write(&params).await.map_err(|e| core::error::Error::Custom(format!("copy write: {}", e)))?;
-                    let h_i64 = b.height as i64;
-                    if h_i64 > max_height_in_batch { max_height_in_batch = h_i64; }
-                    if h_i64 < min_height_in_batch { min_height_in_batch = h_i64; }
                 }
                 writer.as_mut().finish().await.map_err(|e| core::error::Error::Custom(format!("copy finish: {}", e)))?;
-                transaction.batch_execute("INSERT INTO blocks (height, hash, timestamp) SELECT height, hash, to_timestamp(ts_seconds) FROM tmp_blocks ON CONFLICT (height) DO UPDATE SET hash = EXCLUDED.hash, timestamp = EXCLUDED.timestamp;").await.map_err(|e| core::error::Error::Custom(format!("copy upsert: {}", e)))?;
+                transaction.batch_execute(&format!("INSERT INTO blocks (height, hash, timestamp, bitcoin_block_height, previous_block_hash) SELECT height, hash, to_timestamp(ts_seconds), bitcoin_block_height, previous_block_hash FROM tmp_blocks{};", BLOCK_UPSERT)).await.map_err(|e| core::error::Error::Custom(format!("copy upsert: {}", e)))?;
                 transaction.commit().await.map_err(|e| core::error::Error::Custom(format!("copy tx commit: {}", e)))?;
-                let _ = metrics.increment_counter("block_write_success", data.len() as u64).await;
+                let _ = metrics.increment_counter("block_write_success", rows.len() as u64).await;
             }
             tx.commit().await.map_err(|e| core::error::Error::Custom(format!("db commit: {}", e)))?;
 
@@ -561,7 +702,7 @@ pub async fn run_syncing_pipeline(rpc_url: &str, ws_url: &str, rocks_path: &str,
 
     async fn resolve_pubkeys_for_txids(
         pool: &PgPool,
-        rpc: &crate::arch_rpc::ArchRpcClient,
+        rpc: &ArchRpcClient,
         txids: &[String],
     ) -> anyhow::Result<HashSet<arch_program::pubkey::Pubkey>> {
         use std::collections::HashSet as StdHashSet;
@@ -588,7 +729,7 @@ pub async fn run_syncing_pipeline(rpc_url: &str, ws_url: &str, rocks_path: &str,
         Ok(result)
     }
 
-    struct RolledbackTxProcessor { pool: Arc<PgPool>, rpc: Arc<crate::arch_rpc::ArchRpcClient> }
+    struct RolledbackTxProcessor { pool: Arc<PgPool>, rpc: Arc<ArchRpcClient> }
     #[async_trait::async_trait]
     impl core::processor::Processor for RolledbackTxProcessor {
         type InputType = core::datasource::RolledbackTransactionsEvent;
@@ -600,12 +741,10 @@ pub async fn run_syncing_pipeline(rpc_url: &str, ws_url: &str, rocks_path: &str,
         ) -> core::error::IndexerResult<Self::OutputType> {
             let mut all_txids: Vec<String> = Vec::new();
             for ev in &data { all_txids.extend(ev.transaction_hashes.clone()); }
-            if !all_txids.is_empty() {
-                let _ = sqlx::query("UPDATE transactions SET status = jsonb_set(COALESCE(status, '{}'), '{rolled_back}', 'true') WHERE txid = ANY($1)")
-                    .bind(&all_txids[..])
-                    .execute(&*self.pool)
-                    .await;
-            }
+            let rolled_back = crate::indexer::rollback::rollback_transactions(&self.pool, &all_txids)
+                .await
+                .map_err(|e| core::error::Error::Custom(format!("rollback: {}", e)))?;
+            let _ = metrics.increment_counter("tx_rolled_back", rolled_back.len() as u64).await;
             let mut pubkeys: HashSet<arch_program::pubkey::Pubkey> = HashSet::new();
             if let Ok(set) = resolve_pubkeys_for_txids(&self.pool, &self.rpc, &all_txids).await { pubkeys = set; }
             let _ = metrics.increment_counter("rollback_refresh_pubkeys", pubkeys.len() as u64).await;
@@ -613,7 +752,7 @@ pub async fn run_syncing_pipeline(rpc_url: &str, ws_url: &str, rocks_path: &str,
         }
     }
 
-    struct ReappliedTxProcessor { pool: Arc<PgPool>, rpc: Arc<crate::arch_rpc::ArchRpcClient> }
+    struct ReappliedTxProcessor { pool: Arc<PgPool>, rpc: Arc<ArchRpcClient> }
     #[async_trait::async_trait]
     impl core::processor::Processor for ReappliedTxProcessor {
         type InputType = core::datasource::ReappliedTransactionsEvent;
@@ -625,12 +764,10 @@ pub async fn run_syncing_pipeline(rpc_url: &str, ws_url: &str, rocks_path: &str,
         ) -> core::error::IndexerResult<Self::OutputType> {
             let mut all_txids: Vec<String> = Vec::new();
             for ev in &data { all_txids.extend(ev.transaction_hashes.clone()); }
-            if !all_txids.is_empty() {
-                let _ = sqlx::query("UPDATE transactions SET status = (status - 'rolled_back') WHERE txid = ANY($1)")
-                    .bind(&all_txids[..])
-                    .execute(&*self.pool)
-                    .await;
-            }
+            let reapplied = crate::indexer::rollback::reapply_transactions(&self.pool, &all_txids)
+                .await
+                .map_err(|e| core::error::Error::Custom(format!("reapply: {}", e)))?;
+            let _ = metrics.increment_counter("tx_reapplied", reapplied.len() as u64).await;
             let mut pubkeys: HashSet<arch_program::pubkey::Pubkey> = HashSet::new();
             if let Ok(set) = resolve_pubkeys_for_txids(&self.pool, &self.rpc, &all_txids).await { pubkeys = set; }
             let _ = metrics.increment_counter("reapplied_refresh_pubkeys", pubkeys.len() as u64).await;
@@ -638,10 +775,9 @@ pub async fn run_syncing_pipeline(rpc_url: &str, ws_url: &str, rocks_path: &str,
         }
     }
 
-    let rpc_for_resolver = Arc::new(crate::arch_rpc::ArchRpcClient::new(rpc_url.to_string()));
-    let rb_proc = RolledbackTxProcessor { pool: db_pool.clone(), rpc: rpc_for_resolver.clone() };
+    let rb_proc = RolledbackTxProcessor { pool: db_pool.clone(), rpc: Arc::clone(&rpc) };
     pipeline_builder = pipeline_builder.rolledback_transactions(rb_proc);
-    let rp_proc = ReappliedTxProcessor { pool: db_pool.clone(), rpc: rpc_for_resolver.clone() };
+    let rp_proc = ReappliedTxProcessor { pool: db_pool.clone(), rpc: Arc::clone(&rpc) };
     pipeline_builder = pipeline_builder.reapplied_transactions(rp_proc);
 
     // Accounts processor: upsert into accounts table
@@ -673,19 +809,20 @@ pub async fn run_syncing_pipeline(rpc_url: &str, ws_url: &str, rocks_path: &str,
             data: Vec<Self::InputType>,
             _metrics: Arc<core::metrics::MetricsCollection>,
         ) -> core::error::IndexerResult<Self::OutputType> {
-            if data.is_empty() { return Ok(()); }
-            let mut qb = QueryBuilder::<sqlx::Postgres>::new(
-                "INSERT INTO accounts (pubkey, lamports, owner, data, height) VALUES ",
-            );
-            qb.push_values(data.iter(), |mut b, (meta, decoded, _raw)| {
-                b.push_bind(hex::encode(meta.pubkey))
-                    .push_bind(decoded.lamports as i64)
-                    .push_bind(format!("{:?}", decoded.owner))
-                    .push_bind(&decoded.data)
-                    .push_bind(meta.height as i64);
-            });
-            qb.push(" ON CONFLICT (pubkey) DO UPDATE SET lamports = EXCLUDED.lamports, owner = EXCLUDED.owner, data = EXCLUDED.data, height = EXCLUDED.height, updated_at = CURRENT_TIMESTAMP");
-            qb.build().execute(&*self.pool).await.map_err(|e| core::error::Error::Custom(format!("accounts upsert: {}", e)))?;
+            let states: Vec<crate::indexer::accounts::AccountState> = data
+                .iter()
+                .map(|(meta, decoded, _raw)| crate::indexer::accounts::AccountState {
+                    pubkey: hex::encode(meta.pubkey),
+                    lamports: decoded.lamports as i64,
+                    // Same lowercase hex as the legacy account_update path
+                    owner: decoder::key_to_hex(&serde_json::to_value(decoded.owner).unwrap_or_default()),
+                    data: decoded.data.clone(),
+                    height: meta.height as i64,
+                })
+                .collect();
+            crate::indexer::accounts::store_account_states(&self.pool, &states)
+                .await
+                .map_err(|e| core::error::Error::Custom(format!("accounts upsert: {}", e)))?;
             Ok(())
         }
     }
@@ -701,6 +838,20 @@ pub async fn run_syncing_pipeline(rpc_url: &str, ws_url: &str, rocks_path: &str,
             _metrics: Arc<core::metrics::MetricsCollection>,
         ) -> core::error::IndexerResult<Self::OutputType> {
             if data.is_empty() { return Ok(()); }
+            let tombstones: BTreeSet<(String, i64)> = data.iter().map(|d| (hex::encode(d.pubkey), d.height as i64)).collect();
+            let (history_pubkeys, heights): (Vec<String>, Vec<i64>) = tombstones.into_iter().unzip();
+            sqlx::query(
+                r#"INSERT INTO account_history (pubkey, height, is_deleted)
+                   SELECT pubkey, height, TRUE FROM UNNEST($1::text[], $2::bigint[]) AS s(pubkey, height)
+                   ON CONFLICT (pubkey, height) DO UPDATE SET lamports = NULL, owner = NULL, data = NULL,
+                       is_deleted = TRUE, recorded_at = CURRENT_TIMESTAMP"#,
+            )
+            .bind(&history_pubkeys)
+            .bind(&heights)
+            .execute(&*self.pool)
+            .await
+            .map_err(|e| core::error::Error::Custom(format!("account history tombstone: {}", e)))?;
+
             let pubkeys: Vec<String> = data.into_iter().map(|d| hex::encode(d.pubkey)).collect();
             let query = "DELETE FROM accounts WHERE pubkey = ANY($1)";
             sqlx::query(query).bind(&pubkeys[..]).execute(&*self.pool).await.map_err(|e| core::error::Error::Custom(format!("accounts delete: {}", e)))?;
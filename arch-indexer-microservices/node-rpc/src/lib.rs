@@ -0,0 +1,16 @@
+//! Transport for Arch node JSON-RPC shared by the indexer and the API server: the weighted
+//! endpoint pool, the adaptive concurrency limiter, retries behind a circuit breaker, the
+//! TLS setup of HTTP and WebSocket connections, and the fixture recorder. Each service
+//! builds its `ArchRpcClient` and WebSocket client on top of these pieces from its own
+//! `arch_node` settings.
+
+pub mod limiter;
+pub mod pool;
+pub mod recorder;
+pub mod retry;
+pub mod settings;
+pub mod tls;
+
+pub use limiter::AdaptiveLimiter;
+pub use pool::EndpointPool;
+pub use retry::{CircuitBreaker, RetryPolicy, RpcError};
@@ -0,0 +1,221 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Multiplier applied to the limit on timeouts, HTTP 429/5xx and RPC errors.
+const BACKOFF_FACTOR: f64 = 0.5;
+/// Gentler multiplier applied when a request succeeds but exceeds the latency target.
+const SLOW_FACTOR: f64 = 0.9;
+
+#[derive(Debug, Clone, Copy)]
+pub struct LimiterConfig {
+    pub initial: usize,
+    pub min: usize,
+    pub max: usize,
+    pub latency_target: Duration,
+}
+
+impl LimiterConfig {
+    /// Limits from the `arch_node` concurrency settings: `min` is at least 1, `max` at least
+    /// `min`, and the limit starts at `initial` clamped between them.
+    pub fn bounded(min: usize, max: usize, initial: usize, latency_target_ms: u64) -> Self {
+        let min = min.max(1);
+        let max = max.max(min);
+        Self {
+            initial: initial.clamp(min, max),
+            min,
+            max,
+            latency_target: Duration::from_millis(latency_target_ms.max(1)),
+        }
+    }
+}
+
+/// How a request that held a permit went, as seen by the limiter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Success,
+    /// Timeout, connection error, HTTP 429/5xx or a JSON-RPC error
+    Overloaded,
+}
+
+#[derive(Debug)]
+struct LimiterState {
+    limit: f64,
+    /// Permits in circulation; tracks `limit.floor()` within `[min, max]`
+    capacity: usize,
+    /// Permits held by in-flight requests that are forgotten on release after a decrease
+    debt: usize,
+}
+
+/// AIMD limiter for in-flight Arch RPC requests. Each success under the latency target
+/// grows the limit by `1 / limit` (about one permit per window of requests); errors and
+/// slow responses shrink it multiplicatively, never below `min` or above `max`.
+#[derive(Debug)]
+pub struct AdaptiveLimiter {
+    semaphore: Arc<Semaphore>,
+    state: Arc<Mutex<LimiterState>>,
+    config: LimiterConfig,
+}
+
+/// A slot for one in-flight request. Hand it back with `AdaptiveLimiter::release`; one
+/// dropped unreleased (its request future was cancelled) still settles outstanding debt.
+#[derive(Debug)]
+pub struct Permit {
+    permit: Option<OwnedSemaphorePermit>,
+    state: Arc<Mutex<LimiterState>>,
+    started: Instant,
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        let Some(permit) = self.permit.take() else { return };
+        let mut state = self.state.lock().unwrap();
+        if state.debt > 0 {
+            state.debt -= 1;
+            permit.forget();
+        }
+    }
+}
+
+impl AdaptiveLimiter {
+    pub fn new(config: LimiterConfig) -> Self {
+        let limiter = Self {
+            semaphore: Arc::new(Semaphore::new(config.initial)),
+            state: Arc::new(Mutex::new(LimiterState { limit: config.initial as f64, capacity: config.initial, debt: 0 })),
+            config,
+        };
+        limiter.export(config.initial);
+        limiter
+    }
+
+    pub fn limit(&self) -> usize {
+        self.state.lock().unwrap().capacity
+    }
+
+    pub fn in_flight(&self) -> usize {
+        let state = self.state.lock().unwrap();
+        (state.capacity + state.debt).saturating_sub(self.semaphore.available_permits())
+    }
+
+    pub async fn acquire(&self) -> Permit {
+        // The semaphore is never closed, so acquisition cannot fail
+        let permit = Arc::clone(&self.semaphore).acquire_owned().await.ok();
+        metrics::gauge!("arch_rpc_in_flight", self.in_flight() as f64);
+        Permit { permit, state: Arc::clone(&self.state), started: Instant::now() }
+    }
+
+    /// Returns the permit and adjusts the limit from the request's outcome and latency.
+    pub fn release(&self, mut permit: Permit, outcome: Outcome) {
+        let latency = permit.started.elapsed();
+        metrics::histogram!("arch_rpc_request_latency_seconds", latency.as_secs_f64());
+        let capacity = {
+            let mut state = self.state.lock().unwrap();
+            match outcome {
+                Outcome::Success if latency <= self.config.latency_target => {
+                    state.limit += 1.0 / state.limit;
+                }
+                Outcome::Success => state.limit *= SLOW_FACTOR,
+                Outcome::Overloaded => state.limit *= BACKOFF_FACTOR,
+            }
+            state.limit = state.limit.clamp(self.config.min as f64, self.config.max as f64);
+            let target = state.limit.floor() as usize;
+            while state.capacity < target {
+                if state.debt > 0 {
+                    state.debt -= 1;
+                } else {
+                    self.semaphore.add_permits(1);
+                }
+                state.capacity += 1;
+            }
+            while state.capacity > target {
+                state.debt += 1;
+                state.capacity -= 1;
+            }
+            if let Some(p) = permit.permit.take() {
+                if state.debt > 0 {
+                    state.debt -= 1;
+                    p.forget();
+                }
+            }
+            // Idle permits above the new limit are retired right away
+            while state.debt > 0 {
+                let Ok(p) = self.semaphore.try_acquire() else { break };
+                p.forget();
+                state.debt -= 1;
+            }
+            state.capacity
+        };
+        self.export(capacity);
+    }
+
+    fn export(&self, capacity: usize) {
+        metrics::gauge!("arch_rpc_concurrency_limit", capacity as f64);
+        metrics::gauge!("arch_rpc_in_flight", self.in_flight() as f64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(initial: usize) -> AdaptiveLimiter {
+        AdaptiveLimiter::new(LimiterConfig {
+            initial,
+            min: 1,
+            max: 16,
+            latency_target: Duration::from_secs(60),
+        })
+    }
+
+    #[tokio::test]
+    async fn limit_grows_additively_and_halves_on_overload() {
+        let l = limiter(4);
+        for _ in 0..5 {
+            let p = l.acquire().await;
+            l.release(p, Outcome::Success);
+        }
+        assert_eq!(l.limit(), 5);
+
+        let p = l.acquire().await;
+        l.release(p, Outcome::Overloaded);
+        assert_eq!(l.limit(), 2);
+        assert_eq!(l.semaphore.available_permits(), 2);
+    }
+
+    #[tokio::test]
+    async fn shrinking_with_permits_held_forgets_them_on_release() {
+        let l = limiter(8);
+        let held: Vec<Permit> = futures_util::future::join_all((0..8).map(|_| l.acquire())).await;
+        assert_eq!(l.in_flight(), 8);
+        let mut held = held.into_iter();
+        l.release(held.next().unwrap(), Outcome::Overloaded);
+        assert_eq!(l.limit(), 4);
+        for p in held {
+            l.release(p, Outcome::Overloaded);
+        }
+        assert_eq!(l.limit(), 1);
+        assert_eq!(l.in_flight(), 0);
+        assert_eq!(l.semaphore.available_permits(), 1);
+    }
+
+    #[tokio::test]
+    async fn cancelled_requests_settle_debt_after_a_decrease() {
+        let l = limiter(4);
+        let first = l.acquire().await;
+        let mut held: Vec<Permit> = futures_util::future::join_all((0..3).map(|_| l.acquire())).await;
+        l.release(first, Outcome::Overloaded);
+        assert_eq!(l.limit(), 2);
+
+        // A request future holding a permit is cancelled instead of releasing it
+        let in_request = held.pop().unwrap();
+        let cancelled = tokio::time::timeout(Duration::from_millis(1), async move {
+            let _permit = in_request;
+            std::future::pending::<()>().await
+        })
+        .await;
+        assert!(cancelled.is_err());
+        drop(held);
+        assert_eq!(l.in_flight(), 0);
+        assert_eq!(l.semaphore.available_permits(), 2);
+    }
+}
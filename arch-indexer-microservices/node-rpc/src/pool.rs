@@ -0,0 +1,216 @@
+use reqwest::Client;
+use serde_json::json;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU32, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::settings::ArchNodeEndpoint;
+
+/// Consecutive request failures before an endpoint is ejected until its next healthy probe.
+const MAX_CONSECUTIVE_FAILURES: u32 = 3;
+
+#[derive(Debug)]
+pub struct NodeEndpoint {
+    pub url: String,
+    weight: u32,
+    healthy: AtomicBool,
+    consecutive_failures: AtomicU32,
+    last_height: AtomicI64,
+}
+
+impl NodeEndpoint {
+    pub fn is_healthy(&self) -> bool {
+        self.healthy.load(Ordering::Relaxed)
+    }
+
+    pub fn last_height(&self) -> i64 {
+        self.last_height.load(Ordering::Relaxed)
+    }
+
+    fn set_healthy(&self, healthy: bool) {
+        let was = self.healthy.swap(healthy, Ordering::Relaxed);
+        if was != healthy {
+            if healthy {
+                info!("🟢 Arch node {} is healthy again", self.url);
+            } else {
+                warn!("🔴 Ejecting Arch node {} from the RPC pool", self.url);
+            }
+        }
+        metrics::gauge!("arch_rpc_endpoint_healthy", if healthy { 1.0 } else { 0.0 }, "endpoint" => self.url.clone());
+    }
+}
+
+/// Weighted pool of Arch node endpoints. Requests are spread by weight across healthy
+/// endpoints; endpoints that error repeatedly or lag the best tip are ejected until a
+/// health probe finds them ready again.
+#[derive(Debug)]
+pub struct EndpointPool {
+    endpoints: Vec<NodeEndpoint>,
+    cursor: AtomicUsize,
+    max_lag_blocks: i64,
+}
+
+impl EndpointPool {
+    pub fn new(endpoints: Vec<ArchNodeEndpoint>, max_lag_blocks: i64) -> Self {
+        let endpoints: Vec<NodeEndpoint> = endpoints
+            .into_iter()
+            .map(|e| NodeEndpoint {
+                url: e.url,
+                weight: e.weight.max(1),
+                healthy: AtomicBool::new(true),
+                consecutive_failures: AtomicU32::new(0),
+                last_height: AtomicI64::new(-1),
+            })
+            .collect();
+        assert!(!endpoints.is_empty(), "RPC pool needs at least one endpoint");
+        Self { endpoints, cursor: AtomicUsize::new(0), max_lag_blocks }
+    }
+
+    pub fn single(url: String) -> Self {
+        Self::new(vec![ArchNodeEndpoint { url, websocket_url: None, weight: 1 }], i64::MAX)
+    }
+
+    pub fn len(&self) -> usize {
+        self.endpoints.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.endpoints.is_empty()
+    }
+
+    pub fn urls(&self) -> Vec<String> {
+        self.endpoints.iter().map(|e| e.url.clone()).collect()
+    }
+
+    /// Picks the next endpoint by weighted round-robin over healthy endpoints, falling back
+    /// to every endpoint when none is currently healthy.
+    pub fn pick(&self) -> &NodeEndpoint {
+        let healthy: Vec<&NodeEndpoint> = self.endpoints.iter().filter(|e| e.is_healthy()).collect();
+        let candidates: Vec<&NodeEndpoint> = if healthy.is_empty() { self.endpoints.iter().collect() } else { healthy };
+        let total: usize = candidates.iter().map(|e| e.weight as usize).sum();
+        let mut n = self.cursor.fetch_add(1, Ordering::Relaxed) % total;
+        for e in &candidates {
+            if n < e.weight as usize {
+                return e;
+            }
+            n -= e.weight as usize;
+        }
+        candidates[0]
+    }
+
+    pub fn record_success(&self, endpoint: &NodeEndpoint) {
+        endpoint.consecutive_failures.store(0, Ordering::Relaxed);
+    }
+
+    pub fn record_failure(&self, endpoint: &NodeEndpoint) {
+        let failures = endpoint.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+        // A lone endpoint is never ejected; there is nothing to fail over to
+        if failures >= MAX_CONSECUTIVE_FAILURES && self.endpoints.len() > 1 {
+            endpoint.set_healthy(false);
+        }
+    }
+
+    /// Probes every endpoint with `is_node_ready` and `get_block_count`, then marks endpoints
+    /// healthy only if they are ready and within `max_lag_blocks` of the best tip.
+    pub async fn probe(&self, client: &Client) {
+        let mut heights: Vec<Option<i64>> = Vec::with_capacity(self.endpoints.len());
+        for e in &self.endpoints {
+            let ready = probe_call(client, &e.url, "is_node_ready").await;
+            // Nodes that do not implement is_node_ready are judged by block count alone
+            let ready = ready.map(|v| v.as_bool().unwrap_or(true)).unwrap_or(true);
+            let height = if ready {
+                probe_call(client, &e.url, "get_block_count").await.and_then(|v| v.as_i64())
+            } else {
+                None
+            };
+            if let Some(h) = height {
+                e.last_height.store(h, Ordering::Relaxed);
+                metrics::gauge!("arch_rpc_endpoint_height", h as f64, "endpoint" => e.url.clone());
+            }
+            heights.push(height);
+        }
+
+        let best = heights.iter().flatten().copied().max();
+        for (e, height) in self.endpoints.iter().zip(heights) {
+            let healthy = match (height, best) {
+                (Some(h), Some(best)) => best.saturating_sub(h) <= self.max_lag_blocks,
+                _ => false,
+            };
+            if healthy {
+                e.consecutive_failures.store(0, Ordering::Relaxed);
+            }
+            e.set_healthy(healthy);
+        }
+    }
+
+    /// Runs `probe` every `interval` in the background. No-op for single-endpoint pools.
+    pub fn spawn_health_checks(self: &Arc<Self>, client: Client, interval: Duration) {
+        if self.endpoints.len() <= 1 {
+            return;
+        }
+        if tokio::runtime::Handle::try_current().is_err() {
+            return;
+        }
+        let pool = Arc::clone(self);
+        tokio::spawn(async move {
+            loop {
+                pool.probe(&client).await;
+                tokio::time::sleep(interval).await;
+            }
+        });
+    }
+}
+
+async fn probe_call(client: &Client, url: &str, method: &str) -> Option<serde_json::Value> {
+    let response = client
+        .post(url)
+        .timeout(Duration::from_secs(5))
+        .json(&json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": [],
+            "id": 1
+        }))
+        .send()
+        .await
+        .ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+    let body = response.json::<serde_json::Value>().await.ok()?;
+    if body.get("error").map(|e| !e.is_null()).unwrap_or(false) {
+        return None;
+    }
+    body.get("result").cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoint(url: &str, weight: u32) -> ArchNodeEndpoint {
+        ArchNodeEndpoint { url: url.to_string(), websocket_url: None, weight }
+    }
+
+    #[test]
+    fn pick_respects_weights_and_skips_ejected_endpoints() {
+        let pool = EndpointPool::new(vec![endpoint("a", 3), endpoint("b", 1)], 10);
+        let picks: Vec<&str> = (0..8).map(|_| pool.pick().url.as_str()).collect();
+        assert_eq!(picks.iter().filter(|u| **u == "a").count(), 6);
+
+        for _ in 0..MAX_CONSECUTIVE_FAILURES {
+            pool.record_failure(&pool.endpoints[0]);
+        }
+        assert!((0..4).all(|_| pool.pick().url == "b"));
+    }
+
+    #[test]
+    fn lone_endpoint_is_never_ejected() {
+        let pool = EndpointPool::single("a".to_string());
+        for _ in 0..10 {
+            pool.record_failure(pool.pick());
+        }
+        assert!(pool.pick().is_healthy());
+    }
+}
@@ -0,0 +1,128 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use tracing::{info, warn};
+
+/// JSON-RPC exchanges recorded by `ArchRpcClient`, one `RpcRecord` per line.
+pub const RPC_FILE: &str = "rpc.jsonl";
+/// WebSocket events received by `WebSocketClient`, one `WebSocketEvent` per line.
+pub const WS_FILE: &str = "ws.jsonl";
+
+/// One recorded call. Batch requests are split so every call can be replayed on its own.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcRecord {
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<Value>,
+}
+
+/// Appends RPC exchanges and WebSocket events to fixture files under a directory.
+/// Enabled process-wide by setting ARCH_RECORD_DIR.
+#[derive(Debug)]
+pub struct Recorder {
+    rpc: Mutex<File>,
+    ws: Mutex<File>,
+}
+
+static RECORDER: OnceLock<Option<Recorder>> = OnceLock::new();
+
+/// The recorder configured by ARCH_RECORD_DIR, if any.
+pub fn global() -> Option<&'static Recorder> {
+    RECORDER
+        .get_or_init(|| {
+            let dir = std::env::var("ARCH_RECORD_DIR").ok().filter(|d| !d.is_empty())?;
+            match Recorder::open(&dir) {
+                Ok(recorder) => {
+                    info!("📼 Recording Arch node RPC and WebSocket traffic to {}", dir);
+                    Some(recorder)
+                }
+                Err(e) => {
+                    warn!("Cannot record to {}: {}", dir, e);
+                    None
+                }
+            }
+        })
+        .as_ref()
+}
+
+impl Recorder {
+    pub fn open(dir: impl AsRef<Path>) -> std::io::Result<Self> {
+        let dir: PathBuf = dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(&dir)?;
+        let append = |name: &str| OpenOptions::new().create(true).append(true).open(dir.join(name));
+        Ok(Self { rpc: Mutex::new(append(RPC_FILE)?), ws: Mutex::new(append(WS_FILE)?) })
+    }
+
+    /// Records a request body (single call or batch) with the response it received.
+    pub fn record_rpc(&self, request: &Value, response: &Value) {
+        for record in pair_records(request, response) {
+            write_line(&self.rpc, &record);
+        }
+    }
+
+    pub fn record_ws<E: Serialize>(&self, event: &E) {
+        write_line(&self.ws, event);
+    }
+}
+
+fn write_line<T: Serialize>(file: &Mutex<File>, value: &T) {
+    let Ok(mut line) = serde_json::to_string(value) else { return };
+    line.push('\n');
+    if let Err(e) = file.lock().unwrap().write_all(line.as_bytes()) {
+        warn!("Failed to write fixture record: {}", e);
+    }
+}
+
+/// Splits a request/response pair into per-call records, matching batch entries by id.
+pub fn pair_records(request: &Value, response: &Value) -> Vec<RpcRecord> {
+    let record = |req: &Value, resp: &Value| -> Option<RpcRecord> {
+        Some(RpcRecord {
+            method: req.get("method")?.as_str()?.to_string(),
+            params: req.get("params").cloned().unwrap_or(Value::Null),
+            result: resp.get("result").filter(|_| resp.get("error").is_none_or(Value::is_null)).cloned(),
+            error: resp.get("error").filter(|e| !e.is_null()).cloned(),
+        })
+    };
+    match (request, response) {
+        (Value::Array(requests), Value::Array(responses)) => requests
+            .iter()
+            .filter_map(|req| {
+                let id = req.get("id")?;
+                let resp = responses.iter().find(|r| r.get("id") == Some(id))?;
+                record(req, resp)
+            })
+            .collect(),
+        (Value::Object(_), Value::Object(_)) => record(request, response).into_iter().collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn batch_exchanges_are_split_per_call() {
+        let request = json!([
+            { "jsonrpc": "2.0", "method": "get_block_hash", "params": 1, "id": 0 },
+            { "jsonrpc": "2.0", "method": "get_block_hash", "params": 2, "id": 1 }
+        ]);
+        let response = json!([
+            { "jsonrpc": "2.0", "id": 1, "error": { "code": -32000 } },
+            { "jsonrpc": "2.0", "id": 0, "result": "aa" }
+        ]);
+        let records = pair_records(&request, &response);
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].params, json!(1));
+        assert_eq!(records[0].result, Some(json!("aa")));
+        assert!(records[1].result.is_none() && records[1].error.is_some());
+    }
+}
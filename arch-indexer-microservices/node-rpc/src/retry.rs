@@ -0,0 +1,337 @@
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+use tracing::warn;
+
+use crate::settings::RpcRetrySettings;
+
+/// Failure of a single Arch RPC attempt, classified so the retry layer knows whether
+/// another attempt (possibly on another endpoint) can help.
+#[derive(Debug, thiserror::Error)]
+pub enum RpcError {
+    #[error("request error: {0}")]
+    Transport(#[from] reqwest::Error),
+    #[error("HTTP error: {0}")]
+    Http(reqwest::StatusCode),
+    #[error("RPC error {code}: {message}")]
+    Rpc { code: i64, message: String },
+    #[error("invalid response: {0}")]
+    Decode(String),
+    #[error("circuit breaker open; Arch node RPC calls are failing fast")]
+    CircuitOpen,
+}
+
+impl RpcError {
+    pub fn from_rpc_error(error: &serde_json::Value) -> Self {
+        RpcError::Rpc {
+            code: error.get("code").and_then(|c| c.as_i64()).unwrap_or(0),
+            message: error
+                .get("message")
+                .and_then(|m| m.as_str())
+                .map(|m| m.to_string())
+                .unwrap_or_else(|| error.to_string()),
+        }
+    }
+
+    /// Transport failures, 408/429/5xx, server-side RPC errors and malformed results are
+    /// retryable; malformed requests (bad method or params) and an open breaker are not.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            RpcError::Transport(_) | RpcError::Decode(_) => true,
+            RpcError::Http(status) => {
+                status.is_server_error()
+                    || *status == reqwest::StatusCode::TOO_MANY_REQUESTS
+                    || *status == reqwest::StatusCode::REQUEST_TIMEOUT
+            }
+            // JSON-RPC 2.0: parse error, invalid request, method not found, invalid params
+            RpcError::Rpc { code, .. } => !matches!(code, -32700 | -32600 | -32601 | -32602),
+            RpcError::CircuitOpen => false,
+        }
+    }
+}
+
+/// Exponential backoff with jitter: attempt `n` waits a random duration in
+/// `[d / 2, d]` where `d = min(base * 2^n, max)`.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    pub fn from_settings(settings: &RpcRetrySettings) -> Self {
+        Self {
+            max_attempts: settings.max_attempts.max(1),
+            base_delay: Duration::from_millis(settings.base_delay_ms),
+            max_delay: Duration::from_millis(settings.max_delay_ms.max(settings.base_delay_ms)),
+        }
+    }
+
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let ceiling = self.base_delay.saturating_mul(1u32 << attempt.min(16)).min(self.max_delay);
+        let half = ceiling / 2;
+        let jitter = RandomState::new().build_hasher().finish() % (half.as_millis() as u64 + 1);
+        half + Duration::from_millis(jitter)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakerState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+impl BreakerState {
+    fn as_gauge(self) -> f64 {
+        match self {
+            BreakerState::Closed => 0.0,
+            BreakerState::HalfOpen => 1.0,
+            BreakerState::Open => 2.0,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct BreakerInner {
+    state: BreakerState,
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+}
+
+/// Opens after `failure_threshold` consecutive retryable failures and rejects calls for
+/// `open_for`; then lets a single probe through (half-open) and closes on its success.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    inner: Mutex<BreakerInner>,
+    failure_threshold: u32,
+    open_for: Duration,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, open_for: Duration) -> Self {
+        metrics::gauge!("arch_rpc_circuit_state", BreakerState::Closed.as_gauge());
+        Self {
+            inner: Mutex::new(BreakerInner { state: BreakerState::Closed, consecutive_failures: 0, opened_at: None }),
+            failure_threshold: failure_threshold.max(1),
+            open_for,
+        }
+    }
+
+    pub fn from_settings(settings: &RpcRetrySettings) -> Self {
+        Self::new(settings.breaker_failure_threshold, Duration::from_secs(settings.breaker_open_seconds))
+    }
+
+    pub fn state(&self) -> BreakerState {
+        self.inner.lock().unwrap().state
+    }
+
+    /// Whether a call may proceed. An open breaker past its cool-down admits one probe.
+    pub fn allow(&self) -> bool {
+        self.admit().map(|permit| permit.forget()).is_some()
+    }
+
+    /// Like [`allow`](Self::allow), but a probe's permit hands the half-open slot back when
+    /// it is dropped without an outcome, as when the caller's future is cancelled mid-call.
+    fn admit(&self) -> Option<Permit<'_>> {
+        let mut inner = self.inner.lock().unwrap();
+        match inner.state {
+            BreakerState::Closed => Some(Permit { breaker: self, probe: false }),
+            BreakerState::HalfOpen => None,
+            BreakerState::Open => {
+                if inner.opened_at.map(|t| t.elapsed() >= self.open_for).unwrap_or(true) {
+                    Self::transition(&mut inner, BreakerState::HalfOpen);
+                    Some(Permit { breaker: self, probe: true })
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    pub fn record_success(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.consecutive_failures = 0;
+        if inner.state != BreakerState::Closed {
+            Self::transition(&mut inner, BreakerState::Closed);
+        }
+    }
+
+    pub fn record_failure(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.consecutive_failures += 1;
+        let trip = inner.state == BreakerState::HalfOpen
+            || (inner.state == BreakerState::Closed && inner.consecutive_failures >= self.failure_threshold);
+        if trip {
+            inner.opened_at = Some(Instant::now());
+            Self::transition(&mut inner, BreakerState::Open);
+            metrics::increment_counter!("arch_rpc_circuit_opened_total");
+        }
+    }
+
+    /// Re-opens a half-open breaker whose probe never reported back. `opened_at` is kept, so
+    /// the cool-down has already elapsed and the next call is admitted as the new probe.
+    fn release_probe(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.state == BreakerState::HalfOpen {
+            Self::transition(&mut inner, BreakerState::Open);
+        }
+    }
+
+    fn transition(inner: &mut BreakerInner, to: BreakerState) {
+        if inner.state != to {
+            warn!("⚡ Arch RPC circuit breaker {:?} -> {:?}", inner.state, to);
+        }
+        inner.state = to;
+        metrics::gauge!("arch_rpc_circuit_state", to.as_gauge());
+    }
+}
+
+/// Admission of one call by a [`CircuitBreaker`]. Recording the outcome consumes it; a
+/// probe permit dropped before that releases the breaker's half-open slot.
+struct Permit<'a> {
+    breaker: &'a CircuitBreaker,
+    probe: bool,
+}
+
+impl Permit<'_> {
+    fn success(self) {
+        self.breaker.record_success();
+        self.forget();
+    }
+
+    fn failure(self) {
+        self.breaker.record_failure();
+        self.forget();
+    }
+
+    fn forget(mut self) {
+        self.probe = false;
+    }
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        if self.probe {
+            self.breaker.release_probe();
+        }
+    }
+}
+
+/// Runs `attempt` under `policy`, consulting `breaker` before every try. Only retryable
+/// errors count against the breaker; a fatal error means the node answered.
+pub async fn with_retry<T, F, Fut>(
+    policy: &RetryPolicy,
+    breaker: &CircuitBreaker,
+    method: &str,
+    mut attempt: F,
+) -> Result<T, RpcError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, RpcError>>,
+{
+    let mut n = 0;
+    loop {
+        let Some(permit) = breaker.admit() else {
+            return Err(RpcError::CircuitOpen);
+        };
+        let err = match attempt().await {
+            Ok(value) => {
+                permit.success();
+                return Ok(value);
+            }
+            Err(e) if !e.is_retryable() => {
+                permit.success();
+                return Err(e);
+            }
+            Err(e) => e,
+        };
+        permit.failure();
+        n += 1;
+        if n >= policy.max_attempts {
+            return Err(err);
+        }
+        metrics::increment_counter!("arch_rpc_retries_total", "method" => method.to_string());
+        warn!("{} failed (attempt {}/{}): {}", method, n, policy.max_attempts, err);
+        sleep(policy.delay(n - 1)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rpc_errors_are_classified() {
+        let fatal = RpcError::from_rpc_error(&serde_json::json!({ "code": -32602, "message": "invalid params" }));
+        assert!(!fatal.is_retryable());
+        let server = RpcError::from_rpc_error(&serde_json::json!({ "code": -32000, "message": "busy" }));
+        assert!(server.is_retryable());
+        assert!(RpcError::Http(reqwest::StatusCode::TOO_MANY_REQUESTS).is_retryable());
+        assert!(!RpcError::Http(reqwest::StatusCode::NOT_FOUND).is_retryable());
+    }
+
+    #[test]
+    fn backoff_is_exponential_jittered_and_capped() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(1000),
+        };
+        for _ in 0..20 {
+            let d = policy.delay(2);
+            assert!(d >= Duration::from_millis(200) && d <= Duration::from_millis(400));
+            assert!(policy.delay(10) <= Duration::from_millis(1000));
+        }
+    }
+
+    #[test]
+    fn breaker_opens_then_half_opens_after_cooldown() {
+        let breaker = CircuitBreaker::new(3, Duration::from_millis(0));
+        for _ in 0..3 {
+            assert!(breaker.allow());
+            breaker.record_failure();
+        }
+        assert_eq!(breaker.state(), BreakerState::Open);
+        assert!(breaker.allow(), "cool-down elapsed, one probe is admitted");
+        assert!(!breaker.allow(), "only one probe while half-open");
+        breaker.record_success();
+        assert_eq!(breaker.state(), BreakerState::Closed);
+
+        let breaker = CircuitBreaker::new(1, Duration::from_secs(60));
+        breaker.record_failure();
+        assert!(!breaker.allow());
+    }
+
+    #[tokio::test]
+    async fn cancelled_probe_releases_the_half_open_slot() {
+        let policy = RetryPolicy { max_attempts: 1, base_delay: Duration::ZERO, max_delay: Duration::ZERO };
+        let breaker = CircuitBreaker::new(1, Duration::from_millis(0));
+        breaker.record_failure();
+        assert_eq!(breaker.state(), BreakerState::Open);
+
+        let probe = with_retry(&policy, &breaker, "test", std::future::pending::<Result<(), RpcError>>);
+        assert!(tokio::time::timeout(Duration::from_millis(10), probe).await.is_err());
+        assert_eq!(breaker.state(), BreakerState::Open);
+        assert!(breaker.allow(), "the cancelled probe's slot is admitted again");
+        assert!(!breaker.allow());
+    }
+
+    #[tokio::test]
+    async fn fatal_errors_are_not_retried() {
+        let policy = RetryPolicy { max_attempts: 5, base_delay: Duration::ZERO, max_delay: Duration::ZERO };
+        let breaker = CircuitBreaker::new(10, Duration::from_secs(60));
+        let mut calls = 0;
+        let result: Result<(), RpcError> = with_retry(&policy, &breaker, "test", || {
+            calls += 1;
+            async { Err(RpcError::Rpc { code: -32601, message: "method not found".into() }) }
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+}
@@ -0,0 +1,97 @@
+use serde::Deserialize;
+use std::env;
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct ArchNodeEndpoint {
+    pub url: String,
+    #[serde(default)]
+    pub websocket_url: Option<String>,
+    #[serde(default = "default_endpoint_weight")]
+    pub weight: u32,
+}
+
+pub fn default_endpoint_weight() -> u32 { 1 }
+
+/// Retry and circuit-breaker limits shared by every `ArchRpcClient` method. Fields missing
+/// from the config file default to their ARCH_RPC_* environment variable.
+#[derive(Debug, Deserialize, Clone)]
+pub struct RpcRetrySettings {
+    #[serde(default = "default_retry_max_attempts")]
+    pub max_attempts: u32,
+    #[serde(default = "default_retry_base_delay_ms")]
+    pub base_delay_ms: u64,
+    #[serde(default = "default_retry_max_delay_ms")]
+    pub max_delay_ms: u64,
+    /// Consecutive retryable failures that open the breaker.
+    #[serde(default = "default_breaker_failure_threshold")]
+    pub breaker_failure_threshold: u32,
+    /// How long an open breaker fails calls fast before admitting a probe.
+    #[serde(default = "default_breaker_open_seconds")]
+    pub breaker_open_seconds: u64,
+}
+
+impl Default for RpcRetrySettings {
+    fn default() -> Self {
+        Self {
+            max_attempts: default_retry_max_attempts(),
+            base_delay_ms: default_retry_base_delay_ms(),
+            max_delay_ms: default_retry_max_delay_ms(),
+            breaker_failure_threshold: default_breaker_failure_threshold(),
+            breaker_open_seconds: default_breaker_open_seconds(),
+        }
+    }
+}
+
+fn env_or<T: std::str::FromStr>(var: &str, default: T) -> T {
+    env::var(var).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+}
+
+fn default_retry_max_attempts() -> u32 { env_or("ARCH_RPC_MAX_ATTEMPTS", 5) }
+fn default_retry_base_delay_ms() -> u64 { env_or("ARCH_RPC_BASE_DELAY_MS", 100) }
+fn default_retry_max_delay_ms() -> u64 { env_or("ARCH_RPC_MAX_DELAY_MS", 5_000) }
+fn default_breaker_failure_threshold() -> u32 { env_or("ARCH_RPC_BREAKER_THRESHOLD", 20) }
+fn default_breaker_open_seconds() -> u64 { env_or("ARCH_RPC_BREAKER_OPEN_SECONDS", 30) }
+
+/// TLS for HTTP RPC and WebSocket connections to the node. Certificates are verified
+/// against the public web roots plus `ca_cert_path` unless `insecure_skip_verify` is set.
+/// Fields missing from the config file default to their ARCH_NODE_TLS_* environment variable.
+#[derive(Debug, Deserialize, Clone)]
+pub struct TlsSettings {
+    /// PEM bundle of additional trusted CA certificates
+    #[serde(default = "default_tls_ca_cert_path")]
+    pub ca_cert_path: Option<String>,
+    /// PEM client certificate chain and private key for mTLS; both or neither
+    #[serde(default = "default_tls_client_cert_path")]
+    pub client_cert_path: Option<String>,
+    #[serde(default = "default_tls_client_key_path")]
+    pub client_key_path: Option<String>,
+    #[serde(default = "default_tls_insecure_skip_verify")]
+    pub insecure_skip_verify: bool,
+}
+
+impl Default for TlsSettings {
+    fn default() -> Self {
+        Self {
+            ca_cert_path: default_tls_ca_cert_path(),
+            client_cert_path: default_tls_client_cert_path(),
+            client_key_path: default_tls_client_key_path(),
+            insecure_skip_verify: default_tls_insecure_skip_verify(),
+        }
+    }
+}
+
+fn default_tls_ca_cert_path() -> Option<String> {
+    env::var("ARCH_NODE_TLS_CA_CERT").ok()
+}
+
+fn default_tls_client_cert_path() -> Option<String> {
+    env::var("ARCH_NODE_TLS_CLIENT_CERT").ok()
+}
+
+fn default_tls_client_key_path() -> Option<String> {
+    env::var("ARCH_NODE_TLS_CLIENT_KEY").ok()
+}
+
+fn default_tls_insecure_skip_verify() -> bool {
+    env::var("ARCH_NODE_TLS_INSECURE_SKIP_VERIFY").map(|v| v == "true" || v == "1").unwrap_or(false)
+}
@@ -0,0 +1,119 @@
+use anyhow::{Context, Result};
+use rustls::client::{ServerCertVerified, ServerCertVerifier};
+use rustls::{Certificate, ClientConfig, OwnedTrustAnchor, PrivateKey, RootCertStore, ServerName};
+use std::io::BufReader;
+use std::sync::Arc;
+use std::time::SystemTime;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+use tokio_tungstenite::WebSocketStream;
+use tracing::warn;
+use url::Url;
+
+use crate::settings::TlsSettings;
+
+/// Builds the rustls configuration shared by the RPC and WebSocket clients: the public
+/// web roots plus an optional CA bundle, an optional client identity for mTLS, and
+/// full certificate verification unless `insecure_skip_verify` is set.
+pub fn client_config(settings: &TlsSettings) -> Result<ClientConfig> {
+    let mut roots = RootCertStore::empty();
+    roots.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|ta| {
+        OwnedTrustAnchor::from_subject_spki_name_constraints(ta.subject, ta.spki, ta.name_constraints)
+    }));
+    if let Some(path) = &settings.ca_cert_path {
+        for cert in read_certs(path)? {
+            roots.add(&cert).with_context(|| format!("invalid CA certificate in {}", path))?;
+        }
+    }
+
+    let builder = ClientConfig::builder().with_safe_defaults().with_root_certificates(roots);
+    let mut config = match (&settings.client_cert_path, &settings.client_key_path) {
+        (Some(cert), Some(key)) => builder
+            .with_client_auth_cert(read_certs(cert)?, read_key(key)?)
+            .context("invalid TLS client certificate or key")?,
+        (None, None) => builder.with_no_client_auth(),
+        _ => anyhow::bail!("tls.client_cert_path and tls.client_key_path must be set together"),
+    };
+
+    if settings.insecure_skip_verify {
+        warn!("⚠️ TLS certificate verification is disabled for Arch node connections (insecure_skip_verify)");
+        config.dangerous().set_certificate_verifier(Arc::new(NoVerification));
+    }
+    Ok(config)
+}
+
+fn read_certs(path: &str) -> Result<Vec<Certificate>> {
+    let file = std::fs::File::open(path).with_context(|| format!("cannot open certificate file {}", path))?;
+    let certs = rustls_pemfile::certs(&mut BufReader::new(file))
+        .with_context(|| format!("cannot parse certificates in {}", path))?;
+    if certs.is_empty() {
+        anyhow::bail!("no PEM certificates found in {}", path);
+    }
+    Ok(certs.into_iter().map(Certificate).collect())
+}
+
+fn read_key(path: &str) -> Result<PrivateKey> {
+    let file = std::fs::File::open(path).with_context(|| format!("cannot open key file {}", path))?;
+    for item in rustls_pemfile::read_all(&mut BufReader::new(file))
+        .with_context(|| format!("cannot parse key file {}", path))?
+    {
+        match item {
+            rustls_pemfile::Item::PKCS8Key(der)
+            | rustls_pemfile::Item::RSAKey(der)
+            | rustls_pemfile::Item::ECKey(der) => return Ok(PrivateKey(der)),
+            _ => {}
+        }
+    }
+    anyhow::bail!("no PEM private key found in {}", path)
+}
+
+/// Accepts any server certificate. Only installed when `insecure_skip_verify` is set.
+struct NoVerification;
+
+impl ServerCertVerifier for NoVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &Certificate,
+        _intermediates: &[Certificate],
+        _server_name: &ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: SystemTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+}
+
+pub trait NodeStream: AsyncRead + AsyncWrite + Unpin + Send {}
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> NodeStream for T {}
+
+/// Opens a WebSocket to `url`, wrapping `wss://` connections in TLS from `config`.
+pub async fn connect_websocket(
+    url: &Url,
+    config: &Arc<ClientConfig>,
+) -> Result<WebSocketStream<Box<dyn NodeStream>>> {
+    let host = url.host_str().ok_or_else(|| anyhow::anyhow!("WebSocket URL {} has no host", url))?;
+    let port = url.port_or_known_default().unwrap_or(80);
+    let tcp = TcpStream::connect((host, port)).await?;
+    let stream: Box<dyn NodeStream> = if url.scheme() == "wss" {
+        let server_name = ServerName::try_from(host).map_err(|_| anyhow::anyhow!("invalid TLS server name {}", host))?;
+        Box::new(TlsConnector::from(Arc::clone(config)).connect(server_name, tcp).await?)
+    } else {
+        Box::new(tcp)
+    };
+    let (ws_stream, _) = tokio_tungstenite::client_async(url.as_str(), stream).await?;
+    Ok(ws_stream)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn client_cert_requires_key() {
+        let settings = TlsSettings { client_cert_path: Some("client.pem".to_string()), ..Default::default() };
+        assert!(client_config(&settings).is_err());
+        assert!(client_config(&TlsSettings::default()).is_ok());
+    }
+}
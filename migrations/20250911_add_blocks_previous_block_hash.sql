@@ -0,0 +1,2 @@
+-- Track each block's parent hash so the indexer can detect chain reorganizations
+ALTER TABLE blocks ADD COLUMN IF NOT EXISTS previous_block_hash TEXT;
@@ -0,0 +1,20 @@
+-- Decoded instructions, written by the indexer at index time so the API can serve and
+-- aggregate them without re-fetching and re-decoding transactions per request.
+
+CREATE TABLE IF NOT EXISTS instructions (
+    txid TEXT NOT NULL REFERENCES transactions(txid) ON DELETE CASCADE,
+    instruction_index INTEGER NOT NULL,
+    block_height BIGINT NOT NULL,
+    program_id TEXT NOT NULL,
+    instruction_type TEXT,
+    args JSONB,
+    -- [{address_hex, role, is_signer, is_writable}] in instruction account order
+    accounts JSONB NOT NULL DEFAULT '[]'::jsonb,
+    data BYTEA NOT NULL DEFAULT ''::bytea,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (txid, instruction_index)
+);
+
+CREATE INDEX IF NOT EXISTS idx_instructions_type ON instructions(instruction_type);
+CREATE INDEX IF NOT EXISTS idx_instructions_program_type ON instructions(program_id, instruction_type);
+CREATE INDEX IF NOT EXISTS idx_instructions_block_height ON instructions(block_height);
@@ -0,0 +1,19 @@
+-- Native transfer ledger: one row per System Program Transfer or CreateAccount funding,
+-- written by the indexer so lamport movements can be audited per transfer rather than
+-- only through the running totals in native_balances.
+
+CREATE TABLE IF NOT EXISTS native_transfers (
+    txid TEXT NOT NULL REFERENCES transactions(txid) ON DELETE CASCADE,
+    instruction_index INTEGER NOT NULL,
+    block_height BIGINT NOT NULL,
+    block_time TIMESTAMPTZ,
+    kind TEXT NOT NULL CHECK (kind IN ('transfer', 'create_funding')),
+    source_address TEXT NOT NULL,
+    destination_address TEXT NOT NULL,
+    lamports NUMERIC(20, 0) NOT NULL,
+    PRIMARY KEY (txid, instruction_index)
+);
+
+CREATE INDEX IF NOT EXISTS idx_native_transfers_source ON native_transfers(source_address, block_height DESC);
+CREATE INDEX IF NOT EXISTS idx_native_transfers_destination ON native_transfers(destination_address, block_height DESC);
+CREATE INDEX IF NOT EXISTS idx_native_transfers_block_time ON native_transfers(block_time);
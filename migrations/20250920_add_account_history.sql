@@ -0,0 +1,17 @@
+-- Account history: every account state seen by the Atlas account processor, one row per
+-- account and height, with deletions kept as tombstones. `accounts` only holds the latest
+-- state, so this is what answers "what did this account look like at height H".
+
+CREATE TABLE IF NOT EXISTS account_history (
+    pubkey TEXT NOT NULL,
+    height BIGINT NOT NULL,
+    lamports BIGINT,
+    owner TEXT,
+    data BYTEA,
+    data_hash TEXT GENERATED ALWAYS AS (encode(sha256(data), 'hex')) STORED,
+    is_deleted BOOLEAN NOT NULL DEFAULT FALSE,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (pubkey, height)
+);
+
+CREATE INDEX IF NOT EXISTS idx_account_history_height ON account_history(height);
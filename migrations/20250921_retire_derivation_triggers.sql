@@ -0,0 +1,18 @@
+-- Programs, account participation and native balances are now derived by the indexer in
+-- the same database transaction as the block write (see indexer/src/indexer/programs.rs,
+-- participation.rs and native.rs). Retire the triggers that used to do this; left in
+-- place they would apply every effect a second time.
+
+DROP TRIGGER IF EXISTS transaction_programs_trigger ON transactions;
+DROP TRIGGER IF EXISTS account_participation_trigger ON transactions;
+DROP TRIGGER IF EXISTS native_balances_trigger ON transactions;
+DROP TRIGGER IF EXISTS token_balances_trigger ON transactions;
+DROP TRIGGER IF EXISTS token_indexing_trigger ON transactions;
+
+DROP FUNCTION IF EXISTS update_transaction_programs();
+DROP FUNCTION IF EXISTS populate_account_participation();
+DROP FUNCTION IF EXISTS populate_native_balances_from_tx();
+DROP FUNCTION IF EXISTS nb_apply_delta(TEXT, NUMERIC);
+DROP FUNCTION IF EXISTS canonical_program_id(JSONB);
+DROP FUNCTION IF EXISTS populate_token_entities_from_tx();
+DROP FUNCTION IF EXISTS update_token_balances_from_transaction();
@@ -0,0 +1,16 @@
+-- Whether a transaction's derived effects (balances, program links, derived rows) are
+-- currently applied. Set when the indexer applies them and cleared when a node rollback
+-- reverses them, so rollbacks and reapplies act on a transaction only once.
+
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS effects_applied BOOLEAN;
+
+-- Stored transactions had their effects applied when indexed, unless rolled back since
+UPDATE transactions t
+SET effects_applied = COALESCE(
+    (SELECT r.action FROM transaction_rollbacks r WHERE r.txid = t.txid ORDER BY r.id DESC LIMIT 1),
+    'reapplied'
+) = 'reapplied'
+WHERE effects_applied IS NULL;
+
+ALTER TABLE transactions ALTER COLUMN effects_applied SET DEFAULT FALSE;
+ALTER TABLE transactions ALTER COLUMN effects_applied SET NOT NULL;
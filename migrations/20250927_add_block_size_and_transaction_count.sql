@@ -0,0 +1,7 @@
+-- Block size (sum of the approximate wire size of its transactions) and transaction count,
+-- written by the indexer with the block so the API does not recount or re-estimate them
+-- per request. Blocks indexed before these columns existed have NULLs, and the API falls
+-- back to deriving them from the stored transactions.
+
+ALTER TABLE blocks ADD COLUMN IF NOT EXISTS block_size_bytes BIGINT;
+ALTER TABLE blocks ADD COLUMN IF NOT EXISTS transaction_count BIGINT;